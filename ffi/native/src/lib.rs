@@ -2,58 +2,128 @@
 extern crate neon;
 extern crate ratel;
 extern crate ratel_codegen;
+extern crate ratel_transformer;
+extern crate ratel_visitor;
 extern crate serde;
+#[macro_use]
 extern crate serde_json;
 
-use neon::vm::{Call, JsResult};
-use neon::js::{JsString, JsBoolean};
+use neon::vm::{Call, JsResult, Lock};
+use neon::mem::Handle;
+use neon::scope::Scope;
+use neon::js::{JsValue, JsString, JsBoolean, JsObject, Object};
+use neon::js::binary::JsBuffer;
 use neon::js::error::{JsError, Kind};
-use ratel::Module;
+use ratel::{Module, Estree, EstreeOptions, Flavor};
 use ratel::error::{Error, ParseError};
+use ratel_transformer::Settings;
+use ratel_visitor::query::{self, Selector};
 
 #[inline]
-fn format_errors(errors: Vec<Error>, source: neon::mem::Handle<JsString>) -> Vec<String> {
+fn format_errors(errors: Vec<Error>, source: &[u8]) -> String {
+    let source = String::from_utf8_lossy(source).into_owned();
+
     errors
     .into_iter()
     .map(|err| {
         match err {
             Error { start, end, .. } => {
-               ParseError::UnexpectedToken { start, end, source: source.value() }
+               ParseError::UnexpectedToken { start, end, source: source.clone() }
             }
         }
     })
     .map(|err| format!("{}", err))
-    .collect()
+    .collect::<Vec<String>>()
+    .join("\n")
+}
+
+/// Parse the source, which can be either a string or a `Buffer`. Buffers are
+/// parsed directly from their contents, without converting them to a string.
+#[inline]
+fn with_module<F>(source: Handle<JsValue>, f: F) -> Option<Result<String, String>>
+where
+    F: FnOnce(&Module, &str) -> String
+{
+    let parse = |source: &[u8]| {
+        ratel::parse_bytes(source)
+            .map(|module| f(&module, &String::from_utf8_lossy(source)))
+            .map_err(|errors| format_errors(errors, source))
+    };
+
+    if let Some(string) = source.downcast::<JsString>() {
+        return Some(parse(string.value().as_bytes()));
+    }
+
+    source.downcast::<JsBuffer>().map(|mut buffer| {
+        buffer.grab(|contents| parse(contents.as_slice()))
+    })
 }
 
 #[inline]
-fn generate_ast(module: &Module, minify: bool) -> Result<String, serde_json::Error> {
+fn generate_ast(module: &Module, source: &str, minify: bool, options: EstreeOptions) -> Result<String, serde_json::Error> {
+    let estree = Estree::new(module, source, options);
+
     if minify {
-        serde_json::to_string(&module)
+        serde_json::to_string(&estree)
     } else {
-        serde_json::to_string_pretty(&module)
+        serde_json::to_string_pretty(&estree)
+    }
+}
+
+/// Read a boolean from an options object, falling back to the default.
+#[inline]
+fn flag<'a, T: Scope<'a>>(scope: &mut T, object: Handle<JsObject>, key: &str, default: bool) -> bool {
+    object.get(scope, key).ok()
+        .and_then(|value| value.downcast::<JsBoolean>())
+        .map(|value| value.value())
+        .unwrap_or(default)
+}
+
+/// Read `{ loc, range, raw, flavor }` from an options object, where
+/// `flavor` is either `'estree'` or `'babel'`.
+fn estree_options<'a, T: Scope<'a>>(scope: &mut T, object: Handle<JsObject>) -> Result<EstreeOptions, String> {
+    let mut options = EstreeOptions::default();
+
+    options.loc = flag(scope, object, "loc", options.loc);
+    options.range = flag(scope, object, "range", options.range);
+    options.raw = flag(scope, object, "raw", options.raw);
+
+    let flavor = object.get(scope, "flavor").ok().and_then(|value| value.downcast::<JsString>());
+
+    if let Some(flavor) = flavor {
+        options.flavor = match flavor.value().as_str() {
+            "estree" => Flavor::Estree,
+            "babel"  => Flavor::Babel,
+            flavor   => return Err(format!("Unknown AST flavor `{}`", flavor)),
+        };
     }
+
+    Ok(options)
 }
 
 fn ast(call: Call) -> JsResult<JsString> {
     let scope = call.scope;
 
     if call.arguments.len() == 0 {
-        return JsError::throw(Kind::TypeError, "First argument must be a string")
+        return JsError::throw(Kind::TypeError, "First argument must be a string or a Buffer")
     }
 
-    let source = call.arguments.require(scope, 0)?.check::<JsString>()?;
+    let source = call.arguments.require(scope, 0)?;
     let minify = call.arguments.require(scope, 1)?.check::<JsBoolean>()?;
 
-    let module = match ratel::parse(&source.value()) {
-        Err(errors) => {
-            let str = format_errors(errors, source).join("\n");
-            return JsError::throw(Kind::SyntaxError, &str)
+    let options = match call.arguments.get(scope, 2).and_then(|value| value.downcast::<JsObject>()) {
+        Some(object) => match estree_options(scope, object) {
+            Ok(options) => options,
+            Err(str)    => return JsError::throw(Kind::TypeError, &str),
         },
-        Ok(module) => module,
+        None => EstreeOptions::default(),
     };
 
-    let result = generate_ast(&module, minify.value()).unwrap();
+    let result = match with_module(source, |module, source| generate_ast(module, source, minify.value(), options).unwrap()) {
+        None => return JsError::throw(Kind::TypeError, "First argument must be a string or a Buffer"),
+        Some(Err(str)) => return JsError::throw(Kind::SyntaxError, &str),
+        Some(Ok(result)) => result,
+    };
 
     Ok(JsString::new(scope, &result).unwrap())
 }
@@ -62,21 +132,20 @@ fn transform(call: Call) -> JsResult<JsString> {
     let scope = call.scope;
 
     if call.arguments.len() == 0 {
-        return JsError::throw(Kind::TypeError, "First argument must be a string")
+        return JsError::throw(Kind::TypeError, "First argument must be a string or a Buffer")
     }
 
-    let source = call.arguments.require(scope, 0)?.check::<JsString>()?;
+    let source = call.arguments.require(scope, 0)?;
     let minify = call.arguments.require(scope, 1)?.check::<JsBoolean>()?;
 
-    let module = match ratel::parse(&source.value()) {
-        Err(errors) => {
-            let str = format_errors(errors, source).join("\n");
-            return JsError::throw(Kind::SyntaxError, &str)
-        },
-        Ok(module) => module,
+    let out = match with_module(source, |module, _| {
+        ratel_transformer::transform(module, Settings::target_es5());
+        ratel_codegen::codegen(module, minify.value())
+    }) {
+        None => return JsError::throw(Kind::TypeError, "First argument must be a string or a Buffer"),
+        Some(Err(str)) => return JsError::throw(Kind::SyntaxError, &str),
+        Some(Ok(out)) => out,
     };
-    // transformer::transform(&mut module, transformer::Settings::target_es5());
-    let out = ratel_codegen::codegen(&module, minify.value());
 
     Ok(JsString::new(scope, &out).unwrap())
 }
@@ -85,20 +154,55 @@ fn parse(call: Call) -> JsResult<JsString> {
     let scope = call.scope;
 
     if call.arguments.len() == 0 {
-        return JsError::throw(Kind::TypeError, "First argument must be a string")
+        return JsError::throw(Kind::TypeError, "First argument must be a string or a Buffer")
     }
 
-    let source = call.arguments.require(scope, 0)?.check::<JsString>()?;
+    let source = call.arguments.require(scope, 0)?;
 
-    let module = match ratel::parse(&source.value()) {
-        Err(errors) => {
-            let str = format_errors(errors, source).join("\n");
-            return JsError::throw(Kind::SyntaxError, &str)
-        },
-        Ok(module) => module,
+    let out = match with_module(source, |module, _| format!("{:?}", module.body())) {
+        None => return JsError::throw(Kind::TypeError, "First argument must be a string or a Buffer"),
+        Some(Err(str)) => return JsError::throw(Kind::SyntaxError, &str),
+        Some(Ok(out)) => out,
+    };
+
+    Ok(JsString::new(scope, &out).unwrap())
+}
+
+/// Find the nodes matching a selector, returned as JSON, an array of
+/// `{ type, start, end }` objects.
+fn query(call: Call) -> JsResult<JsString> {
+    let scope = call.scope;
+
+    if call.arguments.len() == 0 {
+        return JsError::throw(Kind::TypeError, "First argument must be a string or a Buffer")
+    }
+
+    let source = call.arguments.require(scope, 0)?;
+    let selector = call.arguments.require(scope, 1)?.check::<JsString>()?.value();
+
+    let selector = match Selector::parse(&selector) {
+        Ok(selector) => selector,
+        Err(error)   => return JsError::throw(Kind::SyntaxError, &error.to_string()),
     };
 
-    let out = format!("{:?}", module.body());
+    let result = with_module(source, |module, _| {
+        let nodes = query::query(module, &selector)
+            .iter()
+            .map(|node| json!({
+                "type": query::node_type(node),
+                "start": node.start(),
+                "end": node.end(),
+            }))
+            .collect::<Vec<_>>();
+
+        serde_json::to_string(&nodes).unwrap()
+    });
+
+    let out = match result {
+        None => return JsError::throw(Kind::TypeError, "First argument must be a string or a Buffer"),
+        Some(Err(str)) => return JsError::throw(Kind::SyntaxError, &str),
+        Some(Ok(out)) => out,
+    };
 
     Ok(JsString::new(scope, &out).unwrap())
 }
@@ -107,5 +211,6 @@ register_module!(m, {
     m.export("transform", transform)?;
     m.export("parse", parse)?;
     m.export("ast", ast)?;
+    m.export("query", query)?;
     Ok(())
 });
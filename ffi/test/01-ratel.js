@@ -21,11 +21,22 @@ describe('Ratel ffi', () => {
     });
 
     it('transforms', () => {
-      // const result = Ratel.transform('2**2', true);
-      const result = Ratel.transform('Math.pow(2, 2)', true);
+      const result = Ratel.transform('2**2', true);
       assert.equal(typeof result, 'string');
       assert.equal(result, 'Math.pow(2,2);');
     });
+
+    it('transforms buffers', () => {
+      const result = Ratel.transform(Buffer.from('Math.pow(2, 2)'), true);
+      assert.equal(typeof result, 'string');
+      assert.equal(result, 'Math.pow(2,2);');
+    });
+
+    it('throws syntax errors on invalid UTF-8', () => {
+      assert.throws(() => {
+        Ratel.transform(Buffer.from([0x66, 0x6f, 0x6f, 0xff]), true);
+      }, /Unexpected token/);
+    });
   });
 
   describe('parse', () => {
@@ -80,5 +91,26 @@ describe('Ratel ffi', () => {
       delete acornAST['sourceType'];
       assert.deepEqual(tree, acornAST);
     });
+
+    it('accepts ESTree options', () => {
+      const ast = Ratel.ast(`'foo';`, true, { range: true, flavor: 'babel' });
+      const literal = JSON.parse(ast).body[0].expression;
+      assert.equal(literal.type, 'StringLiteral');
+      assert.deepEqual(literal.range, [0, 5]);
+      assert.deepEqual(literal.extra, { rawValue: 'foo', raw: `'foo'` });
+    });
+  });
+
+  describe('query', () => {
+    it('throws syntax errors on invalid selectors', () => {
+      assert.throws(() => {
+        Ratel.query('foo;', 'Identifier[');
+      }, SyntaxError);
+    });
+
+    it('returns matching nodes', () => {
+      const nodes = JSON.parse(Ratel.query(`require('a'); foo('b');`, 'CallExpression[callee.name=require] > Literal'));
+      assert.deepEqual(nodes, [{ type: 'Literal', start: 8, end: 11 }]);
+    });
   });
 });
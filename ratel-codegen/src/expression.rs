@@ -1,4 +1,4 @@
-use ratel::ast::{Expression, Literal, OperatorKind, Property, PropertyKey, Pattern};
+use ratel::ast::{Expression, ExpressionNode, Literal, OperatorKind, Property, PropertyKey, Pattern};
 use ratel::ast::expression::*;
 
 use {ToCode, Generator};
@@ -30,6 +30,7 @@ impl<'ast, G: Generator> ToCode<G> for Expression<'ast> {
             Object(ref object)           => gen.write(object),
             Function(ref function)       => gen.write(function),
             Class(ref class)             => gen.write(class),
+            TypeCast(ref cast)           => gen.write(cast),
         }
     }
 }
@@ -90,7 +91,7 @@ impl<'ast, G: Generator> ToCode<G> for Property<'ast> {
                 gen.write(key);
                 gen.write_byte(b':');
                 gen.write_pretty(b' ');
-                gen.write(value);
+                gen.write_expression(value, 1);
             },
             Method {
                 ref key,
@@ -114,15 +115,25 @@ impl<'ast, G: Generator> ToCode<G> for ArrayExpression<'ast> {
     #[inline]
     fn to_code(&self, gen: &mut G) {
         gen.write_byte(b'[');
-        gen.write_list(&self.body);
+        gen.write_expressions(&self.body);
         gen.write_byte(b']');
     }
 }
 
+/// Members and calls can be chained without parenthesis, `a.b().c`, other
+/// expressions are wrapped if they bind weaker, `(a + b).c`.
+#[inline]
+fn write_object<'ast, G: Generator>(object: &ExpressionNode<'ast>, gen: &mut G) {
+    match object.item {
+        Expression::Member(_) | Expression::Call(_) => gen.write(object),
+        _                                           => gen.write_expression(object, 19),
+    }
+}
+
 impl<'ast, G: Generator> ToCode<G> for MemberExpression<'ast> {
     #[inline]
     fn to_code(&self, gen: &mut G) {
-        gen.write_expression(&self.object, 19);
+        write_object(&self.object, gen);
         gen.write_byte(b'.');
         gen.write(&self.property);
     }
@@ -131,7 +142,7 @@ impl<'ast, G: Generator> ToCode<G> for MemberExpression<'ast> {
 impl<'ast, G: Generator> ToCode<G> for ComputedMemberExpression<'ast> {
     #[inline]
     fn to_code(&self, gen: &mut G) {
-        gen.write_expression(&self.object, 19);
+        write_object(&self.object, gen);
         gen.write_byte(b'[');
         gen.write(&self.property);
         gen.write_byte(b']');
@@ -143,7 +154,7 @@ impl<'ast, G: Generator> ToCode<G> for CallExpression<'ast> {
     fn to_code(&self, gen: &mut G) {
         gen.write(&self.callee);
         gen.write_byte(b'(');
-        gen.write_list(&self.arguments);
+        gen.write_expressions(&self.arguments);
         gen.write_byte(b')');
     }
 }
@@ -175,6 +186,28 @@ impl<'ast, G: Generator> ToCode<G> for BinaryExpression<'ast> {
     }
 }
 
+/// Whether a call in the callee of `new` would take the arguments of
+/// `new` as its own, `new (a.b())()`.
+fn has_call(callee: &ExpressionNode) -> bool {
+    match callee.item {
+        Expression::Call(_)                                                 => true,
+        Expression::Member(MemberExpression { object, .. })                 |
+        Expression::ComputedMember(ComputedMemberExpression { object, .. }) => has_call(&object),
+        _                                                                   => false,
+    }
+}
+
+#[inline]
+fn write_callee<'ast, G: Generator>(callee: &ExpressionNode<'ast>, gen: &mut G) {
+    if has_call(callee) {
+        gen.write_byte(b'(');
+        gen.write(callee);
+        gen.write_byte(b')');
+    } else {
+        gen.write_expression(callee, 15);
+    }
+}
+
 impl<'ast, G: Generator> ToCode<G> for PrefixExpression<'ast> {
     #[inline]
     fn to_code(&self, gen: &mut G) {
@@ -182,7 +215,21 @@ impl<'ast, G: Generator> ToCode<G> for PrefixExpression<'ast> {
         if self.operator.is_word() {
             gen.write_byte(b' ');
         }
-        gen.write(&self.operand);
+
+        if self.operator != OperatorKind::New {
+            return gen.write_expression(&self.operand, 15);
+        }
+
+        // Arguments of `new` are held by a call as its operand
+        match self.operand.item {
+            Expression::Call(CallExpression { callee, arguments }) => {
+                write_callee(&callee, gen);
+                gen.write_byte(b'(');
+                gen.write_expressions(&arguments);
+                gen.write_byte(b')');
+            },
+            _ => write_callee(&self.operand, gen),
+        }
     }
 }
 
@@ -197,15 +244,15 @@ impl<'ast, G: Generator> ToCode<G> for PostfixExpression<'ast> {
 impl<'ast, G: Generator> ToCode<G> for ConditionalExpression<'ast> {
     #[inline]
     fn to_code(&self, gen: &mut G) {
-        gen.write(&self.test);
+        gen.write_expression(&self.test, 5);
         gen.write_pretty(b' ');
         gen.write_byte(b'?');
         gen.write_pretty(b' ');
-        gen.write(&self.consequent);
+        gen.write_expression(&self.consequent, 1);
         gen.write_pretty(b' ');
         gen.write_byte(b':');
         gen.write_pretty(b' ');
-        gen.write(&self.alternate);
+        gen.write_expression(&self.alternate, 1);
     }
 }
 
@@ -250,7 +297,7 @@ impl<'ast, G: Generator> ToCode<G> for SpreadExpression<'ast> {
     #[inline]
     fn to_code(&self, gen: &mut G) {
         gen.write_bytes(b"...");
-        gen.write(&self.argument);
+        gen.write_expression(&self.argument, 1);
     }
 }
 
@@ -258,7 +305,7 @@ impl<'ast, G: Generator> ToCode<G> for ArrowBody<'ast> {
     #[inline]
     fn to_code(&self, gen: &mut G) {
         match *self {
-            ArrowBody::Expression(ref expression) => gen.write(expression),
+            ArrowBody::Expression(ref expression) => gen.write_expression(expression, 1),
             ArrowBody::Block(ref block)           => gen.write(block),
         }
     }
@@ -267,12 +314,18 @@ impl<'ast, G: Generator> ToCode<G> for ArrowBody<'ast> {
 impl<'ast, G: Generator> ToCode<G> for ArrowExpression<'ast> {
     #[inline]
     fn to_code(&self, gen: &mut G) {
+        let annotated = !self.type_params.is_empty() || self.return_type.is_some();
+
         match self.params.only_element().map(|el| &el.item) {
-            Some(&Pattern::Identifier(ref ident)) => gen.write(ident),
+            Some(&Pattern::Identifier(ref ident)) if !annotated => gen.write(ident),
             _ => {
+                gen.write_type_params(&self.type_params);
                 gen.write_byte(b'(');
                 gen.write_list(&self.params);
                 gen.write_byte(b')');
+                if let Some(ref return_type) = self.return_type {
+                    gen.write_annotation(return_type);
+                }
             }
         }
         gen.write_pretty(b' ');
@@ -338,6 +391,15 @@ mod test {
         assert_min("foo`${ 10 }`", "foo`${10}`;");
     }
 
+    #[test]
+    fn template_expression_escapes() {
+        assert_min(r"`\`${ a }\${b}`", r"`\`${a}\${b}`;");
+        assert_min(r"`\\${ a }\n\u0041`", r"`\\${a}\n\u0041`;");
+        assert_min(r#"`"quoted" 'quoted'`"#, r#"`"quoted" 'quoted'`;"#);
+        assert_min("`line\nbreak`", "`line\nbreak`;");
+        assert_min(r"tag`\unicode and \u{55}`", r"tag`\unicode and \u{55}`;");
+    }
+
     #[test]
     fn sequence_expression() {
         assert_min("foo, bar, baz;", "foo,bar,baz;");
@@ -350,6 +412,16 @@ mod test {
         assert_min("(1,2,3)+4;", "(1,2,3)+4;");
     }
 
+    #[test]
+    fn sequence_expression_in_list() {
+        assert_min("foo((a, b), c);", "foo((a,b),c);");
+        assert_min("[(a, b), c];", "[(a,b),c];");
+        assert_min("({ a: (b, c), d });", "({a:(b,c),d});");
+        assert_min("foo(...(a, b));", "foo(...(a,b));");
+        assert_min("var a = (b, c), d;", "var a=(b,c),d;");
+        assert_min("() => (a, b);", "()=>(a,b);");
+    }
+
     #[test]
     fn binary_expression() {
         assert_min("a = 10", "a=10;");
@@ -391,8 +463,15 @@ mod test {
         assert_min("++foo", "++foo;");
         assert_min("--foo", "--foo;");
         assert_min("new foo", "new foo;");
+        assert_min("new foo.bar(baz)", "new foo.bar(baz);");
+        assert_min("new (foo())()", "new (foo())();");
+        assert_min("new (foo.bar().baz)()", "new (foo.bar().baz)();");
+        assert_min("new (foo || bar)()", "new (foo||bar)();");
         assert_min("void foo", "void foo;");
         assert_min("typeof foo", "typeof foo;");
+        assert_min("!(foo = bar)", "!(foo=bar);");
+        assert_min("typeof (foo + bar)", "typeof (foo+bar);");
+        assert_min("!foo.bar()", "!foo.bar();");
     }
 
     #[test]
@@ -403,7 +482,11 @@ mod test {
 
     #[test]
     fn conditional_expression() {
-        assert_min("true ? foo : bar", "true?foo:bar;")
+        assert_min("true ? foo : bar", "true?foo:bar;");
+        assert_min("a ? (b, c) : (d, e)", "a?(b,c):(d,e);");
+        assert_min("(a ? b : c) ? d : e", "(a?b:c)?d:e;");
+        assert_min("(a = b) ? c : d", "(a=b)?c:d;");
+        assert_min("a || b ? c : d", "a||b?c:d;");
     }
 
     #[test]
@@ -434,6 +517,9 @@ mod test {
         assert_min("10..fooz", "10..fooz;");
         assert_min("foo[10]", "foo[10];");
         assert_min(r#"foo["bar"]"#, r#"foo["bar"];"#);
+        assert_min("foo.bar.baz", "foo.bar.baz;");
+        assert_min("foo().bar[baz]", "foo().bar[baz];");
+        assert_min("(a + b).c", "(a+b).c;");
     }
 
     #[test]
@@ -1,4 +1,4 @@
-use ratel::ast::{Function, Class, ClassMember, Name, EmptyName, MandatoryName, OptionalName, MethodKind};
+use ratel::ast::{Function, Class, ClassMember, Name, EmptyName, MandatoryName, OptionalName, MethodKind, Type};
 
 use {ToCode, Generator};
 
@@ -60,9 +60,13 @@ impl<'ast, G, N> ToCode<G> for Function<'ast, N> where
     fn to_code(&self, gen: &mut G) {
         N::write_function(gen);
         gen.write(&self.name);
+        gen.write_type_params(&self.type_params);
         gen.write_byte(b'(');
         gen.write_list(&self.params);
         gen.write_byte(b')');
+        if let Some(ref return_type) = self.return_type {
+            gen.write_annotation(return_type);
+        }
         gen.write_pretty(b' ');
         gen.write(&self.body);
     }
@@ -106,6 +110,41 @@ impl<'ast, G: Generator> ToCode<G> for ClassMember<'ast> {
                 gen.write_pretty(b' ');
                 gen.write(value);
                 gen.write_byte(b';');
+            },
+            Property {
+                is_static,
+                ref key,
+                ref annotation,
+                ref value,
+            } => {
+                // Declaration without a value only exists for the type checker
+                if let (Type::Comment(annotation), None) = (annotation.item, *value) {
+                    gen.write_bytes(b"/*::");
+                    gen.write_pretty(b' ');
+                    if is_static {
+                        gen.write_bytes(b"static ");
+                    }
+                    gen.write(key);
+                    gen.write_byte(b':');
+                    gen.write_pretty(b' ');
+                    gen.write(&annotation);
+                    gen.write_byte(b';');
+                    gen.write_pretty(b' ');
+                    gen.write_bytes(b"*/");
+                    return;
+                }
+                if is_static {
+                    gen.write_bytes(b"static ");
+                }
+                gen.write(key);
+                gen.write_annotation(annotation);
+                if let Some(ref value) = *value {
+                    gen.write_pretty(b' ');
+                    gen.write_byte(b'=');
+                    gen.write_pretty(b' ');
+                    gen.write(value);
+                }
+                gen.write_byte(b';');
             }
         }
     }
@@ -119,9 +158,15 @@ impl<'ast, G, N> ToCode<G> for Class<'ast, N> where
     fn to_code(&self, gen: &mut G) {
         N::write_class(gen);
         gen.write(&self.name);
+        gen.write_type_params(&self.type_params);
         if let Some(ref super_class) = self.extends {
             gen.write_bytes(b" extends ");
             gen.write(super_class);
+            gen.write_type_args(&self.super_type_args);
+        }
+        if !self.implements.is_empty() {
+            gen.write_bytes(b" implements ");
+            gen.write_list(self.implements);
         }
         gen.write_pretty(b' ');
         gen.write(&self.body);
@@ -130,7 +175,7 @@ impl<'ast, G, N> ToCode<G> for Class<'ast, N> where
 
 #[cfg(test)]
 mod test {
-    use assert_min;
+    use {assert_min, assert_flow_min};
 
     #[test]
     fn function() {
@@ -156,4 +201,15 @@ mod test {
         assert_min("class Foo { a = 10; b = 20; }", "class Foo{a=10;b=20;}");
         assert_min("class Foo { static a = 10; b = 20; }", "class Foo{static a=10;b=20;}");
     }
+
+    #[test]
+    fn flow_function() {
+        assert_flow_min("function foo<T>(a: T, b?: number = 1, ...c: T[]): ?T {}", "function foo<T>(a:T,b?:number=1,...c:T[]):?T{}");
+    }
+
+    #[test]
+    fn flow_class() {
+        assert_flow_min("class Foo<T> extends Bar<T> implements Baz {}", "class Foo<T> extends Bar<T> implements Baz{}");
+        assert_flow_min("class Foo { a: number; static b: string = 'b'; }", "class Foo{a:number;static b:string='b';}");
+    }
 }
@@ -3,12 +3,17 @@
 extern crate pretty_assertions;
 extern crate ratel;
 
-use ratel::ast::{Node, ExpressionNode, Loc, Block, Pattern};
+#[cfg(test)]
+extern crate serde_json;
+
+use ratel::ast::{Node, ExpressionNode, ExpressionList, Loc, Block, Pattern};
+use ratel::ast::{Type, TypeNode, TypeList, TypeParameterList};
 use ratel::Module;
 
 mod expression;
 mod statement;
 mod function;
+mod types;
 
 pub trait Generator: Sized {
     type Output;
@@ -37,6 +42,73 @@ pub trait Generator: Sized {
         }
     }
 
+    /// Write a list of expressions separated by commas, sequences are
+    /// wrapped in parenthesis so that they stay a single item
+    #[inline]
+    fn write_expressions<'ast>(&mut self, items: &ExpressionList<'ast>) {
+        let mut items = items.iter();
+
+        if let Some(item) = items.next() {
+            self.write_expression(item, 1);
+        }
+
+        for item in items {
+            self.write_byte(b',');
+            self.write_pretty(b' ');
+            self.write_expression(item, 1);
+        }
+    }
+
+    /// Helper that adds parenthesis if required by the binding power of inner type
+    #[inline]
+    fn write_type<'ast>(&mut self, item: &TypeNode<'ast>, bp: u8) {
+        if item.binding_power() < bp {
+            self.write_byte(b'(');
+            item.to_code(self);
+            self.write_byte(b')');
+        } else {
+            item.to_code(self);
+        }
+    }
+
+    /// Helper that writes a type annotation including the colon, or places
+    /// it in a `/*: */` comment if it was moved there by the transformer
+    #[inline]
+    fn write_annotation<'ast>(&mut self, annotation: &TypeNode<'ast>) {
+        match annotation.item {
+            Type::Comment(ref annotation) => {
+                self.write_bytes(b"/*:");
+                self.write_pretty(b' ');
+                self.write(annotation);
+                self.write_pretty(b' ');
+                self.write_bytes(b"*/");
+            },
+            _ => {
+                self.write_byte(b':');
+                self.write_pretty(b' ');
+                self.write(annotation);
+            }
+        }
+    }
+
+    #[inline]
+    fn write_type_params<'ast>(&mut self, params: &TypeParameterList<'ast>) {
+        if !params.is_empty() {
+            self.write_byte(b'<');
+            self.write_list(params);
+            self.write_byte(b'>');
+        }
+    }
+
+    #[inline]
+    fn write_type_args<'ast>(&mut self, args: &TypeList<'ast>) {
+        if !args.is_empty() {
+            self.write_byte(b'<');
+            self.write_list(args);
+            self.write_byte(b'>');
+        }
+    }
+
     #[inline]
     fn write_list<'a, T, I>(&mut self, items: I) where
         T: ToCode<Self> + 'a,
@@ -177,10 +249,22 @@ impl Generator for PrettyGenerator {
     }
 }
 
+/// The hashbang always needs its own line, regardless of minification.
+#[inline]
+fn write_hashbang<G: Generator>(gen: &mut G, module: &Module) {
+    if let Some(hashbang) = module.hashbang() {
+        gen.write_bytes(b"#!");
+        gen.write_bytes(hashbang.as_bytes());
+        gen.write_byte(b'\n');
+    }
+}
+
 pub fn codegen<'ast>(module: &Module, minify: bool) -> String {
     if minify {
         let mut gen = MinifyingGenerator::new();
 
+        write_hashbang(&mut gen, module);
+
         for statement in module.body() {
             gen.write(statement);
         }
@@ -190,6 +274,8 @@ pub fn codegen<'ast>(module: &Module, minify: bool) -> String {
         let mut gen = PrettyGenerator::new();
         let mut body = module.body().iter();
 
+        write_hashbang(&mut gen, module);
+
         gen.write(&body.next().map(|s| *s));
 
         for statement in body {
@@ -305,6 +391,31 @@ impl<'ast, G: Generator> ToCode<G> for Pattern<'ast> {
                 gen.write_bytes(b"...");
                 gen.write(argument);
             },
+            Typed {
+                ref pattern,
+                optional,
+                ref annotation,
+            } => {
+                gen.write(pattern);
+                match annotation.item {
+                    // `a?/*: T */` wouldn't be valid JavaScript
+                    Type::Comment(ref annotation) if optional => {
+                        gen.write_bytes(b"/*::");
+                        gen.write_pretty(b' ');
+                        gen.write_bytes(b"?:");
+                        gen.write_pretty(b' ');
+                        gen.write(annotation);
+                        gen.write_pretty(b' ');
+                        gen.write_bytes(b"*/");
+                    },
+                    _ => {
+                        if optional {
+                            gen.write_byte(b'?');
+                        }
+                        gen.write_annotation(annotation);
+                    }
+                }
+            },
             AssignmentPattern {
                 ref left,
                 ref right,
@@ -350,6 +461,15 @@ fn assert_min(source: &str, expected: &str) {
     assert_eq!(codegen(&module, true).as_str(), expected);
 }
 
+#[cfg(test)]
+fn assert_module_min(source: &str, expected: &str) {
+    use ratel::{parse_with_options, ParseOptions, Goal};
+
+    let module = parse_with_options(source, ParseOptions { goal: Goal::Module, ..ParseOptions::default() }).unwrap();
+
+    assert_eq!(codegen(&module, true).as_str(), expected);
+}
+
 #[cfg(test)]
 fn assert_pretty(source: &str, expected: &str) {
     use ratel::parse;
@@ -358,3 +478,28 @@ fn assert_pretty(source: &str, expected: &str) {
 
     assert_eq!(codegen(&module, false).as_str(), expected);
 }
+
+#[cfg(test)]
+fn assert_flow_min(source: &str, expected: &str) {
+    use ratel::{parse_with_options, ParseOptions, Goal};
+
+    let module = parse_with_options(source, ParseOptions { goal: Goal::Module, flow: true, ..ParseOptions::default() }).unwrap();
+
+    assert_eq!(codegen(&module, true).as_str(), expected);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ratel::{parse_with_options, estree, ParseOptions, Goal};
+
+    #[test]
+    fn estree_roundtrip() {
+        let source = "import foo from 'foo';class Bar extends foo{constructor(){this.baz=[1,...qux]}}export default function(a,b=2){for(let i in a)b+=i;return new Bar(`${a}!`)}";
+
+        let module = parse_with_options(source, ParseOptions { goal: Goal::Module, ..ParseOptions::default() }).unwrap();
+        let json = serde_json::to_string(&module).unwrap();
+
+        assert_eq!(codegen(&estree::from_str(&json).unwrap(), true), codegen(&module, true));
+    }
+}
@@ -1,4 +1,4 @@
-use ratel::ast::{Statement, Declarator, DeclarationKind};
+use ratel::ast::{Statement, Expression, Declarator, DeclarationKind};
 use ratel::ast::statement::*;
 
 use {ToCode, Generator};
@@ -31,6 +31,7 @@ impl<'ast, G: Generator> ToCode<G> for Statement<'ast> {
             If(ref if_statement)         => gen.write(if_statement),
             While(ref while_statement)   => gen.write(while_statement),
             Do(ref do_statement)         => gen.write(do_statement),
+            With(ref with_statement)     => gen.write(with_statement),
             For(ref for_statement)       => gen.write(for_statement),
             ForIn(ref for_in)            => gen.write(for_in),
             ForOf(ref for_of)            => gen.write(for_of),
@@ -40,7 +41,18 @@ impl<'ast, G: Generator> ToCode<G> for Statement<'ast> {
             Function(ref function)       => gen.write(function),
             Class(ref class)             => gen.write(class),
             Continue(ref cont)           => gen.write(cont),
-            Switch(ref switch)           => gen.write(switch)
+            Switch(ref switch)           => gen.write(switch),
+            TypeAlias(ref alias)         => gen.write(alias),
+            Declare(ref declare)         => gen.write(declare),
+            Import(ref import)           => gen.write(import),
+            Export(ref export)           => gen.write(export),
+            Commented(ref statement)     => {
+                gen.write_bytes(b"/*::");
+                gen.write_pretty(b' ');
+                gen.write(statement);
+                gen.write_pretty(b' ');
+                gen.write_bytes(b"*/");
+            },
         }
     }
 }
@@ -67,7 +79,7 @@ impl<'ast, G: Generator> ToCode<G> for Declarator<'ast> {
             gen.write_pretty(b' ');
             gen.write_byte(b'=');
             gen.write_pretty(b' ');
-            gen.write(init);
+            gen.write_expression(init, 1);
         }
     }
 }
@@ -158,6 +170,19 @@ impl<'ast, G: Generator> ToCode<G> for WhileStatement<'ast> {
     }
 }
 
+impl<'ast, G: Generator> ToCode<G> for WithStatement<'ast> {
+    #[inline]
+    fn to_code(&self, gen: &mut G) {
+        gen.write_bytes(b"with");
+        gen.write_pretty(b' ');
+        gen.write_byte(b'(');
+        gen.write(&self.object);
+        gen.write_byte(b')');
+        gen.write_pretty(b' ');
+        gen.write(&self.body);
+    }
+}
+
 impl<'ast, G: Generator> ToCode<G> for DoStatement<'ast> {
     #[inline]
     fn to_code(&self, gen: &mut G) {
@@ -252,6 +277,8 @@ impl<'ast, G: Generator> ToCode<G> for TryStatement<'ast> {
             gen.write(&handler.body);
         }
         if let Some(ref finalizer) = self.finalizer {
+            gen.write_pretty(b' ');
+            gen.write_bytes(b"finally");
             gen.write_pretty(b' ');
             gen.write(finalizer);
         }
@@ -309,9 +336,126 @@ impl<'ast, G: Generator> ToCode<G> for SwitchCase<'ast> {
     }
 }
 
+impl<'ast, G: Generator> ToCode<G> for ImportSpecifier<'ast> {
+    #[inline]
+    fn to_code(&self, gen: &mut G) {
+        use ratel::ast::statement::ImportSpecifier::*;
+
+        match *self {
+            Default(ref local) => gen.write(local),
+            Namespace(ref local) => {
+                gen.write_bytes(b"* as ");
+                gen.write(local);
+            },
+            Named { ref imported, ref local } => {
+                gen.write(imported);
+                if imported != local {
+                    gen.write_bytes(b" as ");
+                    gen.write(local);
+                }
+            },
+        }
+    }
+}
+
+impl<'ast, G: Generator> ToCode<G> for ImportStatement<'ast> {
+    #[inline]
+    fn to_code(&self, gen: &mut G) {
+        gen.write_bytes(b"import ");
+        match self.kind {
+            ImportKind::Value  => {},
+            ImportKind::Type   => gen.write_bytes(b"type "),
+            ImportKind::Typeof => gen.write_bytes(b"typeof "),
+        }
+
+        if self.specifiers.is_empty() {
+            gen.write(&self.source);
+            gen.write_byte(b';');
+
+            return;
+        }
+
+        let mut specifiers = self.specifiers.iter().peekable();
+        let mut named = false;
+
+        while let Some(specifier) = specifiers.next() {
+            if !named && matches!(specifier.item, ImportSpecifier::Named { .. }) {
+                gen.write_byte(b'{');
+                named = true;
+            }
+
+            gen.write(specifier);
+
+            if specifiers.peek().is_some() {
+                gen.write_byte(b',');
+                gen.write_pretty(b' ');
+            }
+        }
+
+        if named {
+            gen.write_byte(b'}');
+        }
+
+        gen.write_bytes(b" from ");
+        gen.write(&self.source);
+        gen.write_byte(b';');
+    }
+}
+
+impl<'ast, G: Generator> ToCode<G> for ExportSpecifier<'ast> {
+    #[inline]
+    fn to_code(&self, gen: &mut G) {
+        gen.write(&self.local);
+        if self.local != self.exported {
+            gen.write_bytes(b" as ");
+            gen.write(&self.exported);
+        }
+    }
+}
+
+impl<'ast, G: Generator> ToCode<G> for ExportStatement<'ast> {
+    #[inline]
+    fn to_code(&self, gen: &mut G) {
+        use ratel::ast::statement::ExportStatement::*;
+
+        gen.write_bytes(b"export ");
+
+        match *self {
+            Declaration(ref declaration) => gen.write(declaration),
+            Default(ref expression) => {
+                gen.write_bytes(b"default ");
+                gen.write(expression);
+
+                match expression.item {
+                    Expression::Function(_) | Expression::Class(_) => {},
+                    _ => gen.write_byte(b';'),
+                }
+            },
+            Named { kind, ref specifiers, ref source } => {
+                if kind == ExportKind::Type {
+                    gen.write_bytes(b"type ");
+                }
+                gen.write_byte(b'{');
+                gen.write_list(specifiers);
+                gen.write_byte(b'}');
+                if let Some(ref source) = *source {
+                    gen.write_bytes(b" from ");
+                    gen.write(source);
+                }
+                gen.write_byte(b';');
+            },
+            All { ref source } => {
+                gen.write_bytes(b"* from ");
+                gen.write(source);
+                gen.write_byte(b';');
+            },
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
-    use assert_min;
+    use {assert_min, assert_module_min};
 
     #[test]
     fn block_statement() {
@@ -362,6 +506,12 @@ mod test {
         assert_min("while (true) { foo; }", "while(true){foo;}");
     }
 
+    #[test]
+    fn with_statement() {
+        assert_min("with (foo) bar;", "with(foo)bar;");
+        assert_min("with (foo) { bar; }", "with(foo){bar;}");
+    }
+
     #[test]
     fn do_statement() {
         assert_min("do { foo; } while (true)", "do{foo;}while(true)");
@@ -378,4 +528,34 @@ mod test {
         assert_min("for (foo of bar){}", "for(foo of bar){}");
         assert_min("for (let foo of bar){}", "for(let foo of bar){}");
     }
+
+    #[test]
+    fn try_statement() {
+        assert_min("try { foo; } catch (err) { bar; }", "try{foo;}catch(err){bar;}");
+        assert_min("try { foo; } finally { bar; }", "try{foo;}finally{bar;}");
+        assert_min("try {} catch (err) {} finally {}", "try{}catch(err){}finally{}");
+    }
+
+    #[test]
+    fn import_statement() {
+        assert_module_min("import foo from 'foo';", "import foo from 'foo';");
+        assert_module_min("import * as foo from 'foo';", "import * as foo from 'foo';");
+        assert_module_min("import foo, { bar as baz } from 'foo';", "import foo,{bar as baz} from 'foo';");
+        assert_module_min("import 'foo';", "import 'foo';");
+    }
+
+    #[test]
+    fn export_statement() {
+        assert_module_min("export default foo;", "export default foo;");
+        assert_module_min("export default function () {}", "export default function(){}");
+        assert_module_min("export const foo = 1;", "export const foo=1;");
+        assert_module_min("export { foo, bar as baz };", "export {foo,bar as baz};");
+        assert_module_min("export { foo } from 'foo';", "export {foo} from 'foo';");
+        assert_module_min("export * from 'foo';", "export * from 'foo';");
+    }
+
+    #[test]
+    fn hashbang() {
+        assert_min("#!/usr/bin/env node\nfoo;", "#!/usr/bin/env node\nfoo;");
+    }
 }
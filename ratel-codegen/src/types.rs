@@ -0,0 +1,327 @@
+use ratel::ast::{Type, Primitive, TypeParameter};
+use ratel::ast::types::{FunctionType, FunctionTypeParam, ObjectTypeMember};
+use ratel::ast::statement::{TypeAliasStatement, DeclareStatement};
+use ratel::ast::expression::TypeCastExpression;
+
+use {ToCode, Generator};
+
+
+impl<G: Generator> ToCode<G> for Primitive {
+    #[inline]
+    fn to_code(&self, gen: &mut G) {
+        use ratel::ast::Primitive::*;
+
+        match *self {
+            Number  => gen.write_bytes(b"number"),
+            String  => gen.write_bytes(b"string"),
+            Boolean => gen.write_bytes(b"boolean"),
+            Null    => gen.write_bytes(b"null"),
+            Void    => gen.write_bytes(b"void"),
+            Symbol  => gen.write_bytes(b"symbol"),
+        }
+    }
+}
+
+impl<'ast, G: Generator> ToCode<G> for Type<'ast> {
+    #[inline]
+    fn to_code(&self, gen: &mut G) {
+        use ratel::ast::Type::*;
+
+        match *self {
+            Any                      => gen.write_bytes(b"any"),
+            Mixed                    => gen.write_bytes(b"mixed"),
+            Empty                    => gen.write_bytes(b"empty"),
+            Existential              => gen.write_byte(b'*'),
+            Primitive(ref primitive) => gen.write(primitive),
+            StringLiteral(ref value) |
+            NumberLiteral(ref value) |
+            Identifier(ref value)    => gen.write(value),
+            BooleanLiteral(true)     => gen.write_bytes(b"true"),
+            BooleanLiteral(false)    => gen.write_bytes(b"false"),
+            Typeof(ref ident) => {
+                gen.write_bytes(b"typeof ");
+                gen.write(ident);
+            },
+            Nullable(ref inner) => {
+                gen.write_byte(b'?');
+                gen.write_type(inner, 4);
+            },
+            Array(ref inner) => {
+                gen.write_type(inner, 5);
+                gen.write_bytes(b"[]");
+            },
+            Union { ref variants } => {
+                let mut variants = variants.iter();
+
+                if let Some(variant) = variants.next() {
+                    gen.write_type(variant, 3);
+                }
+
+                for variant in variants {
+                    gen.write_pretty(b' ');
+                    gen.write_byte(b'|');
+                    gen.write_pretty(b' ');
+                    gen.write_type(variant, 3);
+                }
+            },
+            Intersection { ref variants } => {
+                let mut variants = variants.iter();
+
+                if let Some(variant) = variants.next() {
+                    gen.write_type(variant, 4);
+                }
+
+                for variant in variants {
+                    gen.write_pretty(b' ');
+                    gen.write_byte(b'&');
+                    gen.write_pretty(b' ');
+                    gen.write_type(variant, 4);
+                }
+            },
+            Generic { ref ident, ref subtypes } => {
+                gen.write(ident);
+                gen.write_type_args(subtypes);
+            },
+            Tuple { ref elements } => {
+                gen.write_byte(b'[');
+                gen.write_list(elements);
+                gen.write_byte(b']');
+            },
+            Object { exact, ref members } => {
+                match exact {
+                    true  => gen.write_bytes(b"{|"),
+                    false => gen.write_byte(b'{'),
+                }
+                gen.write_list(members);
+                match exact {
+                    true  => gen.write_bytes(b"|}"),
+                    false => gen.write_byte(b'}'),
+                }
+            },
+            Function(ref function) => {
+                write_function_type_params(gen, function);
+                gen.write_pretty(b' ');
+                gen.write_bytes(b"=>");
+                gen.write_pretty(b' ');
+                gen.write(&function.return_type);
+            },
+            Comment(ref annotation) => gen.write(annotation),
+        }
+    }
+}
+
+/// Write the type parameters and parameters of a function type, `<T>(a: T)`.
+#[inline]
+fn write_function_type_params<'ast, G: Generator>(gen: &mut G, function: &FunctionType<'ast>) {
+    gen.write_type_params(&function.type_params);
+    gen.write_byte(b'(');
+    gen.write_list(function.params);
+    if let Some(ref rest) = function.rest {
+        if !function.params.is_empty() {
+            gen.write_byte(b',');
+            gen.write_pretty(b' ');
+        }
+        gen.write_bytes(b"...");
+        gen.write(rest);
+    }
+    gen.write_byte(b')');
+}
+
+/// Write a function type in the method form, `(a: T): U`.
+#[inline]
+fn write_method_type<'ast, G: Generator>(gen: &mut G, function: &Type<'ast>) {
+    match *function {
+        Type::Function(ref function) => {
+            write_function_type_params(gen, function);
+            gen.write_annotation(&function.return_type);
+        },
+        _ => gen.write(function),
+    }
+}
+
+impl<'ast, G: Generator> ToCode<G> for TypeParameter<'ast> {
+    #[inline]
+    fn to_code(&self, gen: &mut G) {
+        gen.write(&self.name);
+        if let Some(ref bound) = self.bound {
+            gen.write_annotation(bound);
+        }
+        if let Some(ref default) = self.default {
+            gen.write_pretty(b' ');
+            gen.write_byte(b'=');
+            gen.write_pretty(b' ');
+            gen.write(default);
+        }
+    }
+}
+
+impl<'ast, G: Generator> ToCode<G> for FunctionTypeParam<'ast> {
+    #[inline]
+    fn to_code(&self, gen: &mut G) {
+        match self.name {
+            Some(ref name) => {
+                gen.write(name);
+                if self.optional {
+                    gen.write_byte(b'?');
+                }
+                gen.write_annotation(&self.annotation);
+            },
+            None => gen.write(&self.annotation),
+        }
+    }
+}
+
+impl<'ast, G: Generator> ToCode<G> for ObjectTypeMember<'ast> {
+    #[inline]
+    fn to_code(&self, gen: &mut G) {
+        use ratel::ast::types::ObjectTypeMember::*;
+
+        match *self {
+            Property { ref key, optional, method, ref value } => {
+                gen.write(key);
+                if method {
+                    write_method_type(gen, value);
+                } else {
+                    if optional {
+                        gen.write_byte(b'?');
+                    }
+                    gen.write_annotation(value);
+                }
+            },
+            Indexer { ref name, ref key, ref value } => {
+                gen.write_byte(b'[');
+                if let Some(ref name) = *name {
+                    gen.write(name);
+                    gen.write_byte(b':');
+                    gen.write_pretty(b' ');
+                }
+                gen.write(key);
+                gen.write_byte(b']');
+                gen.write_annotation(value);
+            },
+            Spread(ref argument) => {
+                gen.write_bytes(b"...");
+                gen.write(argument);
+            },
+        }
+    }
+}
+
+impl<'ast, G: Generator> ToCode<G> for TypeCastExpression<'ast> {
+    #[inline]
+    fn to_code(&self, gen: &mut G) {
+        gen.write_byte(b'(');
+        gen.write(&self.expression);
+        gen.write_annotation(&self.annotation);
+        gen.write_byte(b')');
+    }
+}
+
+/// Shared by type alias statements and `declare type`.
+#[inline]
+fn write_type_alias<'ast, G: Generator>(gen: &mut G, alias: &TypeAliasStatement<'ast>) {
+    if alias.opaque {
+        gen.write_bytes(b"opaque ");
+    }
+    gen.write_bytes(b"type ");
+    gen.write(&alias.name);
+    gen.write_type_params(&alias.type_params);
+    if let Some(ref supertype) = alias.supertype {
+        gen.write_annotation(supertype);
+    }
+    if let Some(ref value) = alias.value {
+        gen.write_pretty(b' ');
+        gen.write_byte(b'=');
+        gen.write_pretty(b' ');
+        gen.write(value);
+    }
+    gen.write_byte(b';');
+}
+
+impl<'ast, G: Generator> ToCode<G> for TypeAliasStatement<'ast> {
+    #[inline]
+    fn to_code(&self, gen: &mut G) {
+        write_type_alias(gen, self);
+    }
+}
+
+impl<'ast, G: Generator> ToCode<G> for DeclareStatement<'ast> {
+    #[inline]
+    fn to_code(&self, gen: &mut G) {
+        use ratel::ast::statement::DeclareStatement::*;
+
+        gen.write_bytes(b"declare ");
+
+        match *self {
+            Variable { ref kind, ref name, ref annotation } => {
+                gen.write(kind);
+                gen.write(name);
+                if let Some(ref annotation) = *annotation {
+                    gen.write_annotation(annotation);
+                }
+                gen.write_byte(b';');
+            },
+            Function { ref name, ref annotation } => {
+                gen.write_bytes(b"function ");
+                gen.write(name);
+                write_method_type(gen, annotation);
+                gen.write_byte(b';');
+            },
+            Class { ref name, ref type_params, ref extends, ref body } => {
+                gen.write_bytes(b"class ");
+                gen.write(name);
+                gen.write_type_params(type_params);
+                if let Some(ref extends) = *extends {
+                    gen.write_bytes(b" extends ");
+                    gen.write(extends);
+                }
+                gen.write_pretty(b' ');
+                gen.write(body);
+            },
+            TypeAlias(ref alias) => write_type_alias(gen, alias),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use assert_flow_min;
+
+    #[test]
+    fn flow_annotations() {
+        assert_flow_min("let a: number = 1;", "let a:number=1;");
+        assert_flow_min("let a: ?(string | number)[];", "let a:?(string|number)[];");
+        assert_flow_min("let a: (A & B) | C;", "let a:A&B|C;");
+        assert_flow_min("let a: Map<string, Array<T>>;", "let a:Map<string,Array<T>>;");
+        assert_flow_min("let a: (x: number, ...rest: string[]) => void;", "let a:(x:number,...rest:string[])=>void;");
+        assert_flow_min("let a: {| a: 1, b?: 'b', [key: string]: mixed, c(): void |};", "let a:{|a:1,b?:'b',[key:string]:mixed,c():void|};");
+        assert_flow_min("let a: [typeof foo, *];", "let a:[typeof foo,*];");
+    }
+
+    #[test]
+    fn flow_type_cast() {
+        assert_flow_min("(foo: any);", "(foo:any);");
+        assert_flow_min("((a: number) => a);", "(a:number)=>a;");
+    }
+
+    #[test]
+    fn flow_arrow_functions() {
+        assert_flow_min("(a: number, b: string): boolean => a > b;", "(a:number,b:string):boolean=>a>b;");
+        assert_flow_min("(): void => {};", "():void=>{};");
+        assert_flow_min("(a): T => a;", "(a):T=>a;");
+        assert_flow_min("<T>(a: T): T => a;", "<T>(a:T):T=>a;");
+    }
+
+    #[test]
+    fn flow_statements() {
+        assert_flow_min("type A<T> = B<T>;", "type A<T>=B<T>;");
+        assert_flow_min("opaque type A: B = C;", "opaque type A:B=C;");
+        assert_flow_min("declare var a: number;", "declare var a:number;");
+        assert_flow_min("declare function foo(a: string): void;", "declare function foo(a:string):void;");
+        assert_flow_min("declare class Foo<T> extends Bar { a: T }", "declare class Foo<T> extends Bar{a:T}");
+        assert_flow_min("declare opaque type A;", "declare opaque type A;");
+        assert_flow_min("import type A, { B, C as D } from 'e';", "import type A,{B,C as D} from 'e';");
+        assert_flow_min("import typeof * as A from 'b';", "import typeof * as A from 'b';");
+        assert_flow_min("export type { A, B as C };", "export type {A,B as C};");
+    }
+}
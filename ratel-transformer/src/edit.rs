@@ -0,0 +1,213 @@
+//! Inserting and removing statements around the one being transformed.
+//!
+//! Hooks can't rebuild the statement list they are in while it is being
+//! traversed, so changes are queued on the `Transformer` and applied by the
+//! `ApplyEdits` visitor once all statements of the list have been visited.
+//! `ApplyEdits` has to come after the passes queueing changes:
+//!
+//! ```ignore
+//! module.traverse(&(PresetES2015, ApplyEdits), &mut transformer);
+//! ```
+
+use std::mem::{self, size_of};
+
+use ratel::ast::{StatementNode, StatementList};
+use toolshed::CopyCell;
+use ratel_visitor::{StaticVisitor, DynamicVisitor, NodePath};
+
+use Transformer;
+
+/// Change queued on an item of a statement list.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Edit<'ast> {
+    Before(StatementNode<'ast>),
+    After(StatementNode<'ast>),
+    Remove,
+}
+
+/// Statements are identified by their place in the list, which is the
+/// node hooks are called with, so that swapping them doesn't matter.
+#[inline]
+fn key<'ast>(node: &StatementNode<'ast>) -> *const StatementNode<'ast> {
+    node as *const StatementNode<'ast>
+}
+
+impl<'ast> Transformer<'ast> {
+    /// Insert a statement before `node`, which has to be an item of a
+    /// statement list, such as the body of a block or a function. Changes
+    /// to other statements, like the body of an `if` without a block, are
+    /// never applied.
+    #[inline]
+    pub fn insert_before(&mut self, node: &StatementNode<'ast>, statement: StatementNode<'ast>) {
+        self.edits.push((key(node), Edit::Before(statement)));
+    }
+
+    /// Insert a statement after `node`, after any statement that has been
+    /// inserted after it before.
+    #[inline]
+    pub fn insert_after(&mut self, node: &StatementNode<'ast>, statement: StatementNode<'ast>) {
+        self.edits.push((key(node), Edit::After(statement)));
+    }
+
+    /// Remove `node` from its statement list. Statements inserted around
+    /// it are kept.
+    #[inline]
+    pub fn remove(&mut self, node: &StatementNode<'ast>) {
+        self.edits.push((key(node), Edit::Remove));
+    }
+
+    /// Replace `node` with any number of statements.
+    pub fn replace_with_many(&mut self, node: &StatementNode<'ast>, statements: StatementList<'ast>) {
+        for statement in statements.iter() {
+            self.insert_before(node, *statement);
+        }
+
+        self.remove(node);
+    }
+
+    /// Apply the changes queued on the items of the list.
+    fn apply_edits(&mut self, body: &StatementList<'ast>) {
+        if self.edits.is_empty() || !body.iter().any(|node| self.edits.iter().any(|&(k, _)| k == key(node))) {
+            return;
+        }
+
+        let mut statements = Vec::new();
+
+        for node in body.iter() {
+            let mut removed = false;
+            let mut after = Vec::new();
+
+            // Changes on other lists stay queued
+            self.edits.retain(|&(k, edit)| {
+                if k != key(node) {
+                    return true;
+                }
+
+                match edit {
+                    Edit::Before(statement) => statements.push(statement),
+                    Edit::After(statement)  => after.push(statement),
+                    Edit::Remove            => removed = true,
+                }
+
+                false
+            });
+
+            if !removed {
+                statements.push(*node);
+            }
+
+            statements.extend(after);
+        }
+
+        let list = self.list(statements);
+
+        // The list is held by its parent, which hooks can't reach. A `List`
+        // is nothing but a `CopyCell` holding its first item, so it can be
+        // written the same way nodes are swapped.
+        debug_assert_eq!(size_of::<StatementList>(), size_of::<CopyCell<StatementList>>());
+
+        let cell: &CopyCell<StatementList<'ast>> = unsafe { mem::transmute(body) };
+
+        cell.set(list);
+    }
+}
+
+/// Applies the changes queued on statements when leaving their list.
+pub struct ApplyEdits;
+
+impl<'ast> StaticVisitor<'ast> for ApplyEdits {
+    type Context = Transformer<'ast>;
+
+    #[inline]
+    fn on_leave_statement_list(body: &StatementList<'ast>, _: &NodePath<'ast>, t: &mut Transformer<'ast>) {
+        t.apply_edits(body);
+    }
+
+    #[inline]
+    fn register(dv: &mut DynamicVisitor<'ast, Transformer<'ast>>) {
+        dv.on_leave_statement_list.push(ApplyEdits::on_leave_statement_list);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ratel::parse;
+    use ratel::ast::{NodeList, Statement, StatementNode, Expression, ExpressionNode};
+    use ratel::ast::statement::DeclarationStatement;
+    use ratel_visitor::{Visitable, Control};
+    use ratel_codegen::codegen;
+
+    struct EditTest;
+
+    impl<'ast> StaticVisitor<'ast> for EditTest {
+        type Context = Transformer<'ast>;
+
+        fn on_expression_statement(item: &ExpressionNode<'ast>, node: &StatementNode<'ast>, _: &NodePath<'ast>, t: &mut Transformer<'ast>) -> Control {
+            match item.item {
+                Expression::Identifier("before") => {
+                    let statement = t.alloc(expr!(t, "inserted"));
+                    t.insert_before(node, statement);
+                },
+                Expression::Identifier("after") => {
+                    t.insert_after(node, t.alloc(expr!(t, "first")));
+                    t.insert_after(node, t.alloc(expr!(t, "second")));
+                },
+                Expression::Identifier("remove") => t.remove(node),
+                _ => {},
+            }
+
+            Control::Continue
+        }
+
+        fn on_declaration_statement(item: &DeclarationStatement<'ast>, node: &StatementNode<'ast>, _: &NodePath<'ast>, t: &mut Transformer<'ast>) -> Control {
+            // Split declarators into their own declarations
+            if item.declarators.iter().nth(1).is_some() {
+                let statements = item.declarators.iter().map(|declarator| {
+                    t.alloc_as_loc::<Statement, _, _>(*declarator, DeclarationStatement {
+                        kind: item.kind,
+                        declarators: NodeList::from(t.arena, *declarator),
+                    })
+                }).collect::<Vec<_>>();
+
+                let statements = t.list(statements);
+
+                t.replace_with_many(node, statements);
+            }
+
+            Control::Continue
+        }
+
+        fn register(_dv: &mut DynamicVisitor<'ast, Transformer<'ast>>) {
+            unimplemented!()
+        }
+    }
+
+    fn assert_edits(source: &str, expected: &str) {
+        let module = parse(source).unwrap();
+        let mut transformer = Transformer::new(&module);
+
+        module.traverse(&(EditTest, ApplyEdits), &mut transformer);
+
+        assert_eq!(codegen(&module, true), expected);
+    }
+
+    #[test]
+    fn insert_and_remove() {
+        assert_edits("before; foo; after; remove;", "inserted;before;foo;after;first;second;");
+        assert_edits("remove;", "");
+    }
+
+    #[test]
+    fn edits_in_nested_lists() {
+        assert_edits(
+            "function foo() { before; { remove; after; } } switch (a) { case 1: remove; }",
+            "function foo(){inserted;before;{after;first;second;}}switch(a){case 1:}"
+        );
+    }
+
+    #[test]
+    fn replace_with_many() {
+        assert_edits("let a = 1, b; foo;", "let a=1;let b;foo;");
+    }
+}
@@ -1,16 +1,189 @@
-use ratel::ast::{NodeList, ExpressionNode, Function, Name, OptionalName, Block};
-use ratel::ast::expression::{ArrowExpression, ArrowBody};
+use ratel::ast::{NodeList, Expression, ExpressionNode, Statement, Function, Name, OptionalName};
+use ratel::ast::{Loc, Block, BlockNode, Identifier, Property, ClassMember};
+use ratel::ast::expression::{ArrowExpression, ArrowBody, ThisExpression};
 use ratel::ast::statement::ReturnStatement;
-use ratel_visitor::{StaticVisitor, DynamicVisitor};
+use ratel_visitor::{StaticVisitor, DynamicVisitor, NodePath, Ancestor, Control};
 
 use Transformer;
 
 pub struct TransformArrow;
 
+/// Where the `this` and `arguments` of arrows come from: the body of the
+/// closest function that isn't an arrow, or the module.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Binding<'ast> {
+    Function(*const BlockNode<'ast, Statement<'ast>>),
+    Module,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Alias {
+    This,
+    Arguments,
+}
+
+impl Alias {
+    #[inline]
+    fn name(&self) -> &'static str {
+        match *self {
+            Alias::This      => "_this",
+            Alias::Arguments => "_arguments",
+        }
+    }
+}
+
+/// Variables declared for `this` and `arguments` of arrows, and arrows that
+/// have to be bound to `this` instead.
+#[derive(Debug, Default)]
+pub(crate) struct Lexical<'ast> {
+    aliases: Vec<(Binding<'ast>, Alias, Identifier<'ast>)>,
+    bound: Vec<*const Loc<Expression<'ast>>>,
+}
+
+/// How `this` and `arguments` in an arrow can be kept once it is a function.
+enum Scope<'a, 'ast> {
+    /// Not in an arrow.
+    Own,
+
+    /// Replaced with a variable declared at the top of the body, if any.
+    Alias(Binding<'ast>, Option<&'a BlockNode<'ast, Statement<'ast>>>),
+
+    /// Arrows in parameters run before the body, and are bound to `this`.
+    Bind(Vec<*const Loc<Expression<'ast>>>),
+}
+
+/// Find what the `this` and `arguments` at the end of the path refer to.
+/// Class properties are left alone, their values aren't in a function
+/// until classes are transformed.
+fn scope<'a, 'ast>(path: &'a NodePath<'ast>) -> Scope<'a, 'ast> {
+    let mut arrows = Vec::new();
+    let mut in_params = false;
+
+    for ancestor in path.ancestors() {
+        let body = match *ancestor {
+            Ancestor::Expression(ref node) => match node.item {
+                Expression::Arrow(_)               => {
+                    arrows.push(&**node as *const Loc<Expression>);
+                    in_params = false;
+                    continue;
+                },
+                Expression::Function(ref function) => &function.body,
+                _                                  => continue,
+            },
+            Ancestor::Statement(ref node) => match node.item {
+                Statement::Function(ref function) => &function.body,
+                _                                 => {
+                    in_params = false;
+                    continue;
+                },
+            },
+            Ancestor::Property(ref node) => match node.item {
+                Property::Method { ref value, .. } => &value.body,
+                _                                  => continue,
+            },
+            Ancestor::ClassMember(ref node) => match node.item {
+                ClassMember::Method { ref value, .. } => &value.body,
+                _                                     => return Scope::Own,
+            },
+            Ancestor::Pattern(_) => {
+                in_params = true;
+                continue;
+            },
+            _ => continue,
+        };
+
+        return match (arrows.is_empty(), in_params) {
+            (true, _)      => Scope::Own,
+            (false, true)  => Scope::Bind(arrows),
+            (false, false) => Scope::Alias(Binding::Function(body), Some(body)),
+        };
+    }
+
+    match arrows.is_empty() {
+        true  => Scope::Own,
+        false => Scope::Alias(Binding::Module, None),
+    }
+}
+
+impl<'ast> Transformer<'ast> {
+    /// Get the name of the variable holding `this` or `arguments` for the
+    /// arrows in the binding, declaring it at the top of its body first.
+    fn alias(&mut self, binding: Binding<'ast>, body: Option<&BlockNode<'ast, Statement<'ast>>>, alias: Alias) -> Identifier<'ast> {
+        let found = self.lexical.aliases.iter().find(|&&(b, a, _)| b == binding && a == alias);
+
+        if let Some(&(_, _, name)) = found {
+            return name;
+        }
+
+        let name = self.unique_name(alias.name());
+        let declaration = match alias {
+            Alias::This      => stmts!(self, "var $0 = this;", name),
+            Alias::Arguments => stmts!(self, "var $0 = arguments;", name),
+        };
+
+        let list = match body {
+            Some(body) => body.body,
+            None       => self.module.body(),
+        };
+
+        // The arrow is in the body, which can't be empty
+        if let Some(first) = list.first_element() {
+            self.insert_before(first, *declaration.only_element().unwrap());
+        }
+
+        self.lexical.aliases.push((binding, alias, name));
+
+        name
+    }
+
+    /// Get `this` as seen from the end of the path, such that it keeps its
+    /// value once the arrows around it are functions.
+    pub(crate) fn lexical_this(&mut self, path: &NodePath<'ast>) -> ExpressionNode<'ast> {
+        match scope(path) {
+            Scope::Own                  => self.alloc(ThisExpression),
+            Scope::Alias(binding, body) => {
+                let name = self.alias(binding, body, Alias::This);
+
+                self.alloc(Expression::Identifier(name))
+            },
+            Scope::Bind(arrows) => {
+                self.lexical.bound.extend(arrows);
+
+                self.alloc(ThisExpression)
+            },
+        }
+    }
+}
+
 impl<'ast> StaticVisitor<'ast> for TransformArrow {
     type Context = Transformer<'ast>;
 
-    fn on_arrow_expression(node: &ArrowExpression<'ast>, ptr: &ExpressionNode<'ast>, t: &mut Transformer<'ast>) {
+    fn on_this_expression(ptr: &ExpressionNode<'ast>, path: &NodePath<'ast>, t: &mut Transformer<'ast>) -> Control {
+        let this = t.lexical_this(path);
+
+        if let Expression::Identifier(_) = this.item {
+            t.swap(ptr, this.item);
+        }
+
+        Control::Continue
+    }
+
+    fn on_identifier_expression(item: &Identifier<'ast>, ptr: &ExpressionNode<'ast>, path: &NodePath<'ast>, t: &mut Transformer<'ast>) -> Control {
+        if *item != "arguments" {
+            return Control::Continue;
+        }
+
+        // `arguments` isn't bound in modules, and can't be bound in parameters
+        if let Scope::Alias(binding @ Binding::Function(_), body) = scope(path) {
+            let name = t.alias(binding, body, Alias::Arguments);
+
+            t.swap(ptr, Expression::Identifier(name));
+        }
+
+        Control::Continue
+    }
+
+    fn on_leave_arrow_expression(node: &ArrowExpression<'ast>, ptr: &ExpressionNode<'ast>, _: &NodePath<'ast>, t: &mut Transformer<'ast>) -> Control {
         let body = match node.body {
             ArrowBody::Block(block)     => block,
             ArrowBody::Expression(expr) => {
@@ -24,25 +197,54 @@ impl<'ast> StaticVisitor<'ast> for TransformArrow {
             }
         };
 
-        t.swap(ptr, Function {
+        let function = Function {
             name: OptionalName::empty(),
+            type_params: node.type_params,
             params: node.params,
+            return_type: node.return_type,
             body,
-        });
+        };
+
+        let arrow = &**ptr as *const Loc<Expression>;
+
+        match t.lexical.bound.contains(&arrow) {
+            true  => {
+                let function: ExpressionNode = t.alloc_as_loc(*ptr, function);
+
+                t.swap(ptr, expr!(t, "$0.bind(this)", function).item);
+            },
+            false => t.swap(ptr, function),
+        }
+
+        Control::Continue
     }
 
     #[inline]
     fn register(dv: &mut DynamicVisitor<'ast, Transformer<'ast>>) {
-        dv.on_arrow_expression.push(TransformArrow::on_arrow_expression);
+        dv.on_this_expression.push(TransformArrow::on_this_expression);
+        dv.on_identifier_expression.push(TransformArrow::on_identifier_expression);
+        dv.on_leave_arrow_expression.push(TransformArrow::on_leave_arrow_expression);
     }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
-    use ratel_visitor::Visitor;
+    use ratel::parse;
+    use ratel_visitor::{Visitor, Visitable};
+    use ratel_codegen::codegen;
+    use edit::ApplyEdits;
     use std::mem::size_of;
 
+    fn assert_transform(source: &str, expected: &str) {
+        let module = parse(source).unwrap();
+        let mut transformer = Transformer::new(&module);
+
+        module.traverse(&(TransformArrow, ApplyEdits), &mut transformer);
+
+        assert_eq!(codegen(&module, true), expected);
+    }
+
     #[test]
     fn transform_arrow_impls_visitor() {
         let _: &Visitor<Context = Transformer> = &TransformArrow;
@@ -61,7 +263,72 @@ mod test {
 
         TransformArrow.register(&mut *dv);
 
-        assert_eq!(dv.on_arrow_expression.len(), 1);
+        assert_eq!(dv.on_leave_arrow_expression.len(), 1);
+        assert_eq!(dv.on_this_expression.len(), 1);
         assert_eq!(dv.on_expression_statement.len(), 0);
     }
+
+    #[test]
+    fn arrow() {
+        assert_transform("f(a => a * 2);", "f(function(a){return a*2;});");
+        assert_transform("f(() => { g(); });", "f(function(){g();});");
+    }
+
+    #[test]
+    fn lexical_this() {
+        assert_transform(
+            "function f() { g(); return () => this.x; }",
+            "function f(){var _this=this;g();return function(){return _this.x;};}"
+        );
+        assert_transform(
+            "var o = { m() { return () => this; } };",
+            "var o={m(){var _this=this;return function(){return _this;};}};"
+        );
+        assert_transform(
+            "f(() => this);",
+            "var _this=this;f(function(){return _this;});"
+        );
+    }
+
+    #[test]
+    fn lexical_arguments() {
+        assert_transform(
+            "function f() { return () => arguments[0] + this; }",
+            "function f(){var _arguments=arguments;var _this=this;return function(){return _arguments[0]+_this;};}"
+        );
+        assert_transform("f(() => arguments);", "f(function(){return arguments;});");
+    }
+
+    #[test]
+    fn nested_arrows() {
+        assert_transform(
+            "function f() { return () => () => this; }",
+            "function f(){var _this=this;return function(){return function(){return _this;};};}"
+        );
+        assert_transform(
+            "function f() { g(() => this); return () => function () { return () => this; }; }",
+            "function f(){var _this=this;g(function(){return _this;});return function(){return function(){var _this=this;return function(){return _this;};};};}"
+        );
+    }
+
+    #[test]
+    fn this_outside_of_arrows() {
+        assert_transform("function f() { return this; }", "function f(){return this;}");
+    }
+
+    #[test]
+    fn unique_alias() {
+        assert_transform(
+            "function f(_this) { return () => this; }",
+            "function f(_this){var _this2=this;return function(){return _this2;};}"
+        );
+    }
+
+    #[test]
+    fn arrow_in_parameters() {
+        assert_transform(
+            "function f(a = () => () => this) { return () => this; }",
+            "function f(a=function(){return function(){return this;}.bind(this);}.bind(this)){var _this=this;return function(){return _this;};}"
+        );
+    }
 }
@@ -0,0 +1,941 @@
+use std::{mem, ptr};
+
+use ratel::ast::{Node, NodeList, Expression, ExpressionNode, Statement, StatementNode, StatementList};
+use ratel::ast::{Pattern, Property, PropertyKey, DeclarationKind, OperatorKind, Literal, Block};
+use ratel::ast::{Function, Name, OptionalName, Identifier};
+use ratel::ast::expression::{BinaryExpression, PrefixExpression, PostfixExpression, SequenceExpression};
+use ratel::ast::expression::{CallExpression, FunctionExpression, ArrowExpression, ClassExpression};
+use ratel::ast::statement::{DeclarationStatement, ForInit, ForStatement, ForInStatement, ForOfStatement};
+use ratel::ast::statement::{WhileStatement, DoStatement, BreakStatement, ContinueStatement, ReturnStatement};
+use ratel::ast::statement::{CatchClause, FunctionStatement, ClassStatement, LabeledStatement};
+use ratel_visitor::{Visitable, StaticVisitor, DynamicVisitor, NodePath, Ancestor, Control, ScopeKind};
+
+use es2015::destructuring::declare;
+use scope::Scope;
+use Transformer;
+
+/// Turns `let` and `const` into `var`. Bindings that would clash with other
+/// variables of the function are renamed, assignments to constants throw,
+/// and the bodies of loops with closures capturing bindings of a single
+/// iteration are moved to a function called on each of them.
+pub struct TransformBlockScoping;
+
+const READ_ONLY_ERROR: &str = "function $0(name) {
+    throw new TypeError(\"\\\"\" + name + \"\\\" is read-only\");
+}";
+
+/// Variable visible in the scope being traversed.
+#[derive(Debug, Clone, Copy)]
+struct Binding<'ast> {
+    name: Identifier<'ast>,
+    renamed: Option<Identifier<'ast>>,
+    kind: Option<DeclarationKind>,
+}
+
+/// Where the traversal is in the tree of scopes analyzed before the module
+/// was transformed, and the bindings it can see from there.
+#[derive(Debug, Default)]
+pub(crate) struct Scoping<'ast> {
+    /// Scopes entered, with the number of their children entered so far and
+    /// the number of bindings declared outside of them.
+    scopes: Vec<(&'ast Scope<'ast>, usize, usize)>,
+    bindings: Vec<Binding<'ast>>,
+    left: Option<&'ast Scope<'ast>>,
+    /// Scopes of the loops left so far.
+    loops: Vec<&'ast Scope<'ast>>,
+    jumps: Jumps<'ast>,
+}
+
+/// Statements leaving the body of a loop that is moved to a function. They
+/// are looked for first, then turned into returns from the function.
+#[derive(Debug, Default)]
+struct Jumps<'ast> {
+    label: Option<&'ast str>,
+    rewrite: bool,
+    breaks: bool,
+    returns: bool,
+    unsupported: bool,
+    assigned: Vec<Identifier<'ast>>,
+    copies: Vec<StatementNode<'ast>>,
+}
+
+/// Where a `break` or `continue` in the body of the loop goes.
+#[derive(PartialEq)]
+enum Target {
+    Loop,
+    Inner,
+    Outer,
+}
+
+impl<'ast> Transformer<'ast> {
+    /// Enter the scope, declaring the bindings in it. Block scoped ones get a
+    /// new name if the same one is used elsewhere in the function.
+    fn enter_scope(&mut self, scope: &'ast Scope<'ast>) {
+        let outside = self.scoping.bindings.len();
+        let function = scope.function();
+
+        for (&name, _) in scope.declared_refs.iter() {
+            let kind = scope.lexical_refs.get(name);
+            let renamed = match (scope.kind, kind) {
+                (ScopeKind::Block, Some(_)) if function.uses_outside(name, scope) => {
+                    let name = self.arena.alloc_str(&format!("_{}", name));
+
+                    Some(self.fresh_name(name))
+                },
+                _ => None,
+            };
+
+            self.scoping.bindings.push(Binding {
+                name,
+                renamed,
+                kind,
+            });
+        }
+
+        self.scoping.scopes.push((scope, 0, outside));
+    }
+
+    /// The module scope is entered before anything else.
+    #[inline]
+    fn enter_module(&mut self) {
+        if self.scoping.scopes.is_empty() {
+            let module = self.scope;
+
+            self.enter_scope(module);
+        }
+    }
+
+    /// Binding the name refers to, either as it is written or renamed.
+    fn binding(&mut self, name: Identifier<'ast>) -> Option<Binding<'ast>> {
+        self.enter_module();
+
+        self.scoping.bindings.iter().rev().find(|binding| {
+            binding.name == name || binding.renamed == Some(name)
+        }).cloned()
+    }
+
+    /// New name of the variable, if it has one it isn't referred to by yet.
+    #[inline]
+    fn renamed(&mut self, name: Identifier<'ast>) -> Option<Identifier<'ast>> {
+        match self.binding(name) {
+            Some(Binding { renamed: Some(renamed), .. }) if renamed != name => Some(renamed),
+            _                                                             => None,
+        }
+    }
+
+    /// Whether the name refers to a `const`.
+    #[inline]
+    fn is_const(&mut self, name: Identifier<'ast>) -> bool {
+        matches!(self.binding(name), Some(Binding { kind: Some(DeclarationKind::Const), .. }))
+    }
+
+    /// Call to the helper throwing for assignments to the constant.
+    fn read_only_error(&mut self, name: Identifier<'ast>) -> ExpressionNode<'ast> {
+        let original = self.binding(name).map_or(name, |binding| binding.name);
+        let helper = self.helper("_readOnlyError", READ_ONLY_ERROR);
+        let message = self.arena.alloc_str(&format!("\"{}\"", original));
+        let message: ExpressionNode = self.alloc(Literal::String(message));
+
+        expr!(self, "$0($1)", helper, message)
+    }
+}
+
+/// `var` declaration with the same declarators, for `let` and `const`.
+#[inline]
+fn var<'ast>(declaration: &DeclarationStatement<'ast>) -> Option<DeclarationStatement<'ast>> {
+    match declaration.kind {
+        DeclarationKind::Var => None,
+        _                    => Some(DeclarationStatement {
+            kind: DeclarationKind::Var,
+            ..*declaration
+        }),
+    }
+}
+
+/// Turn the `let` and `const` declarations of the list into `var`.
+fn var_statements<'ast>(list: StatementList<'ast>, t: &mut Transformer<'ast>) {
+    for statement in list.iter() {
+        if let Statement::Declaration(ref declaration) = statement.item {
+            if let Some(declaration) = var(declaration) {
+                t.swap(statement, declaration);
+            }
+        }
+    }
+}
+
+/// Turn a `let` or `const` in the head of a loop into `var`, and get the
+/// names it declares.
+fn var_head<'ast>(head: &Node<'ast, ForInit<'ast>>, t: &mut Transformer<'ast>) -> Vec<Identifier<'ast>> {
+    let mut names = Vec::new();
+
+    if let ForInit::Declaration(ref declaration) = head.item {
+        if let Some(var) = var(declaration) {
+            for declarator in declaration.declarators.iter() {
+                pattern_names(&declarator.id.item, &mut names);
+            }
+
+            t.swap(head, ForInit::Declaration(var));
+        }
+    }
+
+    names
+}
+
+fn pattern_names<'ast>(pattern: &Pattern<'ast>, names: &mut Vec<Identifier<'ast>>) {
+    match *pattern {
+        Pattern::Void => {},
+        Pattern::Identifier(name) => names.push(name),
+        Pattern::ObjectPattern { properties } => {
+            for property in properties.iter() {
+                match property.item {
+                    Property::Shorthand(name)     => names.push(name),
+                    Property::Literal { value, .. } => expression_names(&value.item, names),
+                    Property::Method { .. }       => {},
+                }
+            }
+        },
+        Pattern::ArrayPattern { elements } => {
+            for element in elements.iter() {
+                pattern_names(&element.item, names);
+            }
+        },
+        Pattern::RestElement { argument } => names.push(argument.item),
+        Pattern::AssignmentPattern { left, .. } => pattern_names(&left.item, names),
+        Pattern::Typed { pattern, .. } => pattern_names(&pattern.item, names),
+    }
+}
+
+/// Names declared by an expression within an object pattern.
+fn expression_names<'ast>(expression: &Expression<'ast>, names: &mut Vec<Identifier<'ast>>) {
+    match *expression {
+        Expression::Identifier(name) => names.push(name),
+        Expression::Binary(BinaryExpression {
+            operator: OperatorKind::Assign,
+            left,
+            ..
+        }) => expression_names(&left.item, names),
+        Expression::Object(ref object) => {
+            for property in object.body.iter() {
+                match property.item {
+                    Property::Shorthand(name)     => names.push(name),
+                    Property::Literal { value, .. } => expression_names(&value.item, names),
+                    Property::Method { .. }       => {},
+                }
+            }
+        },
+        Expression::Array(ref array) => {
+            for element in array.body.iter() {
+                expression_names(&element.item, names);
+            }
+        },
+        _ => {},
+    }
+}
+
+/// Names declared with `let` or `const` in the scope, and the blocks in it.
+/// Those of inner loops are left to them.
+fn block_bindings<'ast>(scope: &'ast Scope<'ast>, loops: &[&'ast Scope<'ast>], names: &mut Vec<Identifier<'ast>>) {
+    names.extend(scope.lexical_refs.iter().map(|(&name, _)| name));
+
+    for child in scope.children.as_list().iter() {
+        if child.kind == ScopeKind::Block && !loops.iter().any(|inner| ptr::eq(*inner, *child)) {
+            block_bindings(child, loops, names);
+        }
+    }
+}
+
+/// Whether a function in the scope uses any of the names.
+fn is_captured<'ast>(scope: &'ast Scope<'ast>, names: &[Identifier<'ast>]) -> bool {
+    scope.children.as_list().iter().any(|child| match child.kind {
+        ScopeKind::Function => names.iter().any(|name| child.uses(name)),
+        ScopeKind::Block    => is_captured(child, names),
+    })
+}
+
+fn uses_this<'ast>(scope: &'ast Scope<'ast>) -> bool {
+    scope.used_this.get() || scope.children.as_list().iter().any(|child| uses_this(child))
+}
+
+/// Whether the statement at the end of the path is an item of a list, as
+/// opposed to the body of an `if`, a loop, or a label.
+pub(crate) fn is_listed<'ast>(path: &NodePath<'ast>) -> bool {
+    match path.parent() {
+        Some(Ancestor::Statement(parent)) => !matches!(
+            parent.item,
+            Statement::If(_) |
+            Statement::While(_) |
+            Statement::Do(_) |
+            Statement::For(_) |
+            Statement::ForIn(_) |
+            Statement::ForOf(_) |
+            Statement::Labeled(_)
+        ),
+        _ => true,
+    }
+}
+
+/// Label of the loop at the end of the path.
+fn label<'ast>(path: &NodePath<'ast>) -> Option<&'ast str> {
+    match path.parent() {
+        Some(Ancestor::Statement(parent)) => match parent.item {
+            Statement::Labeled(LabeledStatement { label, .. }) => Some(label),
+            _                                                  => None,
+        },
+        _ => None,
+    }
+}
+
+/// Where the `break` or `continue` at the end of the path goes, the path
+/// starting at the body of the loop.
+fn target<'ast>(label: Option<&'ast str>, is_break: bool, path: &NodePath<'ast>, t: &Transformer<'ast>) -> Target {
+    for ancestor in path.ancestors() {
+        if let Ancestor::Statement(ref node) = *ancestor {
+            match (node.item, label) {
+                (Statement::Labeled(LabeledStatement { label: inner, .. }), Some(label)) if inner == label => return Target::Inner,
+                (Statement::While(_), None) |
+                (Statement::Do(_), None) |
+                (Statement::For(_), None) |
+                (Statement::ForIn(_), None) |
+                (Statement::ForOf(_), None) => return Target::Inner,
+                (Statement::Switch(_), None) if is_break => return Target::Inner,
+                _ => {},
+            }
+        }
+    }
+
+    match label {
+        None                                         => Target::Loop,
+        Some(label) if t.scoping.jumps.label == Some(label) => Target::Loop,
+        Some(_)                                      => Target::Outer,
+    }
+}
+
+/// Statement with the copies of the bindings assigned in the body put
+/// before it, for statements leaving the function of the body.
+fn with_copies<'ast>(statement: StatementNode<'ast>, t: &mut Transformer<'ast>) -> Statement<'ast> {
+    if t.scoping.jumps.copies.is_empty() {
+        return statement.item;
+    }
+
+    let mut statements = t.scoping.jumps.copies.clone();
+
+    statements.push(statement);
+
+    Statement::Block(Block { body: t.list(statements) })
+}
+
+/// Finds and rewrites the jumps out of the body of a loop.
+struct JumpsVisitor;
+
+impl<'ast> StaticVisitor<'ast> for JumpsVisitor {
+    type Context = Transformer<'ast>;
+
+    fn on_leave_break_statement(item: &BreakStatement<'ast>, node: &StatementNode<'ast>, path: &NodePath<'ast>, t: &mut Transformer<'ast>) -> Control {
+        match target(item.label.map(|label| label.item), true, path, t) {
+            Target::Inner => {},
+            Target::Outer => t.scoping.jumps.unsupported = true,
+            Target::Loop  => {
+                t.scoping.jumps.breaks = true;
+
+                if t.scoping.jumps.rewrite {
+                    let statement = stmts!(t, "return \"break\";");
+                    let statement = with_copies(*statement.only_element().unwrap(), t);
+
+                    t.swap(node, statement);
+                }
+            },
+        }
+
+        Control::Continue
+    }
+
+    fn on_leave_continue_statement(item: &ContinueStatement<'ast>, node: &StatementNode<'ast>, path: &NodePath<'ast>, t: &mut Transformer<'ast>) -> Control {
+        match target(item.label.map(|label| label.item), false, path, t) {
+            Target::Inner => {},
+            Target::Outer => t.scoping.jumps.unsupported = true,
+            Target::Loop  => {
+                if t.scoping.jumps.rewrite {
+                    let statement = t.alloc(ReturnStatement { value: None });
+                    let statement = with_copies(statement, t);
+
+                    t.swap(node, statement);
+                }
+            },
+        }
+
+        Control::Continue
+    }
+
+    fn on_leave_return_statement(item: &ReturnStatement<'ast>, node: &StatementNode<'ast>, _: &NodePath<'ast>, t: &mut Transformer<'ast>) -> Control {
+        t.scoping.jumps.returns = true;
+
+        if t.scoping.jumps.rewrite {
+            let value = match item.value {
+                Some(value) => value,
+                None        => expr!(t, "void 0"),
+            };
+            let statement = stmts!(t, "return { v: $0 };", value);
+            let statement = with_copies(*statement.only_element().unwrap(), t);
+
+            t.swap(node, statement);
+        }
+
+        Control::Continue
+    }
+
+    fn on_binary_expression(item: &BinaryExpression<'ast>, _: &ExpressionNode<'ast>, _: &NodePath<'ast>, t: &mut Transformer<'ast>) -> Control {
+        if let Expression::Identifier(name) = item.left.item {
+            if item.operator.assignment() {
+                t.scoping.jumps.assigned.push(name);
+            }
+        }
+
+        Control::Continue
+    }
+
+    fn on_prefix_expression(item: &PrefixExpression<'ast>, _: &ExpressionNode<'ast>, _: &NodePath<'ast>, t: &mut Transformer<'ast>) -> Control {
+        if let (OperatorKind::Increment, Expression::Identifier(name)) |
+               (OperatorKind::Decrement, Expression::Identifier(name)) = (item.operator, item.operand.item) {
+            t.scoping.jumps.assigned.push(name);
+        }
+
+        Control::Continue
+    }
+
+    fn on_postfix_expression(item: &PostfixExpression<'ast>, _: &ExpressionNode<'ast>, _: &NodePath<'ast>, t: &mut Transformer<'ast>) -> Control {
+        if let (OperatorKind::Increment, Expression::Identifier(name)) |
+               (OperatorKind::Decrement, Expression::Identifier(name)) = (item.operator, item.operand.item) {
+            t.scoping.jumps.assigned.push(name);
+        }
+
+        Control::Continue
+    }
+
+    // Jumps in functions don't leave the loop
+
+    fn on_function_statement(_: &FunctionStatement<'ast>, _: &StatementNode<'ast>, _: &NodePath<'ast>, _: &mut Transformer<'ast>) -> Control {
+        Control::Skip
+    }
+
+    fn on_class_statement(_: &ClassStatement<'ast>, _: &StatementNode<'ast>, _: &NodePath<'ast>, _: &mut Transformer<'ast>) -> Control {
+        Control::Skip
+    }
+
+    fn on_function_expression(_: &FunctionExpression<'ast>, _: &ExpressionNode<'ast>, _: &NodePath<'ast>, _: &mut Transformer<'ast>) -> Control {
+        Control::Skip
+    }
+
+    fn on_arrow_expression(_: &ArrowExpression<'ast>, _: &ExpressionNode<'ast>, _: &NodePath<'ast>, _: &mut Transformer<'ast>) -> Control {
+        Control::Skip
+    }
+
+    fn on_class_expression(_: &ClassExpression<'ast>, _: &ExpressionNode<'ast>, _: &NodePath<'ast>, _: &mut Transformer<'ast>) -> Control {
+        Control::Skip
+    }
+
+    fn on_property(item: &Property<'ast>, _: &Node<'ast, Property<'ast>>, _: &NodePath<'ast>, _: &mut Transformer<'ast>) -> Control {
+        match *item {
+            Property::Method { .. } => Control::Skip,
+            _                       => Control::Continue,
+        }
+    }
+
+    fn register(_: &mut DynamicVisitor<'ast, Transformer<'ast>>) {
+        unreachable!()
+    }
+}
+
+/// Move the body of the loop to a function called on each iteration, if
+/// closures in it capture a binding declared by the loop. The function
+/// takes the bindings of the head as params, and copies the ones it
+/// assigns back once it returns, if `copy` is set.
+fn loop_function<'ast>(
+    node: &StatementNode<'ast>,
+    head: Vec<Identifier<'ast>>,
+    body: &StatementNode<'ast>,
+    copy: bool,
+    path: &NodePath<'ast>,
+    t: &mut Transformer<'ast>
+) {
+    let scope = match t.scoping.left {
+        Some(scope) => scope,
+        None        => return,
+    };
+
+    let mut bindings = Vec::new();
+
+    block_bindings(scope, &t.scoping.loops, &mut bindings);
+
+    t.scoping.loops.push(scope);
+
+    // `arguments` in the body would be the ones of the new function
+    if !is_captured(scope, &bindings) || scope.uses("arguments") {
+        return;
+    }
+
+    let list = match body.item {
+        Statement::Block(Block { body }) => body,
+        _                                => NodeList::from(t.arena, *body),
+    };
+
+    let jumps = mem::replace(&mut t.scoping.jumps, Jumps {
+        label: label(path),
+        ..Jumps::default()
+    });
+
+    for statement in list.iter() {
+        statement.traverse(&JumpsVisitor, t);
+    }
+
+    if t.scoping.jumps.unsupported {
+        t.scoping.jumps = jumps;
+
+        return;
+    }
+
+    // Bindings of the head assigned in the body are copied out, so that
+    // the next iteration starts with their new values
+    let mut copies = Vec::new();
+    let mut restores = Vec::new();
+    let mut outs = Vec::new();
+
+    let assigned = head.iter().filter(|name| copy && t.scoping.jumps.assigned.contains(name)).cloned().collect::<Vec<_>>();
+
+    for name in assigned {
+        let out = t.arena.alloc_str(&format!("_{}", name));
+        let out = t.fresh_name(out);
+
+        copies.extend(stmts!(t, "$0 = $1;", out, name).iter().cloned());
+        restores.extend(stmts!(t, "$0 = $1;", name, out).iter().cloned());
+        outs.push(out);
+    }
+
+    declare(&outs, path, t);
+
+    t.scoping.jumps.rewrite = true;
+    t.scoping.jumps.copies = copies.clone();
+
+    for statement in list.iter() {
+        statement.traverse(&JumpsVisitor, t);
+    }
+
+    let jumps = mem::replace(&mut t.scoping.jumps, jumps);
+
+    let params = head.iter().map(|&name| t.alloc(Pattern::Identifier(name))).collect::<Vec<_>>();
+    let params = t.list(params);
+
+    let mut statements = list.iter().cloned().collect::<Vec<_>>();
+
+    statements.extend(copies);
+
+    let body_list = t.list(statements);
+    let function: ExpressionNode = t.alloc(Function {
+        name: OptionalName::empty(),
+        type_params: NodeList::empty(),
+        params,
+        return_type: None,
+        body: t.alloc(Block { body: body_list }),
+    });
+
+    let name = t.fresh_name("_loop");
+    let declaration = stmts!(t, "var $0 = $1;", name, function);
+
+    let mut arguments: Vec<ExpressionNode> = head.iter().map(|&name| t.alloc(name)).collect();
+
+    let callee = match uses_this(scope) {
+        true  => {
+            arguments.insert(0, expr!(t, "this"));
+
+            expr!(t, "$0.call", name)
+        },
+        false => t.alloc(name),
+    };
+    let arguments = t.list(arguments);
+    let call: ExpressionNode = t.alloc(CallExpression {
+        callee,
+        arguments,
+    });
+
+    let mut statements = Vec::new();
+
+    match (jumps.returns, jumps.breaks) {
+        (false, false) => {
+            statements.push(t.alloc(call));
+            statements.extend(restores);
+        },
+        (false, true) if restores.is_empty() => {
+            statements.extend(stmts!(t, "if ($0 === \"break\") break;", call).iter().cloned());
+        },
+        _ => {
+            let ret = t.fresh_name("_ret");
+
+            statements.extend(stmts!(t, "var $0 = $1;", ret, call).iter().cloned());
+            statements.extend(restores);
+
+            if jumps.breaks {
+                statements.extend(stmts!(t, "if ($0 === \"break\") break;", ret).iter().cloned());
+            }
+
+            if jumps.returns {
+                statements.extend(stmts!(t, "if (typeof $0 === \"object\") return $0.v;", ret).iter().cloned());
+            }
+        },
+    }
+
+    let statements = t.list(statements);
+
+    t.swap(body, Block { body: statements });
+
+    let declaration = *declaration.only_element().unwrap();
+
+    match is_listed(path) {
+        true  => t.insert_before(node, declaration),
+        false => {
+            let statement = t.alloc(node.item);
+            let body = t.list([declaration, statement]);
+
+            t.swap(node, Block { body });
+        },
+    }
+}
+
+impl<'ast> StaticVisitor<'ast> for TransformBlockScoping {
+    type Context = Transformer<'ast>;
+
+    fn on_enter_scope(_: ScopeKind, _: &NodePath<'ast>, t: &mut Transformer<'ast>) {
+        t.enter_module();
+
+        let scope = {
+            let current = t.scoping.scopes.last_mut().unwrap();
+            let scope = current.0.children.as_list().iter().nth(current.1).cloned();
+
+            current.1 += 1;
+
+            // Nodes are only changed once left, so the scopes are the same
+            // as when they were analyzed
+            scope.unwrap_or(current.0)
+        };
+
+        t.enter_scope(scope);
+    }
+
+    fn on_leave_scope(_: &NodePath<'ast>, t: &mut Transformer<'ast>) {
+        if let Some((scope, _, outside)) = t.scoping.scopes.pop() {
+            t.scoping.bindings.truncate(outside);
+            t.scoping.left = Some(scope);
+        }
+    }
+
+    fn on_leave_identifier_expression(_: &Identifier<'ast>, ptr: &ExpressionNode<'ast>, _: &NodePath<'ast>, t: &mut Transformer<'ast>) -> Control {
+        if let Expression::Identifier(name) = ptr.item {
+            if let Some(renamed) = t.renamed(name) {
+                t.swap(ptr, Expression::Identifier(renamed));
+            }
+        }
+
+        Control::Continue
+    }
+
+    fn on_leave_pattern(_: &Pattern<'ast>, node: &Node<'ast, Pattern<'ast>>, _: &NodePath<'ast>, t: &mut Transformer<'ast>) -> Control {
+        if let Pattern::Identifier(name) = node.item {
+            if let Some(renamed) = t.renamed(name) {
+                t.swap(node, Pattern::Identifier(renamed));
+            }
+        }
+
+        Control::Continue
+    }
+
+    fn on_leave_property(_: &Property<'ast>, node: &Node<'ast, Property<'ast>>, _: &NodePath<'ast>, t: &mut Transformer<'ast>) -> Control {
+        if let Property::Shorthand(name) = node.item {
+            if let Some(renamed) = t.renamed(name) {
+                let key = t.alloc(PropertyKey::Literal(name));
+                let value = t.alloc(renamed);
+
+                t.swap(node, Property::Literal { key, value });
+            }
+        }
+
+        Control::Continue
+    }
+
+    fn on_leave_binary_expression(_: &BinaryExpression<'ast>, ptr: &ExpressionNode<'ast>, _: &NodePath<'ast>, t: &mut Transformer<'ast>) -> Control {
+        if let Expression::Binary(BinaryExpression { operator, left, right }) = ptr.item {
+            if let Expression::Identifier(name) = left.item {
+                if operator.assignment() && t.is_const(name) {
+                    let error = t.read_only_error(name);
+                    let body = t.list([right, error]);
+
+                    t.swap(ptr, SequenceExpression { body });
+                }
+            }
+        }
+
+        Control::Continue
+    }
+
+    fn on_leave_prefix_expression(_: &PrefixExpression<'ast>, ptr: &ExpressionNode<'ast>, _: &NodePath<'ast>, t: &mut Transformer<'ast>) -> Control {
+        if let Expression::Prefix(PrefixExpression { operator, operand }) = ptr.item {
+            if let (OperatorKind::Increment, Expression::Identifier(name)) |
+                   (OperatorKind::Decrement, Expression::Identifier(name)) = (operator, operand.item) {
+                if t.is_const(name) {
+                    let error = t.read_only_error(name);
+
+                    t.swap(ptr, error.item);
+                }
+            }
+        }
+
+        Control::Continue
+    }
+
+    fn on_leave_postfix_expression(_: &PostfixExpression<'ast>, ptr: &ExpressionNode<'ast>, _: &NodePath<'ast>, t: &mut Transformer<'ast>) -> Control {
+        if let Expression::Postfix(PostfixExpression { operand, .. }) = ptr.item {
+            if let Expression::Identifier(name) = operand.item {
+                if t.is_const(name) {
+                    let error = t.read_only_error(name);
+
+                    t.swap(ptr, error.item);
+                }
+            }
+        }
+
+        Control::Continue
+    }
+
+    fn on_leave_declaration_statement(_: &DeclarationStatement<'ast>, node: &StatementNode<'ast>, _: &NodePath<'ast>, t: &mut Transformer<'ast>) -> Control {
+        if let Statement::Declaration(ref declaration) = node.item {
+            if let Some(declaration) = var(declaration) {
+                t.swap(node, declaration);
+            }
+        }
+
+        Control::Continue
+    }
+
+    fn on_leave_catch_clause(_: &CatchClause<'ast>, node: &Node<'ast, CatchClause<'ast>>, _: &NodePath<'ast>, t: &mut Transformer<'ast>) -> Control {
+        // Destructured params are declared at the start of the body
+        var_statements(node.item.body.body, t);
+
+        Control::Continue
+    }
+
+    fn on_leave_for_statement(_: &ForStatement<'ast>, node: &StatementNode<'ast>, path: &NodePath<'ast>, t: &mut Transformer<'ast>) -> Control {
+        if let Statement::For(ref item) = node.item {
+            let head = match item.init {
+                Some(ref init) => var_head(init, t),
+                None           => Vec::new(),
+            };
+
+            loop_function(node, head, &item.body, true, path, t);
+        }
+
+        Control::Continue
+    }
+
+    fn on_leave_for_in_statement(_: &ForInStatement<'ast>, node: &StatementNode<'ast>, path: &NodePath<'ast>, t: &mut Transformer<'ast>) -> Control {
+        if let Statement::ForIn(ref item) = node.item {
+            let head = var_head(&item.left, t);
+
+            if let Statement::Block(ref block) = item.body.item {
+                var_statements(block.body, t);
+            }
+
+            loop_function(node, head, &item.body, false, path, t);
+        }
+
+        Control::Continue
+    }
+
+    fn on_leave_for_of_statement(_: &ForOfStatement<'ast>, node: &StatementNode<'ast>, path: &NodePath<'ast>, t: &mut Transformer<'ast>) -> Control {
+        if let Statement::ForOf(ref item) = node.item {
+            let head = var_head(&item.left, t);
+
+            if let Statement::Block(ref block) = item.body.item {
+                var_statements(block.body, t);
+            }
+
+            loop_function(node, head, &item.body, false, path, t);
+        }
+
+        Control::Continue
+    }
+
+    fn on_leave_while_statement(_: &WhileStatement<'ast>, node: &StatementNode<'ast>, path: &NodePath<'ast>, t: &mut Transformer<'ast>) -> Control {
+        if let Statement::While(ref item) = node.item {
+            if let Statement::Block(_) = item.body.item {
+                loop_function(node, Vec::new(), &item.body, false, path, t);
+            }
+        }
+
+        Control::Continue
+    }
+
+    fn on_leave_do_statement(_: &DoStatement<'ast>, node: &StatementNode<'ast>, path: &NodePath<'ast>, t: &mut Transformer<'ast>) -> Control {
+        if let Statement::Do(ref item) = node.item {
+            if let Statement::Block(_) = item.body.item {
+                loop_function(node, Vec::new(), &item.body, false, path, t);
+            }
+        }
+
+        Control::Continue
+    }
+
+    #[inline]
+    fn register(dv: &mut DynamicVisitor<'ast, Transformer<'ast>>) {
+        dv.on_enter_scope.push(TransformBlockScoping::on_enter_scope);
+        dv.on_leave_scope.push(TransformBlockScoping::on_leave_scope);
+        dv.on_leave_identifier_expression.push(TransformBlockScoping::on_leave_identifier_expression);
+        dv.on_leave_pattern.push(TransformBlockScoping::on_leave_pattern);
+        dv.on_leave_property.push(TransformBlockScoping::on_leave_property);
+        dv.on_leave_binary_expression.push(TransformBlockScoping::on_leave_binary_expression);
+        dv.on_leave_prefix_expression.push(TransformBlockScoping::on_leave_prefix_expression);
+        dv.on_leave_postfix_expression.push(TransformBlockScoping::on_leave_postfix_expression);
+        dv.on_leave_declaration_statement.push(TransformBlockScoping::on_leave_declaration_statement);
+        dv.on_leave_catch_clause.push(TransformBlockScoping::on_leave_catch_clause);
+        dv.on_leave_for_statement.push(TransformBlockScoping::on_leave_for_statement);
+        dv.on_leave_for_in_statement.push(TransformBlockScoping::on_leave_for_in_statement);
+        dv.on_leave_for_of_statement.push(TransformBlockScoping::on_leave_for_of_statement);
+        dv.on_leave_while_statement.push(TransformBlockScoping::on_leave_while_statement);
+        dv.on_leave_do_statement.push(TransformBlockScoping::on_leave_do_statement);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ratel::{parse_with_options, ParseOptions, Goal};
+    use ratel_codegen::codegen;
+    use edit::ApplyEdits;
+
+    const READ_ONLY: &str = "function _readOnlyError(name){throw new TypeError(\"\\\"\"+name+\"\\\" is read-only\");}";
+
+    fn assert_transform(source: &str, expected: &str) {
+        let module = parse_with_options(source, ParseOptions { goal: Goal::Module, ..ParseOptions::default() }).unwrap();
+        let mut transformer = Transformer::new(&module);
+
+        module.traverse(&(TransformBlockScoping, ApplyEdits), &mut transformer);
+
+        assert_eq!(codegen(&module, true), expected);
+    }
+
+    #[test]
+    fn declarations() {
+        assert_transform("let a = 1; const b = 2;", "var a=1;var b=2;");
+        assert_transform("{ let a; } function f() { const b = 1; }", "{var a;}function f(){var b=1;}");
+        assert_transform("export let a = 1;", "export var a=1;");
+    }
+
+    #[test]
+    fn renames_clashing_bindings() {
+        assert_transform("var a = 1; { let a = 2; f(a); } f(a);", "var a=1;{var _a=2;f(_a);}f(a);");
+        assert_transform("{ let a = 1; } f(a);", "{var _a=1;}f(a);");
+        assert_transform("{ let a; { let a; a; } a; }", "{var a;{var _a;_a;}a;}");
+        assert_transform("function f() { { let a; } } var a;", "function f(){{var a;}}var a;");
+    }
+
+    #[test]
+    fn renames_references() {
+        assert_transform(
+            "var a; { let a = 1; a = 2; a++; g({ a }); g(function (a) { return a; }); }",
+            "var a;{var _a=1;_a=2;_a++;g({a:_a});g(function(a){return a;});}"
+        );
+        assert_transform(
+            "var a, b; { let { a, c: b } = d; }",
+            "var a,b;{var {a:_a,c:_b}=d;}"
+        );
+    }
+
+    #[test]
+    fn const_assignments() {
+        assert_transform("const a = 1; a = 2;", &format!("{}var a=1;2,_readOnlyError(\"a\");", READ_ONLY));
+        assert_transform("const a = 1; a++; --a;", &format!("{}var a=1;_readOnlyError(\"a\");_readOnlyError(\"a\");", READ_ONLY));
+        assert_transform("const a = 1; function f(a) { a = 2; }", "var a=1;function f(a){a=2;}");
+        assert_transform("var a; { const a = 1; a += 2; }", &format!("{}var a;{{var _a=1;2,_readOnlyError(\"a\");}}", READ_ONLY));
+    }
+
+    #[test]
+    fn catch_clause() {
+        assert_transform("var e; try {} catch (e) { let a = e; }", "var e;try{}catch(_e){var a=_e;}");
+    }
+
+    #[test]
+    fn loop_heads() {
+        assert_transform("for (let i = 0; i < 1; i++) {}", "for(var i=0;i<1;i++){}");
+        assert_transform("for (const a in b) {} for (let a of b) {}", "for(var _a in b){}for(var _a2 of b){}");
+    }
+
+    #[test]
+    fn loop_closures() {
+        assert_transform(
+            "for (let i = 0; i < 3; i++) { f(function () { return i; }); }",
+            "var _loop=function(i){f(function(){return i;});};for(var i=0;i<3;i++){_loop(i);}"
+        );
+        assert_transform(
+            "for (let a of b) f(() => a);",
+            "var _loop=function(a){f(()=>a);};for(var a of b){_loop(a);}"
+        );
+        assert_transform(
+            "while (a) { let b = c(); f(() => b); }",
+            "var _loop=function(){var b=c();f(()=>b);};while(a){_loop();}"
+        );
+    }
+
+    #[test]
+    fn loop_without_closures() {
+        assert_transform(
+            "for (let i = 0; i < 3; i++) { let a = i; f(a); }",
+            "for(var i=0;i<3;i++){var a=i;f(a);}"
+        );
+        assert_transform(
+            "var i; for (let i of a) { f(() => i); }",
+            "var i;var _loop=function(_i){f(()=>_i);};for(var _i of a){_loop(_i);}"
+        );
+    }
+
+    #[test]
+    fn loop_jumps() {
+        assert_transform(
+            "for (let a of b) { if (a) continue; if (!a) break; f(() => a); }",
+            "var _loop=function(a){if(a)return;if(!a)return \"break\";f(()=>a);};for(var a of b){if(_loop(a)===\"break\")break;}"
+        );
+        assert_transform(
+            "function g() { for (let a of b) { for (;;) break; if (a) return a; f(() => a); } }",
+            "function g(){var _loop=function(a){for(;;)break;if(a)return {v:a};f(()=>a);};\
+             for(var a of b){var _ret=_loop(a);if(typeof _ret===\"object\")return _ret.v;}}"
+        );
+        assert_transform(
+            "outer: for (let a of b) { for (;;) continue outer; f(() => a); }",
+            "outer:{var _loop=function(a){for(;;)return;f(()=>a);};for(var a of b){_loop(a);}}"
+        );
+    }
+
+    #[test]
+    fn loop_copies_assigned_bindings() {
+        assert_transform(
+            "for (let i = 0; i < 3; i++) { f(() => i); i += 1; }",
+            "var _i;var _loop=function(i){f(()=>i);i+=1;_i=i;};for(var i=0;i<3;i++){_loop(i);i=_i;}"
+        );
+    }
+
+    #[test]
+    fn loop_this() {
+        assert_transform(
+            "for (let a of b) { f(() => a, this); }",
+            "var _loop=function(a){f(()=>a,this);};for(var a of b){_loop.call(this,a);}"
+        );
+    }
+
+    #[test]
+    fn loop_left_alone() {
+        // Jumps to outer labels and `arguments` aren't supported
+        assert_transform(
+            "a: for (;;) { for (let b of c) { f(() => b); continue a; } }",
+            "a:for(;;){for(var b of c){f(()=>b);continue a;}}"
+        );
+        assert_transform(
+            "function g() { for (let b of c) { f(() => b, arguments); } }",
+            "function g(){for(var b of c){f(()=>b,arguments);}}"
+        );
+    }
+}
@@ -0,0 +1,572 @@
+use std::ptr;
+
+use ratel::ast::{Node, NodeList, Expression, ExpressionNode, Statement, StatementNode, Literal};
+use ratel::ast::{Function, Class, ClassMember, MethodKind, Name, OptionalName, MandatoryName};
+use ratel::ast::{Block, Pattern, Property, PropertyKey};
+use ratel::ast::expression::{ClassExpression, CallExpression, MemberExpression, ComputedMemberExpression};
+use ratel::ast::expression::ThisExpression;
+use ratel::ast::statement::{ClassStatement, ReturnStatement};
+use ratel_visitor::{StaticVisitor, DynamicVisitor, NodePath, Ancestor, Control};
+
+use Transformer;
+
+/// Turns classes into constructor functions, with their methods set on the
+/// prototype, and calls to `super` into calls to the parent class.
+pub struct TransformClass;
+
+const CLASS_CALL_CHECK: &str = "function $0(instance, Constructor) {
+    if (instance instanceof Constructor) {
+        return;
+    }
+    throw new TypeError(\"Cannot call a class as a function\");
+}";
+
+const INHERITS: &str = "function $0(subClass, superClass) {
+    subClass.prototype = Object.create(superClass && superClass.prototype, {
+        constructor: { value: subClass, writable: true, configurable: true }
+    });
+    if (superClass) {
+        subClass.__proto__ = superClass;
+    }
+}";
+
+/// Getter and setter sharing a key, defined together.
+struct Accessor<'ast> {
+    is_static: bool,
+    key: PropertyKey<'ast>,
+    get: Option<ExpressionNode<'ast>>,
+    set: Option<ExpressionNode<'ast>>,
+}
+
+/// Statement defining a member, or the accessor to define in its place.
+enum Definition<'ast> {
+    Statement(StatementNode<'ast>),
+    Accessor(usize),
+}
+
+#[inline]
+fn is_name(label: &str) -> bool {
+    match label.bytes().next() {
+        Some(byte) => byte.is_ascii_alphabetic() || byte == b'_' || byte == b'$' || byte >= 0x80,
+        None       => false,
+    }
+}
+
+/// Key of a member as an expression, as passed to `Object.defineProperty`.
+fn key<'ast>(key: PropertyKey<'ast>, t: &Transformer<'ast>) -> ExpressionNode<'ast> {
+    match key {
+        PropertyKey::Computed(expression) => expression,
+        PropertyKey::Literal(label) if is_name(label) => {
+            t.alloc(Literal::String(t.arena.alloc_str(&format!("\"{}\"", label))))
+        },
+        PropertyKey::Literal(number) => t.alloc(Literal::Number(number)),
+        PropertyKey::Binary(number)  => t.alloc(Literal::Binary(number)),
+    }
+}
+
+/// `object.key`, or `object[key]` for keys that aren't names.
+pub(crate) fn member<'ast>(object: ExpressionNode<'ast>, key: PropertyKey<'ast>, t: &Transformer<'ast>) -> ExpressionNode<'ast> {
+    match key {
+        PropertyKey::Literal(label) if is_name(label) => t.alloc(MemberExpression {
+            object,
+            property: t.alloc(label),
+        }),
+        _ => t.alloc(ComputedMemberExpression {
+            object,
+            property: self::key(key, t),
+        }),
+    }
+}
+
+/// Object the member of the class is defined on.
+#[inline]
+fn target<'ast>(name: &'ast str, is_static: bool, t: &Transformer<'ast>) -> ExpressionNode<'ast> {
+    match is_static {
+        true  => t.alloc(Expression::Identifier(name)),
+        false => expr!(t, "$0.prototype", name),
+    }
+}
+
+fn method<'ast>(value: Node<'ast, Function<'ast, impl Name<'ast>>>, t: &Transformer<'ast>) -> ExpressionNode<'ast> {
+    t.alloc_as_loc(value, Function {
+        name: OptionalName::empty(),
+        type_params: value.type_params,
+        params: value.params,
+        return_type: value.return_type,
+        body: value.body,
+    })
+}
+
+/// Whether the statement calls the parent class, once transformed.
+fn is_super_call(statement: &Statement, parent: &str) -> bool {
+    let call = match *statement {
+        Statement::Expression(ref expression) => match expression.item {
+            Expression::Call(ref call) => call,
+            _                          => return false,
+        },
+        _ => return false,
+    };
+
+    match call.callee.item {
+        Expression::Member(ref member) => match member.object.item {
+            Expression::Identifier(object) => {
+                object == parent && (member.property.item == "call" || member.property.item == "apply")
+            },
+            _                              => false,
+        },
+        _ => false,
+    }
+}
+
+/// Build a function returning the constructor of the class, called with
+/// the parent class, if any.
+fn class<'ast, N>(class: &Class<'ast, N>, name: Option<&'ast str>, t: &mut Transformer<'ast>) -> ExpressionNode<'ast>
+where
+    N: Name<'ast>,
+{
+    let name = match name {
+        Some(name) => name,
+        None       => t.unique_name("_class"),
+    };
+    let parent = class.extends.map(|_| t.unique_name("_super"));
+    let check = t.helper("_classCallCheck", CLASS_CALL_CHECK);
+
+    let mut constructor = None;
+    let mut fields = Vec::new();
+    let mut definitions = Vec::new();
+    let mut accessors: Vec<Accessor<'ast>> = Vec::new();
+    let mut statics = Vec::new();
+
+    for node in class.body.body.iter() {
+        match node.item {
+            ClassMember::Method { kind: MethodKind::Constructor, value, .. } => constructor = Some(value),
+            ClassMember::Method { is_static, key, kind: MethodKind::Method, value } => {
+                let target = target(name, is_static, t);
+                let member = member(target, key.item, t);
+                let value = method(value, t);
+
+                definitions.push(Definition::Statement(t.alloc_as_loc(*node, expr!(t, "$0 = $1", member, value))));
+            },
+            ClassMember::Method { is_static, key, kind, value } => {
+                let value = method(value, t);
+
+                // Computed keys can't be told apart before they are evaluated
+                let key = key.item;
+                let index = match key {
+                    PropertyKey::Computed(_) => None,
+                    _ => accessors.iter().position(|accessor| accessor.is_static == is_static && accessor.key == key),
+                };
+
+                let index = match index {
+                    Some(index) => index,
+                    None        => {
+                        accessors.push(Accessor { is_static, key, get: None, set: None });
+                        definitions.push(Definition::Accessor(accessors.len() - 1));
+                        accessors.len() - 1
+                    },
+                };
+
+                match kind {
+                    MethodKind::Get => accessors[index].get = Some(value),
+                    _               => accessors[index].set = Some(value),
+                }
+            },
+            ClassMember::Literal { is_static, key, value } |
+            ClassMember::Property { is_static, key, value: Some(value), .. } => {
+                let target = match is_static {
+                    true  => t.alloc(Expression::Identifier(name)),
+                    false => t.alloc(ThisExpression),
+                };
+                let member = member(target, key.item, t);
+                let statement = t.alloc_as_loc(*node, expr!(t, "$0 = $1", member, value));
+
+                match is_static {
+                    true  => statics.push(statement),
+                    false => fields.push(statement),
+                }
+            },
+            ClassMember::Property { value: None, .. } | ClassMember::Error => {},
+        }
+    }
+
+    let mut body = vec![*stmts!(t, "$0(this, $1);", check, name).only_element().unwrap()];
+
+    let params = match constructor {
+        Some(constructor) => {
+            body.extend(constructor.body.body.iter().cloned());
+
+            constructor.params
+        },
+        None => {
+            if let Some(parent) = parent {
+                body.push(*stmts!(t, "$0.apply(this, arguments);", parent).only_element().unwrap());
+            }
+
+            NodeList::empty()
+        }
+    };
+
+    // Fields are set once the parent class is done with the instance
+    let index = match parent {
+        Some(parent) => body.iter().position(|statement| is_super_call(&statement.item, parent)).unwrap_or(0) + 1,
+        None         => 1,
+    };
+
+    body.splice(index..index, fields);
+
+    let body = t.list(body);
+    let constructor: StatementNode = t.alloc(Function {
+        name: MandatoryName(t.alloc(name)),
+        type_params: NodeList::empty(),
+        params,
+        return_type: None,
+        body: t.alloc(Block { body }),
+    });
+
+    let mut statements = Vec::new();
+
+    if let Some(parent) = parent {
+        let inherits = t.helper("_inherits", INHERITS);
+
+        statements.push(*stmts!(t, "$0($1, $2);", inherits, name, parent).only_element().unwrap());
+    }
+
+    statements.push(constructor);
+
+    for definition in definitions {
+        let accessor = match definition {
+            Definition::Statement(statement) => {
+                statements.push(statement);
+                continue;
+            },
+            Definition::Accessor(index) => &accessors[index],
+        };
+
+        let target = target(name, accessor.is_static, t);
+        let key = key(accessor.key, t);
+
+        let define = match (accessor.get, accessor.set) {
+            (Some(get), Some(set)) => expr!(t, "Object.defineProperty($0, $1, { get: $2, set: $3, configurable: true })", target, key, get, set),
+            (Some(get), None)      => expr!(t, "Object.defineProperty($0, $1, { get: $2, configurable: true })", target, key, get),
+            (None, Some(set))      => expr!(t, "Object.defineProperty($0, $1, { set: $2, configurable: true })", target, key, set),
+            (None, None)           => unreachable!(),
+        };
+
+        statements.push(t.alloc(define));
+    }
+
+    statements.extend(statics);
+    statements.push(t.alloc(ReturnStatement {
+        value: Some(t.alloc(Expression::Identifier(name)))
+    }));
+
+    let params = match parent {
+        Some(parent) => {
+            let param = t.alloc(Pattern::Identifier(parent));
+
+            NodeList::from(t.arena, param)
+        },
+        None => NodeList::empty(),
+    };
+
+    let body = t.list(statements);
+    let function: ExpressionNode = t.alloc(Function {
+        name: OptionalName::empty(),
+        type_params: NodeList::empty(),
+        params,
+        return_type: None,
+        body: t.alloc(Block { body }),
+    });
+
+    let arguments = match class.extends {
+        Some(extends) => NodeList::from(t.arena, extends),
+        None          => NodeList::empty(),
+    };
+
+    t.alloc(CallExpression {
+        callee: function,
+        arguments,
+    })
+}
+
+/// Find the parent class `super` at the end of the path refers to, and
+/// whether it is used in a static member.
+fn parent<'ast>(path: &NodePath<'ast>, t: &Transformer<'ast>) -> Option<(&'ast str, bool)> {
+    let mut ancestors = path.ancestors();
+
+    // Only arrows keep the `super` of the member they are in
+    let is_static = loop {
+        match *ancestors.next()? {
+            Ancestor::Expression(ref node) => match node.item {
+                Expression::Function(_) | Expression::Class(_) => return None,
+                _                                              => {},
+            },
+            Ancestor::Statement(ref node) => match node.item {
+                Statement::Function(_) | Statement::Class(_) => return None,
+                _                                            => {},
+            },
+            Ancestor::Property(ref node) => if let Property::Method { .. } = node.item {
+                return None;
+            },
+            Ancestor::ClassMember(ref node) => break match node.item {
+                ClassMember::Method { is_static, .. }   |
+                ClassMember::Literal { is_static, .. }  |
+                ClassMember::Property { is_static, .. } => is_static,
+                ClassMember::Error                      => return None,
+            },
+            _ => {},
+        }
+    };
+
+    let extends = match *ancestors.next()? {
+        Ancestor::Expression(ref node) => match node.item {
+            Expression::Class(ref class) => class.extends,
+            _                            => None,
+        },
+        Ancestor::Statement(ref node) => match node.item {
+            Statement::Class(ref class) => class.extends,
+            _                           => None,
+        },
+        _ => None,
+    };
+
+    extends.map(|_| (t.unique_name("_super"), is_static))
+}
+
+#[inline]
+fn is_super(expression: &ExpressionNode) -> bool {
+    expression.item == Expression::Identifier("super")
+}
+
+/// Whether the expression is called by its parent, which then takes care
+/// of it.
+fn is_callee<'ast>(ptr: &ExpressionNode<'ast>, path: &NodePath<'ast>) -> bool {
+    match path.ancestors().next() {
+        Some(Ancestor::Expression(node)) => match node.item {
+            Expression::Call(ref call) => ptr::eq(&*call.callee, &**ptr),
+            _                          => false,
+        },
+        _ => false,
+    }
+}
+
+/// The parent class in static members, its prototype otherwise.
+#[inline]
+fn home<'ast>(parent: &'ast str, is_static: bool, t: &Transformer<'ast>) -> ExpressionNode<'ast> {
+    target(parent, is_static, t)
+}
+
+impl<'ast> StaticVisitor<'ast> for TransformClass {
+    type Context = Transformer<'ast>;
+
+    fn on_leave_call_expression(item: &CallExpression<'ast>, ptr: &ExpressionNode<'ast>, path: &NodePath<'ast>, t: &mut Transformer<'ast>) -> Control {
+        let callee = match item.callee.item {
+            Expression::Identifier("super") => match parent(path, t) {
+                Some((parent, _)) => expr!(t, "$0.call", parent),
+                None              => return Control::Continue,
+            },
+            Expression::Member(ref member) if is_super(&member.object) => match parent(path, t) {
+                Some((parent, is_static)) => {
+                    let home = home(parent, is_static, t);
+
+                    expr!(t, "$0.$1.call", home, member.property.item)
+                },
+                None => return Control::Continue,
+            },
+            Expression::ComputedMember(ref member) if is_super(&member.object) => match parent(path, t) {
+                Some((parent, is_static)) => {
+                    let home = home(parent, is_static, t);
+
+                    expr!(t, "$0[$1].call", home, member.property)
+                },
+                None => return Control::Continue,
+            },
+            _ => return Control::Continue,
+        };
+
+        let mut arguments = vec![t.lexical_this(path)];
+
+        arguments.extend(item.arguments.iter().cloned());
+
+        let arguments = t.list(arguments);
+
+        t.swap(ptr, CallExpression { callee, arguments });
+
+        Control::Continue
+    }
+
+    fn on_leave_member_expression(item: &MemberExpression<'ast>, ptr: &ExpressionNode<'ast>, path: &NodePath<'ast>, t: &mut Transformer<'ast>) -> Control {
+        if !is_super(&item.object) || is_callee(ptr, path) {
+            return Control::Continue;
+        }
+
+        if let Some((parent, is_static)) = parent(path, t) {
+            let home = home(parent, is_static, t);
+
+            t.swap(ptr, MemberExpression {
+                object: home,
+                property: item.property,
+            });
+        }
+
+        Control::Continue
+    }
+
+    fn on_leave_computed_member_expression(item: &ComputedMemberExpression<'ast>, ptr: &ExpressionNode<'ast>, path: &NodePath<'ast>, t: &mut Transformer<'ast>) -> Control {
+        if !is_super(&item.object) || is_callee(ptr, path) {
+            return Control::Continue;
+        }
+
+        if let Some((parent, is_static)) = parent(path, t) {
+            let home = home(parent, is_static, t);
+
+            t.swap(ptr, ComputedMemberExpression {
+                object: home,
+                property: item.property,
+            });
+        }
+
+        Control::Continue
+    }
+
+    fn on_leave_class_expression(item: &ClassExpression<'ast>, ptr: &ExpressionNode<'ast>, _: &NodePath<'ast>, t: &mut Transformer<'ast>) -> Control {
+        let name = item.name.0.map(|name| name.item);
+        let class = class(item, name, t);
+
+        t.swap(ptr, class.item);
+
+        Control::Continue
+    }
+
+    fn on_leave_class_statement(item: &ClassStatement<'ast>, ptr: &StatementNode<'ast>, _: &NodePath<'ast>, t: &mut Transformer<'ast>) -> Control {
+        let name = item.name.0.item;
+        let class = class(item, Some(name), t);
+        let declaration = stmts!(t, "var $0 = $1;", name, class);
+
+        t.swap(ptr, declaration.only_element().unwrap().item);
+
+        Control::Continue
+    }
+
+    #[inline]
+    fn register(dv: &mut DynamicVisitor<'ast, Transformer<'ast>>) {
+        dv.on_leave_call_expression.push(TransformClass::on_leave_call_expression);
+        dv.on_leave_member_expression.push(TransformClass::on_leave_member_expression);
+        dv.on_leave_computed_member_expression.push(TransformClass::on_leave_computed_member_expression);
+        dv.on_leave_class_expression.push(TransformClass::on_leave_class_expression);
+        dv.on_leave_class_statement.push(TransformClass::on_leave_class_statement);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ratel::{parse_with_options, ParseOptions, Goal};
+    use ratel_visitor::Visitable;
+    use ratel_codegen::codegen;
+    use edit::ApplyEdits;
+    use es2015::TransformArrow;
+
+    const CHECK: &str = "function _classCallCheck(instance,Constructor){if(instance instanceof Constructor){return;}throw new TypeError(\"Cannot call a class as a function\");}";
+
+    const INHERIT: &str = "function _inherits(subClass,superClass){subClass.prototype=Object.create(superClass&&superClass.prototype,{constructor:{value:subClass,writable:true,configurable:true}});if(superClass){subClass.__proto__=superClass;}}";
+
+    fn assert_transform(source: &str, expected: &str) {
+        let module = parse_with_options(source, ParseOptions { goal: Goal::Module, ..ParseOptions::default() }).unwrap();
+        let mut transformer = Transformer::new(&module);
+
+        module.traverse(&((TransformClass, TransformArrow), ApplyEdits), &mut transformer);
+
+        assert_eq!(codegen(&module, true), expected);
+    }
+
+    fn assert_class(source: &str, helpers: &[&str], expected: &str) {
+        assert_transform(source, &format!("{}{}", helpers.concat(), expected));
+    }
+
+    #[test]
+    fn empty_class() {
+        assert_class(
+            "class Foo {}",
+            &[CHECK],
+            "var Foo=function(){function Foo(){_classCallCheck(this,Foo);}return Foo;}();"
+        );
+    }
+
+    #[test]
+    fn methods() {
+        assert_class(
+            "class Foo { constructor(a) { this.a = a; } bar() { return 1; } static baz() {} }",
+            &[CHECK],
+            "var Foo=function(){function Foo(a){_classCallCheck(this,Foo);this.a=a;}Foo.prototype.bar=function(){return 1;};Foo.baz=function(){};return Foo;}();"
+        );
+    }
+
+    #[test]
+    fn accessors() {
+        assert_class(
+            "class Foo { get a() { return 1; } set a(v) {} static get b() {} }",
+            &[CHECK],
+            "var Foo=function(){function Foo(){_classCallCheck(this,Foo);}Object.defineProperty(Foo.prototype,\"a\",{get:function(){return 1;},set:function(v){},configurable:true});Object.defineProperty(Foo,\"b\",{get:function(){},configurable:true});return Foo;}();"
+        );
+    }
+
+    #[test]
+    fn computed_keys() {
+        assert_class(
+            "class Foo { [a]() {} 1() {} static [b]() {} }",
+            &[CHECK],
+            "var Foo=function(){function Foo(){_classCallCheck(this,Foo);}Foo.prototype[a]=function(){};Foo.prototype[1]=function(){};Foo[b]=function(){};return Foo;}();"
+        );
+    }
+
+    #[test]
+    fn extends() {
+        assert_class(
+            "class Foo extends Bar { constructor(a) { super(a); this.b = 1; } m() { return super.m(1) + super.x; } static s() { return super.s(); } }",
+            &[CHECK, INHERIT],
+            "var Foo=function(_super){_inherits(Foo,_super);function Foo(a){_classCallCheck(this,Foo);_super.call(this,a);this.b=1;}Foo.prototype.m=function(){return _super.prototype.m.call(this,1)+_super.prototype.x;};Foo.s=function(){return _super.s.call(this);};return Foo;}(Bar);"
+        );
+        assert_class(
+            "class Foo extends mixin(A, B) {}",
+            &[CHECK, INHERIT],
+            "var Foo=function(_super){_inherits(Foo,_super);function Foo(){_classCallCheck(this,Foo);_super.apply(this,arguments);}return Foo;}(mixin(A,B));"
+        );
+    }
+
+    #[test]
+    fn super_in_arrow() {
+        assert_class(
+            "class Foo extends Bar { m() { return () => super.m(); } }",
+            &[CHECK, INHERIT],
+            "var Foo=function(_super){_inherits(Foo,_super);function Foo(){_classCallCheck(this,Foo);_super.apply(this,arguments);}Foo.prototype.m=function(){var _this=this;return function(){return _super.prototype.m.call(_this);};};return Foo;}(Bar);"
+        );
+    }
+
+    #[test]
+    fn properties() {
+        assert_class(
+            "class Foo extends Bar { a = 1; static b = 2; constructor() { foo(); super(); bar(); } }",
+            &[CHECK, INHERIT],
+            "var Foo=function(_super){_inherits(Foo,_super);function Foo(){_classCallCheck(this,Foo);foo();_super.call(this);this.a=1;bar();}Foo.b=2;return Foo;}(Bar);"
+        );
+    }
+
+    #[test]
+    fn class_expression() {
+        assert_class(
+            "var A = class { a() {} }; var B = class B2 extends A {};",
+            &[CHECK, INHERIT],
+            "var A=function(){function _class(){_classCallCheck(this,_class);}_class.prototype.a=function(){};return _class;}();var B=function(_super){_inherits(B2,_super);function B2(){_classCallCheck(this,B2);_super.apply(this,arguments);}return B2;}(A);"
+        );
+    }
+
+    #[test]
+    fn exported_class() {
+        assert_class(
+            "export class Foo {}",
+            &[CHECK],
+            "export var Foo=function(){function Foo(){_classCallCheck(this,Foo);}return Foo;}();"
+        );
+    }
+}
@@ -0,0 +1,812 @@
+use ratel::ast::{Node, NodeList, Expression, ExpressionNode, Statement, StatementNode, StatementList};
+use ratel::ast::{Pattern, PatternList, Property, PropertyKey, Literal, OperatorKind, DeclarationKind};
+use ratel::ast::{Function, Name, Block, BlockNode, ClassMember};
+use ratel::ast::expression::{ArrowExpression, ArrowBody, BinaryExpression, ObjectExpression, ArrayExpression};
+use ratel::ast::expression::{SpreadExpression, SequenceExpression, ComputedMemberExpression, FunctionExpression};
+use ratel::ast::statement::{DeclarationStatement, Declarator, ForInit, ForStatement, ForInStatement, ForOfStatement};
+use ratel::ast::statement::{CatchClause, FunctionStatement};
+use ratel_visitor::{StaticVisitor, DynamicVisitor, NodePath, Ancestor, Control};
+
+use es2015::class::member;
+use Transformer;
+
+/// Takes destructuring patterns apart into variables, or assignments, each
+/// reading a single property. Arrays are read by index, without iterating.
+pub struct TransformDestructuring;
+
+/// Element of an array pattern, or of an array being assigned to.
+#[derive(Clone, Copy)]
+enum Element<'ast> {
+    Pattern(Pattern<'ast>),
+    Expression(ExpressionNode<'ast>),
+}
+
+/// Variables, or targets of assignments, a pattern is taken apart into,
+/// along with their values in the order they have to be read.
+#[derive(Default)]
+struct Destructure<'ast> {
+    bindings: Vec<(ExpressionNode<'ast>, ExpressionNode<'ast>)>,
+    temporaries: Vec<&'ast str>,
+}
+
+#[inline]
+fn name<'ast>(name: &'ast str, t: &Transformer<'ast>) -> ExpressionNode<'ast> {
+    t.alloc(Expression::Identifier(name))
+}
+
+/// Value read by all parts of a pattern, which is only stored in a variable
+/// if there is more than one part.
+#[inline]
+fn source<'ast>(stored: Option<&'ast str>, value: ExpressionNode<'ast>, t: &Transformer<'ast>) -> ExpressionNode<'ast> {
+    match stored {
+        Some(stored) => name(stored, t),
+        None         => value,
+    }
+}
+
+/// Whether the pattern takes a value apart, rather than naming it.
+fn is_destructuring(pattern: &Pattern) -> bool {
+    match *pattern {
+        Pattern::ObjectPattern { .. } |
+        Pattern::ArrayPattern { .. }  => true,
+        Pattern::Typed { pattern, .. } => is_destructuring(&pattern.item),
+        _                              => false,
+    }
+}
+
+/// Whether taking the pattern apart assigns to `name`.
+fn binds_pattern(pattern: &Pattern, name: &str) -> bool {
+    match *pattern {
+        Pattern::Void                           => false,
+        Pattern::Identifier(ident)              => ident == name,
+        Pattern::RestElement { argument }       => argument.item == name,
+        Pattern::ObjectPattern { properties }   => properties.iter().any(|property| binds_property(&property.item, name)),
+        Pattern::ArrayPattern { elements }      => elements.iter().any(|element| binds_pattern(&element.item, name)),
+        Pattern::AssignmentPattern { left, .. } => binds_pattern(&left.item, name),
+        Pattern::Typed { pattern, .. }          => binds_pattern(&pattern.item, name),
+    }
+}
+
+fn binds_property(property: &Property, name: &str) -> bool {
+    match *property {
+        Property::Shorthand(ident)       => ident == name,
+        Property::Literal { value, .. } => binds_expression(&value.item, name),
+        Property::Method { .. }         => false,
+    }
+}
+
+fn binds_expression(target: &Expression, name: &str) -> bool {
+    match *target {
+        Expression::Identifier(ident) => ident == name,
+        Expression::Object(ObjectExpression { body }) => body.iter().any(|property| binds_property(&property.item, name)),
+        Expression::Array(ArrayExpression { body }) => body.iter().any(|element| binds_expression(&element.item, name)),
+        Expression::Spread(SpreadExpression { argument }) => binds_expression(&argument.item, name),
+        Expression::Binary(BinaryExpression {
+            operator: OperatorKind::Assign,
+            left,
+            ..
+        }) => binds_expression(&left.item, name),
+        _ => false,
+    }
+}
+
+impl<'ast> Destructure<'ast> {
+    /// Store the value in a new variable.
+    fn temporary(&mut self, value: ExpressionNode<'ast>, t: &mut Transformer<'ast>) -> &'ast str {
+        let temporary = t.fresh_name("_ref");
+
+        self.bindings.push((name(temporary, t), value));
+        self.temporaries.push(temporary);
+
+        temporary
+    }
+
+    /// Name the value can be read from any number of times.
+    #[inline]
+    fn reusable(&mut self, value: ExpressionNode<'ast>, t: &mut Transformer<'ast>) -> &'ast str {
+        match value.item {
+            Expression::Identifier(name) => name,
+            _                            => self.temporary(value, t),
+        }
+    }
+
+    /// The value, or the default if it is `undefined`.
+    fn with_default(&mut self, value: ExpressionNode<'ast>, default: ExpressionNode<'ast>, t: &mut Transformer<'ast>) -> ExpressionNode<'ast> {
+        let value = self.reusable(value, t);
+
+        expr!(t, "$0 === void 0 ? $1 : $0", value, default)
+    }
+
+    /// Take apart a value a variable, which the pattern assigns to, can
+    /// be read from. The value is read before the variable changes.
+    fn top(&mut self, pattern: Pattern<'ast>, value: ExpressionNode<'ast>, t: &mut Transformer<'ast>) {
+        let value = match value.item {
+            Expression::Identifier(ident) if binds_pattern(&pattern, ident) => {
+                let temporary = self.temporary(value, t);
+
+                name(temporary, t)
+            },
+            _ => value,
+        };
+
+        self.pattern(pattern, value, t);
+    }
+
+    fn pattern(&mut self, pattern: Pattern<'ast>, value: ExpressionNode<'ast>, t: &mut Transformer<'ast>) {
+        match pattern {
+            Pattern::Void => {},
+            Pattern::Identifier(ident) => {
+                self.bindings.push((name(ident, t), value));
+            },
+            Pattern::RestElement { argument } => {
+                self.bindings.push((name(argument.item, t), value));
+            },
+            Pattern::ObjectPattern { properties } => self.object(properties, value, t),
+            Pattern::ArrayPattern { elements } => {
+                let elements = elements.iter().map(|element| Element::Pattern(element.item)).collect();
+
+                self.array(elements, value, t);
+            },
+            Pattern::AssignmentPattern { left, right } => {
+                let value = self.with_default(value, right, t);
+
+                self.pattern(left.item, value, t);
+            },
+            Pattern::Typed { pattern, .. } => self.pattern(pattern.item, value, t),
+        }
+    }
+
+    fn expression(&mut self, target: ExpressionNode<'ast>, value: ExpressionNode<'ast>, t: &mut Transformer<'ast>) {
+        match target.item {
+            Expression::Object(ObjectExpression { body }) => self.object(body, value, t),
+            Expression::Array(ArrayExpression { body }) => {
+                let elements = body.iter().map(|element| Element::Expression(*element)).collect();
+
+                self.array(elements, value, t);
+            },
+            Expression::Binary(BinaryExpression {
+                operator: OperatorKind::Assign,
+                left,
+                right,
+            }) => {
+                let value = self.with_default(value, right, t);
+
+                self.expression(left, value, t);
+            },
+            _ => self.bindings.push((target, value)),
+        }
+    }
+
+    fn object(&mut self, properties: NodeList<'ast, Property<'ast>>, value: ExpressionNode<'ast>, t: &mut Transformer<'ast>) {
+        let stored = match properties.iter().count() {
+            // Nothing to read, but the value is still evaluated
+            0 => {
+                self.temporary(value, t);
+
+                return;
+            },
+            1 => None,
+            _ => Some(self.reusable(value, t)),
+        };
+
+        for property in properties.iter() {
+            let object = source(stored, value, t);
+
+            match property.item {
+                Property::Shorthand(ident) => {
+                    let value = member(object, PropertyKey::Literal(ident), t);
+
+                    self.bindings.push((name(ident, t), value));
+                },
+                Property::Literal { key, value: target } => {
+                    let value = member(object, key.item, t);
+
+                    self.expression(target, value, t);
+                },
+                Property::Method { .. } => {},
+            }
+        }
+    }
+
+    fn array(&mut self, elements: Vec<Element<'ast>>, value: ExpressionNode<'ast>, t: &mut Transformer<'ast>) {
+        let holes = elements.iter().filter(|element| match **element {
+            Element::Pattern(Pattern::Void) => true,
+            Element::Expression(expression) => expression.item == Expression::Void,
+            _                               => false,
+        }).count();
+
+        let stored = match elements.len() - holes {
+            0 => {
+                self.temporary(value, t);
+
+                return;
+            },
+            1 => None,
+            _ => Some(self.reusable(value, t)),
+        };
+
+        for (index, element) in elements.into_iter().enumerate() {
+            let array = source(stored, value, t);
+            let index: ExpressionNode = t.alloc(Literal::Number(t.arena.alloc_str(&index.to_string())));
+            let item = t.alloc(ComputedMemberExpression {
+                object: array,
+                property: index,
+            });
+
+            match element {
+                Element::Pattern(Pattern::Void) => {},
+                Element::Pattern(pattern @ Pattern::RestElement { .. }) => {
+                    let rest = expr!(t, "$0.slice($1)", array, index);
+
+                    self.pattern(pattern, rest, t);
+                },
+                Element::Pattern(pattern) => self.pattern(pattern, item, t),
+                Element::Expression(expression) => match expression.item {
+                    Expression::Void => {},
+                    Expression::Spread(SpreadExpression { argument }) => {
+                        let rest = expr!(t, "$0.slice($1)", array, index);
+
+                        self.expression(argument, rest, t);
+                    },
+                    _ => self.expression(expression, item, t),
+                },
+            }
+        }
+    }
+
+    /// Param to take the place of a destructuring one, which is taken apart
+    /// at the start of the body instead. Defaults stay on the param.
+    fn param(&mut self, param: Pattern<'ast>, t: &mut Transformer<'ast>) -> Option<Pattern<'ast>> {
+        match param {
+            Pattern::ObjectPattern { .. } |
+            Pattern::ArrayPattern { .. }  => {
+                let temporary = t.fresh_name("_ref");
+
+                self.pattern(param, name(temporary, t), t);
+
+                Some(Pattern::Identifier(temporary))
+            },
+            Pattern::AssignmentPattern { left, right } => {
+                let replaced = self.param(left.item, t)?;
+
+                Some(Pattern::AssignmentPattern {
+                    left: t.alloc_as_loc(left, replaced),
+                    right,
+                })
+            },
+            Pattern::Typed { pattern, optional, annotation } => {
+                let replaced = self.param(pattern.item, t)?;
+
+                Some(Pattern::Typed {
+                    pattern: t.alloc_as_loc(pattern, replaced),
+                    optional,
+                    annotation,
+                })
+            },
+            _ => None,
+        }
+    }
+
+    /// Declarators of the variables. Only names can be declared, other
+    /// targets are left out.
+    fn declarators(self, t: &Transformer<'ast>) -> Vec<Node<'ast, Declarator<'ast>>> {
+        self.bindings.into_iter().filter_map(|(target, value)| match target.item {
+            Expression::Identifier(ident) => Some(t.alloc(Declarator {
+                id: t.alloc(Pattern::Identifier(ident)),
+                init: Some(value),
+            })),
+            _ => None,
+        }).collect()
+    }
+
+    fn declaration(self, kind: DeclarationKind, t: &mut Transformer<'ast>) -> StatementNode<'ast> {
+        let declarators = self.declarators(t);
+        let declarators = t.list(declarators);
+
+        t.alloc(DeclarationStatement {
+            kind,
+            declarators,
+        })
+    }
+
+    /// Assignments to the targets, with the temporaries declared in the
+    /// closest function. `result` is the value of the expression, if any.
+    fn assignments(self, result: Option<&'ast str>, path: &NodePath<'ast>, t: &mut Transformer<'ast>) -> ExpressionNode<'ast> {
+        declare(&self.temporaries, path, t);
+
+        let mut expressions: Vec<ExpressionNode> = self.bindings.into_iter().map(|(left, right)| t.alloc(BinaryExpression {
+            operator: OperatorKind::Assign,
+            left,
+            right,
+        })).collect();
+
+        if let Some(result) = result {
+            expressions.push(name(result, t));
+        }
+
+        match expressions.len() {
+            1 => expressions[0],
+            _ => {
+                let body = t.list(expressions);
+
+                t.alloc(SequenceExpression { body })
+            }
+        }
+    }
+}
+
+/// Declare the variables at the start of the closest function with a body
+/// block, or the module.
+pub(crate) fn declare<'ast>(names: &[&'ast str], path: &NodePath<'ast>, t: &mut Transformer<'ast>) {
+    if names.is_empty() {
+        return;
+    }
+
+    let mut body = t.module.body();
+
+    for ancestor in path.ancestors() {
+        body = match *ancestor {
+            Ancestor::Statement(ref node) => match node.item {
+                Statement::Function(ref function) => function.body.body,
+                _                                 => continue,
+            },
+            Ancestor::Expression(ref node) => match node.item {
+                Expression::Function(ref function) => function.body.body,
+                Expression::Arrow(ArrowExpression {
+                    body: ArrowBody::Block(ref block),
+                    ..
+                }) => block.body,
+                _ => continue,
+            },
+            Ancestor::Property(ref node) => match node.item {
+                Property::Method { ref value, .. } => value.body.body,
+                _                                  => continue,
+            },
+            Ancestor::ClassMember(ref node) => match node.item {
+                ClassMember::Method { ref value, .. } => value.body.body,
+                _                                     => continue,
+            },
+            _ => continue,
+        };
+
+        break;
+    }
+
+    let declarators = names.iter().map(|&ident| t.alloc(Declarator {
+        id: t.alloc(Pattern::Identifier(ident)),
+        init: None,
+    })).collect::<Vec<_>>();
+    let declarators = t.list(declarators);
+    let declaration = t.alloc(DeclarationStatement {
+        kind: DeclarationKind::Var,
+        declarators,
+    });
+
+    // The assignment is in the body, which can't be empty
+    if let Some(first) = body.first_element() {
+        t.insert_before(first, declaration);
+    }
+}
+
+/// List with the statement put before the others.
+fn prepend<'ast>(statement: StatementNode<'ast>, list: StatementList<'ast>, t: &mut Transformer<'ast>) -> StatementList<'ast> {
+    let mut statements = vec![statement];
+
+    statements.extend(list.iter().cloned());
+
+    t.list(statements)
+}
+
+/// Declarators with the destructuring ones taken apart.
+fn declarators<'ast>(list: NodeList<'ast, Declarator<'ast>>, t: &mut Transformer<'ast>) -> NodeList<'ast, Declarator<'ast>> {
+    let mut declarators = Vec::new();
+
+    for declarator in list.iter() {
+        match declarator.init {
+            Some(init) if is_destructuring(&declarator.id.item) => {
+                let mut destructure = Destructure::default();
+
+                destructure.top(declarator.id.item, init, t);
+                declarators.extend(destructure.declarators(t));
+            },
+            _ => declarators.push(*declarator),
+        }
+    }
+
+    t.list(declarators)
+}
+
+/// Replace the destructuring params with temporaries, which are taken
+/// apart at the start of the body.
+fn params<'ast>(params: PatternList<'ast>, body: &BlockNode<'ast, Statement<'ast>>, t: &mut Transformer<'ast>) {
+    let mut destructure = Destructure::default();
+
+    for param in params.iter() {
+        if let Some(replaced) = destructure.param(param.item, t) {
+            t.swap(param, replaced);
+        }
+    }
+
+    if destructure.bindings.is_empty() {
+        return;
+    }
+
+    let declaration = destructure.declaration(DeclarationKind::Var, t);
+    let body_list = prepend(declaration, body.body, t);
+
+    t.swap(body, Block { body: body_list });
+}
+
+#[inline]
+fn function<'ast, N: Name<'ast>>(function: &Function<'ast, N>, t: &mut Transformer<'ast>) {
+    params(function.params, &function.body, t);
+}
+
+/// Take apart the head of a `for-in` or `for-of` loop at the start of its
+/// body, the loop itself gets a temporary.
+fn for_head<'ast>(left: &Node<'ast, ForInit<'ast>>, body: &StatementNode<'ast>, path: &NodePath<'ast>, t: &mut Transformer<'ast>) {
+    let mut destructure = Destructure::default();
+
+    let (kind, temporary, statement) = match left.item {
+        ForInit::Declaration(DeclarationStatement { kind, declarators }) => {
+            let pattern = match declarators.only_element() {
+                Some(declarator) if is_destructuring(&declarator.id.item) => declarator.id.item,
+                _                                                         => return,
+            };
+            let temporary = t.fresh_name("_ref");
+
+            destructure.pattern(pattern, name(temporary, t), t);
+
+            (kind, temporary, destructure.declaration(kind, t))
+        },
+        ForInit::Expression(target) => {
+            match target.item {
+                Expression::Object(_) | Expression::Array(_) => {},
+                _                                            => return,
+            }
+
+            let temporary = t.fresh_name("_ref");
+
+            destructure.expression(target, name(temporary, t), t);
+
+            let assignments = destructure.assignments(None, path, t);
+
+            (DeclarationKind::Var, temporary, t.alloc(assignments))
+        },
+    };
+
+    let declarators = NodeList::from(t.arena, t.alloc(Declarator {
+        id: t.alloc(Pattern::Identifier(temporary)),
+        init: None,
+    }));
+
+    t.swap(left, DeclarationStatement {
+        kind,
+        declarators,
+    });
+
+    let statements = match body.item {
+        Statement::Block(Block { body: list }) => prepend(statement, list, t),
+        _                                      => {
+            let list = NodeList::from(t.arena, *body);
+
+            prepend(statement, list, t)
+        },
+    };
+
+    t.swap(body, Block { body: statements });
+}
+
+/// Whether the assignment is itself within a pattern, as the default value
+/// of `[{ a } = b] = c`, and gets taken apart with the rest of it.
+fn is_nested<'ast>(ptr: &ExpressionNode<'ast>, path: &NodePath<'ast>) -> bool {
+    let address = |node: &ExpressionNode<'ast>| &**node as *const _;
+    let mut child = address(ptr);
+
+    for ancestor in path.ancestors() {
+        match *ancestor {
+            Ancestor::Pattern(_)  => return true,
+            Ancestor::Property(_) => continue,
+            Ancestor::Expression(ref node) => {
+                match node.item {
+                    Expression::Array(_)  |
+                    Expression::Object(_) |
+                    Expression::Spread(_) => {},
+                    Expression::Binary(BinaryExpression {
+                        operator: OperatorKind::Assign,
+                        left,
+                        ..
+                    }) => return address(&left) == child,
+                    _ => return false,
+                }
+
+                child = address(node);
+            },
+            Ancestor::Statement(ref node) => return match node.item {
+                Statement::ForIn(ForInStatement { left, .. }) |
+                Statement::ForOf(ForOfStatement { left, .. }) => match left.item {
+                    ForInit::Expression(target) => address(&target) == child,
+                    _                           => false,
+                },
+                _ => false,
+            },
+            _ => return false,
+        }
+    }
+
+    false
+}
+
+impl<'ast> StaticVisitor<'ast> for TransformDestructuring {
+    type Context = Transformer<'ast>;
+
+    fn on_leave_declaration_statement(item: &DeclarationStatement<'ast>, node: &StatementNode<'ast>, _: &NodePath<'ast>, t: &mut Transformer<'ast>) -> Control {
+        if item.declarators.iter().any(|declarator| is_destructuring(&declarator.id.item)) {
+            let declarators = declarators(item.declarators, t);
+
+            t.swap(node, DeclarationStatement {
+                kind: item.kind,
+                declarators,
+            });
+        }
+
+        Control::Continue
+    }
+
+    fn on_leave_binary_expression(item: &BinaryExpression<'ast>, ptr: &ExpressionNode<'ast>, path: &NodePath<'ast>, t: &mut Transformer<'ast>) -> Control {
+        match (item.operator, item.left.item) {
+            (OperatorKind::Assign, Expression::Object(_)) |
+            (OperatorKind::Assign, Expression::Array(_))  => {},
+            _                                             => return Control::Continue,
+        }
+
+        if is_nested(ptr, path) {
+            return Control::Continue;
+        }
+
+        // The value of the assignment is the value taken apart, which only
+        // has to be kept if the assignment isn't a statement on its own
+        let used = match path.ancestors().next() {
+            Some(Ancestor::Statement(node)) => !matches!(node.item, Statement::Expression(_)),
+            _                               => true,
+        };
+
+        let mut destructure = Destructure::default();
+
+        let value = match item.right.item {
+            Expression::Identifier(ident) if !binds_expression(&item.left.item, ident) => Some(ident),
+            Expression::Identifier(_) => Some(destructure.temporary(item.right, t)),
+            _ if used                 => Some(destructure.temporary(item.right, t)),
+            _                         => None,
+        };
+
+        match value {
+            Some(value) => destructure.expression(item.left, name(value, t), t),
+            None        => destructure.expression(item.left, item.right, t),
+        }
+
+        let assignments = destructure.assignments(if used { value } else { None }, path, t);
+
+        t.swap(ptr, assignments.item);
+
+        Control::Continue
+    }
+
+    fn on_leave_for_statement(item: &ForStatement<'ast>, _: &StatementNode<'ast>, _: &NodePath<'ast>, t: &mut Transformer<'ast>) -> Control {
+        if let Some(ref init) = item.init {
+            if let ForInit::Declaration(DeclarationStatement { kind, declarators: list }) = init.item {
+                if list.iter().any(|declarator| is_destructuring(&declarator.id.item)) {
+                    let declarators = declarators(list, t);
+
+                    t.swap(init, DeclarationStatement {
+                        kind,
+                        declarators,
+                    });
+                }
+            }
+        }
+
+        Control::Continue
+    }
+
+    fn on_leave_for_in_statement(item: &ForInStatement<'ast>, _: &StatementNode<'ast>, path: &NodePath<'ast>, t: &mut Transformer<'ast>) -> Control {
+        for_head(&item.left, &item.body, path, t);
+
+        Control::Continue
+    }
+
+    fn on_leave_for_of_statement(item: &ForOfStatement<'ast>, _: &StatementNode<'ast>, path: &NodePath<'ast>, t: &mut Transformer<'ast>) -> Control {
+        for_head(&item.left, &item.body, path, t);
+
+        Control::Continue
+    }
+
+    fn on_leave_catch_clause(item: &CatchClause<'ast>, _: &Node<'ast, CatchClause<'ast>>, _: &NodePath<'ast>, t: &mut Transformer<'ast>) -> Control {
+        if !is_destructuring(&item.param.item) {
+            return Control::Continue;
+        }
+
+        let temporary = t.fresh_name("_ref");
+        let mut destructure = Destructure::default();
+
+        destructure.pattern(item.param.item, name(temporary, t), t);
+
+        // The param is only in scope of the block, same as `let`
+        let declaration = destructure.declaration(DeclarationKind::Let, t);
+        let body = prepend(declaration, item.body.body, t);
+
+        t.swap(&item.param, Pattern::Identifier(temporary));
+        t.swap(&item.body, Block { body });
+
+        Control::Continue
+    }
+
+    fn on_leave_function_statement(item: &FunctionStatement<'ast>, _: &StatementNode<'ast>, _: &NodePath<'ast>, t: &mut Transformer<'ast>) -> Control {
+        function(item, t);
+
+        Control::Continue
+    }
+
+    fn on_leave_function_expression(item: &FunctionExpression<'ast>, _: &ExpressionNode<'ast>, _: &NodePath<'ast>, t: &mut Transformer<'ast>) -> Control {
+        function(item, t);
+
+        Control::Continue
+    }
+
+    fn on_leave_arrow_expression(_: &ArrowExpression<'ast>, ptr: &ExpressionNode<'ast>, _: &NodePath<'ast>, t: &mut Transformer<'ast>) -> Control {
+        // Arrows can already be functions, if they are transformed as well
+        match ptr.item {
+            Expression::Function(ref item) => function(item, t),
+            Expression::Arrow(ArrowExpression {
+                params: list,
+                body: ArrowBody::Block(ref body),
+                ..
+            }) => params(list, body, t),
+            _ => {},
+        }
+
+        Control::Continue
+    }
+
+    fn on_leave_property(item: &Property<'ast>, _: &Node<'ast, Property<'ast>>, _: &NodePath<'ast>, t: &mut Transformer<'ast>) -> Control {
+        if let Property::Method { value, .. } = *item {
+            function(&value.item, t);
+        }
+
+        Control::Continue
+    }
+
+    fn on_leave_class_member(item: &ClassMember<'ast>, _: &Node<'ast, ClassMember<'ast>>, _: &NodePath<'ast>, t: &mut Transformer<'ast>) -> Control {
+        if let ClassMember::Method { value, .. } = *item {
+            function(&value.item, t);
+        }
+
+        Control::Continue
+    }
+
+    #[inline]
+    fn register(dv: &mut DynamicVisitor<'ast, Transformer<'ast>>) {
+        dv.on_leave_declaration_statement.push(TransformDestructuring::on_leave_declaration_statement);
+        dv.on_leave_binary_expression.push(TransformDestructuring::on_leave_binary_expression);
+        dv.on_leave_for_statement.push(TransformDestructuring::on_leave_for_statement);
+        dv.on_leave_for_in_statement.push(TransformDestructuring::on_leave_for_in_statement);
+        dv.on_leave_for_of_statement.push(TransformDestructuring::on_leave_for_of_statement);
+        dv.on_leave_catch_clause.push(TransformDestructuring::on_leave_catch_clause);
+        dv.on_leave_function_statement.push(TransformDestructuring::on_leave_function_statement);
+        dv.on_leave_function_expression.push(TransformDestructuring::on_leave_function_expression);
+        dv.on_leave_arrow_expression.push(TransformDestructuring::on_leave_arrow_expression);
+        dv.on_leave_property.push(TransformDestructuring::on_leave_property);
+        dv.on_leave_class_member.push(TransformDestructuring::on_leave_class_member);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ratel::{parse_with_options, ParseOptions, Goal};
+    use ratel_visitor::Visitable;
+    use ratel_codegen::codegen;
+    use edit::ApplyEdits;
+
+    fn assert_transform(source: &str, expected: &str) {
+        let module = parse_with_options(source, ParseOptions { goal: Goal::Module, ..ParseOptions::default() }).unwrap();
+        let mut transformer = Transformer::new(&module);
+
+        module.traverse(&(TransformDestructuring, ApplyEdits), &mut transformer);
+
+        assert_eq!(codegen(&module, true), expected);
+    }
+
+    #[test]
+    fn object_declaration() {
+        assert_transform("var {a, b: c} = d;", "var a=d.a,c=d.b;");
+        assert_transform("let {a} = f();", "let a=f().a;");
+        assert_transform("const {a, b} = f();", "const _ref=f(),a=_ref.a,b=_ref.b;");
+        assert_transform("var {'a-b': a, 1: b, [c]: d} = e;", "var a=e['a-b'],b=e[1],d=e[c];");
+        assert_transform("var x = 1, {a} = b, y;", "var x=1,a=b.a,y;");
+    }
+
+    #[test]
+    fn array_declaration() {
+        assert_transform("var [a, b] = c;", "var a=c[0],b=c[1];");
+        assert_transform("var [a, , b] = f();", "var _ref=f(),a=_ref[0],b=_ref[2];");
+        assert_transform("var [, a] = b;", "var a=b[1];");
+        assert_transform("var [a, b] = a;", "var _ref=a,a=_ref[0],b=_ref[1];");
+    }
+
+    #[test]
+    fn nested_declaration() {
+        assert_transform("var {a: {b, c}} = d;", "var _ref=d.a,b=_ref.b,c=_ref.c;");
+        assert_transform("var [{a}, [b]] = c;", "var a=c[0].a,b=c[1][0];");
+    }
+
+    #[test]
+    fn empty_patterns() {
+        assert_transform("var {} = f();", "var _ref=f();");
+        assert_transform("var [,] = a;", "var _ref=a;");
+    }
+
+    #[test]
+    fn default_values() {
+        assert_transform("var [a = 1, b] = c;", "var _ref=c[0],a=_ref===void 0?1:_ref,b=c[1];");
+        assert_transform("var {a: b = f()} = c;", "var _ref=c.a,b=_ref===void 0?f():_ref;");
+        assert_transform("var [{a} = {}] = b;", "var _ref=b[0],a=(_ref===void 0?{}:_ref).a;");
+    }
+
+    #[test]
+    fn exported_declaration() {
+        assert_transform("export var {a, b} = c;", "export var a=c.a,b=c.b;");
+    }
+
+    #[test]
+    fn params() {
+        assert_transform("function f([a, b], c) { return a; }", "function f(_ref,c){var a=_ref[0],b=_ref[1];return a;}");
+        assert_transform("function f({a} = {}, [b]) {}", "function f(_ref={},_ref2){var a=_ref.a,b=_ref2[0];}");
+        assert_transform("(function ({a: {b}}) {});", "(function(_ref){var b=_ref.a.b;});");
+        assert_transform("({ m({a}) { a; } });", "({m(_ref){var a=_ref.a;a;}});");
+        assert_transform("class A { m([a]) {} }", "class A{m(_ref){var a=_ref[0];}}");
+    }
+
+    #[test]
+    fn catch_param() {
+        assert_transform("try {} catch ({message}) { log(message); }", "try{}catch(_ref){let message=_ref.message;log(message);}");
+    }
+
+    #[test]
+    fn for_heads() {
+        assert_transform("for (var [k, v] of x) f(k, v);", "for(var _ref of x){var k=_ref[0],v=_ref[1];f(k,v);}");
+        assert_transform("for (let {a} in b) {}", "for(let _ref in b){let a=_ref.a;}");
+        assert_transform("for ([a, b] of c) {}", "for(var _ref of c){a=_ref[0],b=_ref[1];}");
+        assert_transform("for (var [a, b] = c; a; b) {}", "for(var a=c[0],b=c[1];a;b){}");
+    }
+
+    #[test]
+    fn assignment() {
+        assert_transform("[a, b] = [b, a];", "var _ref;_ref=[b,a],a=_ref[0],b=_ref[1];");
+        assert_transform("({a: x.y, b: z[0]} = o);", "x.y=o.a,z[0]=o.b;");
+        assert_transform("[a] = f();", "a=f()[0];");
+        assert_transform("[a, ...b] = c;", "a=c[0],b=c.slice(1);");
+        assert_transform("[c, a] = c;", "var _ref;_ref=c,c=_ref[0],a=_ref[1];");
+    }
+
+    #[test]
+    fn assignment_value() {
+        assert_transform("x = ([a] = y);", "x=(a=y[0],y);");
+        assert_transform("f([a, b] = g());", "var _ref;f((_ref=g(),a=_ref[0],b=_ref[1],_ref));");
+    }
+
+    #[test]
+    fn nested_assignment() {
+        assert_transform("[{a} = b] = c;", "var _ref;_ref=c[0],a=(_ref===void 0?b:_ref).a;");
+        assert_transform("({a: [b] = []} = c);", "var _ref;_ref=c.a,b=(_ref===void 0?[]:_ref)[0];");
+        assert_transform("x = [{a} = b];", "x=[(a=b.a,b)];");
+    }
+
+    #[test]
+    fn assignment_in_function() {
+        assert_transform(
+            "function f() { g(); [a, b] = h(); }",
+            "function f(){var _ref;g();_ref=h(),a=_ref[0],b=_ref[1];}"
+        );
+    }
+}
@@ -0,0 +1,346 @@
+use ratel::ast::{Node, ExpressionNode, Statement, StatementNode, Block};
+use ratel::ast::statement::{ForOfStatement, ForInit, DeclarationStatement, Declarator, LabeledStatement};
+use ratel_visitor::{StaticVisitor, DynamicVisitor, NodePath, Ancestor, Control};
+
+use es2015::block_scoping::is_listed;
+use Transformer;
+
+/// Turns `for-of` loops into `for` loops stepping through the iterator of
+/// the value. The iterator is closed with `return()` if the loop ends early.
+pub struct TransformForOf;
+
+/// Same as `TransformForOf`, but the value is assumed to be an array and
+/// gets indexed instead.
+pub struct TransformForOfLoose;
+
+/// Statement assigning the value of the current step to the head of the loop.
+fn step<'ast>(left: &Node<'ast, ForInit<'ast>>, value: ExpressionNode<'ast>, t: &mut Transformer<'ast>) -> StatementNode<'ast> {
+    match left.item {
+        ForInit::Declaration(DeclarationStatement { kind, declarators }) => {
+            let id = declarators.first_element().expect("Declaration has a declarator").id;
+            let declarator = t.alloc(Declarator {
+                id,
+                init: Some(value),
+            });
+            let declarators = t.list([declarator]);
+
+            t.alloc(DeclarationStatement {
+                kind,
+                declarators,
+            })
+        },
+        ForInit::Expression(target) => t.alloc(expr!(t, "$0 = $1", target, value)),
+    }
+}
+
+/// Body of the loop, starting with the step.
+fn body<'ast>(step: StatementNode<'ast>, body: StatementNode<'ast>, t: &mut Transformer<'ast>) -> StatementNode<'ast> {
+    let mut statements = vec![step];
+
+    match body.item {
+        Statement::Block(Block { body }) => statements.extend(body.iter().cloned()),
+        _                                => statements.push(body),
+    }
+
+    let body = t.list(statements);
+
+    t.alloc(Block { body })
+}
+
+/// Statements replacing the loop, with the labels of the loop kept on the
+/// `for` loop they turn into.
+fn lower<'ast>(item: &ForOfStatement<'ast>, labels: &[&'ast str], loose: bool, t: &mut Transformer<'ast>) -> Vec<StatementNode<'ast>> {
+    let (mut statement, setup) = match loose {
+        true => {
+            let index = t.fresh_name("_i");
+            let array = t.fresh_name("_arr");
+            let value = expr!(t, "$0[$1]", array, index);
+            let step = step(&item.left, value, t);
+            let body = body(step, item.body, t);
+
+            let statement = stmts!(t, "for (var $0 = 0, $1 = $2; $0 < $1.length; $0++) $3;", index, array, item.right, body);
+
+            (*statement.first_element().unwrap(), None)
+        },
+        false => {
+            let normal = t.fresh_name("_iteratorNormalCompletion");
+            let did_error = t.fresh_name("_didIteratorError");
+            let error = t.fresh_name("_iteratorError");
+            let iterator = t.fresh_name("_iterator");
+            let current = t.fresh_name("_step");
+            let value = expr!(t, "$0.value", current);
+            let step = step(&item.left, value, t);
+            let body = body(step, item.body, t);
+
+            let statement = stmts!(
+                t,
+                "for (var $0 = $1[Symbol.iterator](), $2; !($3 = ($2 = $0.next()).done); $3 = true) $4;",
+                iterator,
+                item.right,
+                current,
+                normal,
+                body
+            );
+
+            (*statement.first_element().unwrap(), Some((normal, did_error, error, iterator)))
+        },
+    };
+
+    for label in labels.iter().rev() {
+        statement = t.alloc(LabeledStatement {
+            label,
+            body: statement,
+        });
+    }
+
+    let (normal, did_error, error, iterator) = match setup {
+        Some(setup) => setup,
+        None        => return vec![statement],
+    };
+
+    // Errors thrown by `return()` give way to the one ending the loop
+    stmts!(
+        t,
+        "var $0 = true, $1 = false, $2 = void 0;
+        try {
+            $3;
+        } catch (err) {
+            $1 = true;
+            $2 = err;
+        } finally {
+            try {
+                if (!$0 && $4.return != null) {
+                    $4.return();
+                }
+            } finally {
+                if ($1) {
+                    throw $2;
+                }
+            }
+        }",
+        normal,
+        did_error,
+        error,
+        statement,
+        iterator
+    ).iter().cloned().collect()
+}
+
+/// Replace the statement with the ones the loop turned into.
+fn replace<'ast>(node: &StatementNode<'ast>, statements: Vec<StatementNode<'ast>>, listed: bool, t: &mut Transformer<'ast>) {
+    if statements.len() == 1 {
+        t.swap(node, statements[0].item);
+    } else if listed {
+        let statements = t.list(statements);
+
+        t.replace_with_many(node, statements);
+    } else {
+        let body = t.list(statements);
+
+        t.swap(node, Block { body });
+    }
+}
+
+/// Loop the statement is, or the loop at the end of the block it has been
+/// put in, along with the statements it needs, by `TransformBlockScoping`.
+fn for_of<'a, 'ast>(node: &'a StatementNode<'ast>) -> Option<(&'a StatementNode<'ast>, bool)> {
+    match node.item {
+        Statement::ForOf(_) => Some((node, true)),
+        Statement::Block(Block { body }) => match body.iter().last() {
+            Some(last) if matches!(last.item, Statement::ForOf(_)) => Some((last, false)),
+            _                                                     => None,
+        },
+        _ => None,
+    }
+}
+
+#[inline]
+fn is_labeled<'ast>(path: &NodePath<'ast>) -> bool {
+    matches!(path.parent(), Some(Ancestor::Statement(parent)) if matches!(parent.item, Statement::Labeled(_)))
+}
+
+fn for_of_statement<'ast>(node: &StatementNode<'ast>, path: &NodePath<'ast>, loose: bool, t: &mut Transformer<'ast>) {
+    // Labels have to stay on the loop, labeled loops are done with them
+    if is_labeled(path) {
+        return;
+    }
+
+    if let Some((node, outer)) = for_of(node) {
+        if let Statement::ForOf(ref item) = node.item {
+            let statements = lower(item, &[], loose, t);
+
+            replace(node, statements, outer && is_listed(path), t);
+        }
+    }
+}
+
+fn labeled_statement<'ast>(node: &StatementNode<'ast>, path: &NodePath<'ast>, loose: bool, t: &mut Transformer<'ast>) {
+    if is_labeled(path) {
+        return;
+    }
+
+    let mut labels = Vec::new();
+    let mut body = *node;
+
+    while let Statement::Labeled(LabeledStatement { label, body: inner }) = body.item {
+        labels.push(label);
+        body = inner;
+    }
+
+    if let Some((inner, outer)) = for_of(&body) {
+        if let Statement::ForOf(ref item) = inner.item {
+            // Loops moved to a block don't jump to their labels anymore
+            match outer {
+                true  => {
+                    let statements = lower(item, &labels, loose, t);
+
+                    replace(node, statements, is_listed(path), t);
+                },
+                false => {
+                    let statements = lower(item, &[], loose, t);
+
+                    replace(inner, statements, false, t);
+                },
+            }
+        }
+    }
+}
+
+impl<'ast> StaticVisitor<'ast> for TransformForOf {
+    type Context = Transformer<'ast>;
+
+    fn on_leave_for_of_statement(_: &ForOfStatement<'ast>, node: &StatementNode<'ast>, path: &NodePath<'ast>, t: &mut Transformer<'ast>) -> Control {
+        for_of_statement(node, path, false, t);
+
+        Control::Continue
+    }
+
+    fn on_leave_labeled_statement(_: &LabeledStatement<'ast>, node: &StatementNode<'ast>, path: &NodePath<'ast>, t: &mut Transformer<'ast>) -> Control {
+        labeled_statement(node, path, false, t);
+
+        Control::Continue
+    }
+
+    #[inline]
+    fn register(dv: &mut DynamicVisitor<'ast, Transformer<'ast>>) {
+        dv.on_leave_for_of_statement.push(TransformForOf::on_leave_for_of_statement);
+        dv.on_leave_labeled_statement.push(TransformForOf::on_leave_labeled_statement);
+    }
+}
+
+impl<'ast> StaticVisitor<'ast> for TransformForOfLoose {
+    type Context = Transformer<'ast>;
+
+    fn on_leave_for_of_statement(_: &ForOfStatement<'ast>, node: &StatementNode<'ast>, path: &NodePath<'ast>, t: &mut Transformer<'ast>) -> Control {
+        for_of_statement(node, path, true, t);
+
+        Control::Continue
+    }
+
+    fn on_leave_labeled_statement(_: &LabeledStatement<'ast>, node: &StatementNode<'ast>, path: &NodePath<'ast>, t: &mut Transformer<'ast>) -> Control {
+        labeled_statement(node, path, true, t);
+
+        Control::Continue
+    }
+
+    #[inline]
+    fn register(dv: &mut DynamicVisitor<'ast, Transformer<'ast>>) {
+        dv.on_leave_for_of_statement.push(TransformForOfLoose::on_leave_for_of_statement);
+        dv.on_leave_labeled_statement.push(TransformForOfLoose::on_leave_labeled_statement);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ratel::parse;
+    use ratel_visitor::Visitable;
+    use ratel_codegen::codegen;
+    use edit::ApplyEdits;
+    use es2015::{TransformDestructuring, TransformBlockScoping};
+
+    fn spec(right: &str, body: &str) -> String {
+        format!(
+            "var _iteratorNormalCompletion=true,_didIteratorError=false,_iteratorError=void 0;\
+             try{{{}}}catch(err){{_didIteratorError=true;_iteratorError=err;}}\
+             finally{{try{{if(!_iteratorNormalCompletion&&_iterator.return!=null){{_iterator.return();}}}}\
+             finally{{if(_didIteratorError){{throw _iteratorError;}}}}}}",
+            format!(
+                "for(var _iterator={}[Symbol.iterator](),_step;!(_iteratorNormalCompletion=(_step=_iterator.next()).done);\
+                 _iteratorNormalCompletion=true){}",
+                right,
+                body
+            )
+        )
+    }
+
+    fn assert_transform(source: &str, expected: &str) {
+        let module = parse(source).unwrap();
+        let mut transformer = Transformer::new(&module);
+
+        module.traverse(&((TransformDestructuring, TransformForOf), ApplyEdits), &mut transformer);
+
+        assert_eq!(codegen(&module, true), expected);
+    }
+
+    fn assert_loose(source: &str, expected: &str) {
+        let module = parse(source).unwrap();
+        let mut transformer = Transformer::new(&module);
+
+        module.traverse(&((TransformDestructuring, TransformForOfLoose), ApplyEdits), &mut transformer);
+
+        assert_eq!(codegen(&module, true), expected);
+    }
+
+    #[test]
+    fn for_of() {
+        assert_transform("for (var a of b) f(a);", &spec("b", "{var a=_step.value;f(a);}"));
+        assert_transform("for (let a of b) { f(a); }", &spec("b", "{let a=_step.value;f(a);}"));
+        assert_transform("for (a.b of c) {}", &spec("c", "{a.b=_step.value;}"));
+    }
+
+    #[test]
+    fn for_of_loose() {
+        assert_loose("for (var a of b) f(a);", "for(var _i=0,_arr=b;_i<_arr.length;_i++){var a=_arr[_i];f(a);}");
+        assert_loose("for (a of f()) {}", "for(var _i=0,_arr=f();_i<_arr.length;_i++){a=_arr[_i];}");
+        assert_loose(
+            "for (var a of b) for (var c of a) {}",
+            "for(var _i2=0,_arr2=b;_i2<_arr2.length;_i2++){var a=_arr2[_i2];\
+             for(var _i=0,_arr=a;_i<_arr.length;_i++){var c=_arr[_i];}}"
+        );
+    }
+
+    #[test]
+    fn destructuring() {
+        assert_transform("for (var [a, b] of c) {}", &spec("c", "{var _ref=_step.value;var a=_ref[0],b=_ref[1];}"));
+        assert_loose("for (const {a} of b) {}", "for(var _i=0,_arr=b;_i<_arr.length;_i++){const _ref=_arr[_i];const a=_ref.a;}");
+    }
+
+    #[test]
+    fn nested_statement() {
+        assert_transform("if (a) for (var b of c) {}", &format!("if(a){{{}}}", spec("c", "{var b=_step.value;}")));
+        assert_loose("if (a) for (var b of c) {}", "if(a)for(var _i=0,_arr=c;_i<_arr.length;_i++){var b=_arr[_i];}");
+    }
+
+    #[test]
+    fn labels() {
+        assert_transform("a: for (var b of c) continue a;", &spec("c", "{var b=_step.value;continue a;}").replace(
+            "try{for(",
+            "try{a:for("
+        ));
+        assert_loose("a: b: for (var c of d) break a;", "a:b:for(var _i=0,_arr=d;_i<_arr.length;_i++){var c=_arr[_i];break a;}");
+    }
+
+    #[test]
+    fn block_scoping() {
+        let module = parse("a: for (let b of c) f(() => b);").unwrap();
+        let mut transformer = Transformer::new(&module);
+
+        module.traverse(&((TransformBlockScoping, TransformForOfLoose), ApplyEdits), &mut transformer);
+
+        assert_eq!(
+            codegen(&module, true),
+            "a:{var _loop=function(b){f(()=>b);};for(var _i=0,_arr=c;_i<_arr.length;_i++){var b=_arr[_i];_loop(b);}}"
+        );
+    }
+}
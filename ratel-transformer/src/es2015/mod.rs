@@ -1,5 +1,19 @@
-mod arrow;
+pub(crate) mod arrow;
+pub mod block_scoping;
+pub mod class;
+pub mod destructuring;
+pub mod for_of;
+pub mod parameters;
+pub mod spread;
+pub mod template;
 
-use self::arrow::TransformArrow;
+pub use self::arrow::TransformArrow;
+pub use self::block_scoping::TransformBlockScoping;
+pub use self::class::TransformClass;
+pub use self::destructuring::TransformDestructuring;
+pub use self::for_of::{TransformForOf, TransformForOfLoose};
+pub use self::parameters::TransformParameters;
+pub use self::spread::{TransformSpread, TransformSpreadLoose};
+pub use self::template::TransformTemplate;
 
 pub type PresetES2015 = TransformArrow;
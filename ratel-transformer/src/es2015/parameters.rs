@@ -0,0 +1,258 @@
+use ratel::ast::{Node, Expression, ExpressionNode, StatementNode, Literal};
+use ratel::ast::{Pattern, Property, Function, Name, Block, ClassMember, DeclarationKind};
+use ratel::ast::expression::{ArrowExpression, FunctionExpression};
+use ratel::ast::statement::{FunctionStatement, DeclarationStatement, Declarator};
+use ratel_visitor::{StaticVisitor, DynamicVisitor, NodePath, Control};
+
+use Transformer;
+
+/// Turns default and rest params into statements at the start of the body.
+/// Params from the first default or rest one on are read from `arguments`,
+/// so that the `length` of the function doesn't change.
+pub struct TransformParameters;
+
+/// Param without its Flow type, if it has one.
+fn untyped<'ast>(param: Node<'ast, Pattern<'ast>>) -> Node<'ast, Pattern<'ast>> {
+    match param.item {
+        Pattern::Typed { pattern, .. } => untyped(pattern),
+        _                              => param,
+    }
+}
+
+#[inline]
+fn index<'ast>(index: usize, t: &Transformer<'ast>) -> ExpressionNode<'ast> {
+    t.alloc(Literal::Number(t.arena.alloc_str(&index.to_string())))
+}
+
+fn declaration<'ast>(id: Node<'ast, Pattern<'ast>>, init: ExpressionNode<'ast>, t: &mut Transformer<'ast>) -> StatementNode<'ast> {
+    let declarator = t.alloc(Declarator {
+        id,
+        init: Some(init),
+    });
+    let declarators = t.list([declarator]);
+
+    t.alloc(DeclarationStatement {
+        kind: DeclarationKind::Var,
+        declarators,
+    })
+}
+
+/// Function with the params from the first default or rest one on read in
+/// the body instead, if it has any.
+fn function<'ast, N: Name<'ast>>(function: &Function<'ast, N>, t: &mut Transformer<'ast>) -> Option<Function<'ast, N>> {
+    let kept = function.params.iter().take_while(|param| {
+        !matches!(untyped(**param).item, Pattern::AssignmentPattern { .. } | Pattern::RestElement { .. })
+    }).count();
+
+    if kept == function.params.iter().count() {
+        return None;
+    }
+
+    let mut statements = Vec::new();
+
+    for (position, param) in function.params.iter().enumerate().skip(kept) {
+        let param = untyped(*param);
+
+        match param.item {
+            Pattern::RestElement { argument } => {
+                let key = t.fresh_name("_i");
+                let start = index(position, t);
+                let rest = stmts!(
+                    t,
+                    "var $0 = []; for (var $1 = $2; $1 < arguments.length; $1++) { $0.push(arguments[$1]); }",
+                    argument.item,
+                    key,
+                    start
+                );
+
+                statements.extend(rest.iter().cloned());
+            },
+            Pattern::AssignmentPattern { left, right } => {
+                let left = untyped(left);
+                let value = index(position, t);
+
+                match left.item {
+                    Pattern::Identifier(ident) => {
+                        let default = stmts!(t, "var $0 = arguments[$1]; if ($0 === void 0) $0 = $2;", ident, value, right);
+
+                        statements.extend(default.iter().cloned());
+                    },
+                    _ => {
+                        let test = index(position, t);
+                        let value = expr!(t, "arguments[$0] === void 0 ? $1 : arguments[$2]", test, right, value);
+
+                        statements.push(declaration(left, value, t));
+                    },
+                }
+            },
+            _ => {
+                let value = index(position, t);
+                let value = expr!(t, "arguments[$0]", value);
+
+                statements.push(declaration(param, value, t));
+            },
+        }
+    }
+
+    statements.extend(function.body.body.iter().cloned());
+
+    let params = function.params.iter().take(kept).cloned().collect::<Vec<_>>();
+    let params = t.list(params);
+    let body = t.list(statements);
+
+    Some(Function {
+        params,
+        body: t.alloc_as_loc(function.body, Block { body }),
+        ..*function
+    })
+}
+
+impl<'ast> StaticVisitor<'ast> for TransformParameters {
+    type Context = Transformer<'ast>;
+
+    fn on_leave_function_statement(item: &FunctionStatement<'ast>, node: &StatementNode<'ast>, _: &NodePath<'ast>, t: &mut Transformer<'ast>) -> Control {
+        if let Some(function) = function(item, t) {
+            t.swap(node, function);
+        }
+
+        Control::Continue
+    }
+
+    fn on_leave_function_expression(item: &FunctionExpression<'ast>, ptr: &ExpressionNode<'ast>, _: &NodePath<'ast>, t: &mut Transformer<'ast>) -> Control {
+        if let Some(function) = function(item, t) {
+            t.swap(ptr, function);
+        }
+
+        Control::Continue
+    }
+
+    fn on_leave_arrow_expression(_: &ArrowExpression<'ast>, ptr: &ExpressionNode<'ast>, _: &NodePath<'ast>, t: &mut Transformer<'ast>) -> Control {
+        // `arguments` of an arrow are those of the function around it, its
+        // params can only be read from `arguments` once it's a function
+        if let Expression::Function(ref item) = ptr.item {
+            if let Some(function) = function(item, t) {
+                t.swap(ptr, function);
+            }
+        }
+
+        Control::Continue
+    }
+
+    fn on_leave_property(item: &Property<'ast>, _: &Node<'ast, Property<'ast>>, _: &NodePath<'ast>, t: &mut Transformer<'ast>) -> Control {
+        if let Property::Method { ref value, .. } = *item {
+            if let Some(function) = function(&value.item, t) {
+                t.swap(value, function);
+            }
+        }
+
+        Control::Continue
+    }
+
+    fn on_leave_class_member(item: &ClassMember<'ast>, _: &Node<'ast, ClassMember<'ast>>, _: &NodePath<'ast>, t: &mut Transformer<'ast>) -> Control {
+        if let ClassMember::Method { ref value, .. } = *item {
+            if let Some(function) = function(&value.item, t) {
+                t.swap(value, function);
+            }
+        }
+
+        Control::Continue
+    }
+
+    #[inline]
+    fn register(dv: &mut DynamicVisitor<'ast, Transformer<'ast>>) {
+        dv.on_leave_function_statement.push(TransformParameters::on_leave_function_statement);
+        dv.on_leave_function_expression.push(TransformParameters::on_leave_function_expression);
+        dv.on_leave_arrow_expression.push(TransformParameters::on_leave_arrow_expression);
+        dv.on_leave_property.push(TransformParameters::on_leave_property);
+        dv.on_leave_class_member.push(TransformParameters::on_leave_class_member);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ratel::parse;
+    use ratel_visitor::Visitable;
+    use ratel_codegen::codegen;
+    use edit::ApplyEdits;
+    use es2015::{TransformArrow, TransformDestructuring};
+
+    fn assert_transform(source: &str, expected: &str) {
+        let module = parse(source).unwrap();
+        let mut transformer = Transformer::new(&module);
+
+        module.traverse(&(TransformParameters, ApplyEdits), &mut transformer);
+
+        assert_eq!(codegen(&module, true), expected);
+    }
+
+    #[test]
+    fn default_parameters() {
+        assert_transform(
+            "function f(a, b = 1) { return a + b; }",
+            "function f(a){var b=arguments[1];if(b===void 0)b=1;return a+b;}"
+        );
+        assert_transform(
+            "(function (a = b()) {});",
+            "(function(){var a=arguments[0];if(a===void 0)a=b();});"
+        );
+    }
+
+    #[test]
+    fn keeps_length() {
+        assert_transform(
+            "function f(a, b = 1, c) {}",
+            "function f(a){var b=arguments[1];if(b===void 0)b=1;var c=arguments[2];}"
+        );
+    }
+
+    #[test]
+    fn rest_parameter() {
+        assert_transform(
+            "function f(a, ...b) { return b; }",
+            "function f(a){var b=[];for(var _i=1;_i<arguments.length;_i++){b.push(arguments[_i]);}return b;}"
+        );
+        assert_transform(
+            "function f(...a) {} function g(...b) {}",
+            "function f(){var a=[];for(var _i=0;_i<arguments.length;_i++){a.push(arguments[_i]);}}\
+             function g(){var b=[];for(var _i2=0;_i2<arguments.length;_i2++){b.push(arguments[_i2]);}}"
+        );
+    }
+
+    #[test]
+    fn methods() {
+        assert_transform("({ m(a = 1) {} });", "({m(){var a=arguments[0];if(a===void 0)a=1;}});");
+        assert_transform(
+            "class A { constructor(a, ...b) {} }",
+            "class A{constructor(a){var b=[];for(var _i=1;_i<arguments.length;_i++){b.push(arguments[_i]);}}}"
+        );
+    }
+
+    #[test]
+    fn arrows() {
+        let module = parse("f((a = 1) => a);").unwrap();
+        let mut transformer = Transformer::new(&module);
+
+        module.traverse(&((TransformArrow, TransformParameters), ApplyEdits), &mut transformer);
+
+        assert_eq!(codegen(&module, true), "f(function(){var a=arguments[0];if(a===void 0)a=1;return a;});");
+    }
+
+    #[test]
+    fn destructured_default() {
+        let module = parse("function f({a} = {}) {}").unwrap();
+        let mut transformer = Transformer::new(&module);
+
+        module.traverse(&((TransformDestructuring, TransformParameters), ApplyEdits), &mut transformer);
+
+        assert_eq!(codegen(&module, true), "function f(){var _ref=arguments[0];if(_ref===void 0)_ref={};var a=_ref.a;}");
+    }
+
+    #[test]
+    fn pattern_with_default() {
+        assert_transform(
+            "function f([a] = []) {}",
+            "function f(){var [a]=arguments[0]===void 0?[]:arguments[0];}"
+        );
+    }
+}
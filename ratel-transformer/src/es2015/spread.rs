@@ -0,0 +1,321 @@
+use std::mem;
+
+use ratel::ast::{Expression, ExpressionNode, ExpressionList, OperatorKind};
+use ratel::ast::expression::{ArrayExpression, CallExpression, MemberExpression, ComputedMemberExpression};
+use ratel::ast::expression::{PrefixExpression, SpreadExpression};
+use ratel_visitor::{StaticVisitor, DynamicVisitor, NodePath, Ancestor, Control};
+
+use es2015::destructuring::declare;
+use Transformer;
+
+/// Turns spread arguments into calls to `apply`, or to `bind` for `new`,
+/// and spread elements into arrays concatenated together. Spread values are copied into arrays by
+/// iterating them first.
+pub struct TransformSpread;
+
+/// Same as `TransformSpread`, but spread values are assumed to be arrays
+/// and passed on as they are.
+pub struct TransformSpreadLoose;
+
+const TO_CONSUMABLE_ARRAY: &str = "function $0(iterable) {
+    if (Array.isArray(iterable)) {
+        return iterable.slice();
+    }
+
+    var array = [];
+
+    if (typeof Symbol === \"function\" && iterable[Symbol.iterator]) {
+        var iterator = iterable[Symbol.iterator](), step;
+
+        while (!(step = iterator.next()).done) {
+            array.push(step.value);
+        }
+    } else {
+        for (var i = 0; i < iterable.length; i++) {
+            array.push(iterable[i]);
+        }
+    }
+
+    return array;
+}";
+
+#[inline]
+fn is_spread(list: ExpressionList) -> bool {
+    list.iter().any(|expression| matches!(expression.item, Expression::Spread(_)))
+}
+
+/// Array with the elements of the list, and spread values in their place.
+/// Unless `copy` is set, the array can be a spread value itself.
+fn concat<'ast>(list: ExpressionList<'ast>, loose: bool, copy: bool, t: &mut Transformer<'ast>) -> ExpressionNode<'ast> {
+    let mut parts = Vec::new();
+    let mut elements = Vec::new();
+
+    for expression in list.iter() {
+        match expression.item {
+            Expression::Spread(SpreadExpression { argument }) => {
+                if !elements.is_empty() {
+                    let body = t.list(mem::take(&mut elements));
+
+                    parts.push(t.alloc(ArrayExpression { body }));
+                }
+
+                parts.push(match loose {
+                    true  => argument,
+                    false => {
+                        let helper = t.helper("_toConsumableArray", TO_CONSUMABLE_ARRAY);
+
+                        expr!(t, "$0($1)", helper, argument)
+                    },
+                });
+            },
+            _ => elements.push(*expression),
+        }
+    }
+
+    if !elements.is_empty() {
+        let body = t.list(elements);
+
+        parts.push(t.alloc(ArrayExpression { body }));
+    }
+
+    // Values of the helper are new arrays, as are array literals
+    let fresh = !loose || matches!(parts[0].item, Expression::Array(_));
+
+    if parts.len() == 1 && (fresh || !copy) {
+        return parts[0];
+    }
+
+    let first = match fresh {
+        true  => parts.remove(0),
+        false => expr!(t, "[]"),
+    };
+    let arguments = t.list(parts);
+
+    t.alloc(CallExpression {
+        callee: expr!(t, "$0.concat", first),
+        arguments,
+    })
+}
+
+/// Callee, and the value of `this` it has to be applied with.
+fn callee<'ast>(callee: ExpressionNode<'ast>, path: &NodePath<'ast>, t: &mut Transformer<'ast>) -> (ExpressionNode<'ast>, ExpressionNode<'ast>) {
+    let object = match callee.item {
+        Expression::Member(MemberExpression { object, .. }) |
+        Expression::ComputedMember(ComputedMemberExpression { object, .. }) => object,
+        _ => return (callee, expr!(t, "void 0")),
+    };
+
+    match object.item {
+        Expression::Identifier("super") => return (callee, expr!(t, "this")),
+        Expression::Identifier(_) |
+        Expression::This(_)             => return (callee, object),
+        _                               => {},
+    }
+
+    // The object is evaluated once, when the callee is
+    let temporary = t.fresh_name("_obj");
+    let object = expr!(t, "$0 = $1", temporary, object);
+
+    declare(&[temporary], path, t);
+
+    let callee = match callee.item {
+        Expression::Member(member) => t.alloc(MemberExpression {
+            object,
+            ..member
+        }),
+        Expression::ComputedMember(member) => t.alloc(ComputedMemberExpression {
+            object,
+            ..member
+        }),
+        _ => unreachable!(),
+    };
+
+    (callee, t.alloc(temporary))
+}
+
+fn call<'ast>(item: &CallExpression<'ast>, ptr: &ExpressionNode<'ast>, path: &NodePath<'ast>, loose: bool, t: &mut Transformer<'ast>) {
+    if !is_spread(item.arguments) {
+        return;
+    }
+
+    // Arguments of `new` are spread by `construct`
+    if let Some(Ancestor::Expression(parent)) = path.ancestors().next() {
+        if let Expression::Prefix(PrefixExpression { operator: OperatorKind::New, .. }) = parent.item {
+            return;
+        }
+    }
+
+    let (callee, this) = callee(item.callee, path, t);
+    let arguments = concat(item.arguments, loose, false, t);
+    let apply = expr!(t, "$0.apply($1, $2)", callee, this, arguments);
+
+    t.swap(ptr, apply.item);
+}
+
+/// Constructors can't be applied, so `new` calls the constructor bound
+/// to the arguments instead, `new (Function.prototype.bind.apply(F, args))()`.
+fn construct<'ast>(item: &PrefixExpression<'ast>, ptr: &ExpressionNode<'ast>, loose: bool, t: &mut Transformer<'ast>) {
+    let call = match (item.operator, item.operand.item) {
+        (OperatorKind::New, Expression::Call(call)) if is_spread(call.arguments) => call,
+        _ => return,
+    };
+
+    // `bind` takes the value of `this` first, which `new` ignores
+    let mut arguments = vec![expr!(t, "null")];
+
+    arguments.extend(call.arguments.iter().cloned());
+
+    let arguments = t.list(arguments);
+    let arguments = concat(arguments, loose, false, t);
+    let construct = expr!(t, "new (Function.prototype.bind.apply($0, $1))()", call.callee, arguments);
+
+    t.swap(ptr, construct.item);
+}
+
+fn array<'ast>(item: &ArrayExpression<'ast>, ptr: &ExpressionNode<'ast>, loose: bool, t: &mut Transformer<'ast>) {
+    if is_spread(item.body) {
+        let array = concat(item.body, loose, true, t);
+
+        t.swap(ptr, array.item);
+    }
+}
+
+impl<'ast> StaticVisitor<'ast> for TransformSpread {
+    type Context = Transformer<'ast>;
+
+    fn on_leave_call_expression(item: &CallExpression<'ast>, ptr: &ExpressionNode<'ast>, path: &NodePath<'ast>, t: &mut Transformer<'ast>) -> Control {
+        call(item, ptr, path, false, t);
+
+        Control::Continue
+    }
+
+    fn on_leave_array_expression(item: &ArrayExpression<'ast>, ptr: &ExpressionNode<'ast>, _: &NodePath<'ast>, t: &mut Transformer<'ast>) -> Control {
+        array(item, ptr, false, t);
+
+        Control::Continue
+    }
+
+    fn on_leave_prefix_expression(item: &PrefixExpression<'ast>, ptr: &ExpressionNode<'ast>, _: &NodePath<'ast>, t: &mut Transformer<'ast>) -> Control {
+        construct(item, ptr, false, t);
+
+        Control::Continue
+    }
+
+    #[inline]
+    fn register(dv: &mut DynamicVisitor<'ast, Transformer<'ast>>) {
+        dv.on_leave_call_expression.push(TransformSpread::on_leave_call_expression);
+        dv.on_leave_array_expression.push(TransformSpread::on_leave_array_expression);
+        dv.on_leave_prefix_expression.push(TransformSpread::on_leave_prefix_expression);
+    }
+}
+
+impl<'ast> StaticVisitor<'ast> for TransformSpreadLoose {
+    type Context = Transformer<'ast>;
+
+    fn on_leave_call_expression(item: &CallExpression<'ast>, ptr: &ExpressionNode<'ast>, path: &NodePath<'ast>, t: &mut Transformer<'ast>) -> Control {
+        call(item, ptr, path, true, t);
+
+        Control::Continue
+    }
+
+    fn on_leave_array_expression(item: &ArrayExpression<'ast>, ptr: &ExpressionNode<'ast>, _: &NodePath<'ast>, t: &mut Transformer<'ast>) -> Control {
+        array(item, ptr, true, t);
+
+        Control::Continue
+    }
+
+    fn on_leave_prefix_expression(item: &PrefixExpression<'ast>, ptr: &ExpressionNode<'ast>, _: &NodePath<'ast>, t: &mut Transformer<'ast>) -> Control {
+        construct(item, ptr, true, t);
+
+        Control::Continue
+    }
+
+    #[inline]
+    fn register(dv: &mut DynamicVisitor<'ast, Transformer<'ast>>) {
+        dv.on_leave_call_expression.push(TransformSpreadLoose::on_leave_call_expression);
+        dv.on_leave_array_expression.push(TransformSpreadLoose::on_leave_array_expression);
+        dv.on_leave_prefix_expression.push(TransformSpreadLoose::on_leave_prefix_expression);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ratel::parse;
+    use ratel_visitor::Visitable;
+    use ratel_codegen::codegen;
+    use edit::ApplyEdits;
+
+    const HELPER: &str = "function _toConsumableArray(iterable){if(Array.isArray(iterable)){return iterable.slice();}var array=[];\
+        if(typeof Symbol===\"function\"&&iterable[Symbol.iterator]){var iterator=iterable[Symbol.iterator](),step;\
+        while(!(step=iterator.next()).done){array.push(step.value);}}else{for(var i=0;i<iterable.length;i++){array.push(iterable[i]);}}\
+        return array;}";
+
+    fn assert_transform(source: &str, expected: &str) {
+        let module = parse(source).unwrap();
+        let mut transformer = Transformer::new(&module);
+
+        module.traverse(&(TransformSpread, ApplyEdits), &mut transformer);
+
+        assert_eq!(codegen(&module, true), format!("{}{}", HELPER, expected));
+    }
+
+    fn assert_loose(source: &str, expected: &str) {
+        let module = parse(source).unwrap();
+        let mut transformer = Transformer::new(&module);
+
+        module.traverse(&(TransformSpreadLoose, ApplyEdits), &mut transformer);
+
+        assert_eq!(codegen(&module, true), expected);
+    }
+
+    #[test]
+    fn spread_arguments() {
+        assert_transform("f(...a);", "f.apply(void 0,_toConsumableArray(a));");
+        assert_transform("f(a, ...b, c);", "f.apply(void 0,[a].concat(_toConsumableArray(b),[c]));");
+        assert_transform("f(...a, ...b);", "f.apply(void 0,_toConsumableArray(a).concat(_toConsumableArray(b)));");
+    }
+
+    #[test]
+    fn spread_arguments_loose() {
+        assert_loose("f(...a);", "f.apply(void 0,a);");
+        assert_loose("f(a, ...b, c);", "f.apply(void 0,[a].concat(b,[c]));");
+        assert_loose("f(...a, b);", "f.apply(void 0,[].concat(a,[b]));");
+    }
+
+    #[test]
+    fn method_calls() {
+        assert_loose("a.f(...b);", "a.f.apply(a,b);");
+        assert_loose("this[f](...b);", "this[f].apply(this,b);");
+        assert_loose("super.f(...b);", "super.f.apply(this,b);");
+        assert_loose("a.b.f(...c);", "var _obj;(_obj=a.b).f.apply(_obj,c);");
+        assert_loose("function g() { a().f(...c); }", "function g(){var _obj;(_obj=a()).f.apply(_obj,c);}");
+    }
+
+    #[test]
+    fn spread_elements() {
+        assert_transform("[...a];", "_toConsumableArray(a);");
+        assert_transform("[a, ...b];", "[a].concat(_toConsumableArray(b));");
+        assert_transform("[, a, ...b];", "[,a].concat(_toConsumableArray(b));");
+    }
+
+    #[test]
+    fn spread_elements_loose() {
+        assert_loose("[...a];", "[].concat(a);");
+        assert_loose("[a, ...b];", "[a].concat(b);");
+        assert_loose("[...a, b, ...c];", "[].concat(a,[b],c);");
+    }
+
+    #[test]
+    fn nested() {
+        assert_loose("f(...[a, ...b]);", "f.apply(void 0,[a].concat(b));");
+    }
+
+    #[test]
+    fn new_expressions() {
+        assert_transform("new F(...a);", "new (Function.prototype.bind.apply(F,[null].concat(_toConsumableArray(a))))();");
+        assert_loose("new F(...a);", "new (Function.prototype.bind.apply(F,[null].concat(a)))();");
+        assert_loose("new a.F(b, ...c);", "new (Function.prototype.bind.apply(a.F,[null,b].concat(c)))();");
+        assert_loose("new F(a);", "new F(a);");
+    }
+}
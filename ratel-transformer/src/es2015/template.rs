@@ -0,0 +1,228 @@
+use ratel::ast::{ExpressionNode, Literal, OperatorKind};
+use ratel::ast::expression::{TemplateLiteral, TaggedTemplateExpression, BinaryExpression};
+use ratel::ast::expression::{ArrayExpression, CallExpression};
+use ratel_visitor::{StaticVisitor, DynamicVisitor, NodePath, Control};
+
+use Transformer;
+
+/// Turns template literals into string concatenation, and tagged templates
+/// into calls with the array of strings.
+pub struct TransformTemplate;
+
+const TAGGED_TEMPLATE_LITERAL: &str = "function $0(strings, raw) {
+    return Object.freeze(Object.defineProperties(strings, {
+        raw: { value: Object.freeze(raw) }
+    }));
+}";
+
+/// Push a character of a quasi to a double quoted string, line breaks are
+/// normalized the same way they are in templates.
+#[inline]
+fn push(string: &mut String, character: char) {
+    match character {
+        '"'        => string.push_str("\\\""),
+        '\n'       => string.push_str("\\n"),
+        '\u{2028}' => string.push_str("\\u2028"),
+        '\u{2029}' => string.push_str("\\u2029"),
+        _          => string.push(character),
+    }
+}
+
+/// Source of a string literal with the value of the quasi. Escapes are the
+/// same in strings, other than for the characters delimiting templates.
+fn cooked(quasi: &str) -> String {
+    let mut string = String::with_capacity(quasi.len() + 2);
+    let mut chars = quasi.chars().peekable();
+
+    string.push('"');
+
+    while let Some(character) = chars.next() {
+        match character {
+            '\\' => match chars.next() {
+                Some('`')  => string.push('`'),
+                Some('$')  => string.push('$'),
+                Some(next) => {
+                    string.push('\\');
+                    string.push(next);
+                },
+                None       => string.push_str("\\\\"),
+            },
+            '\r' => {
+                if chars.peek() == Some(&'\n') {
+                    chars.next();
+                }
+
+                push(&mut string, '\n');
+            },
+            _ => push(&mut string, character),
+        }
+    }
+
+    string.push('"');
+    string
+}
+
+/// Source of a string literal with the quasi as it is written, for the
+/// `raw` property of the strings passed to tags.
+fn raw(quasi: &str) -> String {
+    let mut string = String::with_capacity(quasi.len() + 2);
+    let mut chars = quasi.chars().peekable();
+
+    string.push('"');
+
+    while let Some(character) = chars.next() {
+        match character {
+            '\\' => string.push_str("\\\\"),
+            '\r' => {
+                if chars.peek() == Some(&'\n') {
+                    chars.next();
+                }
+
+                push(&mut string, '\n');
+            },
+            _ => push(&mut string, character),
+        }
+    }
+
+    string.push('"');
+    string
+}
+
+#[inline]
+fn string<'ast>(source: String, t: &Transformer<'ast>) -> ExpressionNode<'ast> {
+    t.alloc(Literal::String(t.arena.alloc_str(&source)))
+}
+
+#[inline]
+fn add<'ast>(left: ExpressionNode<'ast>, right: ExpressionNode<'ast>, t: &Transformer<'ast>) -> ExpressionNode<'ast> {
+    t.alloc(BinaryExpression {
+        operator: OperatorKind::Addition,
+        left,
+        right,
+    })
+}
+
+impl<'ast> StaticVisitor<'ast> for TransformTemplate {
+    type Context = Transformer<'ast>;
+
+    fn on_leave_template_literal(item: &TemplateLiteral<'ast>, ptr: &ExpressionNode<'ast>, _: &NodePath<'ast>, t: &mut Transformer<'ast>) -> Control {
+        let mut quasis = item.quasis.iter();
+
+        // The first operand has to be a string for `+` to concatenate,
+        // even if it's empty
+        let mut concat = match quasis.next() {
+            Some(quasi) => string(cooked(quasi), t),
+            None        => string("\"\"".to_owned(), t),
+        };
+
+        for (quasi, expression) in quasis.zip(item.expressions.iter()) {
+            concat = add(concat, *expression, t);
+
+            if !quasi.is_empty() {
+                concat = add(concat, string(cooked(quasi), t), t);
+            }
+        }
+
+        t.swap(ptr, concat.item);
+
+        Control::Continue
+    }
+
+    fn on_leave_tagged_template_expression(item: &TaggedTemplateExpression<'ast>, ptr: &ExpressionNode<'ast>, _: &NodePath<'ast>, t: &mut Transformer<'ast>) -> Control {
+        let helper = t.helper("_taggedTemplateLiteral", TAGGED_TEMPLATE_LITERAL);
+        let name = t.fresh_name("_templateObject");
+
+        let strings = item.quasi.quasis.iter().map(|quasi| string(cooked(quasi), t)).collect::<Vec<_>>();
+        let raws = item.quasi.quasis.iter().map(|quasi| string(raw(quasi), t)).collect::<Vec<_>>();
+
+        let strings = t.list(strings);
+        let raws = t.list(raws);
+
+        let strings: ExpressionNode = t.alloc(ArrayExpression { body: strings });
+        let raws: ExpressionNode = t.alloc(ArrayExpression { body: raws });
+
+        // Tags get the same strings every time the template is evaluated,
+        // so they are created once, before anything else runs
+        let declaration = stmts!(t, "var $0 = $1($2, $3);", name, helper, strings, raws);
+
+        if let Some(first) = t.module.body().first_element() {
+            t.insert_before(first, *declaration.only_element().unwrap());
+        }
+
+        let mut arguments: Vec<ExpressionNode> = vec![t.alloc(name)];
+
+        arguments.extend(item.quasi.expressions.iter().cloned());
+
+        let arguments = t.list(arguments);
+
+        t.swap(ptr, CallExpression {
+            callee: item.tag,
+            arguments,
+        });
+
+        Control::Continue
+    }
+
+    #[inline]
+    fn register(dv: &mut DynamicVisitor<'ast, Transformer<'ast>>) {
+        dv.on_leave_template_literal.push(TransformTemplate::on_leave_template_literal);
+        dv.on_leave_tagged_template_expression.push(TransformTemplate::on_leave_tagged_template_expression);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ratel::parse;
+    use ratel_visitor::Visitable;
+    use ratel_codegen::codegen;
+    use edit::ApplyEdits;
+
+    const TAGGED: &str = "function _taggedTemplateLiteral(strings,raw){return Object.freeze(Object.defineProperties(strings,{raw:{value:Object.freeze(raw)}}));}";
+
+    fn assert_transform(source: &str, expected: &str) {
+        let module = parse(source).unwrap();
+        let mut transformer = Transformer::new(&module);
+
+        module.traverse(&(TransformTemplate, ApplyEdits), &mut transformer);
+
+        assert_eq!(codegen(&module, true), expected);
+    }
+
+    #[test]
+    fn template_literal() {
+        assert_transform("``;", r#""";"#);
+        assert_transform("`foo`;", r#""foo";"#);
+        assert_transform("`foo${bar}baz${qux}`;", r#""foo"+bar+"baz"+qux;"#);
+        assert_transform("`${a}${b}`;", r#"""+a+b;"#);
+        assert_transform("`a${b ? c : d}e${f, g}`;", r#""a"+(b?c:d)+"e"+(f,g);"#);
+    }
+
+    #[test]
+    fn nested_template_literal() {
+        assert_transform("`a${`b${c}`}`;", r#""a"+("b"+c);"#);
+    }
+
+    #[test]
+    fn escapes() {
+        assert_transform(r#"`"quoted"`;"#, r#""\"quoted\"";"#);
+        assert_transform(r"`\`tick\` \${no} \n \\`;", r#""`tick` ${no} \n \\";"#);
+        assert_transform("`line\nbreak\r\nend`;", r#""line\nbreak\nend";"#);
+    }
+
+    #[test]
+    fn tagged_template() {
+        assert_transform(
+            "tag`a${b}c`;",
+            &format!("{}var _templateObject=_taggedTemplateLiteral([\"a\",\"c\"],[\"a\",\"c\"]);tag(_templateObject,b);", TAGGED)
+        );
+    }
+
+    #[test]
+    fn tagged_template_raw() {
+        assert_transform(
+            r"foo.tag`\n${a}`; tag`b`;",
+            &format!(r#"{}var _templateObject=_taggedTemplateLiteral(["\n",""],["\\n",""]);var _templateObject2=_taggedTemplateLiteral(["b"],["b"]);foo.tag(_templateObject,a);tag(_templateObject2);"#, TAGGED)
+        );
+    }
+}
@@ -1,64 +1,82 @@
-use ratel::ast::{Node, Loc, Expression, ExpressionNode, OperatorKind};
-use ratel::ast::expression::{BinaryExpression, MemberExpression, CallExpression};
-use ratel_visitor::{StaticVisitor, DynamicVisitor};
+use ratel::ast::{ExpressionNode, OperatorKind};
+use ratel::ast::expression::BinaryExpression;
+use ratel_visitor::{StaticVisitor, DynamicVisitor, NodePath, Control};
 
 use Transformer;
 
 pub struct PresetES2016;
 
-static MATH: &Loc<Expression> = &Loc {
-    start: 0,
-    end: 0,
-    item: Expression::Identifier("Math")
-};
-static POW: &Loc<&str> = &Loc {
-    start: 0,
-    end: 0,
-    item: "pow"
-};
-
 impl<'ast> StaticVisitor<'ast> for PresetES2016 {
     type Context = Transformer<'ast>;
 
-    fn on_binary_expression(node: &BinaryExpression<'ast>, ptr: &ExpressionNode<'ast>, t: &mut Transformer<'ast>) {
-        match node.operator {
+    fn on_leave_binary_expression(node: &BinaryExpression<'ast>, ptr: &ExpressionNode<'ast>, _: &NodePath<'ast>, t: &mut Transformer<'ast>) -> Control {
+        let expression = match node.operator {
             OperatorKind::Exponent => {
-                let callee = t.alloc(MemberExpression {
-                    object: Node::from_static(MATH),
-                    property: Node::from_static(POW),
-                });
-                let arguments = t.list([node.left, node.right]);
-
-                t.swap(ptr, CallExpression {
-                    callee,
-                    arguments
-                });
+                expr!(t, "Math.pow($0, $1)", node.left, node.right)
             },
-
             OperatorKind::ExponentAssign => {
-                let callee = t.alloc(MemberExpression {
-                    object: Node::from_static(MATH),
-                    property: Node::from_static(POW),
-                });
-                let arguments = t.list([node.left, node.right]);
-                let right = t.alloc(CallExpression {
-                    callee,
-                    arguments
-                });
-
-                t.swap(ptr, BinaryExpression {
-                    operator: OperatorKind::Assign,
-                    left: node.left,
-                    right,
-                });
+                expr!(t, "$0 = Math.pow($0, $1)", node.left, node.right)
             },
+            _ => return Control::Continue
+        };
 
-            _ => {}
-        }
+        t.swap(ptr, expression.item);
+
+        Control::Continue
     }
 
     #[inline]
     fn register(dv: &mut DynamicVisitor<'ast, Transformer<'ast>>) {
-        dv.on_binary_expression.push(PresetES2016::on_binary_expression);
+        dv.on_leave_binary_expression.push(PresetES2016::on_leave_binary_expression);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ratel::parse;
+    use ratel::ast::{Loc, Statement, Expression};
+    use ratel_visitor::Visitable;
+    use ratel_codegen::codegen;
+
+    fn assert_transform(source: &str, expected: &str) {
+        let module = parse(source).unwrap();
+        let mut transformer = Transformer::new(&module);
+
+        module.traverse(&PresetES2016, &mut transformer);
+
+        assert_eq!(codegen(&module, true), expected);
+    }
+
+    #[test]
+    fn exponent() {
+        assert_transform("a ** b;", "Math.pow(a,b);");
+        assert_transform("foo(a ** (b + 1));", "foo(Math.pow(a,b+1));");
+    }
+
+    #[test]
+    fn exponent_assign() {
+        assert_transform("a **= 2;", "a=Math.pow(a,2);");
+        assert_transform("a.b **= c;", "a.b=Math.pow(a.b,c);");
+    }
+
+    #[test]
+    fn exponent_assign_does_not_alias_target() {
+        let module = parse("a.b **= c;").unwrap();
+        let mut transformer = Transformer::new(&module);
+
+        module.traverse(&PresetES2016, &mut transformer);
+
+        let statement = module.body().only_element().unwrap();
+
+        if let Statement::Expression(expression) = statement.item {
+            if let Expression::Binary(ref assign) = expression.item {
+                if let Expression::Member(ref member) = assign.left.item {
+                    member.object.set(transformer.arena.alloc(Loc::new(0, 0, Expression::Identifier("d"))));
+                }
+            }
+        }
+
+        assert_eq!(codegen(&module, true), "d.b=Math.pow(a.b,c);");
     }
 }
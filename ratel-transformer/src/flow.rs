@@ -0,0 +1,402 @@
+use ratel::ast::{Node, NodeList, Pattern, PatternList, Function, Class, ClassMember, Name};
+use ratel::ast::{Block, Property, Statement, StatementNode, ExpressionNode, Type, TypeNode};
+use ratel::ast::expression::{ArrowExpression, ObjectExpression, TypeCastExpression};
+use ratel::ast::expression::{FunctionExpression, ClassExpression};
+use ratel::ast::statement::*;
+use ratel_visitor::{StaticVisitor, DynamicVisitor, NodePath, Control};
+
+use Transformer;
+
+/// Removes all Flow type annotations and type-only statements.
+pub struct StripFlow;
+
+/// Moves Flow type annotations and type-only statements into `/*: */`
+/// and `/*:: */` comments, so that the output can still be type checked.
+/// Type parameters of functions and classes are removed.
+pub struct CommentFlow;
+
+#[inline]
+fn comment<'ast>(annotation: TypeNode<'ast>, t: &Transformer<'ast>) -> TypeNode<'ast> {
+    match annotation.is_comment() {
+        true  => annotation,
+        false => t.alloc_as_loc(annotation, Type::Comment(annotation)),
+    }
+}
+
+#[inline]
+fn pattern<'ast>(ptr: &Node<'ast, Pattern<'ast>>, comments: bool, t: &Transformer<'ast>) {
+    match ptr.item {
+        Pattern::Typed { pattern, optional, annotation } => {
+            match comments {
+                true  => t.swap(ptr, Pattern::Typed {
+                    pattern,
+                    optional,
+                    annotation: comment(annotation, t),
+                }),
+                false => t.swap(ptr, pattern.item),
+            }
+        },
+        Pattern::AssignmentPattern { ref left, .. } => self::pattern(left, comments, t),
+        _ => {}
+    }
+}
+
+#[inline]
+fn params<'ast>(params: &PatternList<'ast>, comments: bool, t: &Transformer<'ast>) {
+    for param in params.iter() {
+        pattern(param, comments, t);
+    }
+}
+
+#[inline]
+fn declaration<'ast>(declaration: &DeclarationStatement<'ast>, comments: bool, t: &Transformer<'ast>) {
+    for declarator in declaration.declarators.iter() {
+        pattern(&declarator.id, comments, t);
+    }
+}
+
+#[inline]
+fn for_init<'ast>(init: &ForInit<'ast>, comments: bool, t: &Transformer<'ast>) {
+    if let ForInit::Declaration(ref declaration) = *init {
+        self::declaration(declaration, comments, t);
+    }
+}
+
+fn function<'ast, N, T>(function: &Function<'ast, N>, ptr: &Node<'ast, T>, comments: bool, t: &Transformer<'ast>)
+where
+    N: Name<'ast>,
+    T: Copy + 'ast,
+    Function<'ast, N>: Into<T>,
+{
+    params(&function.params, comments, t);
+
+    let return_type = match function.return_type {
+        Some(annotation) if comments => Some(comment(annotation, t)),
+        _                            => None,
+    };
+
+    if function.type_params.is_empty() && function.return_type == return_type {
+        return;
+    }
+
+    t.swap(ptr, Function {
+        type_params: NodeList::empty(),
+        return_type,
+        ..*function
+    });
+}
+
+fn arrow<'ast>(arrow: &ArrowExpression<'ast>, ptr: &ExpressionNode<'ast>, comments: bool, t: &Transformer<'ast>) {
+    params(&arrow.params, comments, t);
+
+    let return_type = match arrow.return_type {
+        Some(annotation) if comments => Some(comment(annotation, t)),
+        _                            => None,
+    };
+
+    if arrow.type_params.is_empty() && arrow.return_type == return_type {
+        return;
+    }
+
+    t.swap(ptr, ArrowExpression {
+        type_params: NodeList::empty(),
+        return_type,
+        ..*arrow
+    });
+}
+
+fn class<'ast, N, T>(class: &Class<'ast, N>, ptr: &Node<'ast, T>, comments: bool, t: &Transformer<'ast>)
+where
+    N: Name<'ast>,
+    T: Copy + 'ast,
+    Class<'ast, N>: Into<T>,
+{
+    let mut members = Vec::new();
+    let mut changed = false;
+
+    for member in class.body.body.iter() {
+        match member.item {
+            ClassMember::Method { ref value, .. } => function(&value.item, value, comments, t),
+            ClassMember::Property { is_static, key, annotation, value } => {
+                changed = true;
+
+                let member = match (comments, value) {
+                    (true, _) => t.alloc_as_loc(*member, ClassMember::Property {
+                        is_static,
+                        key,
+                        annotation: comment(annotation, t),
+                        value,
+                    }),
+                    (false, Some(value)) => t.alloc_as_loc(*member, ClassMember::Literal {
+                        is_static,
+                        key,
+                        value,
+                    }),
+                    // Declaration only, nothing left to keep
+                    (false, None) => continue,
+                };
+
+                members.push(member);
+
+                continue;
+            },
+            _ => {}
+        }
+
+        members.push(*member);
+    }
+
+    if !changed && class.type_params.is_empty() && class.super_type_args.is_empty() && class.implements.is_empty() {
+        return;
+    }
+
+    let body = match changed {
+        true  => t.alloc_as_loc(class.body, Block { body: NodeList::from_iter(t.arena, members) }),
+        false => class.body,
+    };
+
+    t.swap(ptr, Class {
+        type_params: NodeList::empty(),
+        super_type_args: NodeList::empty(),
+        implements: NodeList::empty(),
+        body,
+        ..*class
+    });
+}
+
+#[inline]
+fn type_statement<'ast>(ptr: &StatementNode<'ast>, comments: bool, t: &Transformer<'ast>) {
+    match comments {
+        true  => t.swap(ptr, Statement::Commented(t.alloc_as_loc(*ptr, ptr.item))),
+        false => t.swap(ptr, Statement::Empty),
+    }
+}
+
+macro_rules! flow_visitor {
+    ($name:ident, $comments:expr) => {
+        impl<'ast> StaticVisitor<'ast> for $name {
+            type Context = Transformer<'ast>;
+
+            fn on_leave_declaration_statement(item: &DeclarationStatement<'ast>, _: &StatementNode<'ast>, _: &NodePath<'ast>, t: &mut Transformer<'ast>) -> Control {
+                declaration(item, $comments, t);
+
+                Control::Continue
+            }
+
+            fn on_leave_for_statement(item: &ForStatement<'ast>, _: &StatementNode<'ast>, _: &NodePath<'ast>, t: &mut Transformer<'ast>) -> Control {
+                if let Some(ref init) = item.init {
+                    for_init(init, $comments, t);
+                }
+
+                Control::Continue
+            }
+
+            fn on_leave_for_in_statement(item: &ForInStatement<'ast>, _: &StatementNode<'ast>, _: &NodePath<'ast>, t: &mut Transformer<'ast>) -> Control {
+                for_init(&item.left, $comments, t);
+
+                Control::Continue
+            }
+
+            fn on_leave_for_of_statement(item: &ForOfStatement<'ast>, _: &StatementNode<'ast>, _: &NodePath<'ast>, t: &mut Transformer<'ast>) -> Control {
+                for_init(&item.left, $comments, t);
+
+                Control::Continue
+            }
+
+            fn on_leave_function_statement(item: &FunctionStatement<'ast>, ptr: &StatementNode<'ast>, _: &NodePath<'ast>, t: &mut Transformer<'ast>) -> Control {
+                function(item, ptr, $comments, t);
+
+                Control::Continue
+            }
+
+            fn on_leave_function_expression(item: &FunctionExpression<'ast>, ptr: &ExpressionNode<'ast>, _: &NodePath<'ast>, t: &mut Transformer<'ast>) -> Control {
+                function(item, ptr, $comments, t);
+
+                Control::Continue
+            }
+
+            fn on_leave_arrow_expression(item: &ArrowExpression<'ast>, ptr: &ExpressionNode<'ast>, _: &NodePath<'ast>, t: &mut Transformer<'ast>) -> Control {
+                arrow(item, ptr, $comments, t);
+
+                Control::Continue
+            }
+
+            fn on_leave_object_expression(item: &ObjectExpression<'ast>, _: &ExpressionNode<'ast>, _: &NodePath<'ast>, t: &mut Transformer<'ast>) -> Control {
+                for property in item.body.iter() {
+                    if let Property::Method { ref value, .. } = property.item {
+                        function(&value.item, value, $comments, t);
+                    }
+                }
+
+                Control::Continue
+            }
+
+            fn on_leave_class_statement(item: &ClassStatement<'ast>, ptr: &StatementNode<'ast>, _: &NodePath<'ast>, t: &mut Transformer<'ast>) -> Control {
+                class(item, ptr, $comments, t);
+
+                Control::Continue
+            }
+
+            fn on_leave_class_expression(item: &ClassExpression<'ast>, ptr: &ExpressionNode<'ast>, _: &NodePath<'ast>, t: &mut Transformer<'ast>) -> Control {
+                class(item, ptr, $comments, t);
+
+                Control::Continue
+            }
+
+            fn on_leave_type_cast_expression(item: &TypeCastExpression<'ast>, ptr: &ExpressionNode<'ast>, _: &NodePath<'ast>, t: &mut Transformer<'ast>) -> Control {
+                match $comments {
+                    true  => t.swap(ptr, TypeCastExpression {
+                        expression: item.expression,
+                        annotation: comment(item.annotation, t),
+                    }),
+                    false => t.swap(ptr, item.expression.item),
+                }
+
+                Control::Continue
+            }
+
+            fn on_leave_type_alias_statement(_: &TypeAliasStatement<'ast>, ptr: &StatementNode<'ast>, _: &NodePath<'ast>, t: &mut Transformer<'ast>) -> Control {
+                type_statement(ptr, $comments, t);
+
+                Control::Continue
+            }
+
+            fn on_leave_declare_statement(_: &DeclareStatement<'ast>, ptr: &StatementNode<'ast>, _: &NodePath<'ast>, t: &mut Transformer<'ast>) -> Control {
+                type_statement(ptr, $comments, t);
+
+                Control::Continue
+            }
+
+            fn on_leave_import_statement(item: &ImportStatement<'ast>, ptr: &StatementNode<'ast>, _: &NodePath<'ast>, t: &mut Transformer<'ast>) -> Control {
+                if item.kind != ImportKind::Value {
+                    type_statement(ptr, $comments, t);
+                }
+
+                Control::Continue
+            }
+
+            fn on_leave_export_statement(item: &ExportStatement<'ast>, ptr: &StatementNode<'ast>, _: &NodePath<'ast>, t: &mut Transformer<'ast>) -> Control {
+                match *item {
+                    // Exported type aliases have already been handled on the inner statement
+                    ExportStatement::Declaration(declaration) => match declaration.item {
+                        Statement::Empty => t.swap(ptr, Statement::Empty),
+                        Statement::Commented(inner) => {
+                            t.swap(ptr, Statement::Commented(t.alloc_as_loc(*ptr, ExportStatement::Declaration(inner))));
+                        },
+                        _ => {},
+                    },
+                    ExportStatement::Named { kind: ExportKind::Type, .. } => type_statement(ptr, $comments, t),
+                    _ => {},
+                }
+
+                Control::Continue
+            }
+
+            #[inline]
+            fn register(dv: &mut DynamicVisitor<'ast, Transformer<'ast>>) {
+                dv.on_leave_declaration_statement.push($name::on_leave_declaration_statement);
+                dv.on_leave_for_statement.push($name::on_leave_for_statement);
+                dv.on_leave_for_in_statement.push($name::on_leave_for_in_statement);
+                dv.on_leave_for_of_statement.push($name::on_leave_for_of_statement);
+                dv.on_leave_function_statement.push($name::on_leave_function_statement);
+                dv.on_leave_function_expression.push($name::on_leave_function_expression);
+                dv.on_leave_arrow_expression.push($name::on_leave_arrow_expression);
+                dv.on_leave_object_expression.push($name::on_leave_object_expression);
+                dv.on_leave_class_statement.push($name::on_leave_class_statement);
+                dv.on_leave_class_expression.push($name::on_leave_class_expression);
+                dv.on_leave_type_cast_expression.push($name::on_leave_type_cast_expression);
+                dv.on_leave_type_alias_statement.push($name::on_leave_type_alias_statement);
+                dv.on_leave_declare_statement.push($name::on_leave_declare_statement);
+                dv.on_leave_import_statement.push($name::on_leave_import_statement);
+                dv.on_leave_export_statement.push($name::on_leave_export_statement);
+            }
+        }
+    };
+}
+
+flow_visitor!(StripFlow, false);
+flow_visitor!(CommentFlow, true);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ratel;
+    use ratel::Module;
+    use ratel_visitor::{Visitable, Visitor};
+    use ratel_codegen::codegen;
+
+    fn transform<'ast, V>(module: &'ast Module<'ast>, visitor: V) where V: Visitor<'ast, Context = Transformer<'ast>> {
+        let mut transformer = Transformer::new(module);
+
+        module.traverse(&visitor, &mut transformer);
+    }
+
+    fn parse_flow<'ast>(source: &str) -> Module<'ast> {
+        ratel::parse_with_options(source, ratel::ParseOptions { goal: ratel::Goal::Module, flow: true, ..Default::default() }).unwrap()
+    }
+
+    fn assert_strip(source: &str, expected: &str) {
+        let module = parse_flow(source);
+
+        transform(&module, StripFlow);
+
+        assert_eq!(codegen(&module, true), expected);
+    }
+
+    fn assert_comment(source: &str, expected: &str) {
+        let module = parse_flow(source);
+
+        transform(&module, CommentFlow);
+
+        assert_eq!(codegen(&module, true), expected);
+    }
+
+    #[test]
+    fn strip_annotations() {
+        assert_strip("let a: number = 1;", "let a=1;");
+        assert_strip("function foo<T>(a: T, b?: number = 1, ...c: T[]): T { return a; }", "function foo(a,b=1,...c){return a;}");
+        assert_strip("const f = function (a: string): void {};", "const f=function(a){};");
+        assert_strip("((a: number, b) => a);", "(a,b)=>a;");
+        assert_strip("((a: number, b: string): boolean => a > b);", "(a,b)=>a>b;");
+        assert_strip("((): void => {});", "()=>{};");
+        assert_strip("(<T>(a: T): T => a);", "a=>a;");
+        assert_strip("({ foo(a: string): void {} });", "({foo(a){}});");
+        assert_strip("(foo: any);", "foo;");
+        assert_strip("for (let i: number = 0; i < 1; i++) {}", "for(let i=0;i<1;i++){}");
+    }
+
+    #[test]
+    fn strip_classes() {
+        assert_strip(
+            "class Foo<T> extends Bar<T> implements Baz { a: number; b: string = 'b'; method(a: T): T { return a; } }",
+            "class Foo extends Bar{b='b';method(a){return a;}}"
+        );
+    }
+
+    #[test]
+    fn strip_type_statements() {
+        assert_strip("type A = number; foo;", "foo;");
+        assert_strip("opaque type A = number; foo;", "foo;");
+        assert_strip("declare var a: number; foo;", "foo;");
+        assert_strip("import type { A } from 'a'; foo;", "foo;");
+        assert_strip("export type A = number; foo;", "foo;");
+        assert_strip("export type { A, B }; foo;", "foo;");
+    }
+
+    #[test]
+    fn comment_annotations() {
+        assert_comment("let a: number = 1;", "let a/*:number*/=1;");
+        assert_comment("function foo(a?: string): void {}", "function foo(a/*::?:string*/)/*:void*/{}");
+        assert_comment("(foo: any);", "(foo/*:any*/);");
+        assert_comment("((a: number): void => {});", "(a/*:number*/)/*:void*/=>{};");
+        assert_comment("class Foo { a: number; b: string = 'b'; }", "class Foo{/*::a:number;*/b/*:string*/='b';}");
+    }
+
+    #[test]
+    fn comment_type_statements() {
+        assert_comment("type A = number;", "/*::type A=number;*/");
+        assert_comment("import type { A } from 'a';", "/*::import type {A} from 'a';*/");
+        assert_comment("export type A = number;", "/*::export type A=number;*/");
+        assert_comment("export type { A } from 'a';", "/*::export type {A} from 'a';*/");
+    }
+}
@@ -0,0 +1,77 @@
+//! Functions the transformed code calls into, such as `_classCallCheck`.
+//! Each helper is declared once, at the top of the module, when a pass
+//! first asks for it.
+
+use Transformer;
+
+impl<'ast> Transformer<'ast> {
+    /// Get the name of a helper, declaring it first if it hasn't been yet.
+    /// `$0` in the source of the helper stands for its name, which is only
+    /// `name` if that isn't used in the module already.
+    pub fn helper(&mut self, name: &'static str, source: &'static str) -> &'ast str {
+        if let Some(&(_, declared)) = self.helpers.iter().find(|&&(helper, _)| helper == name) {
+            return declared;
+        }
+
+        let declared = self.unique_name(name);
+        let declaration = stmts!(self, source, declared);
+
+        // Whatever asked for the helper is in the module, so it isn't empty
+        if let Some(first) = self.module.body().first_element() {
+            for statement in declaration.iter() {
+                self.insert_before(first, *statement);
+            }
+        }
+
+        self.helpers.push((name, declared));
+
+        declared
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ratel::parse;
+    use ratel::ast::{ExpressionNode, StatementNode};
+    use ratel_visitor::{StaticVisitor, DynamicVisitor, Visitable, NodePath, Control};
+    use ratel_codegen::codegen;
+    use edit::ApplyEdits;
+
+    struct HelperTest;
+
+    impl<'ast> StaticVisitor<'ast> for HelperTest {
+        type Context = Transformer<'ast>;
+
+        fn on_expression_statement(_: &ExpressionNode<'ast>, node: &StatementNode<'ast>, _: &NodePath<'ast>, t: &mut Transformer<'ast>) -> Control {
+            let helper = t.helper("_helper", "function $0() {}");
+
+            t.swap(node, expr!(t, "$0()", helper));
+
+            Control::Continue
+        }
+
+        fn register(_dv: &mut DynamicVisitor<'ast, Transformer<'ast>>) {
+            unimplemented!()
+        }
+    }
+
+    fn assert_helpers(source: &str, expected: &str) {
+        let module = parse(source).unwrap();
+        let mut transformer = Transformer::new(&module);
+
+        module.traverse(&(HelperTest, ApplyEdits), &mut transformer);
+
+        assert_eq!(codegen(&module, true), expected);
+    }
+
+    #[test]
+    fn declared_once() {
+        assert_helpers("var a; a; { b; }", "function _helper(){}var a;_helper();{_helper();}");
+    }
+
+    #[test]
+    fn unique_helper_name() {
+        assert_helpers("var _helper; a;", "function _helper2(){}var _helper;_helper2();");
+    }
+}
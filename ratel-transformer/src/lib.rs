@@ -4,24 +4,89 @@ extern crate pretty_assertions;
 extern crate ratel;
 extern crate ratel_visitor;
 extern crate toolshed;
+#[cfg(test)]
+extern crate ratel_codegen;
 
 use toolshed::Arena;
 use toolshed::list::ListBuilder;
-use ratel::ast::{Loc, Node, NodeList};
+use ratel::Module;
+use ratel::ast::{Loc, Node, NodeList, StatementNode};
+use ratel_visitor::{Visitor, Visitable, DynamicVisitor};
 
+#[macro_use]
+pub mod quote;
 pub mod es2015;
-// pub mod es2016;
+pub mod flow;
+pub mod edit;
+pub mod es2016;
+pub mod helpers;
 
 pub mod scope;
 
 use self::scope::Scope;
+use self::edit::{Edit, ApplyEdits};
+use self::es2015::{TransformArrow, TransformClass, TransformDestructuring, TransformParameters, TransformTemplate};
+use self::es2015::{TransformSpread, TransformSpreadLoose, TransformBlockScoping, TransformForOf, TransformForOfLoose};
+use self::es2015::arrow::Lexical;
+use self::es2015::block_scoping::Scoping;
+use self::es2016::PresetES2016;
 
 pub struct Transformer<'ast> {
     pub arena: &'ast Arena,
     pub scope: &'ast Scope<'ast>,
+    module: &'ast Module<'ast>,
+    edits: Vec<(*const StatementNode<'ast>, Edit<'ast>)>,
+    lexical: Lexical<'ast>,
+    scoping: Scoping<'ast>,
+    helpers: Vec<(&'static str, &'ast str)>,
+    names: Vec<&'ast str>,
 }
 
 impl<'ast> Transformer<'ast> {
+    /// Create a transformer for the module, analyzing its scopes.
+    #[inline]
+    pub fn new(module: &'ast Module<'ast>) -> Self {
+        Transformer {
+            arena: module.arena(),
+            scope: scope::analyze(module),
+            module,
+            edits: Vec::new(),
+            lexical: Lexical::default(),
+            scoping: Scoping::default(),
+            helpers: Vec::new(),
+            names: Vec::new(),
+        }
+    }
+
+    /// Get a name that isn't used anywhere in the module, `name` itself if
+    /// possible, otherwise with a number appended to it.
+    pub fn unique_name(&self, name: &'static str) -> &'ast str {
+        if !self.scope.uses(name) {
+            return name;
+        }
+
+        (2..)
+            .map(|n| self.arena.alloc_str(&format!("{}{}", name, n)))
+            .find(|name| !self.scope.uses(name))
+            .unwrap()
+    }
+
+    /// Get a name that isn't used anywhere in the module, nor given out by
+    /// this method before, for variables sharing the same scope.
+    pub fn fresh_name(&mut self, name: &'ast str) -> &'ast str {
+        let mut fresh = name;
+        let mut n = 2;
+
+        while self.names.contains(&fresh) || self.scope.uses(fresh) {
+            fresh = self.arena.alloc_str(&format!("{}{}", name, n));
+            n += 1;
+        }
+
+        self.names.push(fresh);
+
+        fresh
+    }
+
     #[inline]
     pub fn alloc<T, I>(&self, item: I) -> Node<'ast, T> where
         T: Copy,
@@ -72,15 +137,176 @@ impl<'ast> Transformer<'ast> {
     }
 }
 
-// pub fn transform<'ast>(module: &mut Module, settings: Settings) {
-//     let arena = module.arena();
-//     let body = module.body();
+/// Transforms to apply to a module. Each preset enables the transforms
+/// needed for the output to run on engines supporting the target version.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Settings {
+    /// Arrow functions into function expressions.
+    pub transform_arrow: bool,
+    /// Classes into constructor functions.
+    pub transform_class: bool,
+    /// Template literals into string concatenation, and tagged templates
+    /// into calls.
+    pub transform_template_strings: bool,
+    /// Destructuring into variables and assignments reading one property
+    /// at a time.
+    pub transform_destructuring: bool,
+    /// Default and rest params into statements reading `arguments`.
+    pub transform_default_parameters: bool,
+    /// Spread arguments into calls to `apply`, and spread elements into
+    /// concatenated arrays.
+    pub transform_spread: bool,
+    /// `let` and `const` into `var`, renaming clashing bindings and moving
+    /// loop bodies with closures into functions.
+    pub transform_block_scoping: bool,
+    /// `for-of` loops into `for` loops over the iterator of the value.
+    pub transform_for_of: bool,
+    /// The `**` and `**=` operators into calls to `Math.pow`.
+    pub transform_exponentation: bool,
+    /// Assume values being iterated are arrays, for smaller output that
+    /// doesn't depend on `Symbol.iterator`.
+    pub loose: bool,
+}
+
+impl Settings {
+    pub fn target_es5() -> Settings {
+        let mut settings = Settings::target_es2015();
 
-//     let transformer = Transformer {
-//         arena,
-//         settings,
-//         scope: Scope::new(arena),
-//     };
+        settings.transform_arrow = true;
+        settings.transform_class = true;
+        settings.transform_template_strings = true;
+        settings.transform_destructuring = true;
+        settings.transform_default_parameters = true;
+        settings.transform_spread = true;
+        settings.transform_block_scoping = true;
+        settings.transform_for_of = true;
+
+        settings
+    }
+
+    pub fn target_es2015() -> Settings {
+        let mut settings = Settings::no_transform();
+
+        settings.transform_exponentation = true;
+
+        settings
+    }
+
+    pub fn no_transform() -> Settings {
+        Settings {
+            transform_arrow: false,
+            transform_class: false,
+            transform_template_strings: false,
+            transform_destructuring: false,
+            transform_default_parameters: false,
+            transform_spread: false,
+            transform_block_scoping: false,
+            transform_for_of: false,
+            transform_exponentation: false,
+            loose: false,
+        }
+    }
+}
+
+/// Transform the module in place, with the passes enabled by `settings`
+/// composed into a single traversal.
+pub fn transform<'ast>(module: &'ast Module<'ast>, settings: Settings) {
+    let mut dv = DynamicVisitor::new();
+
+    if settings.transform_exponentation {
+        PresetES2016.register(&mut dv);
+    }
+
+    if settings.transform_class {
+        TransformClass.register(&mut dv);
+    }
+
+    if settings.transform_arrow {
+        TransformArrow.register(&mut dv);
+    }
 
-//     transformer.transform(body);
-// }
+    // Arrows are functions by the time their params are taken apart
+    if settings.transform_destructuring {
+        TransformDestructuring.register(&mut dv);
+    }
+
+    if settings.transform_default_parameters {
+        TransformParameters.register(&mut dv);
+    }
+
+    if settings.transform_spread {
+        match settings.loose {
+            true  => TransformSpreadLoose.register(&mut dv),
+            false => TransformSpread.register(&mut dv),
+        }
+    }
+
+    if settings.transform_block_scoping {
+        TransformBlockScoping.register(&mut dv);
+    }
+
+    // Loops are lowered once their heads are `var` and their bodies moved
+    // to functions where needed
+    if settings.transform_for_of {
+        match settings.loose {
+            true  => TransformForOfLoose.register(&mut dv),
+            false => TransformForOf.register(&mut dv),
+        }
+    }
+
+    if settings.transform_template_strings {
+        TransformTemplate.register(&mut dv);
+    }
+
+    ApplyEdits.register(&mut dv);
+
+    let mut transformer = Transformer::new(module);
+
+    module.traverse(&*dv, &mut transformer);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ratel::parse;
+    use ratel_codegen::codegen;
+
+    fn assert_transform(source: &str, settings: Settings, expected: &str) {
+        let module = parse(source).unwrap();
+
+        transform(&module, settings);
+
+        assert_eq!(codegen(&module, true), expected);
+    }
+
+    #[test]
+    fn no_transform() {
+        assert_transform("a ** b; f(() => c);", Settings::no_transform(), "a**b;f(()=>c);");
+    }
+
+    #[test]
+    fn target_es2015() {
+        assert_transform("a ** b; f(() => c);", Settings::target_es2015(), "Math.pow(a,b);f(()=>c);");
+    }
+
+    #[test]
+    fn target_es5() {
+        assert_transform("a ** b; f(() => c);", Settings::target_es5(), "Math.pow(a,b);f(function(){return c;});");
+        assert_transform("f(() => { [a, b] = c(); });", Settings::target_es5(), "f(function(){var _ref;_ref=c(),a=_ref[0],b=_ref[1];});");
+        assert_transform("f((a = 1) => a);", Settings::target_es5(), "f(function(){var a=arguments[0];if(a===void 0)a=1;return a;});");
+        assert_transform("for (let { a } in b) f(() => a);", Settings::target_es5(), "var _loop=function(_ref){var a=_ref.a;f(function(){return a;});};for(var _ref in b){_loop(_ref);}");
+        assert_transform("try {} catch ({ a }) { const b = a; }", Settings::target_es5(), "try{}catch(_ref){var a=_ref.a;var b=a;}");
+    }
+
+    #[test]
+    fn loose() {
+        let settings = Settings {
+            loose: true,
+            ..Settings::target_es5()
+        };
+
+        assert_transform("a.b(...c);", settings, "a.b.apply(a,c);");
+        assert_transform("new A(...b);", settings, "new (Function.prototype.bind.apply(A,[null].concat(b)))();");
+        assert_transform("for (const [a, b] of c) f(a, b);", settings, "for(var _i=0,_arr=c;_i<_arr.length;_i++){var _ref=_arr[_i];var a=_ref[0],b=_ref[1];f(a,b);}");
+    }
+}
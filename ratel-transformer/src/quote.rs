@@ -0,0 +1,401 @@
+//! Building AST nodes from JavaScript snippets, with numbered `$0`, `$1`, ...
+//! placeholders standing in for nodes, or names, supplied by the caller.
+//!
+//! ```ignore
+//! let pow = expr!(t, "Math.pow($0, $1)", node.left, node.right);
+//! let body = stmts!(t, "var $0 = $1; $2;", "_this", this, statement);
+//! ```
+//!
+//! Nodes produced by the snippet are located within the snippet itself. A
+//! placeholder used more than once gets a copy of its node for every use
+//! after the first, so that no two places in the tree share a subtree.
+
+use toolshed::Arena;
+use ratel::{parse_in, ParseOptions};
+use ratel::ast::{Node, Loc, Identifier, Expression, ExpressionNode, Statement, StatementNode, StatementList, CloneIn};
+use ratel_visitor::{StaticVisitor, DynamicVisitor, Visitable, NodePath, Control};
+
+use Transformer;
+
+/// Value substituted for a placeholder. The kind of the value has to
+/// match the position of the placeholder in the snippet.
+#[derive(Debug, Clone, Copy)]
+pub enum Quasi<'ast> {
+    /// Name, which can be used anywhere an identifier is allowed.
+    Identifier(Identifier<'ast>),
+    /// Expression, used wherever an expression is allowed.
+    Expression(ExpressionNode<'ast>),
+    /// Statement, used on its own as an expression statement, such as `$0;`.
+    Statement(StatementNode<'ast>),
+}
+
+impl<'ast> Quasi<'ast> {
+    fn kind(&self) -> &'static str {
+        match *self {
+            Quasi::Identifier(_) => "an identifier",
+            Quasi::Expression(_) => "an expression",
+            Quasi::Statement(_)  => "a statement",
+        }
+    }
+}
+
+impl<'ast> From<Identifier<'ast>> for Quasi<'ast> {
+    #[inline]
+    fn from(val: Identifier<'ast>) -> Self {
+        Quasi::Identifier(val)
+    }
+}
+
+impl<'ast> From<ExpressionNode<'ast>> for Quasi<'ast> {
+    #[inline]
+    fn from(val: ExpressionNode<'ast>) -> Self {
+        Quasi::Expression(val)
+    }
+}
+
+impl<'ast> From<StatementNode<'ast>> for Quasi<'ast> {
+    #[inline]
+    fn from(val: StatementNode<'ast>) -> Self {
+        Quasi::Statement(val)
+    }
+}
+
+/// Build an `ExpressionNode` from a snippet, in the arena of the transformer.
+///
+/// ```ignore
+/// let pow = expr!(t, "Math.pow($0, $1)", node.left, node.right);
+/// ```
+#[macro_export]
+macro_rules! expr {
+    ($t:expr, $template:expr $(, $quasi:expr)* $(,)*) => {
+        $t.quote_expression($template, &[$( $crate::quote::Quasi::from($quasi) ),*])
+    };
+}
+
+/// Build a `StatementList` from a snippet, in the arena of the transformer.
+///
+/// ```ignore
+/// let body = stmts!(t, "var $0 = this; $1;", "_this", statement);
+/// ```
+#[macro_export]
+macro_rules! stmts {
+    ($t:expr, $template:expr $(, $quasi:expr)* $(,)*) => {
+        $t.quote_statements($template, &[$( $crate::quote::Quasi::from($quasi) ),*])
+    };
+}
+
+/// Get the index of a `$N` placeholder.
+#[inline]
+fn placeholder(name: &str) -> Option<usize> {
+    if !name.starts_with('$') {
+        return None;
+    }
+
+    name[1..].parse().ok()
+}
+
+#[inline]
+fn is_identifier_byte(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || byte == b'$' || byte == b'_'
+}
+
+struct Substitution<'ast> {
+    arena: &'ast Arena,
+    quasis: Vec<Quasi<'ast>>,
+    used: Vec<bool>,
+    // Statement placeholders seen as expressions, not yet known to stand alone
+    pending: Vec<usize>,
+}
+
+impl<'ast> Substitution<'ast> {
+    fn new(arena: &'ast Arena, quasis: &[Quasi<'ast>]) -> Self {
+        Substitution {
+            arena,
+            quasis: quasis.to_vec(),
+            used: vec![false; quasis.len()],
+            pending: Vec::new(),
+        }
+    }
+
+    fn quasi(&self, index: usize, template: &str) -> Quasi<'ast> {
+        match self.quasis.get(index) {
+            Some(&quasi) => quasi,
+            None => panic!("Placeholder `${}` in `{}` has no value", index, template),
+        }
+    }
+
+    /// Replace names with the `Identifier` quasis in the template.
+    fn expand(&mut self, template: &str) -> String {
+        let bytes = template.as_bytes();
+        let mut source = String::with_capacity(template.len());
+        let mut copied = 0;
+        let mut index = 0;
+
+        while index < bytes.len() {
+            if bytes[index] != b'$' || (index > 0 && is_identifier_byte(bytes[index - 1])) {
+                index += 1;
+                continue;
+            }
+
+            let start = index;
+            let mut end = start + 1;
+
+            while end < bytes.len() && bytes[end].is_ascii_digit() {
+                end += 1;
+            }
+
+            index = end;
+
+            if end == start + 1 || (end < bytes.len() && is_identifier_byte(bytes[end])) {
+                continue;
+            }
+
+            let number: usize = template[start + 1..end].parse().expect("Placeholder is a number");
+
+            if let Quasi::Identifier(name) = self.quasi(number, template) {
+                source.push_str(&template[copied..start]);
+                source.push_str(name);
+                copied = end;
+
+                self.used[number] = true;
+            }
+        }
+
+        source.push_str(&template[copied..]);
+        source
+    }
+
+    /// Get the node to put in place of the placeholder at `index`. The
+    /// node itself is used the first time, and a copy of it after that.
+    fn node<T>(&self, index: usize, mut node: Node<'ast, T>) -> &'ast Loc<T> where
+        T: Copy + CloneIn<'ast, Cloned = T>,
+    {
+        match self.used[index] {
+            false => node.get_mut(),
+            true  => self.arena.alloc((*node).clone_in(self.arena)),
+        }
+    }
+
+    fn check(&self, template: &str) {
+        if let Some(&index) = self.pending.first() {
+            panic!("Placeholder `${}` in `{}` is a statement, but is used as an expression", index, template);
+        }
+
+        for (index, used) in self.used.iter().enumerate() {
+            if !used {
+                panic!(
+                    "Placeholder `${}` in `{}` is {}, but isn't used where one is allowed",
+                    index,
+                    template,
+                    self.quasis[index].kind()
+                );
+            }
+        }
+    }
+}
+
+struct Quote;
+
+impl<'ast> StaticVisitor<'ast> for Quote {
+    type Context = Substitution<'ast>;
+
+    fn on_leave_identifier_expression(item: &Identifier<'ast>, node: &ExpressionNode<'ast>, _: &NodePath<'ast>, ctx: &mut Substitution<'ast>) -> Control {
+        let index = match placeholder(item) {
+            Some(index) if index < ctx.quasis.len() => index,
+            _ => return Control::Continue,
+        };
+
+        match ctx.quasis[index] {
+            Quasi::Expression(expression) => {
+                node.set(ctx.node(index, expression));
+                ctx.used[index] = true;
+            },
+            Quasi::Statement(_) => ctx.pending.push(index),
+            Quasi::Identifier(_) => {},
+        }
+
+        Control::Continue
+    }
+
+    fn on_leave_expression_statement(item: &ExpressionNode<'ast>, node: &StatementNode<'ast>, _: &NodePath<'ast>, ctx: &mut Substitution<'ast>) -> Control {
+        let index = match item.item {
+            Expression::Identifier(name) => match placeholder(name) {
+                Some(index) => index,
+                None        => return Control::Continue,
+            },
+            _ => return Control::Continue,
+        };
+
+        if let Some(&Quasi::Statement(statement)) = ctx.quasis.get(index) {
+            node.set(ctx.node(index, statement));
+            ctx.used[index] = true;
+            ctx.pending.pop();
+        }
+
+        Control::Continue
+    }
+
+    fn register(dv: &mut DynamicVisitor<'ast, Substitution<'ast>>) {
+        dv.on_leave_identifier_expression.push(Quote::on_leave_identifier_expression);
+        dv.on_leave_expression_statement.push(Quote::on_leave_expression_statement);
+    }
+}
+
+impl<'ast> Transformer<'ast> {
+    fn quote(&self, template: &str, quasis: &[Quasi<'ast>], expression: bool) -> StatementList<'ast> {
+        let mut ctx = Substitution::new(self.arena, quasis);
+        let mut source = ctx.expand(template);
+
+        // Parenthesized, so that object literals and functions are read as expressions
+        if expression {
+            source = format!("({})", source);
+        }
+
+        let body = match parse_in(&source, self.arena, ParseOptions::default()) {
+            Ok(program) => program.body,
+            Err(errors) => panic!("Invalid template `{}`: {:?}", template, errors),
+        };
+
+        body.traverse(&Quote, &mut ctx);
+        ctx.check(template);
+
+        body
+    }
+
+    /// Parse the template into a list of statements, substituting the
+    /// placeholders. Panics if the template is invalid, or a placeholder
+    /// is used where its value isn't allowed.
+    #[inline]
+    pub fn quote_statements(&self, template: &str, quasis: &[Quasi<'ast>]) -> StatementList<'ast> {
+        self.quote(template, quasis, false)
+    }
+
+    /// Parse the template into a single expression, substituting the
+    /// placeholders. Panics if the template is invalid, or a placeholder
+    /// is used where its value isn't allowed.
+    pub fn quote_expression(&self, template: &str, quasis: &[Quasi<'ast>]) -> ExpressionNode<'ast> {
+        let body = self.quote(template, quasis, true);
+
+        match body.only_element().map(|statement| statement.item) {
+            Some(Statement::Expression(expression)) => expression,
+            _ => panic!("Template `{}` is not an expression", template),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ratel::{parse, Module};
+    use ratel::ast::{Block, StatementNode};
+    use ratel::ast::expression::{BinaryExpression, MemberExpression};
+    use ratel_codegen::codegen;
+
+    /// Replace the first statement of the module with a quoted one.
+    fn quote<'ast, F>(module: &'ast Module<'ast>, build: F) -> String
+    where
+        F: FnOnce(&Transformer<'ast>) -> StatementNode<'ast>,
+    {
+        let t = Transformer::new(module);
+
+        let first = module.body().first_element().expect("Module has a statement");
+        let statement = build(&t);
+
+        t.swap(first, statement.item);
+
+        codegen(module, true)
+    }
+
+    #[test]
+    fn quote_expression() {
+        let module = parse("foo;").unwrap();
+
+        let output = quote(&module, |t| {
+            let left = expr!(t, "a.b");
+            let pow = expr!(t, "Math.pow($0, $1)", left, expr!(t, "2"));
+
+            t.alloc(pow)
+        });
+
+        assert_eq!(output, "Math.pow(a.b,2);");
+    }
+
+    #[test]
+    fn quote_object_expression() {
+        let module = parse("foo;").unwrap();
+
+        let output = quote(&module, |t| {
+            let object = expr!(t, "{ $0: $1, [$1]: $0 }", "key", expr!(t, "value"));
+
+            t.alloc(expr!(t, "$0.call($1)", expr!(t, "foo"), object))
+        });
+
+        assert_eq!(output, "foo.call({key:value,[value]:key});");
+    }
+
+    #[test]
+    fn quote_statements() {
+        let module = parse("foo;").unwrap();
+
+        let output = quote(&module, |t| {
+            let statement: StatementNode = t.alloc(expr!(t, "bar()"));
+            let body = stmts!(t, "var $0 = $1; if ($0) { $2; }", "baz", expr!(t, "1 + 2"), statement);
+
+            t.alloc(Block { body })
+        });
+
+        assert_eq!(output, "{var baz=1+2;if(baz){bar();}}");
+    }
+
+    #[test]
+    fn quote_reuses_placeholders() {
+        let module = parse("foo;").unwrap();
+
+        let output = quote(&module, |t| {
+            let expression = expr!(t, "$0 = $0 || $1", expr!(t, "a.b"), expr!(t, "{}"));
+
+            // Each use of `$0` is a separate subtree, changing one leaves the other alone
+            if let Expression::Binary(BinaryExpression { left, .. }) = expression.item {
+                if let Expression::Member(MemberExpression { ref object, .. }) = left.item {
+                    t.swap(object, Expression::Identifier("c"));
+                }
+            }
+
+            t.alloc(expression)
+        });
+
+        assert_eq!(output, "c.b=a.b||{};");
+    }
+
+    #[test]
+    #[should_panic(expected = "Placeholder `$0` in `foo($0)` is a statement, but is used as an expression")]
+    fn statement_as_expression() {
+        let module = parse("foo;").unwrap();
+
+        quote(&module, |t| {
+            let statement: StatementNode = t.alloc(expr!(t, "bar"));
+
+            t.alloc(expr!(t, "foo($0)", statement))
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Placeholder `$0` in `var $0 = 1;` is an expression, but isn't used where one is allowed")]
+    fn expression_as_identifier() {
+        let module = parse("foo;").unwrap();
+
+        quote(&module, |t| {
+            let body = stmts!(t, "var $0 = 1;", expr!(t, "a.b"));
+
+            t.alloc(Block { body })
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Placeholder `$1` in `$0 + $1` has no value")]
+    fn missing_placeholder() {
+        let module = parse("foo;").unwrap();
+
+        quote(&module, |t| t.alloc(expr!(t, "$0 + $1", "a")));
+    }
+}
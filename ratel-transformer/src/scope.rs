@@ -1,8 +1,11 @@
 use std::fmt::{self, Debug};
+use std::ptr;
 
 use ratel::Module;
-use ratel::ast::{Identifier, ExpressionNode};
-use ratel_visitor::{Visitable, StaticVisitor, DynamicVisitor, ScopeKind};
+use ratel::ast::{Identifier, Expression, ExpressionNode, Statement, Property, DeclarationKind, OperatorKind};
+use ratel::ast::expression::BinaryExpression;
+use ratel::ast::statement::{ForInit, ForStatement, ForInStatement, ForOfStatement};
+use ratel_visitor::{Visitable, StaticVisitor, DynamicVisitor, NodePath, Ancestor, Control, ScopeKind};
 use toolshed::{Arena, CopyCell};
 use toolshed::list::GrowableList;
 use toolshed::map::BloomMap;
@@ -36,6 +39,10 @@ pub struct Scope<'ast> {
     /// All references declared in this scope
     pub declared_refs: BloomMap<'ast, &'ast str, ReferenceData>,
 
+    /// References declared with `let` or `const` in this scope, including
+    /// the head of a loop and the param of a catch clause
+    pub lexical_refs: BloomMap<'ast, &'ast str, DeclarationKind>,
+
     /// Parent scope of this scope
     pub parent: Option<&'ast Scope<'ast>>,
 
@@ -52,11 +59,40 @@ impl<'ast> Scope<'ast> {
             used_this: CopyCell::new(false),
             used_refs: BloomMap::new(),
             declared_refs: BloomMap::new(),
+            lexical_refs: BloomMap::new(),
             parent,
             children: GrowableList::new(),
         }
     }
 
+    /// Whether the name is used or declared in this scope or any of the
+    /// scopes nested in it.
+    pub fn uses(&self, name: &'ast str) -> bool {
+        self.used_refs.contains_key(name) ||
+        self.declared_refs.contains_key(name) ||
+        self.children.as_list().iter().any(|child| child.uses(name))
+    }
+
+    /// Whether the name is used or declared in this scope or any of the
+    /// scopes nested in it, other than `scope` and the scopes nested in it.
+    pub fn uses_outside(&self, name: &'ast str, scope: &Scope<'ast>) -> bool {
+        if ptr::eq(self, scope) {
+            return false;
+        }
+
+        self.used_refs.contains_key(name) ||
+        self.declared_refs.contains_key(name) ||
+        self.children.as_list().iter().any(|child| child.uses_outside(name, scope))
+    }
+
+    /// The closest function scope, this one if it is one, or the module.
+    pub fn function(&'ast self) -> &'ast Scope<'ast> {
+        match (self.kind, self.parent) {
+            (ScopeKind::Block, Some(parent)) => parent.function(),
+            _                                => self,
+        }
+    }
+
     #[inline]
     pub fn as_usize(&'ast self) -> usize {
         self as *const Scope as usize
@@ -77,6 +113,7 @@ impl<'ast> Debug for Scope<'ast> {
             .field("used_this", &self.used_this)
             .field("used_refs", &self.used_refs)
             .field("declared_refs", &self.declared_refs)
+            .field("lexical_refs", &self.lexical_refs)
             .field("children", &self.children)
             .finish()
     }
@@ -109,20 +146,97 @@ impl<'ast> ScopeContext<'ast> {
     }
 }
 
+/// Kind of the declaration the identifier at the end of the path is bound
+/// by, if it is `let` or `const`.
+fn lexical_kind<'ast>(path: &NodePath<'ast>) -> Option<DeclarationKind> {
+    let kind = |init: &ForInit| match *init {
+        ForInit::Declaration(ref declaration) => Some(declaration.kind),
+        ForInit::Expression(_)                => None,
+    };
+
+    for ancestor in path.ancestors() {
+        let kind = match *ancestor {
+            Ancestor::Pattern(_) |
+            Ancestor::Declarator(_) |
+            Ancestor::Property(_) => continue,
+            Ancestor::Expression(ref node) => match node.item {
+                Expression::Identifier(_) |
+                Expression::Object(_) |
+                Expression::Array(_) |
+                Expression::Binary(_) => continue,
+                _                     => return None,
+            },
+            Ancestor::Statement(ref node) => match node.item {
+                Statement::Declaration(ref declaration) => Some(declaration.kind),
+                Statement::For(ForStatement { init: Some(ref init), .. }) => kind(&init.item),
+                Statement::ForIn(ForInStatement { ref left, .. }) |
+                Statement::ForOf(ForOfStatement { ref left, .. }) => kind(&left.item),
+                _ => None,
+            },
+            Ancestor::CatchClause(_) => Some(DeclarationKind::Let),
+            _ => None,
+        };
+
+        return match kind {
+            Some(DeclarationKind::Var) => None,
+            kind                       => kind,
+        };
+    }
+
+    None
+}
+
+/// Whether the identifier used at the end of the path is a target of an
+/// object pattern, `b` in `var { a: b } = c`, and so is declared instead.
+fn is_pattern_target<'ast>(path: &NodePath<'ast>) -> bool {
+    let mut child = None;
+
+    for ancestor in path.ancestors() {
+        let target = match *ancestor {
+            Ancestor::Pattern(_) => return child.is_some(),
+            Ancestor::Expression(ref node) => match node.item {
+                Expression::Identifier(_) |
+                Expression::Object(_) |
+                Expression::Array(_) => true,
+                Expression::Binary(BinaryExpression {
+                    operator: OperatorKind::Assign,
+                    left,
+                    ..
+                }) => child.is_some_and(|child| Ancestor::Expression(left).is(child)),
+                _ => false,
+            },
+            Ancestor::Property(ref node) => match node.item {
+                Property::Shorthand(_) => true,
+                Property::Literal { value, .. } => child.is_some_and(|child| Ancestor::Expression(value).is(child)),
+                Property::Method { .. } => false,
+            },
+            _ => false,
+        };
+
+        if !target {
+            return false;
+        }
+
+        child = Some(ancestor);
+    }
+
+    false
+}
+
 struct ScopeAnalizer;
 
 impl<'ast> StaticVisitor<'ast> for ScopeAnalizer {
     type Context = ScopeContext<'ast>;
 
     #[inline]
-    fn on_enter_scope(kind: ScopeKind, ctx: &mut Self::Context) {
+    fn on_enter_scope(kind: ScopeKind, _: &NodePath<'ast>, ctx: &mut Self::Context) {
         ctx.current.set(
             ctx.arena.alloc(Scope::new(kind, Some(ctx.current.get())))
         );
     }
 
     #[inline]
-    fn on_leave_scope(ctx: &mut Self::Context) {
+    fn on_leave_scope(_: &NodePath<'ast>, ctx: &mut Self::Context) {
         let popped = ctx.current.get();
 
         ctx.current.set(popped.parent.unwrap());
@@ -130,18 +244,30 @@ impl<'ast> StaticVisitor<'ast> for ScopeAnalizer {
     }
 
     #[inline]
-    fn on_reference_use(ident: &Identifier<'ast>, ctx: &mut Self::Context) {
+    fn on_reference_use(ident: &Identifier<'ast>, path: &NodePath<'ast>, ctx: &mut Self::Context) {
+        if is_pattern_target(path) {
+            return ScopeAnalizer::on_reference_declaration(ident, path, ctx);
+        }
+
         ctx.current.get().used_refs.insert(ctx.arena, *ident, ());
     }
 
     #[inline]
-    fn on_reference_declaration(ident: &Identifier<'ast>, ctx: &mut Self::Context) {
-        ctx.current.get().declared_refs.insert(ctx.arena, *ident, ());
+    fn on_reference_declaration(ident: &Identifier<'ast>, path: &NodePath<'ast>, ctx: &mut Self::Context) {
+        let current = ctx.current.get();
+
+        current.declared_refs.insert(ctx.arena, *ident, ());
+
+        if let Some(kind) = lexical_kind(path) {
+            current.lexical_refs.insert(ctx.arena, *ident, kind);
+        }
     }
 
     #[inline]
-    fn on_this_expression(_: &ExpressionNode<'ast>, ctx: &mut Self::Context) {
+    fn on_this_expression(_: &ExpressionNode<'ast>, _: &NodePath<'ast>, ctx: &mut Self::Context) -> Control {
         ctx.current.get().used_this.set(true);
+
+        Control::Continue
     }
 
     #[inline]
@@ -183,5 +309,53 @@ mod test {
         assert_eq!(moon.used_refs.contains_key("moon"), true);
         assert_eq!(moon.declared_refs.is_empty(), true);
         assert_eq!(moon.children.as_list().is_empty(), true);
+
+        assert_eq!(root.uses("moon"), true);
+        assert_eq!(root.uses("bar"), true);
+        assert_eq!(moon.uses("doge"), false);
+    }
+
+    #[test]
+    fn lexical_declarations() {
+        let module = parse("var a; let b; { const c = 1, { d, e: f } = g; } for (let h;;) {} try {} catch (i) {}").unwrap();
+        let root = analyze(&module);
+
+        assert_eq!(root.lexical_refs.get("a"), None);
+        assert_eq!(root.lexical_refs.get("b"), Some(DeclarationKind::Let));
+
+        let scopes = root.children.as_list().iter().cloned().collect::<Vec<_>>();
+
+        assert_eq!(scopes[0].lexical_refs.get("c"), Some(DeclarationKind::Const));
+        assert_eq!(scopes[0].lexical_refs.get("d"), Some(DeclarationKind::Const));
+        assert_eq!(scopes[0].lexical_refs.get("f"), Some(DeclarationKind::Const));
+        assert_eq!(scopes[0].declared_refs.contains_key("e"), false);
+        assert_eq!(scopes[0].used_refs.contains_key("g"), true);
+        assert_eq!(scopes[1].lexical_refs.get("h"), Some(DeclarationKind::Let));
+        assert_eq!(scopes[3].lexical_refs.get("i"), Some(DeclarationKind::Let));
+    }
+
+    #[test]
+    fn uses_outside() {
+        let module = parse("function f() { a; { b; } { c; } }").unwrap();
+        let root = analyze(&module);
+        let function = *root.children.as_list().only_element().unwrap();
+        let blocks = function.children.as_list().iter().cloned().collect::<Vec<_>>();
+
+        assert_eq!(function.uses_outside("a", blocks[0]), true);
+        assert_eq!(function.uses_outside("b", blocks[0]), false);
+        assert_eq!(function.uses_outside("c", blocks[0]), true);
+        assert_eq!(blocks[1].function(), function);
+    }
+
+    #[test]
+    fn scope_analysis_escaped_identifiers() {
+        let module = parse(r"var \u0061; { a\u{62}c; }").unwrap();
+        let root = analyze(&module);
+
+        assert_eq!(root.declared_refs.contains_key("a"), true);
+
+        let block = *root.children.as_list().only_element().unwrap();
+
+        assert_eq!(block.used_refs.contains_key("abc"), true);
     }
 }
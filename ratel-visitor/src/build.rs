@@ -1,7 +1,7 @@
 #[macro_export]
 macro_rules! build { (
     $(
-        fn $name:ident( $( $arg:ident : $type:ty ),* );
+        fn $name:ident( $( $arg:ident : $type:ty ),* ) $( -> $ret:ty )*;
     )*
 ) => (
     /// Helper macro for extracting Visitable::Parent type for any T: Visitable
@@ -13,7 +13,9 @@ macro_rules! build { (
         // Construct methods
         $(
             #[inline]
-            fn $name(&self, $( $type, )* &mut Self::Context) {}
+            fn $name(&self, $( $type, )* &NodePath<'ast>, &mut Self::Context) $( -> $ret )* {
+                Default::default()
+            }
         )*
 
         fn register(&self, &mut DynamicVisitor<'ast, Self::Context>);
@@ -25,7 +27,9 @@ macro_rules! build { (
         // Construct associated functions
         $(
             #[inline]
-            fn $name($( $type, )* &mut Self::Context) {}
+            fn $name($( $type, )* &NodePath<'ast>, &mut Self::Context) $( -> $ret )* {
+                Default::default()
+            }
         )*
 
         fn register(&mut DynamicVisitor<'ast, Self::Context>);
@@ -39,8 +43,8 @@ macro_rules! build { (
         // Construct methods
         $(
             #[inline]
-            fn $name(&self, $( $arg: $type, )* ctx: &mut Self::Context) {
-                SV::$name($( $arg, )* ctx);
+            fn $name(&self, $( $arg: $type, )* path: &NodePath<'ast>, ctx: &mut Self::Context) $( -> $ret )* {
+                SV::$name($( $arg, )* path, ctx)
             }
         )*
 
@@ -53,7 +57,7 @@ macro_rules! build { (
     pub struct DynamicVisitor<'ast, CTX> {
         // Construct vectors for handlers
         $(
-            pub $name: Vec<fn($( $type, )* &mut CTX)>,
+            pub $name: Vec<fn($( $type, )* &NodePath<'ast>, &mut CTX) $( -> $ret )*>,
         )*
     }
 
@@ -73,10 +77,10 @@ macro_rules! build { (
         // Construct methods
         $(
             #[inline]
-            fn $name(&self, $( $arg: $type, )* ctx: &mut Self::Context) {
-                for handler in &self.$name {
-                    handler($( $arg, )* ctx);
-                }
+            fn $name(&self, $( $arg: $type, )* path: &NodePath<'ast>, ctx: &mut Self::Context) $( -> $ret )* {
+                self.$name.iter().fold(Default::default(), |outcome, handler| {
+                    Outcome::and_then(outcome, || handler($( $arg, )* path, ctx))
+                })
             }
         )*
 
@@ -96,9 +100,8 @@ macro_rules! build { (
         // Construct associated functions
         $(
             #[inline]
-            fn $name($( $arg: $type, )* ctx: &mut CTX) {
-                A::$name($( $arg, )* ctx);
-                B::$name($( $arg, )* ctx);
+            fn $name($( $arg: $type, )* path: &NodePath<'ast>, ctx: &mut CTX) $( -> $ret )* {
+                Outcome::and_then(A::$name($( $arg, )* path, ctx), || B::$name($( $arg, )* path, ctx))
             }
         )*
 
@@ -109,3 +112,64 @@ macro_rules! build { (
         }
     }
 )}
+
+/// Call the enter hook of a node, then unless it is skipped visit its
+/// children with the node pushed on the path, and call the leave hook.
+macro_rules! visit_node {
+    ($path:ident, $ancestor:expr, $enter:expr, $children:expr, $leave:expr) => {{
+        match $enter {
+            Control::Continue => {
+                $path.push($ancestor);
+                $children;
+                $path.pop();
+            },
+            Control::Skip => {},
+            Control::Stop => return $path.stop(),
+        }
+
+        if !$path.is_stopped() && $leave == Control::Stop {
+            $path.stop();
+        }
+    }}
+}
+
+/// Implement `visit_node` calling the enter and leave hooks.
+macro_rules! node_hooks {
+    ($enter:ident, $leave:ident, $ancestor:ident) => {
+        #[inline]
+        fn visit_node<V>(node: &Node<'ast, Self>, visitor: &V, path: &mut NodePath<'ast>, ctx: &mut V::Context)
+        where
+            V: Visitor<'ast>,
+        {
+            if path.is_stopped() {
+                return;
+            }
+
+            visit_node!(
+                path,
+                Ancestor::$ancestor(*node),
+                visitor.$enter(&node.item, node, path, ctx),
+                node.item.visit(visitor, path, ctx),
+                visitor.$leave(&node.item, node, path, ctx)
+            );
+        }
+    }
+}
+
+/// Evaluate an expression with the node held by an `Ancestor`, whatever
+/// its type.
+macro_rules! with_node {
+    ($ancestor:expr, $node:ident => $body:expr) => {
+        match $ancestor {
+            Ancestor::Statement(ref $node)   => $body,
+            Ancestor::Expression(ref $node)  => $body,
+            Ancestor::Property(ref $node)    => $body,
+            Ancestor::PropertyKey(ref $node) => $body,
+            Ancestor::ClassMember(ref $node) => $body,
+            Ancestor::Pattern(ref $node)     => $body,
+            Ancestor::Declarator(ref $node)  => $body,
+            Ancestor::SwitchCase(ref $node)  => $body,
+            Ancestor::CatchClause(ref $node) => $body,
+        }
+    }
+}
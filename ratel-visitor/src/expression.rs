@@ -1,95 +1,165 @@
 use ratel::ast::{Identifier, Expression, ExpressionNode, StatementNode, Literal};
 use ratel::ast::expression::*;
 
-use {Visitor, Visitable};
+use {Visitor, Visitable, NodePath, Ancestor, Control, ScopeKind, visit_params};
 
 
 impl<'ast> Visitable<'ast> for ExpressionNode<'ast> {
     type Parent = StatementNode<'ast>;
 
     #[inline]
-    fn traverse<V>(&self, visitor: &V, ctx: &mut V::Context)
+    fn visit<V>(&self, visitor: &V, path: &mut NodePath<'ast>, ctx: &mut V::Context)
     where
         V: Visitor<'ast>,
     {
         use self::Expression::*;
 
+        if path.is_stopped() {
+            return;
+        }
+
         match self.item {
             Void => {},
-            This(_) => {
-                visitor.on_this_expression(self, ctx);
-            },
-            Identifier(ref ident) => {
-                ident.traverse(visitor, ctx);
-                visitor.on_identifier_expression(ident, self, ctx);
-            },
-            Literal(ref literal) => {
-                visitor.on_literal_expression(literal, self, ctx);
-            },
-            Sequence(ref sequence) => {
-                sequence.traverse(visitor, ctx);
-                visitor.on_sequence_expression(sequence, self, ctx);
-            },
-            Array(ref array) => {
-                array.traverse(visitor, ctx);
-                visitor.on_array_expression(array, self, ctx);
-            },
-            Member(ref member) => {
-                member.traverse(visitor, ctx);
-                visitor.on_member_expression(member, self, ctx);
-            },
-            ComputedMember(ref computed) => {
-                computed.traverse(visitor, ctx);
-                visitor.on_computed_member_expression(computed, self, ctx);
-            },
-            Call(ref call) => {
-                call.traverse(visitor, ctx);
-                visitor.on_call_expression(call, self, ctx);
-            },
-            Binary(ref binary) => {
-                binary.traverse(visitor, ctx);
-                visitor.on_binary_expression(binary, self, ctx);
-            },
-            Prefix(ref prefix) => {
-                prefix.traverse(visitor, ctx);
-                visitor.on_prefix_expression(prefix, self, ctx);
-            },
-            Postfix(ref postfix) => {
-                postfix.traverse(visitor, ctx);
-                visitor.on_postfix_expression(postfix, self, ctx);
-            },
-            Conditional(ref conditional) => {
-                conditional.traverse(visitor, ctx);
-                visitor.on_conditional_expression(conditional, self, ctx);
-            },
-            Template(ref template) => {
-                template.traverse(visitor, ctx);
-                visitor.on_template_literal(template, self, ctx);
-            },
-            TaggedTemplate(ref tagged) => {
-                tagged.traverse(visitor, ctx);
-                visitor.on_tagged_template_expression(tagged, self, ctx);
-            },
-            Spread(ref spread) => {
-                spread.traverse(visitor, ctx);
-                visitor.on_spread_expression(spread, self, ctx);
-            },
-            Arrow(ref arrow) => {
-                arrow.traverse(visitor, ctx);
-                visitor.on_arrow_expression(arrow, self, ctx);
-            },
-            Object(ref object) => {
-                object.traverse(visitor, ctx);
-                visitor.on_object_expression(object, self, ctx);
-            },
-            Function(ref function) => {
-                function.traverse(visitor, ctx);
-                visitor.on_function_expression(function, self, ctx);
-            },
-            Class(ref class) => {
-                class.traverse(visitor, ctx);
-                visitor.on_class_expression(class, self, ctx);
-            }
+            This(_) => visit_node!(
+                path,
+                Ancestor::Expression(*self),
+                visitor.on_this_expression(self, path, ctx),
+                {},
+                visitor.on_leave_this_expression(self, path, ctx)
+            ),
+            Identifier(ref ident) => visit_node!(
+                path,
+                Ancestor::Expression(*self),
+                visitor.on_identifier_expression(ident, self, path, ctx),
+                ident.visit(visitor, path, ctx),
+                visitor.on_leave_identifier_expression(ident, self, path, ctx)
+            ),
+            Literal(ref literal) => visit_node!(
+                path,
+                Ancestor::Expression(*self),
+                visitor.on_literal_expression(literal, self, path, ctx),
+                {},
+                visitor.on_leave_literal_expression(literal, self, path, ctx)
+            ),
+            Sequence(ref sequence) => visit_node!(
+                path,
+                Ancestor::Expression(*self),
+                visitor.on_sequence_expression(sequence, self, path, ctx),
+                sequence.visit(visitor, path, ctx),
+                visitor.on_leave_sequence_expression(sequence, self, path, ctx)
+            ),
+            Array(ref array) => visit_node!(
+                path,
+                Ancestor::Expression(*self),
+                visitor.on_array_expression(array, self, path, ctx),
+                array.visit(visitor, path, ctx),
+                visitor.on_leave_array_expression(array, self, path, ctx)
+            ),
+            Member(ref member) => visit_node!(
+                path,
+                Ancestor::Expression(*self),
+                visitor.on_member_expression(member, self, path, ctx),
+                member.visit(visitor, path, ctx),
+                visitor.on_leave_member_expression(member, self, path, ctx)
+            ),
+            ComputedMember(ref computed) => visit_node!(
+                path,
+                Ancestor::Expression(*self),
+                visitor.on_computed_member_expression(computed, self, path, ctx),
+                computed.visit(visitor, path, ctx),
+                visitor.on_leave_computed_member_expression(computed, self, path, ctx)
+            ),
+            Call(ref call) => visit_node!(
+                path,
+                Ancestor::Expression(*self),
+                visitor.on_call_expression(call, self, path, ctx),
+                call.visit(visitor, path, ctx),
+                visitor.on_leave_call_expression(call, self, path, ctx)
+            ),
+            Binary(ref binary) => visit_node!(
+                path,
+                Ancestor::Expression(*self),
+                visitor.on_binary_expression(binary, self, path, ctx),
+                binary.visit(visitor, path, ctx),
+                visitor.on_leave_binary_expression(binary, self, path, ctx)
+            ),
+            Prefix(ref prefix) => visit_node!(
+                path,
+                Ancestor::Expression(*self),
+                visitor.on_prefix_expression(prefix, self, path, ctx),
+                prefix.visit(visitor, path, ctx),
+                visitor.on_leave_prefix_expression(prefix, self, path, ctx)
+            ),
+            Postfix(ref postfix) => visit_node!(
+                path,
+                Ancestor::Expression(*self),
+                visitor.on_postfix_expression(postfix, self, path, ctx),
+                postfix.visit(visitor, path, ctx),
+                visitor.on_leave_postfix_expression(postfix, self, path, ctx)
+            ),
+            Conditional(ref conditional) => visit_node!(
+                path,
+                Ancestor::Expression(*self),
+                visitor.on_conditional_expression(conditional, self, path, ctx),
+                conditional.visit(visitor, path, ctx),
+                visitor.on_leave_conditional_expression(conditional, self, path, ctx)
+            ),
+            Template(ref template) => visit_node!(
+                path,
+                Ancestor::Expression(*self),
+                visitor.on_template_literal(template, self, path, ctx),
+                template.visit(visitor, path, ctx),
+                visitor.on_leave_template_literal(template, self, path, ctx)
+            ),
+            TaggedTemplate(ref tagged) => visit_node!(
+                path,
+                Ancestor::Expression(*self),
+                visitor.on_tagged_template_expression(tagged, self, path, ctx),
+                tagged.visit(visitor, path, ctx),
+                visitor.on_leave_tagged_template_expression(tagged, self, path, ctx)
+            ),
+            Spread(ref spread) => visit_node!(
+                path,
+                Ancestor::Expression(*self),
+                visitor.on_spread_expression(spread, self, path, ctx),
+                spread.visit(visitor, path, ctx),
+                visitor.on_leave_spread_expression(spread, self, path, ctx)
+            ),
+            Arrow(ref arrow) => visit_node!(
+                path,
+                Ancestor::Expression(*self),
+                visitor.on_arrow_expression(arrow, self, path, ctx),
+                arrow.visit(visitor, path, ctx),
+                visitor.on_leave_arrow_expression(arrow, self, path, ctx)
+            ),
+            Object(ref object) => visit_node!(
+                path,
+                Ancestor::Expression(*self),
+                visitor.on_object_expression(object, self, path, ctx),
+                object.visit(visitor, path, ctx),
+                visitor.on_leave_object_expression(object, self, path, ctx)
+            ),
+            Function(ref function) => visit_node!(
+                path,
+                Ancestor::Expression(*self),
+                visitor.on_function_expression(function, self, path, ctx),
+                function.visit(visitor, path, ctx),
+                visitor.on_leave_function_expression(function, self, path, ctx)
+            ),
+            Class(ref class) => visit_node!(
+                path,
+                Ancestor::Expression(*self),
+                visitor.on_class_expression(class, self, path, ctx),
+                class.visit(visitor, path, ctx),
+                visitor.on_leave_class_expression(class, self, path, ctx)
+            ),
+            TypeCast(ref cast) => visit_node!(
+                path,
+                Ancestor::Expression(*self),
+                visitor.on_type_cast_expression(cast, self, path, ctx),
+                cast.visit(visitor, path, ctx),
+                visitor.on_leave_type_cast_expression(cast, self, path, ctx)
+            ),
         }
     }
 }
@@ -98,15 +168,15 @@ impl<'ast> Visitable<'ast> for ThisExpression {
     type Parent = ExpressionNode<'ast>;
 
     #[inline]
-    fn traverse<V: Visitor<'ast>>(&self, _: &V, _: &mut V::Context) {}
+    fn visit<V: Visitor<'ast>>(&self, _: &V, _: &mut NodePath<'ast>, _: &mut V::Context) {}
 }
 
 impl<'ast> Visitable<'ast> for Identifier<'ast> {
     type Parent = ExpressionNode<'ast>;
 
     #[inline]
-    fn traverse<V: Visitor<'ast>>(&self, visitor: &V, ctx: &mut V::Context) {
-        visitor.on_reference_use(self, ctx);
+    fn visit<V: Visitor<'ast>>(&self, visitor: &V, path: &mut NodePath<'ast>, ctx: &mut V::Context) {
+        visitor.on_reference_use(self, path, ctx);
     }
 }
 
@@ -114,18 +184,18 @@ impl<'ast> Visitable<'ast> for Literal<'ast> {
     type Parent = ExpressionNode<'ast>;
 
     #[inline]
-    fn traverse<V: Visitor<'ast>>(&self, _: &V, _: &mut V::Context) {}
+    fn visit<V: Visitor<'ast>>(&self, _: &V, _: &mut NodePath<'ast>, _: &mut V::Context) {}
 }
 
 impl<'ast> Visitable<'ast> for SequenceExpression<'ast> {
     type Parent = ExpressionNode<'ast>;
 
     #[inline]
-    fn traverse<V>(&self, visitor: &V, ctx: &mut V::Context)
+    fn visit<V>(&self, visitor: &V, path: &mut NodePath<'ast>, ctx: &mut V::Context)
     where
         V: Visitor<'ast>,
     {
-        self.body.traverse(visitor, ctx);
+        self.body.visit(visitor, path, ctx);
     }
 }
 
@@ -133,11 +203,11 @@ impl<'ast> Visitable<'ast> for ArrayExpression<'ast> {
     type Parent = ExpressionNode<'ast>;
 
     #[inline]
-    fn traverse<V>(&self, visitor: &V, ctx: &mut V::Context)
+    fn visit<V>(&self, visitor: &V, path: &mut NodePath<'ast>, ctx: &mut V::Context)
     where
         V: Visitor<'ast>,
     {
-        self.body.traverse(visitor, ctx);
+        self.body.visit(visitor, path, ctx);
     }
 }
 
@@ -145,11 +215,11 @@ impl<'ast> Visitable<'ast> for MemberExpression<'ast> {
     type Parent = ExpressionNode<'ast>;
 
     #[inline]
-    fn traverse<V>(&self, visitor: &V, ctx: &mut V::Context)
+    fn visit<V>(&self, visitor: &V, path: &mut NodePath<'ast>, ctx: &mut V::Context)
     where
         V: Visitor<'ast>,
     {
-        self.object.traverse(visitor, ctx);
+        self.object.visit(visitor, path, ctx);
     }
 }
 
@@ -157,12 +227,12 @@ impl<'ast> Visitable<'ast> for ComputedMemberExpression<'ast> {
     type Parent = ExpressionNode<'ast>;
 
     #[inline]
-    fn traverse<V>(&self, visitor: &V, ctx: &mut V::Context)
+    fn visit<V>(&self, visitor: &V, path: &mut NodePath<'ast>, ctx: &mut V::Context)
     where
         V: Visitor<'ast>,
     {
-        self.object.traverse(visitor, ctx);
-        self.property.traverse(visitor, ctx);
+        self.object.visit(visitor, path, ctx);
+        self.property.visit(visitor, path, ctx);
     }
 }
 
@@ -170,12 +240,12 @@ impl<'ast> Visitable<'ast> for CallExpression<'ast> {
     type Parent = ExpressionNode<'ast>;
 
     #[inline]
-    fn traverse<V>(&self, visitor: &V, ctx: &mut V::Context)
+    fn visit<V>(&self, visitor: &V, path: &mut NodePath<'ast>, ctx: &mut V::Context)
     where
         V: Visitor<'ast>,
     {
-        self.callee.traverse(visitor, ctx);
-        self.arguments.traverse(visitor, ctx);
+        self.callee.visit(visitor, path, ctx);
+        self.arguments.visit(visitor, path, ctx);
     }
 }
 
@@ -183,12 +253,12 @@ impl<'ast> Visitable<'ast> for BinaryExpression<'ast> {
     type Parent = ExpressionNode<'ast>;
 
     #[inline]
-    fn traverse<V>(&self, visitor: &V, ctx: &mut V::Context)
+    fn visit<V>(&self, visitor: &V, path: &mut NodePath<'ast>, ctx: &mut V::Context)
     where
         V: Visitor<'ast>,
     {
-        self.left.traverse(visitor, ctx);
-        self.right.traverse(visitor, ctx);
+        self.left.visit(visitor, path, ctx);
+        self.right.visit(visitor, path, ctx);
     }
 }
 
@@ -196,11 +266,11 @@ impl<'ast> Visitable<'ast> for PrefixExpression<'ast> {
     type Parent = ExpressionNode<'ast>;
 
     #[inline]
-    fn traverse<V>(&self, visitor: &V, ctx: &mut V::Context)
+    fn visit<V>(&self, visitor: &V, path: &mut NodePath<'ast>, ctx: &mut V::Context)
     where
         V: Visitor<'ast>,
     {
-        self.operand.traverse(visitor, ctx);
+        self.operand.visit(visitor, path, ctx);
     }
 }
 
@@ -208,11 +278,11 @@ impl<'ast> Visitable<'ast> for PostfixExpression<'ast> {
     type Parent = ExpressionNode<'ast>;
 
     #[inline]
-    fn traverse<V>(&self, visitor: &V, ctx: &mut V::Context)
+    fn visit<V>(&self, visitor: &V, path: &mut NodePath<'ast>, ctx: &mut V::Context)
     where
         V: Visitor<'ast>,
     {
-        self.operand.traverse(visitor, ctx);
+        self.operand.visit(visitor, path, ctx);
     }
 }
 
@@ -220,13 +290,13 @@ impl<'ast> Visitable<'ast> for ConditionalExpression<'ast> {
     type Parent = ExpressionNode<'ast>;
 
     #[inline]
-    fn traverse<V>(&self, visitor: &V, ctx: &mut V::Context)
+    fn visit<V>(&self, visitor: &V, path: &mut NodePath<'ast>, ctx: &mut V::Context)
     where
         V: Visitor<'ast>,
     {
-        self.test.traverse(visitor, ctx);
-        self.consequent.traverse(visitor, ctx);
-        self.alternate.traverse(visitor, ctx);
+        self.test.visit(visitor, path, ctx);
+        self.consequent.visit(visitor, path, ctx);
+        self.alternate.visit(visitor, path, ctx);
     }
 }
 
@@ -234,11 +304,31 @@ impl<'ast> Visitable<'ast> for TemplateLiteral<'ast> {
     type Parent = ExpressionNode<'ast>;
 
     #[inline]
-    fn traverse<V>(&self, visitor: &V, ctx: &mut V::Context)
+    fn visit<V>(&self, visitor: &V, path: &mut NodePath<'ast>, ctx: &mut V::Context)
     where
         V: Visitor<'ast>,
     {
-        self.expressions.traverse(visitor, ctx);
+        let mut expressions = self.expressions.iter();
+
+        // Quasis and expressions are interleaved in the source
+        for quasi in self.quasis.iter() {
+            if path.is_stopped() {
+                return;
+            }
+
+            if visitor.on_template_quasi(&quasi.item, quasi, path, ctx) == Control::Stop ||
+               visitor.on_leave_template_quasi(&quasi.item, quasi, path, ctx) == Control::Stop {
+                return path.stop();
+            }
+
+            if let Some(expression) = expressions.next() {
+                expression.visit(visitor, path, ctx);
+            }
+        }
+
+        for expression in expressions {
+            expression.visit(visitor, path, ctx);
+        }
     }
 }
 
@@ -246,12 +336,12 @@ impl<'ast> Visitable<'ast> for TaggedTemplateExpression<'ast> {
     type Parent = ExpressionNode<'ast>;
 
     #[inline]
-    fn traverse<V>(&self, visitor: &V, ctx: &mut V::Context)
+    fn visit<V>(&self, visitor: &V, path: &mut NodePath<'ast>, ctx: &mut V::Context)
     where
         V: Visitor<'ast>,
     {
-        self.tag.traverse(visitor, ctx);
-        self.quasi.traverse(visitor, ctx);
+        self.tag.visit(visitor, path, ctx);
+        self.quasi.visit(visitor, path, ctx);
     }
 }
 
@@ -259,11 +349,11 @@ impl<'ast> Visitable<'ast> for SpreadExpression<'ast> {
     type Parent = ExpressionNode<'ast>;
 
     #[inline]
-    fn traverse<V>(&self, visitor: &V, ctx: &mut V::Context)
+    fn visit<V>(&self, visitor: &V, path: &mut NodePath<'ast>, ctx: &mut V::Context)
     where
         V: Visitor<'ast>,
     {
-        self.argument.traverse(visitor, ctx);
+        self.argument.visit(visitor, path, ctx);
     }
 }
 
@@ -271,13 +361,13 @@ impl<'ast> Visitable<'ast> for ArrowBody<'ast> {
     type Parent = ExpressionNode<'ast>;
 
     #[inline]
-    fn traverse<V>(&self, visitor: &V, ctx: &mut V::Context)
+    fn visit<V>(&self, visitor: &V, path: &mut NodePath<'ast>, ctx: &mut V::Context)
     where
         V: Visitor<'ast>,
     {
         match *self {
-            ArrowBody::Expression(ref expression) => expression.traverse(visitor, ctx),
-            ArrowBody::Block(ref block)           => block.body.traverse(visitor, ctx),
+            ArrowBody::Expression(ref expression) => expression.visit(visitor, path, ctx),
+            ArrowBody::Block(ref block)           => block.body.visit(visitor, path, ctx),
         }
     }
 }
@@ -286,12 +376,26 @@ impl<'ast> Visitable<'ast> for ArrowExpression<'ast> {
     type Parent = ExpressionNode<'ast>;
 
     #[inline]
-    fn traverse<V>(&self, visitor: &V, ctx: &mut V::Context)
+    fn visit<V>(&self, visitor: &V, path: &mut NodePath<'ast>, ctx: &mut V::Context)
+    where
+        V: Visitor<'ast>,
+    {
+        visitor.on_enter_scope(ScopeKind::Function, path, ctx);
+        visit_params(&self.params, visitor, path, ctx);
+        self.body.visit(visitor, path, ctx);
+        visitor.on_leave_scope(path, ctx);
+    }
+}
+
+impl<'ast> Visitable<'ast> for TypeCastExpression<'ast> {
+    type Parent = ExpressionNode<'ast>;
+
+    #[inline]
+    fn visit<V>(&self, visitor: &V, path: &mut NodePath<'ast>, ctx: &mut V::Context)
     where
         V: Visitor<'ast>,
     {
-        self.params.traverse(visitor, ctx);
-        self.body.traverse(visitor, ctx);
+        self.expression.visit(visitor, path, ctx);
     }
 }
 
@@ -299,10 +403,10 @@ impl<'ast> Visitable<'ast> for ObjectExpression<'ast> {
     type Parent = ExpressionNode<'ast>;
 
     #[inline]
-    fn traverse<V>(&self, visitor: &V, ctx: &mut V::Context)
+    fn visit<V>(&self, visitor: &V, path: &mut NodePath<'ast>, ctx: &mut V::Context)
     where
         V: Visitor<'ast>,
     {
-        self.body.traverse(visitor, ctx);
+        self.body.visit(visitor, path, ctx);
     }
 }
@@ -1,14 +1,14 @@
 use ratel::ast::{Function, Class, ClassMember, Name, EmptyName, OptionalName, MandatoryName};
 use ratel::ast::{Node, ExpressionNode, StatementNode};
 
-use {Visitable, Visitor, ScopeKind, NoParent};
+use {Visitable, Visitor, NodePath, Ancestor, Control, ScopeKind, NoParent, visit_params};
 
 
 impl<'ast> Visitable<'ast> for EmptyName {
     type Parent = NoParent;
 
     #[inline]
-    fn traverse<V>(&self, _: &V, _: &mut V::Context)
+    fn visit<V>(&self, _: &V, _: &mut NodePath<'ast>, _: &mut V::Context)
     where
         V: Visitor<'ast>,
     {}
@@ -18,7 +18,7 @@ impl<'ast> Visitable<'ast> for OptionalName<'ast> {
     type Parent = ExpressionNode<'ast>;
 
     #[inline]
-    fn traverse<V>(&self, _: &V, _: &mut V::Context)
+    fn visit<V>(&self, _: &V, _: &mut NodePath<'ast>, _: &mut V::Context)
     where
         V: Visitor<'ast>,
     {}
@@ -28,11 +28,11 @@ impl<'ast> Visitable<'ast> for MandatoryName<'ast> {
     type Parent = StatementNode<'ast>;
 
     #[inline]
-    fn traverse<V>(&self, visitor: &V, ctx: &mut V::Context)
+    fn visit<V>(&self, visitor: &V, path: &mut NodePath<'ast>, ctx: &mut V::Context)
     where
         V: Visitor<'ast>,
     {
-        visitor.on_reference_declaration(&(self.0).item, ctx);
+        visitor.on_reference_declaration(&(self.0).item, path, ctx);
     }
 }
 
@@ -43,26 +43,28 @@ where
     type Parent = N::Parent;
 
     #[inline]
-    fn traverse<V>(&self, visitor: &V, ctx: &mut V::Context)
+    fn visit<V>(&self, visitor: &V, path: &mut NodePath<'ast>, ctx: &mut V::Context)
     where
         V: Visitor<'ast>,
     {
-        self.name.traverse(visitor, ctx);
+        self.name.visit(visitor, path, ctx);
 
         // Call visit on the StatementList instead of BlockNode since we
         // need to make sure that function parameters end up inside the block
-        visitor.on_enter_scope(ScopeKind::Function, ctx);
-        self.params.traverse(visitor, ctx);
-        self.body.body.traverse(visitor, ctx);
-        visitor.on_leave_scope(ctx);
+        visitor.on_enter_scope(ScopeKind::Function, path, ctx);
+        visit_params(&self.params, visitor, path, ctx);
+        self.body.body.visit(visitor, path, ctx);
+        visitor.on_leave_scope(path, ctx);
     }
 }
 
 impl<'ast> Visitable<'ast> for ClassMember<'ast> {
     type Parent = Node<'ast, Self>;
 
+    node_hooks!(on_class_member, on_leave_class_member, ClassMember);
+
     #[inline]
-    fn traverse<V>(&self, visitor: &V, ctx: &mut V::Context)
+    fn visit<V>(&self, visitor: &V, path: &mut NodePath<'ast>, ctx: &mut V::Context)
     where
         V: Visitor<'ast>,
     {
@@ -75,16 +77,24 @@ impl<'ast> Visitable<'ast> for ClassMember<'ast> {
                 ref value,
                 ..
             } => {
-                key.traverse(visitor, ctx);
-                value.traverse(visitor, ctx);
+                key.visit(visitor, path, ctx);
+                value.visit(visitor, path, ctx);
             },
             Literal {
                 ref key,
                 ref value,
                 ..
             } => {
-                key.traverse(visitor, ctx);
-                value.traverse(visitor, ctx);
+                key.visit(visitor, path, ctx);
+                value.visit(visitor, path, ctx);
+            },
+            Property {
+                ref key,
+                ref value,
+                ..
+            } => {
+                key.visit(visitor, path, ctx);
+                value.visit(visitor, path, ctx);
             },
         }
     }
@@ -97,12 +107,12 @@ where
     type Parent = N::Parent;
 
     #[inline]
-    fn traverse<V>(&self, visitor: &V, ctx: &mut V::Context)
+    fn visit<V>(&self, visitor: &V, path: &mut NodePath<'ast>, ctx: &mut V::Context)
     where
         V: Visitor<'ast>,
     {
-        self.name.traverse(visitor, ctx);
-        self.extends.traverse(visitor, ctx);
-        self.body.body.traverse(visitor, ctx);
+        self.name.visit(visitor, path, ctx);
+        self.extends.visit(visitor, path, ctx);
+        self.body.body.visit(visitor, path, ctx);
     }
 }
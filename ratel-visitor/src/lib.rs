@@ -1,10 +1,22 @@
+//! Traversal of the AST, calling hooks on the nodes it goes through.
+//!
+//! Every expression and statement has an `on_*` hook, called when the node
+//! is entered, before its children are traversed, and an `on_leave_*` hook,
+//! called after them. Up to 0.8 the `on_*` hooks were called after the
+//! children. Since 0.9, visitors that need to see children first have to
+//! move to the `on_leave_*` hooks.
+
 #[cfg(test)]
 #[macro_use]
 extern crate pretty_assertions;
+#[cfg(test)]
+extern crate serde_json;
 extern crate ratel;
 
-use ratel::ast::{Node, NodeList, Identifier, Literal, Pattern};
-use ratel::ast::{ExpressionList, StatementList, ExpressionNode, StatementNode};
+use std::ptr;
+
+use ratel::ast::{Node, NodeList, Identifier, Literal, Pattern, PatternList, ClassMember};
+use ratel::ast::{ExpressionList, StatementList, ExpressionNode, StatementNode, PropertyNode};
 use ratel::ast::expression::*;
 use ratel::ast::statement::*;
 
@@ -12,9 +24,13 @@ use ratel::Module;
 
 #[macro_use]
 mod build;
+mod path;
 mod function;
 mod expression;
 mod statement;
+pub mod query;
+
+pub use path::{NodePath, Ancestor, Control, Outcome};
 
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum ScopeKind {
@@ -26,8 +42,12 @@ pub enum ScopeKind {
 pub type NoParent = ();
 
 build! {
-    // Enters a new statement list (program body, block body, switch case, etc.)
-    fn on_statement_list(body: StatementList<'ast>);
+    // Enters a new statement list (program body, block body, switch case, etc.),
+    // the list is the one held by its parent, so that it can be rebuilt
+    fn on_statement_list(body: &StatementList<'ast>);
+
+    // Leaves a statement list, after all of its statements have been visited
+    fn on_leave_statement_list(body: &StatementList<'ast>);
 
     // Entered a new scope
     fn on_enter_scope(kind: ScopeKind);
@@ -41,99 +61,230 @@ build! {
     // A reference has been declared within the current scope
     fn on_reference_declaration(ident: &Identifier<'ast>);
 
-    // expressions
-    fn on_this_expression(node: &ExpressionNode<'ast>);
-    fn on_identifier_expression(item: &Identifier<'ast>, node: &ExpressionNode<'ast>);
-    fn on_literal_expression(item: &Literal<'ast>, node: &ExpressionNode<'ast>);
-    fn on_sequence_expression(item: &SequenceExpression<'ast>, node: &ExpressionNode<'ast>);
-    fn on_array_expression(item: &ArrayExpression<'ast>, node: &ExpressionNode<'ast>);
-    fn on_member_expression(item: &MemberExpression<'ast>, node: &ExpressionNode<'ast>);
-    fn on_computed_member_expression(item: &ComputedMemberExpression<'ast>, node: &ExpressionNode<'ast>);
-    fn on_call_expression(item: &CallExpression<'ast>, node: &ExpressionNode<'ast>);
-    fn on_binary_expression(item: &BinaryExpression<'ast>, node: &ExpressionNode<'ast>);
-    fn on_prefix_expression(item: &PrefixExpression<'ast>, node: &ExpressionNode<'ast>);
-    fn on_postfix_expression(item: &PostfixExpression<'ast>, node: &ExpressionNode<'ast>);
-    fn on_conditional_expression(item: &ConditionalExpression<'ast>, node: &ExpressionNode<'ast>);
-    fn on_template_literal(item: &TemplateLiteral<'ast>, node: &ExpressionNode<'ast>);
-    fn on_tagged_template_expression(item: &TaggedTemplateExpression<'ast>, node: &ExpressionNode<'ast>);
-    fn on_spread_expression(item: &SpreadExpression<'ast>, node: &ExpressionNode<'ast>);
-    fn on_arrow_expression(item: &ArrowExpression<'ast>, node: &ExpressionNode<'ast>);
-    fn on_object_expression(item: &ObjectExpression<'ast>, node: &ExpressionNode<'ast>);
-    fn on_function_expression(item: &FunctionExpression<'ast>, node: &ExpressionNode<'ast>);
-    fn on_class_expression(item: &ClassExpression<'ast>, node: &ExpressionNode<'ast>);
-
-    // statements
-    fn on_expression_statement(item: &ExpressionNode<'ast>, node: &StatementNode<'ast>);
-    fn on_declaration_statement(item: &DeclarationStatement, node: &StatementNode<'ast>);
-    fn on_return_statement(item: &ReturnStatement, node: &StatementNode<'ast>);
-    fn on_break_statement(item: &BreakStatement, node: &StatementNode<'ast>);
-    fn on_continue_statement(item: &ContinueStatement, node: &StatementNode<'ast>);
-    fn on_throw_statement(item: &ThrowStatement, node: &StatementNode<'ast>);
-    fn on_if_statement(item: &IfStatement, node: &StatementNode<'ast>);
-    fn on_while_statement(item: &WhileStatement, node: &StatementNode<'ast>);
-    fn on_do_statement(item: &DoStatement, node: &StatementNode<'ast>);
-    fn on_for_statement(item: &ForStatement, node: &StatementNode<'ast>);
-    fn on_for_in_statement(item: &ForInStatement, node: &StatementNode<'ast>);
-    fn on_for_of_statement(item: &ForOfStatement, node: &StatementNode<'ast>);
-    fn on_try_statement(item: &TryStatement, node: &StatementNode<'ast>);
-    fn on_block_statement(item: &BlockStatement<'ast>, node: &StatementNode<'ast>);
-    fn on_labeled_statement(item: &LabeledStatement, node: &StatementNode<'ast>);
-    fn on_switch_statement(item: &SwitchStatement, node: &StatementNode<'ast>);
-    fn on_function_statement(item: &FunctionStatement<'ast>, node: &StatementNode<'ast>);
-    fn on_class_statement(item: &ClassStatement<'ast>, node: &StatementNode<'ast>);
+    // Expressions, entered before and left after their children. Hooks of
+    // nodes return a `Control`, which can skip the children or stop there.
+    fn on_this_expression(node: &ExpressionNode<'ast>) -> Control;
+    fn on_identifier_expression(item: &Identifier<'ast>, node: &ExpressionNode<'ast>) -> Control;
+    fn on_literal_expression(item: &Literal<'ast>, node: &ExpressionNode<'ast>) -> Control;
+    fn on_sequence_expression(item: &SequenceExpression<'ast>, node: &ExpressionNode<'ast>) -> Control;
+    fn on_array_expression(item: &ArrayExpression<'ast>, node: &ExpressionNode<'ast>) -> Control;
+    fn on_member_expression(item: &MemberExpression<'ast>, node: &ExpressionNode<'ast>) -> Control;
+    fn on_computed_member_expression(item: &ComputedMemberExpression<'ast>, node: &ExpressionNode<'ast>) -> Control;
+    fn on_call_expression(item: &CallExpression<'ast>, node: &ExpressionNode<'ast>) -> Control;
+    fn on_binary_expression(item: &BinaryExpression<'ast>, node: &ExpressionNode<'ast>) -> Control;
+    fn on_prefix_expression(item: &PrefixExpression<'ast>, node: &ExpressionNode<'ast>) -> Control;
+    fn on_postfix_expression(item: &PostfixExpression<'ast>, node: &ExpressionNode<'ast>) -> Control;
+    fn on_conditional_expression(item: &ConditionalExpression<'ast>, node: &ExpressionNode<'ast>) -> Control;
+    fn on_template_literal(item: &TemplateLiteral<'ast>, node: &ExpressionNode<'ast>) -> Control;
+    fn on_tagged_template_expression(item: &TaggedTemplateExpression<'ast>, node: &ExpressionNode<'ast>) -> Control;
+    fn on_spread_expression(item: &SpreadExpression<'ast>, node: &ExpressionNode<'ast>) -> Control;
+    fn on_arrow_expression(item: &ArrowExpression<'ast>, node: &ExpressionNode<'ast>) -> Control;
+    fn on_object_expression(item: &ObjectExpression<'ast>, node: &ExpressionNode<'ast>) -> Control;
+    fn on_function_expression(item: &FunctionExpression<'ast>, node: &ExpressionNode<'ast>) -> Control;
+    fn on_class_expression(item: &ClassExpression<'ast>, node: &ExpressionNode<'ast>) -> Control;
+    fn on_type_cast_expression(item: &TypeCastExpression<'ast>, node: &ExpressionNode<'ast>) -> Control;
+
+    fn on_leave_this_expression(node: &ExpressionNode<'ast>) -> Control;
+    fn on_leave_identifier_expression(item: &Identifier<'ast>, node: &ExpressionNode<'ast>) -> Control;
+    fn on_leave_literal_expression(item: &Literal<'ast>, node: &ExpressionNode<'ast>) -> Control;
+    fn on_leave_sequence_expression(item: &SequenceExpression<'ast>, node: &ExpressionNode<'ast>) -> Control;
+    fn on_leave_array_expression(item: &ArrayExpression<'ast>, node: &ExpressionNode<'ast>) -> Control;
+    fn on_leave_member_expression(item: &MemberExpression<'ast>, node: &ExpressionNode<'ast>) -> Control;
+    fn on_leave_computed_member_expression(item: &ComputedMemberExpression<'ast>, node: &ExpressionNode<'ast>) -> Control;
+    fn on_leave_call_expression(item: &CallExpression<'ast>, node: &ExpressionNode<'ast>) -> Control;
+    fn on_leave_binary_expression(item: &BinaryExpression<'ast>, node: &ExpressionNode<'ast>) -> Control;
+    fn on_leave_prefix_expression(item: &PrefixExpression<'ast>, node: &ExpressionNode<'ast>) -> Control;
+    fn on_leave_postfix_expression(item: &PostfixExpression<'ast>, node: &ExpressionNode<'ast>) -> Control;
+    fn on_leave_conditional_expression(item: &ConditionalExpression<'ast>, node: &ExpressionNode<'ast>) -> Control;
+    fn on_leave_template_literal(item: &TemplateLiteral<'ast>, node: &ExpressionNode<'ast>) -> Control;
+    fn on_leave_tagged_template_expression(item: &TaggedTemplateExpression<'ast>, node: &ExpressionNode<'ast>) -> Control;
+    fn on_leave_spread_expression(item: &SpreadExpression<'ast>, node: &ExpressionNode<'ast>) -> Control;
+    fn on_leave_arrow_expression(item: &ArrowExpression<'ast>, node: &ExpressionNode<'ast>) -> Control;
+    fn on_leave_object_expression(item: &ObjectExpression<'ast>, node: &ExpressionNode<'ast>) -> Control;
+    fn on_leave_function_expression(item: &FunctionExpression<'ast>, node: &ExpressionNode<'ast>) -> Control;
+    fn on_leave_class_expression(item: &ClassExpression<'ast>, node: &ExpressionNode<'ast>) -> Control;
+    fn on_leave_type_cast_expression(item: &TypeCastExpression<'ast>, node: &ExpressionNode<'ast>) -> Control;
+
+    // Statements, entered before and left after their children
+    fn on_expression_statement(item: &ExpressionNode<'ast>, node: &StatementNode<'ast>) -> Control;
+    fn on_declaration_statement(item: &DeclarationStatement<'ast>, node: &StatementNode<'ast>) -> Control;
+    fn on_return_statement(item: &ReturnStatement<'ast>, node: &StatementNode<'ast>) -> Control;
+    fn on_break_statement(item: &BreakStatement<'ast>, node: &StatementNode<'ast>) -> Control;
+    fn on_continue_statement(item: &ContinueStatement<'ast>, node: &StatementNode<'ast>) -> Control;
+    fn on_throw_statement(item: &ThrowStatement<'ast>, node: &StatementNode<'ast>) -> Control;
+    fn on_if_statement(item: &IfStatement<'ast>, node: &StatementNode<'ast>) -> Control;
+    fn on_while_statement(item: &WhileStatement<'ast>, node: &StatementNode<'ast>) -> Control;
+    fn on_do_statement(item: &DoStatement<'ast>, node: &StatementNode<'ast>) -> Control;
+    fn on_with_statement(item: &WithStatement<'ast>, node: &StatementNode<'ast>) -> Control;
+    fn on_for_statement(item: &ForStatement<'ast>, node: &StatementNode<'ast>) -> Control;
+    fn on_for_in_statement(item: &ForInStatement<'ast>, node: &StatementNode<'ast>) -> Control;
+    fn on_for_of_statement(item: &ForOfStatement<'ast>, node: &StatementNode<'ast>) -> Control;
+    fn on_try_statement(item: &TryStatement<'ast>, node: &StatementNode<'ast>) -> Control;
+    fn on_block_statement(item: &BlockStatement<'ast>, node: &StatementNode<'ast>) -> Control;
+    fn on_labeled_statement(item: &LabeledStatement<'ast>, node: &StatementNode<'ast>) -> Control;
+    fn on_switch_statement(item: &SwitchStatement<'ast>, node: &StatementNode<'ast>) -> Control;
+    fn on_function_statement(item: &FunctionStatement<'ast>, node: &StatementNode<'ast>) -> Control;
+    fn on_class_statement(item: &ClassStatement<'ast>, node: &StatementNode<'ast>) -> Control;
+    fn on_type_alias_statement(item: &TypeAliasStatement<'ast>, node: &StatementNode<'ast>) -> Control;
+    fn on_declare_statement(item: &DeclareStatement<'ast>, node: &StatementNode<'ast>) -> Control;
+    fn on_import_statement(item: &ImportStatement<'ast>, node: &StatementNode<'ast>) -> Control;
+    fn on_export_statement(item: &ExportStatement<'ast>, node: &StatementNode<'ast>) -> Control;
+
+    fn on_leave_expression_statement(item: &ExpressionNode<'ast>, node: &StatementNode<'ast>) -> Control;
+    fn on_leave_declaration_statement(item: &DeclarationStatement<'ast>, node: &StatementNode<'ast>) -> Control;
+    fn on_leave_return_statement(item: &ReturnStatement<'ast>, node: &StatementNode<'ast>) -> Control;
+    fn on_leave_break_statement(item: &BreakStatement<'ast>, node: &StatementNode<'ast>) -> Control;
+    fn on_leave_continue_statement(item: &ContinueStatement<'ast>, node: &StatementNode<'ast>) -> Control;
+    fn on_leave_throw_statement(item: &ThrowStatement<'ast>, node: &StatementNode<'ast>) -> Control;
+    fn on_leave_if_statement(item: &IfStatement<'ast>, node: &StatementNode<'ast>) -> Control;
+    fn on_leave_while_statement(item: &WhileStatement<'ast>, node: &StatementNode<'ast>) -> Control;
+    fn on_leave_do_statement(item: &DoStatement<'ast>, node: &StatementNode<'ast>) -> Control;
+    fn on_leave_with_statement(item: &WithStatement<'ast>, node: &StatementNode<'ast>) -> Control;
+    fn on_leave_for_statement(item: &ForStatement<'ast>, node: &StatementNode<'ast>) -> Control;
+    fn on_leave_for_in_statement(item: &ForInStatement<'ast>, node: &StatementNode<'ast>) -> Control;
+    fn on_leave_for_of_statement(item: &ForOfStatement<'ast>, node: &StatementNode<'ast>) -> Control;
+    fn on_leave_try_statement(item: &TryStatement<'ast>, node: &StatementNode<'ast>) -> Control;
+    fn on_leave_block_statement(item: &BlockStatement<'ast>, node: &StatementNode<'ast>) -> Control;
+    fn on_leave_labeled_statement(item: &LabeledStatement<'ast>, node: &StatementNode<'ast>) -> Control;
+    fn on_leave_switch_statement(item: &SwitchStatement<'ast>, node: &StatementNode<'ast>) -> Control;
+    fn on_leave_function_statement(item: &FunctionStatement<'ast>, node: &StatementNode<'ast>) -> Control;
+    fn on_leave_class_statement(item: &ClassStatement<'ast>, node: &StatementNode<'ast>) -> Control;
+    fn on_leave_type_alias_statement(item: &TypeAliasStatement<'ast>, node: &StatementNode<'ast>) -> Control;
+    fn on_leave_declare_statement(item: &DeclareStatement<'ast>, node: &StatementNode<'ast>) -> Control;
+    fn on_leave_import_statement(item: &ImportStatement<'ast>, node: &StatementNode<'ast>) -> Control;
+    fn on_leave_export_statement(item: &ExportStatement<'ast>, node: &StatementNode<'ast>) -> Control;
+
+    // Other nodes, entered before and left after their children. Keys of
+    // class members aren't nodes, they are part of the `ClassMember`.
+    fn on_property(item: &Property<'ast>, node: &PropertyNode<'ast>) -> Control;
+    fn on_property_key(item: &PropertyKey<'ast>, node: &Node<'ast, PropertyKey<'ast>>) -> Control;
+    fn on_class_member(item: &ClassMember<'ast>, node: &Node<'ast, ClassMember<'ast>>) -> Control;
+    fn on_pattern(item: &Pattern<'ast>, node: &Node<'ast, Pattern<'ast>>) -> Control;
+    fn on_param(item: &Pattern<'ast>, node: &Node<'ast, Pattern<'ast>>) -> Control;
+    fn on_declarator(item: &Declarator<'ast>, node: &Node<'ast, Declarator<'ast>>) -> Control;
+    fn on_switch_case(item: &SwitchCase<'ast>, node: &Node<'ast, SwitchCase<'ast>>) -> Control;
+    fn on_catch_clause(item: &CatchClause<'ast>, node: &Node<'ast, CatchClause<'ast>>) -> Control;
+    fn on_template_quasi(item: &&'ast str, node: &Node<'ast, &'ast str>) -> Control;
+
+    fn on_leave_property(item: &Property<'ast>, node: &PropertyNode<'ast>) -> Control;
+    fn on_leave_property_key(item: &PropertyKey<'ast>, node: &Node<'ast, PropertyKey<'ast>>) -> Control;
+    fn on_leave_class_member(item: &ClassMember<'ast>, node: &Node<'ast, ClassMember<'ast>>) -> Control;
+    fn on_leave_pattern(item: &Pattern<'ast>, node: &Node<'ast, Pattern<'ast>>) -> Control;
+    fn on_leave_param(item: &Pattern<'ast>, node: &Node<'ast, Pattern<'ast>>) -> Control;
+    fn on_leave_declarator(item: &Declarator<'ast>, node: &Node<'ast, Declarator<'ast>>) -> Control;
+    fn on_leave_switch_case(item: &SwitchCase<'ast>, node: &Node<'ast, SwitchCase<'ast>>) -> Control;
+    fn on_leave_catch_clause(item: &CatchClause<'ast>, node: &Node<'ast, CatchClause<'ast>>) -> Control;
+    fn on_leave_template_quasi(item: &&'ast str, node: &Node<'ast, &'ast str>) -> Control;
 }
 
 pub trait Visitable<'ast>: 'ast {
     type Parent;
 
-    fn traverse<V>(&self, visitor: &V, ctx: &mut V::Context) where V: Visitor<'ast>;
+    /// Traverse the tree, calling the hooks of the visitor.
+    #[inline]
+    fn traverse<V>(&self, visitor: &V, ctx: &mut V::Context)
+    where
+        V: Visitor<'ast>,
+    {
+        self.visit(visitor, &mut NodePath::new(), ctx);
+    }
+
+    /// Traverse the tree below the ancestors on the `path`.
+    fn visit<V>(&self, visitor: &V, path: &mut NodePath<'ast>, ctx: &mut V::Context) where V: Visitor<'ast>;
+
+    /// Visit a `Node` holding `Self`, types with hooks taking the node
+    /// call them here. The item is read again after the enter hook, so that
+    /// a node swapped by it is the one traversed.
+    #[inline]
+    fn visit_node<V>(node: &Node<'ast, Self>, visitor: &V, path: &mut NodePath<'ast>, ctx: &mut V::Context)
+    where
+        V: Visitor<'ast>,
+        Self: Sized,
+    {
+        node.item.visit(visitor, path, ctx);
+    }
+}
+
+/// Visit the params of a function or an arrow function.
+#[inline]
+fn visit_params<'ast, V>(params: &PatternList<'ast>, visitor: &V, path: &mut NodePath<'ast>, ctx: &mut V::Context)
+where
+    V: Visitor<'ast>,
+{
+    for param in params.iter() {
+        if path.is_stopped() {
+            return;
+        }
+
+        match visitor.on_param(&param.item, param, path, ctx) {
+            Control::Continue => param.visit(visitor, path, ctx),
+            Control::Skip     => {},
+            Control::Stop     => return path.stop(),
+        }
+
+        if !path.is_stopped() && visitor.on_leave_param(&param.item, param, path, ctx) == Control::Stop {
+            path.stop();
+        }
+    }
 }
 
 impl<'ast> Visitable<'ast> for Module<'ast> {
     type Parent = NoParent;
 
     #[inline]
-    fn traverse<V>(&self, visitor: &V, ctx: &mut V::Context)
+    fn visit<V>(&self, visitor: &V, path: &mut NodePath<'ast>, ctx: &mut V::Context)
     where
         V: Visitor<'ast>,
     {
         let body = self.body();
-        body.traverse(visitor, ctx);
+        body.visit(visitor, path, ctx);
+
+        // Hooks can rebuild the list, which the module only holds a copy of,
+        // the read has to be volatile just like the write of the new list
+        self.set_body(unsafe { ptr::read_volatile(&body) });
     }
 }
 
 impl<'ast> Visitable<'ast> for Pattern<'ast> {
     type Parent = Node<'ast, Self>;
 
+    node_hooks!(on_pattern, on_leave_pattern, Pattern);
+
     #[inline]
-    fn traverse<V>(&self, visitor: &V, ctx: &mut V::Context)
+    fn visit<V>(&self, visitor: &V, path: &mut NodePath<'ast>, ctx: &mut V::Context)
     where
         V: Visitor<'ast>,
     {
         match *self {
             Pattern::Void => {},
-            Pattern::Identifier(ref ident) => visitor.on_reference_declaration(ident, ctx),
+            Pattern::Identifier(ref ident) => visitor.on_reference_declaration(ident, path, ctx),
             Pattern::ObjectPattern {
                 ref properties,
             } => {
-                properties.traverse(visitor, ctx);
+                properties.visit(visitor, path, ctx);
             },
             Pattern::ArrayPattern {
                 ref elements,
             } => {
-                elements.traverse(visitor, ctx);
+                elements.visit(visitor, path, ctx);
             },
             Pattern::RestElement {
                 ref argument,
             } => {
-                argument.traverse(visitor, ctx);
+                argument.visit(visitor, path, ctx);
             },
             Pattern::AssignmentPattern {
                 ref left,
                 ref right,
             } => {
-                left.traverse(visitor, ctx);
-                right.traverse(visitor, ctx);
+                left.visit(visitor, path, ctx);
+                right.visit(visitor, path, ctx);
+            },
+            Pattern::Typed {
+                ref pattern,
+                ..
+            } => {
+                pattern.visit(visitor, path, ctx);
             }
         }
     }
@@ -142,13 +293,15 @@ impl<'ast> Visitable<'ast> for Pattern<'ast> {
 impl<'ast> Visitable<'ast> for PropertyKey<'ast> {
     type Parent = Node<'ast, Self>;
 
+    node_hooks!(on_property_key, on_leave_property_key, PropertyKey);
+
     #[inline]
-    fn traverse<V>(&self, visitor: &V, ctx: &mut V::Context)
+    fn visit<V>(&self, visitor: &V, path: &mut NodePath<'ast>, ctx: &mut V::Context)
     where
         V: Visitor<'ast>,
     {
         match *self {
-            PropertyKey::Computed(ref expression) => expression.traverse(visitor, ctx),
+            PropertyKey::Computed(ref expression) => expression.visit(visitor, path, ctx),
             PropertyKey::Literal(_) | PropertyKey::Binary(_) => {},
         }
     }
@@ -157,26 +310,28 @@ impl<'ast> Visitable<'ast> for PropertyKey<'ast> {
 impl<'ast> Visitable<'ast> for Property<'ast> {
     type Parent = Node<'ast, Self>;
 
+    node_hooks!(on_property, on_leave_property, Property);
+
     #[inline]
-    fn traverse<V>(&self, visitor: &V, ctx: &mut V::Context)
+    fn visit<V>(&self, visitor: &V, path: &mut NodePath<'ast>, ctx: &mut V::Context)
     where
         V: Visitor<'ast>,
     {
         match *self {
-            Property::Shorthand(ref ident) => visitor.on_reference_use(ident, ctx),
+            Property::Shorthand(ref ident) => visitor.on_reference_use(ident, path, ctx),
             Property::Literal {
                 ref key,
                 ref value,
             } => {
-                key.traverse(visitor, ctx);
-                value.traverse(visitor, ctx);
+                key.visit(visitor, path, ctx);
+                value.visit(visitor, path, ctx);
             },
             Property::Method {
                 ref key,
                 ref value,
             } => {
-                key.traverse(visitor, ctx);
-                value.traverse(visitor, ctx);
+                key.visit(visitor, path, ctx);
+                value.visit(visitor, path, ctx);
             }
         }
     }
@@ -188,12 +343,12 @@ impl<'ast, T> Visitable<'ast> for Option<T> where
     type Parent = T::Parent;
 
     #[inline]
-    fn traverse<V>(&self, visitor: &V, ctx: &mut V::Context)
+    fn visit<V>(&self, visitor: &V, path: &mut NodePath<'ast>, ctx: &mut V::Context)
     where
         V: Visitor<'ast>,
     {
         if let Some(ref visitable) = *self {
-            visitable.traverse(visitor, ctx);
+            visitable.visit(visitor, path, ctx);
         }
     }
 }
@@ -206,11 +361,11 @@ impl<'ast, T> Visitable<'ast> for Node<'ast, T> where
     type Parent = NoParent;
 
     #[inline]
-    fn traverse<V>(&self, visitor: &V, ctx: &mut V::Context)
+    fn visit<V>(&self, visitor: &V, path: &mut NodePath<'ast>, ctx: &mut V::Context)
     where
         V: Visitor<'ast>,
     {
-        self.item.traverse(visitor, ctx);
+        T::visit_node(self, visitor, path, ctx);
     }
 }
 
@@ -220,12 +375,12 @@ impl<'ast, T> Visitable<'ast> for NodeList<'ast, T> where
     type Parent = NoParent;
 
     #[inline]
-    fn traverse<V>(&self, visitor: &V, ctx: &mut V::Context)
+    fn visit<V>(&self, visitor: &V, path: &mut NodePath<'ast>, ctx: &mut V::Context)
     where
         V: Visitor<'ast>,
     {
         for item in self {
-            item.traverse(visitor, ctx);
+            item.visit(visitor, path, ctx);
         }
     }
 }
@@ -234,12 +389,12 @@ impl<'ast> Visitable<'ast> for ExpressionList<'ast> {
     type Parent = NoParent;
 
     #[inline]
-    fn traverse<V>(&self, visitor: &V, ctx: &mut V::Context)
+    fn visit<V>(&self, visitor: &V, path: &mut NodePath<'ast>, ctx: &mut V::Context)
     where
         V: Visitor<'ast>,
     {
         for node in self.iter() {
-            node.traverse(visitor, ctx);
+            node.visit(visitor, path, ctx);
         }
     }
 }
@@ -248,13 +403,17 @@ impl<'ast> Visitable<'ast> for StatementList<'ast> {
     type Parent = NoParent;
 
     #[inline]
-    fn traverse<V>(&self, visitor: &V, ctx: &mut V::Context)
+    fn visit<V>(&self, visitor: &V, path: &mut NodePath<'ast>, ctx: &mut V::Context)
     where
         V: Visitor<'ast>,
     {
-        visitor.on_statement_list(*self, ctx);
+        visitor.on_statement_list(self, path, ctx);
         for node in self.iter() {
-            node.traverse(visitor, ctx);
+            node.visit(visitor, path, ctx);
+        }
+
+        if !path.is_stopped() {
+            visitor.on_leave_statement_list(self, path, ctx);
         }
     }
 }
@@ -262,7 +421,9 @@ impl<'ast> Visitable<'ast> for StatementList<'ast> {
 #[cfg(test)]
 mod test {
     use super::*;
+    use std::ptr;
     use ratel::parse;
+    use ratel::ast::Expression;
     use ScopeKind::*;
 
     struct TestContext<'ast> {
@@ -290,21 +451,21 @@ mod test {
     impl<'ast> StaticVisitor<'ast> for ScopeTest {
         type Context = TestContext<'ast>;
 
-        fn on_enter_scope(kind: ScopeKind, ctx: &mut TestContext<'ast>) {
+        fn on_enter_scope(kind: ScopeKind, _: &NodePath<'ast>, ctx: &mut TestContext<'ast>) {
             ctx.scopes.push(kind);
             ctx.depth += 1;
             ctx.max_depth = ctx.max_depth.max(ctx.depth);
         }
 
-        fn on_leave_scope(ctx: &mut TestContext<'ast>) {
+        fn on_leave_scope(_: &NodePath<'ast>, ctx: &mut TestContext<'ast>) {
             ctx.depth -= 1;
         }
 
-        fn on_reference_use(ident: &Identifier<'ast>, ctx: &mut TestContext<'ast>) {
+        fn on_reference_use(ident: &Identifier<'ast>, _: &NodePath<'ast>, ctx: &mut TestContext<'ast>) {
             ctx.used_vars.push((*ident, ctx.depth));
         }
 
-        fn on_reference_declaration(ident: &Identifier<'ast>, ctx: &mut TestContext<'ast>) {
+        fn on_reference_declaration(ident: &Identifier<'ast>, _: &NodePath<'ast>, ctx: &mut TestContext<'ast>) {
             ctx.declared_vars.push((*ident, ctx.depth));
         }
 
@@ -477,4 +638,308 @@ mod test {
         assert_eq!(ctx.used_vars, &[]);
         assert_eq!(ctx.declared_vars, &[("doge", 0), ("to", 1), ("the", 1), ("moon", 1)]);
     }
+
+    #[test]
+    fn arrows_are_scopes() {
+        let module = parse("(a) => b; () => { c; };").unwrap();
+        let mut ctx = TestContext::new();
+
+        module.traverse(&ScopeTest, &mut ctx);
+
+        assert_eq!(ctx.scopes, &[Function, Function]);
+        assert_eq!(ctx.depth, 0);
+        assert_eq!(ctx.max_depth, 1);
+        assert_eq!(ctx.used_vars, &[("b", 1), ("c", 1)]);
+        assert_eq!(ctx.declared_vars, &[("a", 1)]);
+    }
+
+    #[test]
+    fn loops_and_catch_clauses_are_scopes() {
+        let module = parse("for (let a;;) {} for (b in c); for (const d of e) f; try {} catch (g) {}").unwrap();
+        let mut ctx = TestContext::new();
+
+        module.traverse(&ScopeTest, &mut ctx);
+
+        assert_eq!(ctx.scopes, &[Block, Block, Block, Block, Block, Block, Block]);
+        assert_eq!(ctx.depth, 0);
+        assert_eq!(ctx.max_depth, 2);
+        assert_eq!(ctx.used_vars, &[("b", 1), ("c", 1), ("e", 1), ("f", 1)]);
+        assert_eq!(ctx.declared_vars, &[("a", 1), ("d", 1), ("g", 1)]);
+    }
+
+    struct OrderTest;
+
+    impl<'ast> StaticVisitor<'ast> for OrderTest {
+        type Context = Vec<&'static str>;
+
+        fn on_identifier_expression(_: &Identifier<'ast>, _: &ExpressionNode<'ast>, _: &NodePath<'ast>, ctx: &mut Vec<&'static str>) -> Control {
+            ctx.push("enter identifier");
+
+            Control::Continue
+        }
+
+        fn on_leave_identifier_expression(_: &Identifier<'ast>, _: &ExpressionNode<'ast>, _: &NodePath<'ast>, ctx: &mut Vec<&'static str>) -> Control {
+            ctx.push("leave identifier");
+
+            Control::Continue
+        }
+
+        fn on_call_expression(_: &CallExpression<'ast>, _: &ExpressionNode<'ast>, _: &NodePath<'ast>, ctx: &mut Vec<&'static str>) -> Control {
+            ctx.push("enter call");
+
+            Control::Continue
+        }
+
+        fn on_leave_call_expression(_: &CallExpression<'ast>, _: &ExpressionNode<'ast>, _: &NodePath<'ast>, ctx: &mut Vec<&'static str>) -> Control {
+            ctx.push("leave call");
+
+            Control::Continue
+        }
+
+        fn on_expression_statement(_: &ExpressionNode<'ast>, _: &StatementNode<'ast>, _: &NodePath<'ast>, ctx: &mut Vec<&'static str>) -> Control {
+            ctx.push("enter statement");
+
+            Control::Continue
+        }
+
+        fn on_leave_expression_statement(_: &ExpressionNode<'ast>, _: &StatementNode<'ast>, _: &NodePath<'ast>, ctx: &mut Vec<&'static str>) -> Control {
+            ctx.push("leave statement");
+
+            Control::Continue
+        }
+
+        fn register(_dv: &mut DynamicVisitor<'ast, Vec<&'static str>>) {
+            unimplemented!()
+        }
+    }
+
+    #[test]
+    fn leave_hooks_are_called_after_children() {
+        let module = parse("foo(bar);").unwrap();
+        let mut ctx = Vec::new();
+
+        module.traverse(&OrderTest, &mut ctx);
+
+        assert_eq!(ctx, &[
+            "enter statement",
+            "enter call",
+            "enter identifier",
+            "leave identifier",
+            "enter identifier",
+            "leave identifier",
+            "leave call",
+            "leave statement",
+        ]);
+    }
+
+    struct NodeTest;
+
+    impl<'ast> StaticVisitor<'ast> for NodeTest {
+        type Context = Vec<String>;
+
+        fn on_property(item: &Property<'ast>, _: &PropertyNode<'ast>, _: &NodePath<'ast>, ctx: &mut Vec<String>) -> Control {
+            if let Property::Shorthand(name) = *item {
+                ctx.push(format!("shorthand {}", name));
+            }
+
+            Control::Continue
+        }
+
+        fn on_property_key(item: &PropertyKey<'ast>, _: &Node<'ast, PropertyKey<'ast>>, _: &NodePath<'ast>, ctx: &mut Vec<String>) -> Control {
+            if let PropertyKey::Literal(key) = *item {
+                ctx.push(format!("key {}", key));
+            }
+
+            Control::Continue
+        }
+
+        fn on_class_member(_: &ClassMember<'ast>, _: &Node<'ast, ClassMember<'ast>>, _: &NodePath<'ast>, ctx: &mut Vec<String>) -> Control {
+            ctx.push("class member".into());
+
+            Control::Continue
+        }
+
+        fn on_param(item: &Pattern<'ast>, _: &Node<'ast, Pattern<'ast>>, _: &NodePath<'ast>, ctx: &mut Vec<String>) -> Control {
+            if let Pattern::Identifier(name) = *item {
+                ctx.push(format!("param {}", name));
+            }
+
+            Control::Continue
+        }
+
+        fn on_leave_pattern(item: &Pattern<'ast>, _: &Node<'ast, Pattern<'ast>>, _: &NodePath<'ast>, ctx: &mut Vec<String>) -> Control {
+            if let Pattern::Identifier(name) = *item {
+                ctx.push(format!("pattern {}", name));
+            }
+
+            Control::Continue
+        }
+
+        fn on_declarator(_: &Declarator<'ast>, _: &Node<'ast, Declarator<'ast>>, _: &NodePath<'ast>, ctx: &mut Vec<String>) -> Control {
+            ctx.push("declarator".into());
+
+            Control::Continue
+        }
+
+        fn on_switch_case(_: &SwitchCase<'ast>, _: &Node<'ast, SwitchCase<'ast>>, _: &NodePath<'ast>, ctx: &mut Vec<String>) -> Control {
+            ctx.push("switch case".into());
+
+            Control::Continue
+        }
+
+        fn on_catch_clause(_: &CatchClause<'ast>, _: &Node<'ast, CatchClause<'ast>>, _: &NodePath<'ast>, ctx: &mut Vec<String>) -> Control {
+            ctx.push("catch clause".into());
+
+            Control::Continue
+        }
+
+        fn on_template_quasi(item: &&'ast str, _: &Node<'ast, &'ast str>, _: &NodePath<'ast>, ctx: &mut Vec<String>) -> Control {
+            ctx.push(format!("quasi {}", item));
+
+            Control::Continue
+        }
+
+        fn on_identifier_expression(item: &Identifier<'ast>, _: &ExpressionNode<'ast>, _: &NodePath<'ast>, ctx: &mut Vec<String>) -> Control {
+            ctx.push(format!("identifier {}", item));
+
+            Control::Continue
+        }
+
+        fn register(_dv: &mut DynamicVisitor<'ast, Vec<String>>) {
+            unimplemented!()
+        }
+    }
+
+    #[test]
+    fn hooks_for_other_nodes() {
+        let module = parse(r"
+            var { a, b: c } = d;
+            function f(g, h = 1) {}
+            class I { j() {} }
+            switch (k) { case 1: }
+            try {} catch (l) {}
+            `m${n}o`;
+        ").unwrap();
+
+        let mut ctx = Vec::new();
+
+        module.traverse(&NodeTest, &mut ctx);
+
+        assert_eq!(ctx, &[
+            "declarator",
+            "shorthand a",
+            // Values of object patterns are parsed as expressions
+            "key b",
+            "identifier c",
+            "identifier d",
+            "param g",
+            "pattern g",
+            "pattern h",
+            "class member",
+            "key j",
+            "identifier k",
+            "switch case",
+            "catch clause",
+            "pattern l",
+            "quasi m",
+            "identifier n",
+            "quasi o",
+        ]);
+    }
+
+    struct PathTest;
+
+    impl<'ast> StaticVisitor<'ast> for PathTest {
+        type Context = Vec<String>;
+
+        fn on_identifier_expression(item: &Identifier<'ast>, node: &ExpressionNode<'ast>, path: &NodePath<'ast>, ctx: &mut Vec<String>) -> Control {
+            let role = match path.parent() {
+                Some(&Ancestor::Expression(parent)) => match parent.item {
+                    Expression::Call(ref call) if ptr::eq(&*call.callee, &**node) => "callee",
+                    Expression::Call(_) => "argument",
+                    _ => "other",
+                },
+                Some(&Ancestor::Statement(_)) => "statement",
+                _ => "other",
+            };
+
+            ctx.push(format!("{} {} {}", role, item, path.depth()));
+
+            Control::Continue
+        }
+
+        fn register(_dv: &mut DynamicVisitor<'ast, Vec<String>>) {
+            unimplemented!()
+        }
+    }
+
+    #[test]
+    fn path_holds_the_ancestors() {
+        let module = parse("foo(bar(baz)); qux;").unwrap();
+        let mut ctx = Vec::new();
+
+        module.traverse(&PathTest, &mut ctx);
+
+        assert_eq!(ctx, &[
+            "callee foo 2",
+            "callee bar 3",
+            "argument baz 3",
+            "statement qux 1",
+        ]);
+    }
+
+    struct ControlTest;
+
+    impl<'ast> StaticVisitor<'ast> for ControlTest {
+        type Context = Vec<String>;
+
+        fn on_call_expression(item: &CallExpression<'ast>, _: &ExpressionNode<'ast>, _: &NodePath<'ast>, _: &mut Vec<String>) -> Control {
+            match item.callee.item {
+                Expression::Identifier("skip") => Control::Skip,
+                _                              => Control::Continue,
+            }
+        }
+
+        fn on_leave_call_expression(_: &CallExpression<'ast>, _: &ExpressionNode<'ast>, _: &NodePath<'ast>, ctx: &mut Vec<String>) -> Control {
+            ctx.push("leave call".into());
+
+            Control::Continue
+        }
+
+        fn on_identifier_expression(item: &Identifier<'ast>, _: &ExpressionNode<'ast>, _: &NodePath<'ast>, ctx: &mut Vec<String>) -> Control {
+            ctx.push(item.to_string());
+
+            match *item {
+                "stop" => Control::Stop,
+                _      => Control::Continue,
+            }
+        }
+
+        fn on_leave_expression_statement(_: &ExpressionNode<'ast>, _: &StatementNode<'ast>, _: &NodePath<'ast>, ctx: &mut Vec<String>) -> Control {
+            ctx.push("leave statement".into());
+
+            Control::Continue
+        }
+
+        fn register(_dv: &mut DynamicVisitor<'ast, Vec<String>>) {
+            unimplemented!()
+        }
+    }
+
+    #[test]
+    fn skip_and_stop_traversal() {
+        let module = parse("skip(a); b; stop; c;").unwrap();
+        let mut ctx = Vec::new();
+
+        module.traverse(&ControlTest, &mut ctx);
+
+        assert_eq!(ctx, &[
+            // Children of a skipped node aren't visited, but it is left
+            "leave call",
+            "leave statement",
+            "b",
+            "leave statement",
+            "stop",
+        ]);
+    }
 }
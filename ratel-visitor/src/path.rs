@@ -0,0 +1,147 @@
+use std::iter::Rev;
+use std::slice::Iter;
+
+use ratel::ast::{Node, Pattern, ClassMember, ExpressionNode, StatementNode, PropertyNode};
+use ratel::ast::expression::PropertyKey;
+use ratel::ast::statement::{Declarator, SwitchCase, CatchClause};
+
+/// What the traversal does after a hook returns.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Control {
+    /// Keep going.
+    Continue,
+
+    /// Don't traverse the children of the node. Its leave hook is still
+    /// called. Returned from a leave hook, same as `Continue`.
+    Skip,
+
+    /// Stop the traversal, no other node hooks are called.
+    Stop,
+}
+
+impl Default for Control {
+    #[inline]
+    fn default() -> Self {
+        Control::Continue
+    }
+}
+
+/// Result of a hook, combining the results of multiple visitors.
+pub trait Outcome: Default {
+    /// Call the `next` hook unless this result stops the traversal.
+    fn and_then<F: FnOnce() -> Self>(self, next: F) -> Self;
+}
+
+impl Outcome for () {
+    #[inline]
+    fn and_then<F: FnOnce()>(self, next: F) {
+        next()
+    }
+}
+
+impl Outcome for Control {
+    #[inline]
+    fn and_then<F: FnOnce() -> Control>(self, next: F) -> Control {
+        match self {
+            Control::Stop     => Control::Stop,
+            Control::Continue => next(),
+            Control::Skip     => match next() {
+                Control::Stop => Control::Stop,
+                _             => Control::Skip,
+            },
+        }
+    }
+}
+
+/// Node that is an ancestor of the node being visited.
+#[derive(Debug, Clone, Copy)]
+pub enum Ancestor<'ast> {
+    Statement(StatementNode<'ast>),
+    Expression(ExpressionNode<'ast>),
+    Property(PropertyNode<'ast>),
+    PropertyKey(Node<'ast, PropertyKey<'ast>>),
+    ClassMember(Node<'ast, ClassMember<'ast>>),
+    Pattern(Node<'ast, Pattern<'ast>>),
+    Declarator(Node<'ast, Declarator<'ast>>),
+    SwitchCase(Node<'ast, SwitchCase<'ast>>),
+    CatchClause(Node<'ast, CatchClause<'ast>>),
+}
+
+impl<'ast> Ancestor<'ast> {
+    /// Offset of the start of the node in the source.
+    #[inline]
+    pub fn start(&self) -> u32 {
+        with_node!(*self, node => node.start)
+    }
+
+    /// Offset of the end of the node in the source.
+    #[inline]
+    pub fn end(&self) -> u32 {
+        with_node!(*self, node => node.end)
+    }
+
+    /// Whether both are the same node, rather than equal ones.
+    #[inline]
+    pub fn is(&self, other: &Ancestor<'ast>) -> bool {
+        let address = |ancestor: &Ancestor<'ast>| with_node!(*ancestor, node => &**node as *const _ as *const ());
+
+        address(self) == address(other)
+    }
+}
+
+/// Ancestors of the node being visited, from the root of the traversal
+/// down to its parent, kept up to date by `Visitable::traverse`.
+///
+/// Ancestors are copies of the nodes, so a node swapped by a hook is still
+/// seen with its previous item by the hooks of its children.
+#[derive(Debug, Default)]
+pub struct NodePath<'ast> {
+    ancestors: Vec<Ancestor<'ast>>,
+    stopped: bool,
+}
+
+impl<'ast> NodePath<'ast> {
+    #[inline]
+    pub fn new() -> Self {
+        NodePath::default()
+    }
+
+    /// Parent of the node being visited, `None` at the root.
+    #[inline]
+    pub fn parent(&self) -> Option<&Ancestor<'ast>> {
+        self.ancestors.last()
+    }
+
+    /// Iterate over the ancestors, starting with the parent.
+    #[inline]
+    pub fn ancestors(&self) -> Rev<Iter<'_, Ancestor<'ast>>> {
+        self.ancestors.iter().rev()
+    }
+
+    /// Number of ancestors.
+    #[inline]
+    pub fn depth(&self) -> usize {
+        self.ancestors.len()
+    }
+
+    /// Whether a hook has stopped the traversal.
+    #[inline]
+    pub fn is_stopped(&self) -> bool {
+        self.stopped
+    }
+
+    #[inline]
+    pub(crate) fn push(&mut self, ancestor: Ancestor<'ast>) {
+        self.ancestors.push(ancestor);
+    }
+
+    #[inline]
+    pub(crate) fn pop(&mut self) {
+        self.ancestors.pop();
+    }
+
+    #[inline]
+    pub(crate) fn stop(&mut self) {
+        self.stopped = true;
+    }
+}
@@ -0,0 +1,539 @@
+use ratel::ast::{Node, NodeList, Statement, Pattern, ClassMember, Literal, Function, Class, Name};
+use ratel::ast::{EmptyName, OptionalName, MandatoryName, MethodKind, DeclarationKind, OperatorKind};
+use ratel::ast::{BlockNode, ExpressionNode, StatementNode, IdentifierNode};
+use ratel::ast::expression::*;
+use ratel::ast::statement::*;
+
+use Ancestor;
+use super::node_type::node_type;
+
+/// Attribute of a node, read from the AST in the shape the node is
+/// serialized with. Flow annotations can only be tested for presence.
+#[derive(Debug)]
+pub enum Attribute<'ast> {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(&'ast str),
+    List(Vec<Attribute<'ast>>),
+    Node(Ancestor<'ast>),
+    /// Identifier that is not a node of its own, such as a label.
+    Identifier(&'ast str),
+    Literal(Node<'ast, Literal<'ast>>),
+    RegEx(&'ast str),
+    Template(Node<'ast, TemplateLiteral<'ast>>),
+    TemplateElement(IdentifierNode<'ast>, bool),
+    TemplateValue(&'ast str),
+    Method(Node<'ast, Function<'ast, EmptyName>>),
+    Block(BlockNode<'ast, Statement<'ast>>),
+    ClassBody(BlockNode<'ast, ClassMember<'ast>>),
+    Declaration(Node<'ast, ForInit<'ast>>, DeclarationStatement<'ast>),
+    ImportSpecifier(ImportSpecifier<'ast>),
+    ExportSpecifier(ExportSpecifier<'ast>),
+    Annotation,
+}
+
+impl<'ast> Attribute<'ast> {
+    #[inline]
+    pub fn is_null(&self) -> bool {
+        matches!(*self, Attribute::Null)
+    }
+
+    /// Attribute under `key`, or `None` if there is no such attribute.
+    pub fn get(self, key: &str) -> Option<Attribute<'ast>> {
+        match key {
+            "type"  => return self.node_type().map(Attribute::String),
+            "start" => return self.location().map(|(start, _)| Attribute::Number(start as f64)),
+            "end"   => return self.location().map(|(_, end)| Attribute::Number(end as f64)),
+            _       => {},
+        }
+
+        Some(match (self, key) {
+            (Attribute::List(list), _)                     => return key.parse().ok().and_then(|index| list.into_iter().nth(index)),
+            (Attribute::Node(node), _)                     => return node_field(node, key),
+            (Attribute::Identifier(name), "name")          => Attribute::String(name),
+            (Attribute::Literal(literal), _)               => return literal_field(literal.item, key),
+            (Attribute::RegEx(regex), "pattern")           => Attribute::String(&regex[1..regex.rfind('/')?]),
+            (Attribute::RegEx(regex), "flags")             => Attribute::String(&regex[regex.rfind('/')? + 1..]),
+            (Attribute::Template(template), _)             => return template_field(&template.item, key),
+            (Attribute::TemplateElement(_, tail), "tail")  => Attribute::Bool(tail),
+            (Attribute::TemplateElement(quasi, _), "value") => Attribute::TemplateValue(quasi.item),
+            (Attribute::TemplateValue(value), "raw")       |
+            (Attribute::TemplateValue(value), "cooked")    => Attribute::String(value),
+            (Attribute::Method(method), _)                 => return function_field(&method.item, key),
+            (Attribute::Block(block), "body")              => list(block.body, statement),
+            (Attribute::ClassBody(body), "body")           => list(body.body, |member| Attribute::Node(Ancestor::ClassMember(member))),
+            (Attribute::Declaration(_, declaration), _)    => return declaration_field(&declaration, key),
+            (Attribute::ImportSpecifier(specifier), _)     => return import_specifier_field(specifier, key),
+            (Attribute::ExportSpecifier(specifier), "local")    => Attribute::Identifier(specifier.local),
+            (Attribute::ExportSpecifier(specifier), "exported") => Attribute::Identifier(specifier.exported),
+            _                                              => return None,
+        })
+    }
+
+    fn node_type(&self) -> Option<&'static str> {
+        Some(match *self {
+            Attribute::Node(ref node)              => return node_type(node),
+            Attribute::Identifier(_)               => "Identifier",
+            Attribute::Literal(_)                  => "Literal",
+            Attribute::Template(_)                 => "TemplateLiteral",
+            Attribute::TemplateElement(..)         => "TemplateElement",
+            Attribute::Method(_)                   => "FunctionExpression",
+            Attribute::Block(_)                    => "BlockStatement",
+            Attribute::ClassBody(_)                => "ClassBody",
+            Attribute::Declaration(..)             => "VariableDeclaration",
+            Attribute::ImportSpecifier(specifier)  => match specifier {
+                ImportSpecifier::Default(_)   => "ImportDefaultSpecifier",
+                ImportSpecifier::Namespace(_) => "ImportNamespaceSpecifier",
+                ImportSpecifier::Named { .. } => "ImportSpecifier",
+            },
+            Attribute::ExportSpecifier(_)          => "ExportSpecifier",
+            _                                      => return None,
+        })
+    }
+
+    fn location(&self) -> Option<(u32, u32)> {
+        Some(match *self {
+            Attribute::Node(ref node)           => (node.start(), node.end()),
+            Attribute::Literal(node)            => (node.start, node.end),
+            Attribute::Template(node)           => (node.start, node.end),
+            Attribute::TemplateElement(node, _) => (node.start, node.end),
+            Attribute::Method(node)             => (node.start, node.end),
+            Attribute::Block(node)              => (node.start, node.end),
+            Attribute::ClassBody(node)          => (node.start, node.end),
+            Attribute::Declaration(node, _)     => (node.start, node.end),
+            _                                   => return None,
+        })
+    }
+}
+
+#[inline]
+fn list<'ast, T, F>(list: NodeList<'ast, T>, attribute: F) -> Attribute<'ast>
+where
+    T: Copy,
+    F: Fn(Node<'ast, T>) -> Attribute<'ast>,
+{
+    Attribute::List(list.iter().map(|node| attribute(*node)).collect())
+}
+
+#[inline]
+fn optional<'ast, T, F>(value: Option<T>, attribute: F) -> Attribute<'ast>
+where
+    F: FnOnce(T) -> Attribute<'ast>,
+{
+    value.map_or(Attribute::Null, attribute)
+}
+
+#[inline]
+fn identifier(node: IdentifierNode) -> Attribute {
+    Attribute::Identifier(node.item)
+}
+
+#[inline]
+fn expression(node: ExpressionNode) -> Attribute {
+    match node.item {
+        Expression::Void => Attribute::Null,
+        _                => Attribute::Node(Ancestor::Expression(node)),
+    }
+}
+
+#[inline]
+fn statement(node: StatementNode) -> Attribute {
+    Attribute::Node(Ancestor::Statement(node))
+}
+
+#[inline]
+fn pattern<'ast>(node: Node<'ast, Pattern<'ast>>) -> Attribute<'ast> {
+    match node.item {
+        Pattern::Void => Attribute::Null,
+        _             => Attribute::Node(Ancestor::Pattern(node)),
+    }
+}
+
+#[inline]
+fn key<'ast>(node: Node<'ast, PropertyKey<'ast>>) -> Attribute<'ast> {
+    match node.item {
+        PropertyKey::Computed(expression) => self::expression(expression),
+        _                                 => Attribute::Node(Ancestor::PropertyKey(node)),
+    }
+}
+
+#[inline]
+fn is_computed(node: Node<PropertyKey>) -> bool {
+    matches!(node.item, PropertyKey::Computed(_))
+}
+
+#[inline]
+fn for_init<'ast>(node: Node<'ast, ForInit<'ast>>) -> Attribute<'ast> {
+    match node.item {
+        ForInit::Declaration(declaration) => Attribute::Declaration(node, declaration),
+        ForInit::Expression(expression)   => self::expression(expression),
+    }
+}
+
+#[inline]
+fn annotation<'ast>(present: bool) -> Option<Attribute<'ast>> {
+    if present {
+        Some(Attribute::Annotation)
+    } else {
+        None
+    }
+}
+
+fn node_field<'ast>(node: Ancestor<'ast>, key: &str) -> Option<Attribute<'ast>> {
+    Some(match (node, key) {
+        (Ancestor::Statement(node), _)      => return statement_field(&node.item, key),
+        (Ancestor::Expression(node), _)     => return expression_field(&node.item, key),
+        (Ancestor::Property(node), _)       => return property_field(&node.item, key),
+        (Ancestor::PropertyKey(node), "name") => match node.item {
+            PropertyKey::Literal(name) |
+            PropertyKey::Binary(name)  => Attribute::String(name),
+            PropertyKey::Computed(_)   => return None,
+        },
+        (Ancestor::ClassMember(node), _)    => return member_field(&node.item, key),
+        (Ancestor::Pattern(node), _)        => return pattern_field(&node.item, key),
+        (Ancestor::Declarator(node), "id")  => pattern(node.id),
+        (Ancestor::Declarator(node), "init") => optional(node.init, expression),
+        (Ancestor::SwitchCase(node), "test") => optional(node.test, expression),
+        (Ancestor::SwitchCase(node), "consequent") => list(node.consequent, statement),
+        (Ancestor::CatchClause(node), "param") => pattern(node.param),
+        (Ancestor::CatchClause(node), "body") => Attribute::Block(node.body),
+        _                                   => return None,
+    })
+}
+
+fn operator<'ast>(operator: OperatorKind) -> Attribute<'ast> {
+    Attribute::String(operator.as_str())
+}
+
+fn expression_field<'ast>(expression: &Expression<'ast>, key: &str) -> Option<Attribute<'ast>> {
+    use self::Expression::*;
+
+    Some(match (*expression, key) {
+        (Identifier(name), "name")               => Attribute::String(name),
+        (Literal(literal), _)                    => return literal_field(literal, key),
+        (Sequence(sequence), "expressions")      => list(sequence.body, self::expression),
+        (Array(array), "elements")               => list(array.body, self::expression),
+        (Member(member), "object")               => self::expression(member.object),
+        (Member(member), "property")             => identifier(member.property),
+        (Member(_), "computed")                  => Attribute::Bool(false),
+        (ComputedMember(member), "object")       => self::expression(member.object),
+        (ComputedMember(member), "property")     => self::expression(member.property),
+        (ComputedMember(_), "computed")          => Attribute::Bool(true),
+        (Call(call), "callee")                   => self::expression(call.callee),
+        (Call(call), "arguments")                => list(call.arguments, self::expression),
+        (Binary(binary), "operator")             => operator(binary.operator),
+        (Binary(binary), "left")                 => self::expression(binary.left),
+        (Binary(binary), "right")                => self::expression(binary.right),
+        (Prefix(prefix), _) if prefix.operator == OperatorKind::New => match (prefix.operand.item, key) {
+            (Call(call), "callee")    => self::expression(call.callee),
+            (Call(call), "arguments") => list(call.arguments, self::expression),
+            (_, "callee")             => self::expression(prefix.operand),
+            (_, "arguments")          => Attribute::List(Vec::new()),
+            _                         => return None,
+        },
+        (Prefix(prefix), "operator")             => operator(prefix.operator),
+        (Prefix(prefix), "argument")             => self::expression(prefix.operand),
+        (Prefix(_), "prefix")                    => Attribute::Bool(true),
+        (Postfix(postfix), "operator")           => operator(postfix.operator),
+        (Postfix(postfix), "argument")           => self::expression(postfix.operand),
+        (Postfix(_), "prefix")                   => Attribute::Bool(false),
+        (Conditional(conditional), "test")       => self::expression(conditional.test),
+        (Conditional(conditional), "consequent") => self::expression(conditional.consequent),
+        (Conditional(conditional), "alternate")  => self::expression(conditional.alternate),
+        (Template(ref template), _)              => return template_field(template, key),
+        (TaggedTemplate(tagged), "tag")          => self::expression(tagged.tag),
+        (TaggedTemplate(tagged), "quasi")        => Attribute::Template(tagged.quasi),
+        (Spread(spread), "argument")             => self::expression(spread.argument),
+        (Arrow(arrow), "params")                 => list(arrow.params, pattern),
+        (Arrow(arrow), "body")                   => match arrow.body {
+            ArrowBody::Expression(expression) => self::expression(expression),
+            ArrowBody::Block(block)           => Attribute::Block(block),
+        },
+        (Arrow(arrow), "typeParameters")         => return annotation(!arrow.type_params.is_empty()),
+        (Arrow(arrow), "returnType")             => return annotation(arrow.return_type.is_some()),
+        (Object(object), "properties")           => list(object.body, |property| Attribute::Node(Ancestor::Property(property))),
+        (Function(ref function), _)              => return function_field(function, key),
+        (Class(ref class), _)                    => return class_field(class, key),
+        (TypeCast(cast), "expression")           => self::expression(cast.expression),
+        (TypeCast(_), "typeAnnotation")          => Attribute::Annotation,
+        _                                        => return None,
+    })
+}
+
+fn number(number: &str) -> Option<f64> {
+    let radix = match number.get(..2) {
+        Some("0x") | Some("0X") => 16,
+        Some("0o") | Some("0O") => 8,
+        Some("0b") | Some("0B") => 2,
+        _                       => return number.parse().ok(),
+    };
+
+    i64::from_str_radix(&number[2..], radix).ok().map(|value| value as f64)
+}
+
+fn literal_field<'ast>(literal: Literal<'ast>, key: &str) -> Option<Attribute<'ast>> {
+    use self::Literal::*;
+
+    Some(match (literal, key) {
+        (Undefined, "raw")          => Attribute::String("undefined"),
+        (Null, "value")             => Attribute::Null,
+        (Null, "raw")               => Attribute::String("null"),
+        (True, "value")             => Attribute::Bool(true),
+        (True, "raw")               => Attribute::String("true"),
+        (False, "value")            => Attribute::Bool(false),
+        (False, "raw")              => Attribute::String("false"),
+        (Number(value), "value")    |
+        (Binary(value), "value")    => Attribute::Number(self::number(value)?),
+        (String(value), "value")    => Attribute::String(&value[1..value.len() - 1]),
+        (RegEx(value), "regex")     => Attribute::RegEx(value),
+        (Number(value), "raw")      |
+        (Binary(value), "raw")      |
+        (String(value), "raw")      |
+        (RegEx(value), "raw")       => Attribute::String(value),
+        _                           => return None,
+    })
+}
+
+fn template_field<'ast>(template: &TemplateLiteral<'ast>, key: &str) -> Option<Attribute<'ast>> {
+    Some(match key {
+        "quasis"      => {
+            let mut quasis = template.quasis.iter().peekable();
+            let mut elements = Vec::new();
+
+            while let Some(quasi) = quasis.next() {
+                elements.push(Attribute::TemplateElement(*quasi, quasis.peek().is_none()));
+            }
+
+            Attribute::List(elements)
+        },
+        "expressions" => list(template.expressions, expression),
+        _             => return None,
+    })
+}
+
+/// Name of a function or class, serialized as its `id`.
+trait Id<'ast>: Name<'ast> {
+    fn id(&self) -> Attribute<'ast>;
+}
+
+impl<'ast> Id<'ast> for EmptyName {
+    #[inline]
+    fn id(&self) -> Attribute<'ast> {
+        Attribute::Null
+    }
+}
+
+impl<'ast> Id<'ast> for OptionalName<'ast> {
+    #[inline]
+    fn id(&self) -> Attribute<'ast> {
+        optional(self.0, identifier)
+    }
+}
+
+impl<'ast> Id<'ast> for MandatoryName<'ast> {
+    #[inline]
+    fn id(&self) -> Attribute<'ast> {
+        identifier(self.0)
+    }
+}
+
+fn function_field<'ast, N: Id<'ast>>(function: &Function<'ast, N>, key: &str) -> Option<Attribute<'ast>> {
+    Some(match key {
+        "id"             => function.name.id(),
+        "params"         => list(function.params, pattern),
+        "body"           => Attribute::Block(function.body),
+        "typeParameters" => return annotation(!function.type_params.is_empty()),
+        "returnType"     => return annotation(function.return_type.is_some()),
+        _                => return None,
+    })
+}
+
+fn class_field<'ast, N: Id<'ast>>(class: &Class<'ast, N>, key: &str) -> Option<Attribute<'ast>> {
+    Some(match key {
+        "id"                  => class.name.id(),
+        "superClass"          => optional(class.extends, expression),
+        "body"                => Attribute::ClassBody(class.body),
+        "typeParameters"      => return annotation(!class.type_params.is_empty()),
+        "superTypeParameters" => return annotation(!class.super_type_args.is_empty()),
+        "implements"          => return annotation(!class.implements.is_empty()),
+        _                     => return None,
+    })
+}
+
+fn property_field<'ast>(property: &Property<'ast>, key: &str) -> Option<Attribute<'ast>> {
+    use self::Property::*;
+
+    Some(match (*property, key) {
+        (Shorthand(name), "name")      => Attribute::String(name),
+        (Shorthand(_), _)              => return None,
+        (Literal { key, .. }, "key")   |
+        (Method { key, .. }, "key")    => self::key(key),
+        (Literal { key, .. }, "computed") => Attribute::Bool(is_computed(key)),
+        (Literal { value, .. }, "value") => expression(value),
+        (Literal { .. }, "method")     => Attribute::Bool(false),
+        (Method { value, .. }, "value") => Attribute::Method(value),
+        (Method { .. }, "computed")    => Attribute::Bool(false),
+        (Method { .. }, "method")      => Attribute::Bool(true),
+        (_, "shorthand")               => Attribute::Bool(false),
+        (_, "kind")                    => Attribute::String("init"),
+        _                              => return None,
+    })
+}
+
+fn method_kind(kind: MethodKind) -> &'static str {
+    match kind {
+        MethodKind::Constructor => "constructor",
+        MethodKind::Method      => "method",
+        MethodKind::Get         => "get",
+        MethodKind::Set         => "set",
+    }
+}
+
+fn member_field<'ast>(member: &ClassMember<'ast>, key: &str) -> Option<Attribute<'ast>> {
+    use self::ClassMember::*;
+
+    Some(match (*member, key) {
+        (Error, _)                                => return None,
+        (Method { kind, .. }, "kind")             => Attribute::String(method_kind(kind)),
+        (Method { value, .. }, "value")           => Attribute::Method(value),
+        (Literal { value, .. }, "value")          => expression(value),
+        (Property { value, .. }, "value")         => optional(value, expression),
+        (Property { .. }, "typeAnnotation")       => Attribute::Annotation,
+        (Method { is_static, .. }, "static")      |
+        (Literal { is_static, .. }, "static")     |
+        (Property { is_static, .. }, "static")    => Attribute::Bool(is_static),
+        (Method { key, .. }, "key")               |
+        (Literal { key, .. }, "key")              |
+        (Property { key, .. }, "key")             => self::key(key),
+        (Method { key, .. }, "computed")          |
+        (Literal { key, .. }, "computed")         |
+        (Property { key, .. }, "computed")        => Attribute::Bool(is_computed(key)),
+        _                                         => return None,
+    })
+}
+
+fn pattern_field<'ast>(pattern: &Pattern<'ast>, key: &str) -> Option<Attribute<'ast>> {
+    use self::Pattern::*;
+
+    Some(match (*pattern, key) {
+        (Identifier(name), "name")                      => Attribute::String(name),
+        (ObjectPattern { properties }, "keys")          => list(properties, |property| Attribute::Node(Ancestor::Property(property))),
+        (ArrayPattern { elements }, "elements")         => list(elements, self::pattern),
+        (RestElement { argument }, "argument")          => identifier(argument),
+        (AssignmentPattern { left, .. }, "left")        => self::pattern(left),
+        (AssignmentPattern { right, .. }, "right")      => expression(right),
+        (Typed { optional, .. }, "optional")            => return if optional { Some(Attribute::Bool(true)) } else { None },
+        (Typed { .. }, "typeAnnotation")                => Attribute::Annotation,
+        (Typed { pattern, .. }, _)                      => return pattern_field(&pattern.item, key),
+        _                                               => return None,
+    })
+}
+
+fn declaration_kind(kind: DeclarationKind) -> &'static str {
+    match kind {
+        DeclarationKind::Var   => "var",
+        DeclarationKind::Let   => "let",
+        DeclarationKind::Const => "const",
+    }
+}
+
+fn declaration_field<'ast>(declaration: &DeclarationStatement<'ast>, key: &str) -> Option<Attribute<'ast>> {
+    Some(match key {
+        "kind"         => Attribute::String(declaration_kind(declaration.kind)),
+        "declarations" => list(declaration.declarators, |declarator| Attribute::Node(Ancestor::Declarator(declarator))),
+        _              => return None,
+    })
+}
+
+fn import_specifier_field<'ast>(specifier: ImportSpecifier<'ast>, key: &str) -> Option<Attribute<'ast>> {
+    use self::ImportSpecifier::*;
+
+    Some(match (specifier, key) {
+        (Default(local), "local")        |
+        (Namespace(local), "local")      |
+        (Named { local, .. }, "local")   => Attribute::Identifier(local),
+        (Named { imported, .. }, "imported") => Attribute::Identifier(imported),
+        _                                => return None,
+    })
+}
+
+fn import_kind(kind: ImportKind) -> &'static str {
+    match kind {
+        ImportKind::Value  => "value",
+        ImportKind::Type   => "type",
+        ImportKind::Typeof => "typeof",
+    }
+}
+
+fn export_kind(kind: ExportKind) -> &'static str {
+    match kind {
+        ExportKind::Value => "value",
+        ExportKind::Type  => "type",
+    }
+}
+
+fn statement_field<'ast>(statement: &Statement<'ast>, key: &str) -> Option<Attribute<'ast>> {
+    use self::Statement::*;
+
+    Some(match (*statement, key) {
+        (Expression(expression), "expression") => self::expression(expression),
+        (Declaration(ref declaration), _)      => return declaration_field(declaration, key),
+        (Return(statement), "argument")              => optional(statement.value, self::expression),
+        (Break(statement), "label")                  => optional(statement.label, identifier),
+        (Continue(statement), "label")               => optional(statement.label, identifier),
+        (Throw(throw), "argument")             => self::expression(throw.value),
+        (If(statement), "test")                      => self::expression(statement.test),
+        (If(statement), "consequent")                => self::statement(statement.consequent),
+        (If(statement), "alternate")                 => optional(statement.alternate, self::statement),
+        (While(statement), "test")                   => self::expression(statement.test),
+        (While(statement), "body")                   => self::statement(statement.body),
+        (Do(statement), "body")                      => self::statement(statement.body),
+        (Do(statement), "test")                      => self::expression(statement.test),
+        (With(statement), "object")                  => self::expression(statement.object),
+        (With(statement), "body")                    => self::statement(statement.body),
+        (For(statement), "init")                     => optional(statement.init, for_init),
+        (For(statement), "test")                     => optional(statement.test, self::expression),
+        (For(statement), "update")                   => optional(statement.update, self::expression),
+        (For(statement), "body")                     => self::statement(statement.body),
+        (ForIn(statement), "left")                   => for_init(statement.left),
+        (ForIn(statement), "right")                  => self::expression(statement.right),
+        (ForIn(statement), "body")                   => self::statement(statement.body),
+        (ForOf(statement), "left")                   => for_init(statement.left),
+        (ForOf(statement), "right")                  => self::expression(statement.right),
+        (ForOf(statement), "body")                   => self::statement(statement.body),
+        (Try(statement), "block")                    => Attribute::Block(statement.block),
+        (Try(statement), "handler")                  => optional(statement.handler, |handler| Attribute::Node(Ancestor::CatchClause(handler))),
+        (Try(statement), "finalizer")                => optional(statement.finalizer, Attribute::Block),
+        (Block(block), "body")                 => list(block.body, self::statement),
+        (Labeled(statement), "label")                => Attribute::Identifier(statement.label),
+        (Labeled(statement), "body")                 => self::statement(statement.body),
+        (Function(ref function), _)            => return function_field(function, key),
+        (Class(ref class), _)                  => return class_field(class, key),
+        (Switch(statement), "discriminant")          => self::expression(statement.discriminant),
+        (Switch(statement), "cases")                 => list(statement.cases.body, |case| Attribute::Node(Ancestor::SwitchCase(case))),
+        (Import(import), "importKind")         => Attribute::String(import_kind(import.kind)),
+        (Import(import), "specifiers")         => list(import.specifiers, |specifier| Attribute::ImportSpecifier(specifier.item)),
+        (Import(import), "source")             => Attribute::Literal(import.source),
+        (Export(export), _)                    => return export_field(export, key),
+        (Commented(statement), _)              => return statement_field(&statement.item, key),
+        _                                      => return None,
+    })
+}
+
+fn export_field<'ast>(export: ExportStatement<'ast>, key: &str) -> Option<Attribute<'ast>> {
+    use self::ExportStatement::*;
+
+    Some(match (export, key) {
+        (Declaration(declaration), "declaration") => statement(declaration),
+        (Declaration(_), "specifiers")            => Attribute::List(Vec::new()),
+        (Declaration(_), "source")                => Attribute::Null,
+        (Default(declaration), "declaration")     => expression(declaration),
+        (Named { kind, .. }, "exportKind")        => Attribute::String(export_kind(kind)),
+        (Named { .. }, "declaration")             => Attribute::Null,
+        (Named { specifiers, .. }, "specifiers")  => list(specifiers, |specifier| Attribute::ExportSpecifier(specifier.item)),
+        (Named { source, .. }, "source")          => optional(source, Attribute::Literal),
+        (All { source }, "source")                => Attribute::Literal(source),
+        _                                         => return None,
+    })
+}
@@ -0,0 +1,520 @@
+//! Querying the AST with selectors in the style of [esquery], which are
+//! CSS selectors matched against the ESTree shape of the nodes.
+//!
+//! Supported are node types, `*` matching any node, attributes tested
+//! by presence (`[init]`), equality (`[name="foo"]`) or inequality
+//! (`[kind!=var]`) with a path into the node (`[callee.object.name=console]`),
+//! descendant (`A B`) and child (`A > B`) combinators, comma separated
+//! lists of selectors, and `:has()` with a relative selector:
+//!
+//! ```ignore
+//! let selector = Selector::parse("CallExpression[callee.name=require]:has(> Literal)")?;
+//!
+//! for node in query(&module, &selector) {
+//!     println!("{} at {}..{}", node_type(&node).unwrap(), node.start(), node.end());
+//! }
+//! ```
+//!
+//! Combinators follow the nodes visitors have hooks for. Bodies of
+//! functions, classes and `catch` clauses aren't nodes of their own, so
+//! `FunctionDeclaration > ReturnStatement` matches `function f() { return; }`.
+//!
+//! [esquery]: https://github.com/estools/esquery
+
+mod parser;
+mod node_type;
+mod attribute;
+
+use std::fmt::{self, Debug, Display};
+use std::iter::Rev;
+use std::marker::PhantomData;
+use std::slice::Iter;
+use std::str::FromStr;
+
+use ratel::ast::{Node, Identifier, Literal, Pattern, ClassMember, ExpressionNode, StatementNode, PropertyNode};
+use ratel::ast::expression::*;
+use ratel::ast::statement::*;
+
+use {Visitable, StaticVisitor, DynamicVisitor, NodePath, Ancestor, Control};
+
+pub use self::node_type::node_type;
+use self::attribute::Attribute;
+
+/// Kind of a `query::Error`.
+#[derive(Debug, PartialEq, Clone)]
+pub enum ErrorKind {
+    /// Selector ends in the middle of a compound, attribute or `:has`.
+    UnexpectedEnd,
+    /// Character that can't appear at this point of the selector.
+    UnexpectedCharacter(char),
+    /// Number in an attribute that can't be read.
+    InvalidNumber,
+    /// Pseudo-class other than `:has`.
+    UnknownPseudoClass(String),
+}
+
+/// Error type returned when parsing a selector, along with the offset
+/// of the selector at which it occurred.
+#[derive(PartialEq, Clone)]
+pub struct Error {
+    pub kind: ErrorKind,
+    pub offset: usize,
+}
+
+impl Debug for Error {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.kind {
+            ErrorKind::UnexpectedEnd => {
+                write!(f, "Unexpected end of selector at {}", self.offset)
+            },
+            ErrorKind::UnexpectedCharacter(character) => {
+                write!(f, "Unexpected character {:?} at {}", character, self.offset)
+            },
+            ErrorKind::InvalidNumber => {
+                write!(f, "Invalid number at {}", self.offset)
+            },
+            ErrorKind::UnknownPseudoClass(ref name) => {
+                write!(f, "Unknown pseudo-class :{} at {}", name, self.offset)
+            },
+        }
+    }
+}
+
+pub type Result<T> = ::std::result::Result<T, Error>;
+
+/// Parsed selector, matching nodes that match any of its comma separated
+/// selectors.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Selector {
+    alternatives: Vec<Complex>,
+}
+
+impl Selector {
+    /// Parse a selector from its source.
+    #[inline]
+    pub fn parse(source: &str) -> Result<Self> {
+        parser::Parser::new(source).parse().map(|alternatives| Selector { alternatives })
+    }
+}
+
+impl FromStr for Selector {
+    type Err = Error;
+
+    #[inline]
+    fn from_str(source: &str) -> Result<Self> {
+        Selector::parse(source)
+    }
+}
+
+/// Compound selectors joined by combinators, `A > B C`.
+#[derive(Debug, PartialEq, Clone)]
+struct Complex {
+    parts: Vec<Part>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+struct Part {
+    /// How this part relates to the previous one, ignored on the first.
+    combinator: Combinator,
+    compound: Compound,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Combinator {
+    Descendant,
+    Child,
+}
+
+/// Type and filters that all have to match the same node, `A[b]:has(C)`.
+#[derive(Debug, PartialEq, Clone)]
+struct Compound {
+    /// Matches the node `:has` is tested on, and nothing else.
+    scope: bool,
+    /// `None` matches any type.
+    node_type: Option<String>,
+    filters: Vec<Filter>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+enum Filter {
+    Attribute {
+        path: Vec<String>,
+        /// `None` only tests that the attribute is present and not `null`.
+        test: Option<(Operator, Value)>,
+    },
+    Has(Vec<Complex>),
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Operator {
+    Equal,
+    NotEqual,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+enum Value {
+    String(String),
+    Number(f64),
+    Bool(bool),
+    Null,
+}
+
+impl Value {
+    fn matches(&self, attribute: Option<&Attribute>) -> bool {
+        match (self, attribute) {
+            (Value::String(value), Some(Attribute::String(attribute))) => value == attribute,
+            (Value::Number(value), Some(Attribute::Number(attribute))) => value == attribute,
+            (Value::Bool(value), Some(Attribute::Bool(attribute)))     => value == attribute,
+            (Value::Null, Some(Attribute::Null))                       => true,
+            _                                                          => false,
+        }
+    }
+}
+
+/// Find all nodes under `root` matching the selector, in the order they
+/// are visited. When `root` is a node, it can match too.
+pub fn query<'ast, T>(root: &T, selector: &Selector) -> Vec<Ancestor<'ast>>
+where
+    T: Visitable<'ast>,
+{
+    let mut search = Search {
+        alternatives: &selector.alternatives,
+        scope: None,
+        first: false,
+        found: Vec::new(),
+    };
+
+    root.traverse(&Matcher(PhantomData), &mut search);
+
+    search.found
+}
+
+/// Whether the node matches the complex selector made of `parts`, given
+/// its `ancestors`, starting with its parent.
+fn matches<'ast>(parts: &[Part], node: &Ancestor<'ast>, mut ancestors: Rev<Iter<Ancestor<'ast>>>, scope: Option<&Ancestor<'ast>>) -> bool {
+    let (last, rest) = match parts.split_last() {
+        Some(split) => split,
+        None        => return true,
+    };
+
+    if !last.compound.matches(node, scope) {
+        return false;
+    }
+
+    if rest.is_empty() {
+        return true;
+    }
+
+    match last.combinator {
+        Combinator::Child => match ancestors.next() {
+            Some(parent) => matches(rest, parent, ancestors, scope),
+            None         => false,
+        },
+        Combinator::Descendant => {
+            while let Some(ancestor) = ancestors.next() {
+                if matches(rest, ancestor, ancestors.clone(), scope) {
+                    return true;
+                }
+            }
+
+            false
+        },
+    }
+}
+
+impl Compound {
+    #[inline]
+    fn scope() -> Self {
+        Compound {
+            scope: true,
+            node_type: None,
+            filters: Vec::new(),
+        }
+    }
+
+    fn matches<'ast>(&self, node: &Ancestor<'ast>, scope: Option<&Ancestor<'ast>>) -> bool {
+        if self.scope {
+            return scope.is_some_and(|scope| node.is(scope));
+        }
+
+        let node_type = match node_type(node) {
+            Some(node_type) => node_type,
+            None            => return false,
+        };
+
+        if let Some(ref expected) = self.node_type {
+            if !expected.eq_ignore_ascii_case(node_type) {
+                return false;
+            }
+        }
+
+        self.filters.iter().all(|filter| match *filter {
+            Filter::Attribute { ref path, ref test } => {
+                let attribute = path.iter().try_fold(Attribute::Node(*node), |attribute, key| attribute.get(key));
+
+                match *test {
+                    None                                 => attribute.is_some_and(|attribute| !attribute.is_null()),
+                    Some((Operator::Equal, ref value))    => value.matches(attribute.as_ref()),
+                    Some((Operator::NotEqual, ref value)) => !value.matches(attribute.as_ref()),
+                }
+            },
+            Filter::Has(ref alternatives) => has(node, alternatives),
+        })
+    }
+}
+
+/// Whether any of the descendants of the node matches the selectors.
+fn has(node: &Ancestor, alternatives: &[Complex]) -> bool {
+    let mut search = Search {
+        alternatives,
+        scope: Some(*node),
+        first: true,
+        found: Vec::new(),
+    };
+
+    with_node!(*node, node => node.traverse(&Matcher(PhantomData), &mut search));
+
+    !search.found.is_empty()
+}
+
+struct Search<'sel, 'ast> {
+    alternatives: &'sel [Complex],
+    scope: Option<Ancestor<'ast>>,
+    /// Stop at the first match.
+    first: bool,
+    found: Vec<Ancestor<'ast>>,
+}
+
+impl<'sel, 'ast> Search<'sel, 'ast> {
+    fn check(&mut self, node: Ancestor<'ast>, path: &NodePath<'ast>) -> Control {
+        let scope = self.scope.as_ref();
+
+        // `:has` only looks at the descendants
+        if scope.is_some_and(|scope| node.is(scope)) {
+            return Control::Continue;
+        }
+
+        if self.alternatives.iter().any(|complex| matches(&complex.parts, &node, path.ancestors(), scope)) {
+            self.found.push(node);
+
+            if self.first {
+                return Control::Stop;
+            }
+        }
+
+        Control::Continue
+    }
+}
+
+/// Visitor calling `Search::check` on every node.
+struct Matcher<'sel>(PhantomData<&'sel ()>);
+
+macro_rules! matcher_hooks {
+    ($( $hook:ident($item:ty, $node:ty) => $ancestor:ident; )*) => {
+        #[inline]
+        fn on_this_expression(node: &ExpressionNode<'ast>, path: &NodePath<'ast>, search: &mut Search<'sel, 'ast>) -> Control {
+            search.check(Ancestor::Expression(*node), path)
+        }
+
+        $(
+            #[inline]
+            fn $hook(_: &$item, node: &$node, path: &NodePath<'ast>, search: &mut Search<'sel, 'ast>) -> Control {
+                search.check(Ancestor::$ancestor(*node), path)
+            }
+        )*
+
+        fn register(dv: &mut DynamicVisitor<'ast, Search<'sel, 'ast>>) {
+            dv.on_this_expression.push(Matcher::on_this_expression);
+            $(
+                dv.$hook.push(Matcher::$hook);
+            )*
+        }
+    }
+}
+
+impl<'sel, 'ast> StaticVisitor<'ast> for Matcher<'sel> {
+    type Context = Search<'sel, 'ast>;
+
+    matcher_hooks! {
+        on_identifier_expression(Identifier<'ast>, ExpressionNode<'ast>) => Expression;
+        on_literal_expression(Literal<'ast>, ExpressionNode<'ast>) => Expression;
+        on_sequence_expression(SequenceExpression<'ast>, ExpressionNode<'ast>) => Expression;
+        on_array_expression(ArrayExpression<'ast>, ExpressionNode<'ast>) => Expression;
+        on_member_expression(MemberExpression<'ast>, ExpressionNode<'ast>) => Expression;
+        on_computed_member_expression(ComputedMemberExpression<'ast>, ExpressionNode<'ast>) => Expression;
+        on_call_expression(CallExpression<'ast>, ExpressionNode<'ast>) => Expression;
+        on_binary_expression(BinaryExpression<'ast>, ExpressionNode<'ast>) => Expression;
+        on_prefix_expression(PrefixExpression<'ast>, ExpressionNode<'ast>) => Expression;
+        on_postfix_expression(PostfixExpression<'ast>, ExpressionNode<'ast>) => Expression;
+        on_conditional_expression(ConditionalExpression<'ast>, ExpressionNode<'ast>) => Expression;
+        on_template_literal(TemplateLiteral<'ast>, ExpressionNode<'ast>) => Expression;
+        on_tagged_template_expression(TaggedTemplateExpression<'ast>, ExpressionNode<'ast>) => Expression;
+        on_spread_expression(SpreadExpression<'ast>, ExpressionNode<'ast>) => Expression;
+        on_arrow_expression(ArrowExpression<'ast>, ExpressionNode<'ast>) => Expression;
+        on_object_expression(ObjectExpression<'ast>, ExpressionNode<'ast>) => Expression;
+        on_function_expression(FunctionExpression<'ast>, ExpressionNode<'ast>) => Expression;
+        on_class_expression(ClassExpression<'ast>, ExpressionNode<'ast>) => Expression;
+        on_type_cast_expression(TypeCastExpression<'ast>, ExpressionNode<'ast>) => Expression;
+
+        on_expression_statement(ExpressionNode<'ast>, StatementNode<'ast>) => Statement;
+        on_declaration_statement(DeclarationStatement<'ast>, StatementNode<'ast>) => Statement;
+        on_return_statement(ReturnStatement<'ast>, StatementNode<'ast>) => Statement;
+        on_break_statement(BreakStatement<'ast>, StatementNode<'ast>) => Statement;
+        on_continue_statement(ContinueStatement<'ast>, StatementNode<'ast>) => Statement;
+        on_throw_statement(ThrowStatement<'ast>, StatementNode<'ast>) => Statement;
+        on_if_statement(IfStatement<'ast>, StatementNode<'ast>) => Statement;
+        on_while_statement(WhileStatement<'ast>, StatementNode<'ast>) => Statement;
+        on_do_statement(DoStatement<'ast>, StatementNode<'ast>) => Statement;
+        on_with_statement(WithStatement<'ast>, StatementNode<'ast>) => Statement;
+        on_for_statement(ForStatement<'ast>, StatementNode<'ast>) => Statement;
+        on_for_in_statement(ForInStatement<'ast>, StatementNode<'ast>) => Statement;
+        on_for_of_statement(ForOfStatement<'ast>, StatementNode<'ast>) => Statement;
+        on_try_statement(TryStatement<'ast>, StatementNode<'ast>) => Statement;
+        on_block_statement(BlockStatement<'ast>, StatementNode<'ast>) => Statement;
+        on_labeled_statement(LabeledStatement<'ast>, StatementNode<'ast>) => Statement;
+        on_switch_statement(SwitchStatement<'ast>, StatementNode<'ast>) => Statement;
+        on_function_statement(FunctionStatement<'ast>, StatementNode<'ast>) => Statement;
+        on_class_statement(ClassStatement<'ast>, StatementNode<'ast>) => Statement;
+        on_type_alias_statement(TypeAliasStatement<'ast>, StatementNode<'ast>) => Statement;
+        on_declare_statement(DeclareStatement<'ast>, StatementNode<'ast>) => Statement;
+        on_import_statement(ImportStatement<'ast>, StatementNode<'ast>) => Statement;
+        on_export_statement(ExportStatement<'ast>, StatementNode<'ast>) => Statement;
+
+        on_property(Property<'ast>, PropertyNode<'ast>) => Property;
+        on_property_key(PropertyKey<'ast>, Node<'ast, PropertyKey<'ast>>) => PropertyKey;
+        on_class_member(ClassMember<'ast>, Node<'ast, ClassMember<'ast>>) => ClassMember;
+        on_pattern(Pattern<'ast>, Node<'ast, Pattern<'ast>>) => Pattern;
+        on_declarator(Declarator<'ast>, Node<'ast, Declarator<'ast>>) => Declarator;
+        on_switch_case(SwitchCase<'ast>, Node<'ast, SwitchCase<'ast>>) => SwitchCase;
+        on_catch_clause(CatchClause<'ast>, Node<'ast, CatchClause<'ast>>) => CatchClause;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::{self, Value as Json};
+    use ratel::{parse, parse_with_options, ParseOptions, Goal};
+
+    fn assert_query(source: &str, selector: &str, expected: &[&str]) {
+        let module = parse(source).unwrap();
+        let selector = Selector::parse(selector).unwrap();
+        let found = query(&module, &selector)
+            .iter()
+            .map(|node| &source[node.start() as usize..node.end() as usize])
+            .collect::<Vec<_>>();
+
+        assert_eq!(found, expected);
+    }
+
+    fn error(selector: &str) -> Error {
+        Selector::parse(selector).unwrap_err()
+    }
+
+    #[test]
+    fn node_types() {
+        assert_query("foo(bar); baz;", "Identifier", &["foo", "bar", "baz"]);
+        assert_query("foo(bar); baz;", "identifier", &["foo", "bar", "baz"]);
+        assert_query("a = 1; a + 1; !a", "AssignmentExpression, UnaryExpression", &["a = 1", "!a"]);
+    }
+
+    #[test]
+    fn attributes() {
+        let source = "const a = require('a'); require(b); foo('c');";
+
+        assert_query(source, r#"VariableDeclarator:has(> CallExpression[callee.name="require"][arguments.0.type="Literal"])"#, &["a = require('a')"]);
+        assert_query(source, "CallExpression[callee.name!=require] > Literal", &["'c'"]);
+        assert_query(source, "VariableDeclarator[init]", &["a = require('a')"]);
+        assert_query("var a; let b; const c = 1;", "VariableDeclaration[kind=let]", &["let b;"]);
+        assert_query("a(1, 2.5, true, null);", "Literal[value=2.5], Literal[value=true], Literal[value=null]", &["2.5", "true", "null"]);
+        assert_query("a(0x10, /b/g, [, c]);", "CallExpression > Literal[value=16], Literal[regex.flags=g], ArrayExpression[elements.0=null]", &["0x10", "/b/g", "[, c]"]);
+        assert_query("function f(a) {} class C { m() { return; } }", "FunctionDeclaration[id.name=f][params.0.name=a][body.type=BlockStatement]", &["function f(a) {}"]);
+        assert_query("function f(a) {} class C { m() { return; } }", "MethodDefinition[key.name=m][value.body.body.0.type=ReturnStatement]", &["m() { return; }"]);
+        assert_query("for (let i in a) foo`b${c}`;", "ForInStatement[left.kind=let] TaggedTemplateExpression[quasi.quasis.1.tail=true]", &["foo`b${c}`"]);
+    }
+
+    #[test]
+    fn combinators() {
+        let source = "function foo() { bar(); if (a) { baz(); } }";
+
+        assert_query(source, "FunctionDeclaration CallExpression > Identifier", &["bar", "baz"]);
+        assert_query(source, "FunctionDeclaration > ExpressionStatement > CallExpression > Identifier", &["bar"]);
+        assert_query(source, "IfStatement > * > * CallExpression > *", &["baz"]);
+    }
+
+    #[test]
+    fn has() {
+        let source = "function foo() { return 1; } function bar() { function baz() { return 2; } }";
+
+        assert_query(source, "FunctionDeclaration:has(ReturnStatement)", &[
+            "function foo() { return 1; }",
+            "function bar() { function baz() { return 2; } }",
+            "function baz() { return 2; }",
+        ]);
+        assert_query(source, "FunctionDeclaration:has(> ReturnStatement)", &[
+            "function foo() { return 1; }",
+            "function baz() { return 2; }",
+        ]);
+        assert_query(source, "FunctionDeclaration:has(FunctionDeclaration)", &[
+            "function bar() { function baz() { return 2; } }",
+        ]);
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(error("").kind, ErrorKind::UnexpectedEnd);
+        assert_eq!(error("Identifier[name=").kind, ErrorKind::UnexpectedEnd);
+        assert_eq!(error("Identifier[name='foo"), Error { kind: ErrorKind::UnexpectedEnd, offset: 20 });
+        assert_eq!(error("Identifier >"), Error { kind: ErrorKind::UnexpectedEnd, offset: 12 });
+        assert_eq!(error("A, ,B"), Error { kind: ErrorKind::UnexpectedCharacter(','), offset: 3 });
+        assert_eq!(error("A:first-child"), Error { kind: ErrorKind::UnknownPseudoClass("first".into()), offset: 2 });
+        assert_eq!(error("Literal[value=1x]"), Error { kind: ErrorKind::InvalidNumber, offset: 14 });
+    }
+
+    #[test]
+    fn node_type_matches_estree() {
+        let source = r#"
+            import a from 'a';
+            export default class Foo extends Bar { baz() { return this.x; } qux = 1; }
+            export { a };
+            let { b, c: [d, ...e] } = f, g = `h${i}`;
+            function j(k = 1, ...l) { for (m of n) {} for (o in p) {} for (;;) { break; } }
+            try { q(...r) } catch (s) { throw s } finally { t++ }
+            switch (u) { case 1: v: while (w) { continue v; } default: do {} while (x) }
+            y = z ? !a && b : -c, new D, typeof e, [f.g, f[h]], { i, j: 1, k() {} };
+            (() => l)``; ;
+        "#;
+
+        let module = parse_with_options(source, ParseOptions { goal: Goal::Module, ..ParseOptions::default() }).unwrap();
+        let nodes = query(&module, &Selector::parse("*").unwrap());
+
+        assert!(nodes.len() > 80);
+
+        for node in nodes {
+            let json = with_node!(node, node => serde_json::to_value(**node)).unwrap();
+            let code = &source[node.start() as usize..node.end() as usize];
+
+            assert_eq!(node_type(&node), json["type"].as_str(), "{}", code);
+
+            // Attributes read from the node match the serialized ones
+            for (key, json) in json.as_object().unwrap() {
+                let value = match *json {
+                    Json::String(ref value) => Value::String(value.clone()),
+                    Json::Number(ref value) => Value::Number(value.as_f64().unwrap()),
+                    Json::Bool(value)       => Value::Bool(value),
+                    Json::Null              => Value::Null,
+                    _                       => continue,
+                };
+
+                assert!(value.matches(Attribute::Node(node).get(key).as_ref()), "{} of {}", key, code);
+            }
+        }
+    }
+}
@@ -0,0 +1,149 @@
+use ratel::ast::{Expression, Statement, Pattern, ClassMember, OperatorKind};
+use ratel::ast::expression::{Property, PropertyKey};
+use ratel::ast::statement::{DeclareStatement, ExportStatement};
+
+use Ancestor;
+
+/// ESTree type of a node, the same as the `type` it is serialized with.
+/// Nodes that aren't serialized on their own, such as computed keys which
+/// are serialized as their expression, have none.
+pub fn node_type(ancestor: &Ancestor) -> Option<&'static str> {
+    Some(match *ancestor {
+        Ancestor::Statement(node)   => return statement_type(&node.item),
+        Ancestor::Expression(node)  => return expression_type(&node.item),
+        Ancestor::Property(node)    => match node.item {
+            Property::Shorthand(_) => "Identifier",
+            _                      => "Property",
+        },
+        Ancestor::PropertyKey(node) => match node.item {
+            PropertyKey::Computed(_) => return None,
+            _                        => "Identifier",
+        },
+        Ancestor::ClassMember(node) => match node.item {
+            ClassMember::Error             => return None,
+            ClassMember::Method { .. }     => "MethodDefinition",
+            ClassMember::Literal { .. }    |
+            ClassMember::Property { .. }   => "ClassProperty",
+        },
+        Ancestor::Pattern(node)     => return pattern_type(&node.item),
+        Ancestor::Declarator(_)     => "VariableDeclarator",
+        Ancestor::SwitchCase(_)     => "SwitchCase",
+        Ancestor::CatchClause(_)    => "CatchClause",
+    })
+}
+
+fn operator_type(operator: OperatorKind, prefix: bool) -> &'static str {
+    use self::OperatorKind::*;
+
+    match operator {
+        Assign              |
+        AddAssign           |
+        ExponentAssign      |
+        MultiplyAssign      |
+        DivideAssign        |
+        RemainderAssign     |
+        BSLAssign           |
+        BSRAssign           |
+        UBSRAssign          |
+        BitOrAssign         |
+        BitXorAssign        |
+        SubtractAssign      |
+        BitAndAssign        => "AssignmentExpression",
+        LogicalAnd          |
+        LogicalOr           => "LogicalExpression",
+        Increment           |
+        Decrement           => "UpdateExpression",
+        Typeof              |
+        Void                |
+        Delete              => "UnaryExpression",
+        Subtraction         |
+        Addition            |
+        LogicalNot          |
+        BitwiseNot if prefix => "UnaryExpression",
+        New                 => "NewExpression",
+        _                   => "BinaryExpression"
+    }
+}
+
+fn expression_type(expression: &Expression) -> Option<&'static str> {
+    use self::Expression::*;
+
+    Some(match *expression {
+        Void                  => return None,
+        This(_)               => "ThisExpression",
+        Identifier(_)         => "Identifier",
+        Literal(_)            => "Literal",
+        Sequence(_)           => "SequenceExpression",
+        Array(_)              => "ArrayExpression",
+        Member(_)             |
+        ComputedMember(_)     => "MemberExpression",
+        Call(_)               => "CallExpression",
+        Binary(ref binary)    => operator_type(binary.operator, false),
+        Prefix(ref prefix)    => operator_type(prefix.operator, true),
+        Postfix(_)            => "UpdateExpression",
+        Conditional(_)        => "ConditionalExpression",
+        Template(_)           => "TemplateLiteral",
+        TaggedTemplate(_)     => "TaggedTemplateExpression",
+        Spread(_)             => "SpreadElement",
+        Arrow(_)              => "ArrowFunctionExpression",
+        Object(_)             => "ObjectExpression",
+        Function(_)           => "FunctionExpression",
+        Class(_)              => "ClassExpression",
+        TypeCast(_)           => "TypeCastExpression",
+    })
+}
+
+fn statement_type(statement: &Statement) -> Option<&'static str> {
+    use self::Statement::*;
+
+    Some(match *statement {
+        Empty                 => "EmptyStatement",
+        Expression(_)         => "ExpressionStatement",
+        Declaration(_)        => "VariableDeclaration",
+        Return(_)             => "ReturnStatement",
+        Break(_)              => "BreakStatement",
+        Continue(_)           => "ContinueStatement",
+        Throw(_)              => "ThrowStatement",
+        If(_)                 => "IfStatement",
+        While(_)              => "WhileStatement",
+        Do(_)                 => "DoWhileStatement",
+        With(_)               => "WithStatement",
+        For(_)                => "ForStatement",
+        ForIn(_)              => "ForInStatement",
+        ForOf(_)              => "ForOfStatement",
+        Try(_)                => "TryStatement",
+        Block(_)              => "BlockStatement",
+        Labeled(_)            => "LabeledStatement",
+        Function(_)           => "FunctionDeclaration",
+        Class(_)              => "ClassDeclaration",
+        Switch(_)             => "SwitchStatement",
+        TypeAlias(ref alias)  => if alias.opaque { "OpaqueType" } else { "TypeAlias" },
+        Declare(ref declare)  => match *declare {
+            DeclareStatement::Variable { .. }  => "DeclareVariable",
+            DeclareStatement::Function { .. }  => "DeclareFunction",
+            DeclareStatement::Class { .. }     => "DeclareClass",
+            DeclareStatement::TypeAlias(alias) => if alias.opaque { "DeclareOpaqueType" } else { "DeclareTypeAlias" },
+        },
+        Import(_)             => "ImportDeclaration",
+        Export(ref export)    => match *export {
+            ExportStatement::Default(_) => "ExportDefaultDeclaration",
+            ExportStatement::All { .. } => "ExportAllDeclaration",
+            _                           => "ExportNamedDeclaration",
+        },
+        Commented(node)       => return statement_type(&node.item),
+    })
+}
+
+fn pattern_type(pattern: &Pattern) -> Option<&'static str> {
+    use self::Pattern::*;
+
+    Some(match *pattern {
+        Void                      => return None,
+        Identifier(_)             => "Identifier",
+        ObjectPattern { .. }      => "ObjectPattern",
+        ArrayPattern { .. }       => "ArrayPattern",
+        RestElement { .. }        => "RestElement",
+        AssignmentPattern { .. }  => "AssignmentPattern",
+        Typed { pattern, .. }     => return pattern_type(&pattern.item),
+    })
+}
@@ -0,0 +1,347 @@
+use super::{Complex, Part, Compound, Combinator, Filter, Operator, Value};
+use super::{Error, ErrorKind, Result};
+
+/// Reads selectors, byte by byte, from their source.
+pub(super) struct Parser<'src> {
+    source: &'src str,
+    index: usize,
+}
+
+#[inline]
+fn is_name_start(byte: u8) -> bool {
+    byte.is_ascii_alphabetic() || byte == b'_' || byte == b'$'
+}
+
+#[inline]
+fn is_name(byte: u8) -> bool {
+    is_name_start(byte) || byte.is_ascii_digit()
+}
+
+impl<'src> Parser<'src> {
+    #[inline]
+    pub fn new(source: &'src str) -> Self {
+        Parser {
+            source,
+            index: 0,
+        }
+    }
+
+    /// Parse the whole source as a list of selectors.
+    pub fn parse(mut self) -> Result<Vec<Complex>> {
+        let list = self.list(false)?;
+
+        match self.peek() {
+            None => Ok(list),
+            Some(_) => Err(self.unexpected()),
+        }
+    }
+
+    #[inline]
+    fn peek(&self) -> Option<u8> {
+        self.source.as_bytes().get(self.index).cloned()
+    }
+
+    #[inline]
+    fn error(&self, kind: ErrorKind) -> Error {
+        Error {
+            kind,
+            offset: self.index,
+        }
+    }
+
+    fn unexpected(&self) -> Error {
+        match self.source[self.index..].chars().next() {
+            Some(character) => self.error(ErrorKind::UnexpectedCharacter(character)),
+            None            => self.error(ErrorKind::UnexpectedEnd),
+        }
+    }
+
+    #[inline]
+    fn expect(&mut self, byte: u8) -> Result<()> {
+        match self.peek() {
+            Some(found) if found == byte => {
+                self.index += 1;
+                Ok(())
+            },
+            _ => Err(self.unexpected()),
+        }
+    }
+
+    /// Skip whitespace, returning whether there was any.
+    fn whitespace(&mut self) -> bool {
+        let start = self.index;
+
+        while let Some(b' ') | Some(b'\t') | Some(b'\n') | Some(b'\r') = self.peek() {
+            self.index += 1;
+        }
+
+        self.index != start
+    }
+
+    /// Comma separated selectors. Relative ones, as found in `:has`, can
+    /// start with a combinator.
+    fn list(&mut self, relative: bool) -> Result<Vec<Complex>> {
+        let mut list = vec![self.complex(relative)?];
+
+        while let Some(b',') = self.peek() {
+            self.index += 1;
+            list.push(self.complex(relative)?);
+        }
+
+        Ok(list)
+    }
+
+    fn complex(&mut self, relative: bool) -> Result<Complex> {
+        self.whitespace();
+
+        let mut parts = Vec::new();
+
+        // Relative selectors are anchored to the node they are matched from
+        if relative {
+            let combinator = match self.peek() {
+                Some(b'>') => {
+                    self.index += 1;
+                    self.whitespace();
+                    Combinator::Child
+                },
+                _ => Combinator::Descendant,
+            };
+
+            parts.push(Part {
+                combinator: Combinator::Descendant,
+                compound: Compound::scope(),
+            });
+            parts.push(Part {
+                combinator,
+                compound: self.required_compound()?,
+            });
+        } else {
+            parts.push(Part {
+                combinator: Combinator::Descendant,
+                compound: self.required_compound()?,
+            });
+        }
+
+        loop {
+            let spaced = self.whitespace();
+
+            let combinator = match self.peek() {
+                Some(b'>') => {
+                    self.index += 1;
+                    self.whitespace();
+                    Combinator::Child
+                },
+                None | Some(b',') | Some(b')') => break,
+                Some(_) if spaced => Combinator::Descendant,
+                Some(_) => return Err(self.unexpected()),
+            };
+
+            parts.push(Part {
+                combinator,
+                compound: self.required_compound()?,
+            });
+        }
+
+        Ok(Complex { parts })
+    }
+
+    fn required_compound(&mut self) -> Result<Compound> {
+        match self.compound()? {
+            Some(compound) => Ok(compound),
+            None           => Err(self.unexpected()),
+        }
+    }
+
+    fn compound(&mut self) -> Result<Option<Compound>> {
+        let start = self.index;
+
+        let node_type = match self.peek() {
+            Some(b'*') => {
+                self.index += 1;
+                None
+            },
+            Some(byte) if is_name_start(byte) => Some(self.name()?.to_owned()),
+            _ => None,
+        };
+
+        let mut filters = Vec::new();
+
+        loop {
+            match self.peek() {
+                Some(b'[') => filters.push(self.attribute()?),
+                Some(b':') => filters.push(self.pseudo_class()?),
+                _ => break,
+            }
+        }
+
+        if self.index == start {
+            return Ok(None);
+        }
+
+        Ok(Some(Compound {
+            scope: false,
+            node_type,
+            filters,
+        }))
+    }
+
+    fn name(&mut self) -> Result<&'src str> {
+        let start = self.index;
+
+        match self.peek() {
+            Some(byte) if is_name_start(byte) => self.index += 1,
+            _ => return Err(self.unexpected()),
+        }
+
+        while let Some(byte) = self.peek() {
+            if !is_name(byte) {
+                break;
+            }
+            self.index += 1;
+        }
+
+        Ok(&self.source[start..self.index])
+    }
+
+    /// `[path]`, `[path=value]` or `[path!=value]`, where the path is a
+    /// list of fields and indexes separated by dots, such as
+    /// `arguments.0.value`.
+    fn attribute(&mut self) -> Result<Filter> {
+        self.expect(b'[')?;
+        self.whitespace();
+
+        let mut path = Vec::new();
+
+        loop {
+            let start = self.index;
+
+            while let Some(byte) = self.peek() {
+                if !is_name(byte) {
+                    break;
+                }
+                self.index += 1;
+            }
+
+            if self.index == start {
+                return Err(self.unexpected());
+            }
+
+            path.push(self.source[start..self.index].to_owned());
+
+            match self.peek() {
+                Some(b'.') => self.index += 1,
+                _          => break,
+            }
+        }
+
+        self.whitespace();
+
+        let operator = match self.peek() {
+            Some(b']') => {
+                self.index += 1;
+                return Ok(Filter::Attribute { path, test: None });
+            },
+            Some(b'=') => {
+                self.index += 1;
+                Operator::Equal
+            },
+            Some(b'!') => {
+                self.index += 1;
+                self.expect(b'=')?;
+                Operator::NotEqual
+            },
+            _ => return Err(self.unexpected()),
+        };
+
+        self.whitespace();
+        let value = self.value()?;
+        self.whitespace();
+        self.expect(b']')?;
+
+        Ok(Filter::Attribute {
+            path,
+            test: Some((operator, value)),
+        })
+    }
+
+    /// Quoted string, number, `true`, `false`, `null`, or a name which is
+    /// read as a string.
+    fn value(&mut self) -> Result<Value> {
+        match self.peek() {
+            Some(quote @ b'"') | Some(quote @ b'\'') => self.string(quote),
+            Some(byte) if byte.is_ascii_digit() || byte == b'-' || byte == b'.' => {
+                let start = self.index;
+
+                self.index += 1;
+
+                while let Some(byte) = self.peek() {
+                    if !byte.is_ascii_alphanumeric() && byte != b'.' {
+                        break;
+                    }
+                    self.index += 1;
+                }
+
+                self.source[start..self.index]
+                    .parse()
+                    .map(Value::Number)
+                    .map_err(|_| Error {
+                        kind: ErrorKind::InvalidNumber,
+                        offset: start,
+                    })
+            },
+            Some(byte) if is_name_start(byte) => Ok(match self.name()? {
+                "true"  => Value::Bool(true),
+                "false" => Value::Bool(false),
+                "null"  => Value::Null,
+                name    => Value::String(name.to_owned()),
+            }),
+            _ => Err(self.unexpected()),
+        }
+    }
+
+    fn string(&mut self, quote: u8) -> Result<Value> {
+        self.index += 1;
+
+        let mut value = String::new();
+        let mut chars = self.source[self.index..].char_indices();
+
+        while let Some((offset, character)) = chars.next() {
+            match character {
+                '\\' => match chars.next() {
+                    Some((_, escaped)) => value.push(escaped),
+                    None               => break,
+                },
+                _ if character as u32 == quote as u32 => {
+                    self.index += offset + 1;
+                    return Ok(Value::String(value));
+                },
+                _ => value.push(character),
+            }
+        }
+
+        self.index = self.source.len();
+
+        Err(self.error(ErrorKind::UnexpectedEnd))
+    }
+
+    fn pseudo_class(&mut self) -> Result<Filter> {
+        self.expect(b':')?;
+
+        let start = self.index;
+
+        match self.name()? {
+            "has" => {
+                self.expect(b'(')?;
+                let list = self.list(true)?;
+                self.whitespace();
+                self.expect(b')')?;
+
+                Ok(Filter::Has(list))
+            },
+            name => Err(Error {
+                kind: ErrorKind::UnknownPseudoClass(name.to_owned()),
+                offset: start,
+            }),
+        }
+    }
+}
@@ -1,108 +1,243 @@
 use ratel::ast::{Node, Statement, StatementNode};
 use ratel::ast::statement::*;
 
-use {Visitor, Visitable, ScopeKind, NoParent};
+use {Visitor, Visitable, NodePath, Ancestor, Control, ScopeKind, NoParent};
 
 
 impl<'ast> Visitable<'ast> for StatementNode<'ast> {
     type Parent = NoParent;
 
     #[inline]
-    fn traverse<V>(&self, visitor: &V, ctx: &mut V::Context)
+    fn visit<V>(&self, visitor: &V, path: &mut NodePath<'ast>, ctx: &mut V::Context)
     where
         V: Visitor<'ast>,
     {
         use self::Statement::*;
 
+        if path.is_stopped() {
+            return;
+        }
+
         match self.item {
             Empty => {},
-            Expression(ref expression)   => {
-                expression.traverse(visitor, ctx);
-                visitor.on_expression_statement(expression, self, ctx);
-            },
-            Declaration(ref declaration) => {
-                declaration.traverse(visitor, ctx);
-                visitor.on_declaration_statement(declaration, self, ctx);
-            },
-            Return(ref return_statement) => {
-                return_statement.traverse(visitor, ctx);
-                visitor.on_return_statement(return_statement, self, ctx);
-            },
-            Break(ref break_statement) => {
-                break_statement.traverse(visitor, ctx);
-                visitor.on_break_statement(break_statement, self, ctx);
-            },
-            Continue(ref continue_statement) => {
-                continue_statement.traverse(visitor, ctx);
-                visitor.on_continue_statement(continue_statement, self, ctx);
-            },
-            Throw(ref throw) => {
-                throw.traverse(visitor, ctx);
-                visitor.on_throw_statement(throw, self, ctx);
-            },
-            If(ref if_statement) => {
-                if_statement.traverse(visitor, ctx);
-                visitor.on_if_statement(if_statement, self, ctx);
-            },
-            While(ref while_statement) => {
-                while_statement.traverse(visitor, ctx);
-                visitor.on_while_statement(while_statement, self, ctx);
-            },
-            Do(ref do_statement) => {
-                do_statement.traverse(visitor, ctx);
-                visitor.on_do_statement(do_statement, self, ctx);
-            },
-            For(ref for_statement) => {
-                for_statement.traverse(visitor, ctx);
-                visitor.on_for_statement(for_statement, self, ctx);
-            },
-            ForIn(ref for_in) => {
-                for_in.traverse(visitor, ctx);
-                visitor.on_for_in_statement(for_in, self, ctx);
-            },
-            ForOf(ref for_of) => {
-                for_of.traverse(visitor, ctx);
-                visitor.on_for_of_statement(for_of, self, ctx);
-            },
-            Try(ref try) => {
-                try.traverse(visitor, ctx);
-                visitor.on_try_statement(try, self, ctx);
-            },
-            Labeled(ref labeled) => {
-                labeled.traverse(visitor, ctx);
-                visitor.on_labeled_statement(labeled, self, ctx);
-            },
-            Block(ref block) => {
-                block.traverse(visitor, ctx);
-                visitor.on_block_statement(block, self, ctx);
-            },
-            Switch(ref switch) => {
-                switch.traverse(visitor, ctx);
-                visitor.on_switch_statement(switch, self, ctx);
-            },
-            Function(ref function) => {
-                function.traverse(visitor, ctx);
-                visitor.on_function_statement(function, self, ctx);
-            },
-            Class(ref class) => {
-                class.traverse(visitor, ctx);
-                visitor.on_class_statement(class, self, ctx);
+            Expression(ref expression) => visit_node!(
+                path,
+                Ancestor::Statement(*self),
+                visitor.on_expression_statement(expression, self, path, ctx),
+                expression.visit(visitor, path, ctx),
+                visitor.on_leave_expression_statement(expression, self, path, ctx)
+            ),
+            Declaration(ref declaration) => visit_node!(
+                path,
+                Ancestor::Statement(*self),
+                visitor.on_declaration_statement(declaration, self, path, ctx),
+                declaration.visit(visitor, path, ctx),
+                visitor.on_leave_declaration_statement(declaration, self, path, ctx)
+            ),
+            Return(ref return_statement) => visit_node!(
+                path,
+                Ancestor::Statement(*self),
+                visitor.on_return_statement(return_statement, self, path, ctx),
+                return_statement.visit(visitor, path, ctx),
+                visitor.on_leave_return_statement(return_statement, self, path, ctx)
+            ),
+            Break(ref break_statement) => visit_node!(
+                path,
+                Ancestor::Statement(*self),
+                visitor.on_break_statement(break_statement, self, path, ctx),
+                break_statement.visit(visitor, path, ctx),
+                visitor.on_leave_break_statement(break_statement, self, path, ctx)
+            ),
+            Continue(ref continue_statement) => visit_node!(
+                path,
+                Ancestor::Statement(*self),
+                visitor.on_continue_statement(continue_statement, self, path, ctx),
+                continue_statement.visit(visitor, path, ctx),
+                visitor.on_leave_continue_statement(continue_statement, self, path, ctx)
+            ),
+            Throw(ref throw) => visit_node!(
+                path,
+                Ancestor::Statement(*self),
+                visitor.on_throw_statement(throw, self, path, ctx),
+                throw.visit(visitor, path, ctx),
+                visitor.on_leave_throw_statement(throw, self, path, ctx)
+            ),
+            If(ref if_statement) => visit_node!(
+                path,
+                Ancestor::Statement(*self),
+                visitor.on_if_statement(if_statement, self, path, ctx),
+                if_statement.visit(visitor, path, ctx),
+                visitor.on_leave_if_statement(if_statement, self, path, ctx)
+            ),
+            While(ref while_statement) => visit_node!(
+                path,
+                Ancestor::Statement(*self),
+                visitor.on_while_statement(while_statement, self, path, ctx),
+                while_statement.visit(visitor, path, ctx),
+                visitor.on_leave_while_statement(while_statement, self, path, ctx)
+            ),
+            With(ref with_statement) => visit_node!(
+                path,
+                Ancestor::Statement(*self),
+                visitor.on_with_statement(with_statement, self, path, ctx),
+                with_statement.visit(visitor, path, ctx),
+                visitor.on_leave_with_statement(with_statement, self, path, ctx)
+            ),
+            Do(ref do_statement) => visit_node!(
+                path,
+                Ancestor::Statement(*self),
+                visitor.on_do_statement(do_statement, self, path, ctx),
+                do_statement.visit(visitor, path, ctx),
+                visitor.on_leave_do_statement(do_statement, self, path, ctx)
+            ),
+            For(ref for_statement) => visit_node!(
+                path,
+                Ancestor::Statement(*self),
+                visitor.on_for_statement(for_statement, self, path, ctx),
+                for_statement.visit(visitor, path, ctx),
+                visitor.on_leave_for_statement(for_statement, self, path, ctx)
+            ),
+            ForIn(ref for_in) => visit_node!(
+                path,
+                Ancestor::Statement(*self),
+                visitor.on_for_in_statement(for_in, self, path, ctx),
+                for_in.visit(visitor, path, ctx),
+                visitor.on_leave_for_in_statement(for_in, self, path, ctx)
+            ),
+            ForOf(ref for_of) => visit_node!(
+                path,
+                Ancestor::Statement(*self),
+                visitor.on_for_of_statement(for_of, self, path, ctx),
+                for_of.visit(visitor, path, ctx),
+                visitor.on_leave_for_of_statement(for_of, self, path, ctx)
+            ),
+            Try(ref try) => visit_node!(
+                path,
+                Ancestor::Statement(*self),
+                visitor.on_try_statement(try, self, path, ctx),
+                try.visit(visitor, path, ctx),
+                visitor.on_leave_try_statement(try, self, path, ctx)
+            ),
+            Labeled(ref labeled) => visit_node!(
+                path,
+                Ancestor::Statement(*self),
+                visitor.on_labeled_statement(labeled, self, path, ctx),
+                labeled.visit(visitor, path, ctx),
+                visitor.on_leave_labeled_statement(labeled, self, path, ctx)
+            ),
+            Block(ref block) => visit_node!(
+                path,
+                Ancestor::Statement(*self),
+                visitor.on_block_statement(block, self, path, ctx),
+                block.visit(visitor, path, ctx),
+                visitor.on_leave_block_statement(block, self, path, ctx)
+            ),
+            Switch(ref switch) => visit_node!(
+                path,
+                Ancestor::Statement(*self),
+                visitor.on_switch_statement(switch, self, path, ctx),
+                switch.visit(visitor, path, ctx),
+                visitor.on_leave_switch_statement(switch, self, path, ctx)
+            ),
+            Function(ref function) => visit_node!(
+                path,
+                Ancestor::Statement(*self),
+                visitor.on_function_statement(function, self, path, ctx),
+                function.visit(visitor, path, ctx),
+                visitor.on_leave_function_statement(function, self, path, ctx)
+            ),
+            Class(ref class) => visit_node!(
+                path,
+                Ancestor::Statement(*self),
+                visitor.on_class_statement(class, self, path, ctx),
+                class.visit(visitor, path, ctx),
+                visitor.on_leave_class_statement(class, self, path, ctx)
+            ),
+            TypeAlias(ref alias) => visit_node!(
+                path,
+                Ancestor::Statement(*self),
+                visitor.on_type_alias_statement(alias, self, path, ctx),
+                {},
+                visitor.on_leave_type_alias_statement(alias, self, path, ctx)
+            ),
+            Declare(ref declare) => visit_node!(
+                path,
+                Ancestor::Statement(*self),
+                visitor.on_declare_statement(declare, self, path, ctx),
+                {},
+                visitor.on_leave_declare_statement(declare, self, path, ctx)
+            ),
+            Import(ref import) => visit_node!(
+                path,
+                Ancestor::Statement(*self),
+                visitor.on_import_statement(import, self, path, ctx),
+                import.visit(visitor, path, ctx),
+                visitor.on_leave_import_statement(import, self, path, ctx)
+            ),
+            Export(ref export) => visit_node!(
+                path,
+                Ancestor::Statement(*self),
+                visitor.on_export_statement(export, self, path, ctx),
+                export.visit(visitor, path, ctx),
+                visitor.on_leave_export_statement(export, self, path, ctx)
+            ),
+            // Only contains type declarations
+            Commented(_) => {}
+        }
+    }
+}
+
+impl<'ast> Visitable<'ast> for ImportStatement<'ast> {
+    type Parent = StatementNode<'ast>;
+
+    #[inline]
+    fn visit<V>(&self, visitor: &V, path: &mut NodePath<'ast>, ctx: &mut V::Context)
+    where
+        V: Visitor<'ast>,
+    {
+        // Flow type imports don't declare any runtime bindings
+        if self.kind != ImportKind::Value {
+            return;
+        }
+
+        for specifier in self.specifiers.iter() {
+            match specifier.item {
+                ImportSpecifier::Default(ref local)     |
+                ImportSpecifier::Namespace(ref local)   |
+                ImportSpecifier::Named { ref local, .. } => visitor.on_reference_declaration(local, path, ctx),
             }
         }
     }
 }
 
+impl<'ast> Visitable<'ast> for ExportStatement<'ast> {
+    type Parent = StatementNode<'ast>;
+
+    #[inline]
+    fn visit<V>(&self, visitor: &V, path: &mut NodePath<'ast>, ctx: &mut V::Context)
+    where
+        V: Visitor<'ast>,
+    {
+        match *self {
+            ExportStatement::Declaration(ref declaration) => declaration.visit(visitor, path, ctx),
+            ExportStatement::Default(ref expression)      => expression.visit(visitor, path, ctx),
+            ExportStatement::Named { .. } |
+            ExportStatement::All { .. }                   => {},
+        }
+    }
+}
+
 impl<'ast> Visitable<'ast> for BlockStatement<'ast> {
     type Parent = StatementNode<'ast>;
 
     #[inline]
-    fn traverse<V>(&self, visitor: &V, ctx: &mut V::Context)
+    fn visit<V>(&self, visitor: &V, path: &mut NodePath<'ast>, ctx: &mut V::Context)
     where
         V: Visitor<'ast>,
     {
-        visitor.on_enter_scope(ScopeKind::Block, ctx);
-        self.body.traverse(visitor, ctx);
-        visitor.on_leave_scope(ctx);
+        visitor.on_enter_scope(ScopeKind::Block, path, ctx);
+        self.body.visit(visitor, path, ctx);
+        visitor.on_leave_scope(path, ctx);
     }
 }
 
@@ -110,24 +245,26 @@ impl<'ast> Visitable<'ast> for Node<'ast, BlockStatement<'ast>> {
     type Parent = StatementNode<'ast>;
 
     #[inline]
-    fn traverse<V>(&self, visitor: &V, ctx: &mut V::Context)
+    fn visit<V>(&self, visitor: &V, path: &mut NodePath<'ast>, ctx: &mut V::Context)
     where
         V: Visitor<'ast>,
     {
-        self.item.traverse(visitor, ctx);
+        self.item.visit(visitor, path, ctx);
     }
 }
 
 impl<'ast> Visitable<'ast> for Declarator<'ast> {
     type Parent = Node<'ast, Self>;
 
+    node_hooks!(on_declarator, on_leave_declarator, Declarator);
+
     #[inline]
-    fn traverse<V>(&self, visitor: &V, ctx: &mut V::Context)
+    fn visit<V>(&self, visitor: &V, path: &mut NodePath<'ast>, ctx: &mut V::Context)
     where
         V: Visitor<'ast>,
     {
-        self.id.traverse(visitor, ctx);
-        self.init.traverse(visitor, ctx);
+        self.id.visit(visitor, path, ctx);
+        self.init.visit(visitor, path, ctx);
     }
 }
 
@@ -135,11 +272,11 @@ impl<'ast> Visitable<'ast> for DeclarationStatement<'ast> {
     type Parent = StatementNode<'ast>;
 
     #[inline]
-    fn traverse<V>(&self, visitor: &V, ctx: &mut V::Context)
+    fn visit<V>(&self, visitor: &V, path: &mut NodePath<'ast>, ctx: &mut V::Context)
     where
         V: Visitor<'ast>,
     {
-        self.declarators.traverse(visitor, ctx);
+        self.declarators.visit(visitor, path, ctx);
     }
 }
 
@@ -147,11 +284,11 @@ impl<'ast> Visitable<'ast> for ReturnStatement<'ast> {
     type Parent = StatementNode<'ast>;
 
     #[inline]
-    fn traverse<V>(&self, visitor: &V, ctx: &mut V::Context)
+    fn visit<V>(&self, visitor: &V, path: &mut NodePath<'ast>, ctx: &mut V::Context)
     where
         V: Visitor<'ast>,
     {
-        self.value.traverse(visitor, ctx);
+        self.value.visit(visitor, path, ctx);
     }
 }
 
@@ -159,7 +296,7 @@ impl<'ast> Visitable<'ast> for BreakStatement<'ast> {
     type Parent = StatementNode<'ast>;
 
     #[inline]
-    fn traverse<V: Visitor<'ast>>(&self, _visitor: &V, _ctx: &mut V::Context) {
+    fn visit<V: Visitor<'ast>>(&self, _visitor: &V, _path: &mut NodePath<'ast>, _ctx: &mut V::Context) {
         // FIXME:
         // INTENTIONALLY KEPT EMPTY FOR NOW!
         // The identifier here is a label reference, _not_ a variable!
@@ -170,7 +307,7 @@ impl<'ast> Visitable<'ast> for ContinueStatement<'ast> {
     type Parent = StatementNode<'ast>;
 
     #[inline]
-    fn traverse<V: Visitor<'ast>>(&self, _visitor: &V, _ctx: &mut V::Context) {
+    fn visit<V: Visitor<'ast>>(&self, _visitor: &V, _path: &mut NodePath<'ast>, _ctx: &mut V::Context) {
         // FIXME:
         // INTENTIONALLY KEPT EMPTY FOR NOW!
         // The identifier here is a label reference, _not_ a variable!
@@ -181,11 +318,11 @@ impl<'ast> Visitable<'ast> for ThrowStatement<'ast> {
     type Parent = StatementNode<'ast>;
 
     #[inline]
-    fn traverse<V>(&self, visitor: &V, ctx: &mut V::Context)
+    fn visit<V>(&self, visitor: &V, path: &mut NodePath<'ast>, ctx: &mut V::Context)
     where
         V: Visitor<'ast>,
     {
-        self.value.traverse(visitor, ctx);
+        self.value.visit(visitor, path, ctx);
     }
 }
 
@@ -193,13 +330,13 @@ impl<'ast> Visitable<'ast> for IfStatement<'ast> {
     type Parent = StatementNode<'ast>;
 
     #[inline]
-    fn traverse<V>(&self, visitor: &V, ctx: &mut V::Context)
+    fn visit<V>(&self, visitor: &V, path: &mut NodePath<'ast>, ctx: &mut V::Context)
     where
         V: Visitor<'ast>,
     {
-        self.test.traverse(visitor, ctx);
-        self.consequent.traverse(visitor, ctx);
-        self.alternate.traverse(visitor, ctx);
+        self.test.visit(visitor, path, ctx);
+        self.consequent.visit(visitor, path, ctx);
+        self.alternate.visit(visitor, path, ctx);
     }
 }
 
@@ -207,12 +344,25 @@ impl<'ast> Visitable<'ast> for WhileStatement<'ast> {
     type Parent = StatementNode<'ast>;
 
     #[inline]
-    fn traverse<V>(&self, visitor: &V, ctx: &mut V::Context)
+    fn visit<V>(&self, visitor: &V, path: &mut NodePath<'ast>, ctx: &mut V::Context)
+    where
+        V: Visitor<'ast>,
+    {
+        self.test.visit(visitor, path, ctx);
+        self.body.visit(visitor, path, ctx);
+    }
+}
+
+impl<'ast> Visitable<'ast> for WithStatement<'ast> {
+    type Parent = StatementNode<'ast>;
+
+    #[inline]
+    fn visit<V>(&self, visitor: &V, path: &mut NodePath<'ast>, ctx: &mut V::Context)
     where
         V: Visitor<'ast>,
     {
-        self.test.traverse(visitor, ctx);
-        self.body.traverse(visitor, ctx);
+        self.object.visit(visitor, path, ctx);
+        self.body.visit(visitor, path, ctx);
     }
 }
 
@@ -220,12 +370,12 @@ impl<'ast> Visitable<'ast> for DoStatement<'ast> {
     type Parent = StatementNode<'ast>;
 
     #[inline]
-    fn traverse<V>(&self, visitor: &V, ctx: &mut V::Context)
+    fn visit<V>(&self, visitor: &V, path: &mut NodePath<'ast>, ctx: &mut V::Context)
     where
         V: Visitor<'ast>,
     {
-        self.body.traverse(visitor, ctx);
-        self.test.traverse(visitor, ctx);
+        self.body.visit(visitor, path, ctx);
+        self.test.visit(visitor, path, ctx);
     }
 }
 
@@ -233,13 +383,13 @@ impl<'ast> Visitable<'ast> for ForInit<'ast> {
     type Parent = Node<'ast, Self>;
 
     #[inline]
-    fn traverse<V>(&self, visitor: &V, ctx: &mut V::Context)
+    fn visit<V>(&self, visitor: &V, path: &mut NodePath<'ast>, ctx: &mut V::Context)
     where
         V: Visitor<'ast>,
     {
         match *self {
-            ForInit::Declaration(ref declaration) => declaration.traverse(visitor, ctx),
-            ForInit::Expression(ref expression) => expression.traverse(visitor, ctx),
+            ForInit::Declaration(ref declaration) => declaration.visit(visitor, path, ctx),
+            ForInit::Expression(ref expression) => expression.visit(visitor, path, ctx),
         }
     }
 }
@@ -248,14 +398,17 @@ impl<'ast> Visitable<'ast> for ForStatement<'ast> {
     type Parent = StatementNode<'ast>;
 
     #[inline]
-    fn traverse<V>(&self, visitor: &V, ctx: &mut V::Context)
+    fn visit<V>(&self, visitor: &V, path: &mut NodePath<'ast>, ctx: &mut V::Context)
     where
         V: Visitor<'ast>,
     {
-        self.init.traverse(visitor, ctx);
-        self.test.traverse(visitor, ctx);
-        self.update.traverse(visitor, ctx);
-        self.body.traverse(visitor, ctx);
+        // Declarations in the head are only visible in the loop
+        visitor.on_enter_scope(ScopeKind::Block, path, ctx);
+        self.init.visit(visitor, path, ctx);
+        self.test.visit(visitor, path, ctx);
+        self.update.visit(visitor, path, ctx);
+        self.body.visit(visitor, path, ctx);
+        visitor.on_leave_scope(path, ctx);
     }
 }
 
@@ -263,13 +416,15 @@ impl<'ast> Visitable<'ast> for ForInStatement<'ast> {
     type Parent = StatementNode<'ast>;
 
     #[inline]
-    fn traverse<V>(&self, visitor: &V, ctx: &mut V::Context)
+    fn visit<V>(&self, visitor: &V, path: &mut NodePath<'ast>, ctx: &mut V::Context)
     where
         V: Visitor<'ast>,
     {
-        self.left.traverse(visitor, ctx);
-        self.right.traverse(visitor, ctx);
-        self.body.traverse(visitor, ctx);
+        visitor.on_enter_scope(ScopeKind::Block, path, ctx);
+        self.left.visit(visitor, path, ctx);
+        self.right.visit(visitor, path, ctx);
+        self.body.visit(visitor, path, ctx);
+        visitor.on_leave_scope(path, ctx);
     }
 }
 
@@ -277,26 +432,32 @@ impl<'ast> Visitable<'ast> for ForOfStatement<'ast> {
     type Parent = StatementNode<'ast>;
 
     #[inline]
-    fn traverse<V>(&self, visitor: &V, ctx: &mut V::Context)
+    fn visit<V>(&self, visitor: &V, path: &mut NodePath<'ast>, ctx: &mut V::Context)
     where
         V: Visitor<'ast>,
     {
-        self.left.traverse(visitor, ctx);
-        self.right.traverse(visitor, ctx);
-        self.body.traverse(visitor, ctx);
+        visitor.on_enter_scope(ScopeKind::Block, path, ctx);
+        self.left.visit(visitor, path, ctx);
+        self.right.visit(visitor, path, ctx);
+        self.body.visit(visitor, path, ctx);
+        visitor.on_leave_scope(path, ctx);
     }
 }
 
 impl<'ast> Visitable<'ast> for CatchClause<'ast> {
     type Parent = Node<'ast, Self>;
 
+    node_hooks!(on_catch_clause, on_leave_catch_clause, CatchClause);
+
     #[inline]
-    fn traverse<V>(&self, visitor: &V, ctx: &mut V::Context)
+    fn visit<V>(&self, visitor: &V, path: &mut NodePath<'ast>, ctx: &mut V::Context)
     where
         V: Visitor<'ast>,
     {
-        self.param.traverse(visitor, ctx);
-        self.body.traverse(visitor, ctx);
+        visitor.on_enter_scope(ScopeKind::Block, path, ctx);
+        self.param.visit(visitor, path, ctx);
+        self.body.visit(visitor, path, ctx);
+        visitor.on_leave_scope(path, ctx);
     }
 }
 
@@ -304,13 +465,13 @@ impl<'ast> Visitable<'ast> for TryStatement<'ast> {
     type Parent = StatementNode<'ast>;
 
     #[inline]
-    fn traverse<V>(&self, visitor: &V, ctx: &mut V::Context)
+    fn visit<V>(&self, visitor: &V, path: &mut NodePath<'ast>, ctx: &mut V::Context)
     where
         V: Visitor<'ast>,
     {
-        self.block.traverse(visitor, ctx);
-        self.handler.traverse(visitor, ctx);
-        self.finalizer.traverse(visitor, ctx);
+        self.block.visit(visitor, path, ctx);
+        self.handler.visit(visitor, path, ctx);
+        self.finalizer.visit(visitor, path, ctx);
     }
 }
 
@@ -318,25 +479,27 @@ impl<'ast> Visitable<'ast> for LabeledStatement<'ast> {
     type Parent = StatementNode<'ast>;
 
     #[inline]
-    fn traverse<V>(&self, visitor: &V, ctx: &mut V::Context)
+    fn visit<V>(&self, visitor: &V, path: &mut NodePath<'ast>, ctx: &mut V::Context)
     where
         V: Visitor<'ast>,
     {
         // FIXME: newtype for label
-        self.body.traverse(visitor, ctx);
+        self.body.visit(visitor, path, ctx);
     }
 }
 
 impl<'ast> Visitable<'ast> for SwitchCase<'ast> {
     type Parent = Node<'ast, Self>;
 
+    node_hooks!(on_switch_case, on_leave_switch_case, SwitchCase);
+
     #[inline]
-    fn traverse<V>(&self, visitor: &V, ctx: &mut V::Context)
+    fn visit<V>(&self, visitor: &V, path: &mut NodePath<'ast>, ctx: &mut V::Context)
     where
         V: Visitor<'ast>,
     {
-        self.test.traverse(visitor, ctx);
-        self.consequent.traverse(visitor, ctx);
+        self.test.visit(visitor, path, ctx);
+        self.consequent.visit(visitor, path, ctx);
     }
 }
 
@@ -344,13 +507,13 @@ impl<'ast> Visitable<'ast> for SwitchStatement<'ast> {
     type Parent = StatementNode<'ast>;
 
     #[inline]
-    fn traverse<V>(&self, visitor: &V, ctx: &mut V::Context)
+    fn visit<V>(&self, visitor: &V, path: &mut NodePath<'ast>, ctx: &mut V::Context)
     where
         V: Visitor<'ast>,
     {
-        self.discriminant.traverse(visitor, ctx);
-        visitor.on_enter_scope(ScopeKind::Block, ctx);
-        self.cases.body.traverse(visitor, ctx);
-        visitor.on_leave_scope(ctx);
+        self.discriminant.visit(visitor, path, ctx);
+        visitor.on_enter_scope(ScopeKind::Block, path, ctx);
+        self.cases.body.visit(visitor, path, ctx);
+        visitor.on_leave_scope(path, ctx);
     }
 }
@@ -13,7 +13,7 @@
 
   Module.onRuntimeInitialized = bindUpdate(function (a) {
     generateAST = Module.cwrap('generate_ast', 'string', ['string', 'number']);
-    generateASTEstree = Module.cwrap('generate_ast_estree', 'string', ['string', 'number']);
+    generateASTEstree = Module.cwrap('generate_ast_estree', 'string', ['string', 'number', 'number', 'number', 'number']);
     transform = Module.cwrap('transform', 'string', ['string', 'number']);
 
     if (Clipboard.isSupported()) {
@@ -69,7 +69,7 @@
       } else if (mode === 1) {
         output = generateAST(value, minify);
       } else if (mode === 2) {
-        output = generateASTEstree(value, minify);
+        output = generateASTEstree(value, minify, 0, 0, 0);
       }
       ast_output.innerHTML = output;
     }, immediate ? 0 : DISPLAY_TIMEOUT);
@@ -1,15 +1,23 @@
 extern crate ratel;
 extern crate ratel_codegen;
+extern crate ratel_transformer;
+extern crate ratel_visitor;
+#[macro_use]
 extern crate serde_json;
 
 use std::os::raw::c_char;
 use std::ffi::CStr;
 use std::ffi::CString;
 use ratel::error::{Error, ParseError};
+use ratel::{Estree, EstreeOptions, Flavor};
+use ratel_transformer::Settings;
+use ratel_visitor::query::{self, Selector};
 
 fn main() {}
 
-fn format_errors(errors: Vec<Error>, source: String) -> String {
+fn format_errors(errors: Vec<Error>, source: &[u8]) -> String {
+    let source = String::from_utf8_lossy(source).into_owned();
+
     let error = errors
     .into_iter()
     .map(|err| {
@@ -29,14 +37,15 @@ fn format_errors(errors: Vec<Error>, source: String) -> String {
 #[no_mangle]
 pub fn transform(i: *const c_char, minify: bool) -> *const c_char {
 	let data = unsafe {
-        CStr::from_ptr(i).to_str().unwrap()
+        CStr::from_ptr(i).to_bytes()
     };
 
-	let result = match ratel::parse(&data) {
+	let result = match ratel::parse_bytes(data) {
 		Ok(module) => {
+			ratel_transformer::transform(&module, Settings::target_es5());
 			ratel_codegen::codegen(&module, minify)
 		},
-		Err(errors) => format_errors(errors, data.to_string())
+		Err(errors) => format_errors(errors, data)
 	};
 
 	CString::new(result.as_str()).unwrap().into_raw()
@@ -45,10 +54,10 @@ pub fn transform(i: *const c_char, minify: bool) -> *const c_char {
 #[no_mangle]
 pub fn generate_ast(i: *const c_char, minify: bool) -> *const c_char {
 	let data = unsafe {
-        CStr::from_ptr(i).to_str().unwrap()
+        CStr::from_ptr(i).to_bytes()
     };
 
-	let result = match ratel::parse(&data) {
+	let result = match ratel::parse_bytes(data) {
 		Ok(module) => {
 			if minify {
 		    	format!("{:?}", module.body())
@@ -56,27 +65,66 @@ pub fn generate_ast(i: *const c_char, minify: bool) -> *const c_char {
 		    	format!("{:#?}", module.body())
 			}
 		},
-		Err(errors) => format_errors(errors, data.to_string())
+		Err(errors) => format_errors(errors, data)
 	};
 
 	CString::new(result.as_str()).unwrap().into_raw()
 }
 
 #[no_mangle]
-pub fn generate_ast_estree(i: *const c_char, minify: bool) -> *const c_char {
+pub fn generate_ast_estree(i: *const c_char, minify: bool, loc: bool, range: bool, babel: bool) -> *const c_char {
 	let data = unsafe {
-        CStr::from_ptr(i).to_str().unwrap()
+        CStr::from_ptr(i).to_bytes()
     };
 
-	let result = match ratel::parse(&data) {
+	let options = EstreeOptions {
+		loc,
+		range,
+		flavor: if babel { Flavor::Babel } else { Flavor::Estree },
+		..EstreeOptions::default()
+	};
+
+	let result = match ratel::parse_bytes(data) {
 		Ok(module) => {
+			let source = String::from_utf8_lossy(data);
+			let estree = Estree::new(&module, &source, options);
+
 			if minify {
-				serde_json::to_string(&module).unwrap()
+				serde_json::to_string(&estree).unwrap()
 			} else {
-				serde_json::to_string_pretty(&module).unwrap()
+				serde_json::to_string_pretty(&estree).unwrap()
 			}
 		},
-		Err(errors) => format_errors(errors, data.to_string())
+		Err(errors) => format_errors(errors, data)
+	};
+
+	CString::new(result.as_str()).unwrap().into_raw()
+}
+
+#[no_mangle]
+pub fn query(i: *const c_char, selector: *const c_char) -> *const c_char {
+	let data = unsafe {
+        CStr::from_ptr(i).to_bytes()
+    };
+	let selector = unsafe {
+        CStr::from_ptr(selector).to_string_lossy()
+    };
+
+	let result = match (ratel::parse_bytes(data), Selector::parse(&selector)) {
+		(Err(errors), _) => format_errors(errors, data),
+		(_, Err(error)) => format!("Error: {}", error),
+		(Ok(module), Ok(selector)) => {
+			let nodes = query::query(&module, &selector)
+				.iter()
+				.map(|node| json!({
+					"type": query::node_type(node),
+					"start": node.start(),
+					"end": node.end(),
+				}))
+				.collect::<Vec<_>>();
+
+			serde_json::to_string(&nodes).unwrap()
+		}
 	};
 
 	CString::new(result.as_str()).unwrap().into_raw()
@@ -109,7 +109,7 @@ fn tokenize(b: &mut Bencher) {
     b.bytes = SOURCE.len() as u64;
 
     b.iter(|| {
-        let mut lexer = unsafe { ratel::lexer::Lexer::from_ptr(ptr) };
+        let mut lexer = unsafe { ratel::lexer::Lexer::from_ptr(&arena, ptr) };
 
         while lexer.token != ratel::lexer::Token::EndOfProgram {
             lexer.consume()
@@ -0,0 +1,607 @@
+//! Deep copies of AST subtrees.
+//!
+//! Copying a `Node` only copies the reference it holds, so both copies point
+//! to the same subtree, and `set` on a node within one changes the other.
+//! `CloneIn` allocates a copy of every node in the subtree instead, either
+//! on the same `Arena` or on another one.
+
+use toolshed::Arena;
+use toolshed::list::List;
+use ast::*;
+use ast::expression::*;
+use ast::statement::*;
+use ast::types::*;
+
+/// Deep copy of an AST type, with all nodes and strings allocated
+/// on the given `Arena`.
+pub trait CloneIn<'dst> {
+    type Cloned: Copy + 'dst;
+
+    /// Copy the value and everything it references into the `Arena`.
+    fn clone_in(&self, arena: &'dst Arena) -> Self::Cloned;
+}
+
+impl<'ast, 'dst, T> CloneIn<'dst> for Node<'ast, T> where
+    T: 'ast + CloneIn<'dst>,
+{
+    type Cloned = Node<'dst, T::Cloned>;
+
+    #[inline]
+    fn clone_in(&self, arena: &'dst Arena) -> Self::Cloned {
+        Node::new(arena.alloc((**self).clone_in(arena)))
+    }
+}
+
+impl<'dst, T: CloneIn<'dst>> CloneIn<'dst> for Loc<T> {
+    type Cloned = Loc<T::Cloned>;
+
+    #[inline]
+    fn clone_in(&self, arena: &'dst Arena) -> Self::Cloned {
+        Loc::new(self.start, self.end, self.item.clone_in(arena))
+    }
+}
+
+impl<'ast, 'dst, T> CloneIn<'dst> for List<'ast, T> where
+    T: 'ast + CloneIn<'dst>,
+{
+    type Cloned = List<'dst, T::Cloned>;
+
+    #[inline]
+    fn clone_in(&self, arena: &'dst Arena) -> Self::Cloned {
+        List::from_iter(arena, self.iter().map(|item| item.clone_in(arena)))
+    }
+}
+
+impl<'dst, T: CloneIn<'dst>> CloneIn<'dst> for Option<T> {
+    type Cloned = Option<T::Cloned>;
+
+    #[inline]
+    fn clone_in(&self, arena: &'dst Arena) -> Self::Cloned {
+        self.as_ref().map(|item| item.clone_in(arena))
+    }
+}
+
+impl<'dst> CloneIn<'dst> for &str {
+    type Cloned = &'dst str;
+
+    #[inline]
+    fn clone_in(&self, arena: &'dst Arena) -> &'dst str {
+        arena.alloc_str(self)
+    }
+}
+
+/// Types that don't reference the arena are simply copied.
+macro_rules! impl_copy {
+    ($( $type:ty ),*) => ($(
+        impl<'dst> CloneIn<'dst> for $type {
+            type Cloned = $type;
+
+            #[inline]
+            fn clone_in(&self, _: &'dst Arena) -> $type {
+                *self
+            }
+        }
+    )*)
+}
+
+impl_copy! {
+    bool,
+    OperatorKind,
+    DeclarationKind,
+    MethodKind,
+    ImportKind,
+    ExportKind,
+    Primitive,
+    EmptyName,
+    ThisExpression
+}
+
+/// Structs are cloned field by field.
+macro_rules! impl_struct {
+    ($( $type:ident { $( $field:ident ),* } )*) => ($(
+        impl<'ast, 'dst> CloneIn<'dst> for $type<'ast> {
+            type Cloned = $type<'dst>;
+
+            #[inline]
+            fn clone_in(&self, arena: &'dst Arena) -> $type<'dst> {
+                $type {
+                    $( $field: self.$field.clone_in(arena) ),*
+                }
+            }
+        }
+    )*)
+}
+
+impl_struct! {
+    Program { source, body, hashbang }
+    SequenceExpression { body }
+    ArrayExpression { body }
+    MemberExpression { object, property }
+    ComputedMemberExpression { object, property }
+    CallExpression { callee, arguments }
+    BinaryExpression { operator, left, right }
+    PrefixExpression { operator, operand }
+    PostfixExpression { operator, operand }
+    ConditionalExpression { test, consequent, alternate }
+    TemplateLiteral { expressions, quasis }
+    TaggedTemplateExpression { tag, quasi }
+    SpreadExpression { argument }
+    ArrowExpression { type_params, params, return_type, body }
+    ObjectExpression { body }
+    TypeCastExpression { expression, annotation }
+    Declarator { id, init }
+    DeclarationStatement { kind, declarators }
+    ReturnStatement { value }
+    BreakStatement { label }
+    ContinueStatement { label }
+    ThrowStatement { value }
+    IfStatement { test, consequent, alternate }
+    WhileStatement { test, body }
+    DoStatement { body, test }
+    WithStatement { object, body }
+    ForStatement { init, test, update, body }
+    ForInStatement { left, right, body }
+    ForOfStatement { left, right, body }
+    CatchClause { param, body }
+    TryStatement { block, handler, finalizer }
+    LabeledStatement { label, body }
+    SwitchStatement { discriminant, cases }
+    SwitchCase { test, consequent }
+    TypeAliasStatement { opaque, name, type_params, supertype, value }
+    ImportStatement { kind, specifiers, source }
+    ExportSpecifier { local, exported }
+    TypeParameter { name, bound, default }
+    FunctionTypeParam { name, optional, annotation }
+    FunctionType { type_params, params, rest, return_type }
+}
+
+impl<'ast, 'dst, T> CloneIn<'dst> for Block<'ast, T> where
+    T: 'ast + CloneIn<'dst>,
+{
+    type Cloned = Block<'dst, T::Cloned>;
+
+    #[inline]
+    fn clone_in(&self, arena: &'dst Arena) -> Self::Cloned {
+        Block {
+            body: self.body.clone_in(arena),
+        }
+    }
+}
+
+impl<'ast, 'dst> CloneIn<'dst> for MandatoryName<'ast> {
+    type Cloned = MandatoryName<'dst>;
+
+    #[inline]
+    fn clone_in(&self, arena: &'dst Arena) -> MandatoryName<'dst> {
+        MandatoryName(self.0.clone_in(arena))
+    }
+}
+
+impl<'ast, 'dst> CloneIn<'dst> for OptionalName<'ast> {
+    type Cloned = OptionalName<'dst>;
+
+    #[inline]
+    fn clone_in(&self, arena: &'dst Arena) -> OptionalName<'dst> {
+        OptionalName(self.0.clone_in(arena))
+    }
+}
+
+impl<'ast, 'dst, N> CloneIn<'dst> for Function<'ast, N> where
+    N: Name<'ast> + CloneIn<'dst>,
+    N::Cloned: Name<'dst>,
+{
+    type Cloned = Function<'dst, N::Cloned>;
+
+    fn clone_in(&self, arena: &'dst Arena) -> Self::Cloned {
+        Function {
+            name: self.name.clone_in(arena),
+            type_params: self.type_params.clone_in(arena),
+            params: self.params.clone_in(arena),
+            return_type: self.return_type.clone_in(arena),
+            body: self.body.clone_in(arena),
+        }
+    }
+}
+
+impl<'ast, 'dst, N> CloneIn<'dst> for Class<'ast, N> where
+    N: Name<'ast> + CloneIn<'dst>,
+    N::Cloned: Name<'dst>,
+{
+    type Cloned = Class<'dst, N::Cloned>;
+
+    fn clone_in(&self, arena: &'dst Arena) -> Self::Cloned {
+        Class {
+            name: self.name.clone_in(arena),
+            type_params: self.type_params.clone_in(arena),
+            extends: self.extends.clone_in(arena),
+            super_type_args: self.super_type_args.clone_in(arena),
+            implements: self.implements.clone_in(arena),
+            body: self.body.clone_in(arena),
+        }
+    }
+}
+
+impl<'ast, 'dst> CloneIn<'dst> for ClassMember<'ast> {
+    type Cloned = ClassMember<'dst>;
+
+    fn clone_in(&self, arena: &'dst Arena) -> ClassMember<'dst> {
+        use self::ClassMember::*;
+
+        match *self {
+            Error => Error,
+            Method { is_static, ref key, kind, ref value } => Method {
+                is_static,
+                key: key.clone_in(arena),
+                kind,
+                value: value.clone_in(arena),
+            },
+            Literal { is_static, ref key, ref value } => Literal {
+                is_static,
+                key: key.clone_in(arena),
+                value: value.clone_in(arena),
+            },
+            Property { is_static, ref key, ref annotation, ref value } => Property {
+                is_static,
+                key: key.clone_in(arena),
+                annotation: annotation.clone_in(arena),
+                value: value.clone_in(arena),
+            },
+        }
+    }
+}
+
+impl<'ast, 'dst> CloneIn<'dst> for Literal<'ast> {
+    type Cloned = Literal<'dst>;
+
+    fn clone_in(&self, arena: &'dst Arena) -> Literal<'dst> {
+        use self::Literal::*;
+
+        match *self {
+            Undefined   => Undefined,
+            Null        => Null,
+            True        => True,
+            False       => False,
+            Number(raw) => Number(arena.alloc_str(raw)),
+            Binary(raw) => Binary(arena.alloc_str(raw)),
+            String(raw) => String(arena.alloc_str(raw)),
+            RegEx(raw)  => RegEx(arena.alloc_str(raw)),
+        }
+    }
+}
+
+impl<'ast, 'dst> CloneIn<'dst> for Pattern<'ast> {
+    type Cloned = Pattern<'dst>;
+
+    fn clone_in(&self, arena: &'dst Arena) -> Pattern<'dst> {
+        use self::Pattern::*;
+
+        match *self {
+            Void => Void,
+            Identifier(name) => Identifier(arena.alloc_str(name)),
+            ObjectPattern { ref properties } => ObjectPattern {
+                properties: properties.clone_in(arena),
+            },
+            ArrayPattern { ref elements } => ArrayPattern {
+                elements: elements.clone_in(arena),
+            },
+            RestElement { ref argument } => RestElement {
+                argument: argument.clone_in(arena),
+            },
+            AssignmentPattern { ref left, ref right } => AssignmentPattern {
+                left: left.clone_in(arena),
+                right: right.clone_in(arena),
+            },
+            Typed { ref pattern, optional, ref annotation } => Typed {
+                pattern: pattern.clone_in(arena),
+                optional,
+                annotation: annotation.clone_in(arena),
+            },
+        }
+    }
+}
+
+impl<'ast, 'dst> CloneIn<'dst> for PropertyKey<'ast> {
+    type Cloned = PropertyKey<'dst>;
+
+    fn clone_in(&self, arena: &'dst Arena) -> PropertyKey<'dst> {
+        match *self {
+            PropertyKey::Computed(ref expression) => PropertyKey::Computed(expression.clone_in(arena)),
+            PropertyKey::Literal(raw)             => PropertyKey::Literal(arena.alloc_str(raw)),
+            PropertyKey::Binary(raw)              => PropertyKey::Binary(arena.alloc_str(raw)),
+        }
+    }
+}
+
+impl<'ast, 'dst> CloneIn<'dst> for Property<'ast> {
+    type Cloned = Property<'dst>;
+
+    fn clone_in(&self, arena: &'dst Arena) -> Property<'dst> {
+        match *self {
+            Property::Shorthand(name) => Property::Shorthand(arena.alloc_str(name)),
+            Property::Literal { ref key, ref value } => Property::Literal {
+                key: key.clone_in(arena),
+                value: value.clone_in(arena),
+            },
+            Property::Method { ref key, ref value } => Property::Method {
+                key: key.clone_in(arena),
+                value: value.clone_in(arena),
+            },
+        }
+    }
+}
+
+impl<'ast, 'dst> CloneIn<'dst> for ArrowBody<'ast> {
+    type Cloned = ArrowBody<'dst>;
+
+    fn clone_in(&self, arena: &'dst Arena) -> ArrowBody<'dst> {
+        match *self {
+            ArrowBody::Expression(ref expression) => ArrowBody::Expression(expression.clone_in(arena)),
+            ArrowBody::Block(ref block)           => ArrowBody::Block(block.clone_in(arena)),
+        }
+    }
+}
+
+impl<'ast, 'dst> CloneIn<'dst> for Expression<'ast> {
+    type Cloned = Expression<'dst>;
+
+    fn clone_in(&self, arena: &'dst Arena) -> Expression<'dst> {
+        use self::Expression::*;
+
+        match *self {
+            Void                      => Void,
+            This(this)                => This(this),
+            Identifier(name)          => Identifier(arena.alloc_str(name)),
+            Literal(ref literal)      => Literal(literal.clone_in(arena)),
+            Sequence(ref item)        => Sequence(item.clone_in(arena)),
+            Array(ref item)           => Array(item.clone_in(arena)),
+            Member(ref item)          => Member(item.clone_in(arena)),
+            ComputedMember(ref item)  => ComputedMember(item.clone_in(arena)),
+            Call(ref item)            => Call(item.clone_in(arena)),
+            Binary(ref item)          => Binary(item.clone_in(arena)),
+            Prefix(ref item)          => Prefix(item.clone_in(arena)),
+            Postfix(ref item)         => Postfix(item.clone_in(arena)),
+            Conditional(ref item)     => Conditional(item.clone_in(arena)),
+            Template(ref item)        => Template(item.clone_in(arena)),
+            TaggedTemplate(ref item)  => TaggedTemplate(item.clone_in(arena)),
+            Spread(ref item)          => Spread(item.clone_in(arena)),
+            Arrow(ref item)           => Arrow(item.clone_in(arena)),
+            Object(ref item)          => Object(item.clone_in(arena)),
+            Function(ref item)        => Function(item.clone_in(arena)),
+            Class(ref item)           => Class(item.clone_in(arena)),
+            TypeCast(ref item)        => TypeCast(item.clone_in(arena)),
+        }
+    }
+}
+
+impl<'ast, 'dst> CloneIn<'dst> for ForInit<'ast> {
+    type Cloned = ForInit<'dst>;
+
+    fn clone_in(&self, arena: &'dst Arena) -> ForInit<'dst> {
+        match *self {
+            ForInit::Declaration(ref declaration) => ForInit::Declaration(declaration.clone_in(arena)),
+            ForInit::Expression(ref expression)   => ForInit::Expression(expression.clone_in(arena)),
+        }
+    }
+}
+
+impl<'ast, 'dst> CloneIn<'dst> for DeclareStatement<'ast> {
+    type Cloned = DeclareStatement<'dst>;
+
+    fn clone_in(&self, arena: &'dst Arena) -> DeclareStatement<'dst> {
+        use self::DeclareStatement::*;
+
+        match *self {
+            Variable { kind, ref name, ref annotation } => Variable {
+                kind,
+                name: name.clone_in(arena),
+                annotation: annotation.clone_in(arena),
+            },
+            Function { ref name, ref annotation } => Function {
+                name: name.clone_in(arena),
+                annotation: annotation.clone_in(arena),
+            },
+            Class { ref name, ref type_params, ref extends, ref body } => Class {
+                name: name.clone_in(arena),
+                type_params: type_params.clone_in(arena),
+                extends: extends.clone_in(arena),
+                body: body.clone_in(arena),
+            },
+            TypeAlias(ref alias) => TypeAlias(alias.clone_in(arena)),
+        }
+    }
+}
+
+impl<'ast, 'dst> CloneIn<'dst> for ImportSpecifier<'ast> {
+    type Cloned = ImportSpecifier<'dst>;
+
+    fn clone_in(&self, arena: &'dst Arena) -> ImportSpecifier<'dst> {
+        match *self {
+            ImportSpecifier::Default(name)   => ImportSpecifier::Default(arena.alloc_str(name)),
+            ImportSpecifier::Namespace(name) => ImportSpecifier::Namespace(arena.alloc_str(name)),
+            ImportSpecifier::Named { imported, local } => ImportSpecifier::Named {
+                imported: arena.alloc_str(imported),
+                local: arena.alloc_str(local),
+            },
+        }
+    }
+}
+
+impl<'ast, 'dst> CloneIn<'dst> for ExportStatement<'ast> {
+    type Cloned = ExportStatement<'dst>;
+
+    fn clone_in(&self, arena: &'dst Arena) -> ExportStatement<'dst> {
+        use self::ExportStatement::*;
+
+        match *self {
+            Declaration(ref statement) => Declaration(statement.clone_in(arena)),
+            Default(ref expression)    => Default(expression.clone_in(arena)),
+            Named { kind, ref specifiers, ref source } => Named {
+                kind,
+                specifiers: specifiers.clone_in(arena),
+                source: source.clone_in(arena),
+            },
+            All { ref source } => All {
+                source: source.clone_in(arena),
+            },
+        }
+    }
+}
+
+impl<'ast, 'dst> CloneIn<'dst> for Statement<'ast> {
+    type Cloned = Statement<'dst>;
+
+    fn clone_in(&self, arena: &'dst Arena) -> Statement<'dst> {
+        use self::Statement::*;
+
+        match *self {
+            Empty                  => Empty,
+            Expression(ref item)   => Expression(item.clone_in(arena)),
+            Declaration(ref item)  => Declaration(item.clone_in(arena)),
+            Return(ref item)       => Return(item.clone_in(arena)),
+            Break(ref item)        => Break(item.clone_in(arena)),
+            Continue(ref item)     => Continue(item.clone_in(arena)),
+            Throw(ref item)        => Throw(item.clone_in(arena)),
+            If(ref item)           => If(item.clone_in(arena)),
+            While(ref item)        => While(item.clone_in(arena)),
+            Do(ref item)           => Do(item.clone_in(arena)),
+            With(ref item)         => With(item.clone_in(arena)),
+            For(ref item)          => For(item.clone_in(arena)),
+            ForIn(ref item)        => ForIn(item.clone_in(arena)),
+            ForOf(ref item)        => ForOf(item.clone_in(arena)),
+            Try(ref item)          => Try(item.clone_in(arena)),
+            Block(ref item)        => Block(item.clone_in(arena)),
+            Labeled(ref item)      => Labeled(item.clone_in(arena)),
+            Function(ref item)     => Function(item.clone_in(arena)),
+            Class(ref item)        => Class(item.clone_in(arena)),
+            Switch(ref item)       => Switch(item.clone_in(arena)),
+            TypeAlias(ref item)    => TypeAlias(item.clone_in(arena)),
+            Declare(ref item)      => Declare(item.clone_in(arena)),
+            Import(ref item)       => Import(item.clone_in(arena)),
+            Export(ref item)       => Export(item.clone_in(arena)),
+            Commented(ref item)    => Commented(item.clone_in(arena)),
+        }
+    }
+}
+
+impl<'ast, 'dst> CloneIn<'dst> for ObjectTypeMember<'ast> {
+    type Cloned = ObjectTypeMember<'dst>;
+
+    fn clone_in(&self, arena: &'dst Arena) -> ObjectTypeMember<'dst> {
+        use self::ObjectTypeMember::*;
+
+        match *self {
+            Property { key, optional, method, ref value } => Property {
+                key: arena.alloc_str(key),
+                optional,
+                method,
+                value: value.clone_in(arena),
+            },
+            Indexer { ref name, ref key, ref value } => Indexer {
+                name: name.clone_in(arena),
+                key: key.clone_in(arena),
+                value: value.clone_in(arena),
+            },
+            Spread(ref argument) => Spread(argument.clone_in(arena)),
+        }
+    }
+}
+
+impl<'ast, 'dst> CloneIn<'dst> for Type<'ast> {
+    type Cloned = Type<'dst>;
+
+    fn clone_in(&self, arena: &'dst Arena) -> Type<'dst> {
+        use self::Type::*;
+
+        match *self {
+            Any                      => Any,
+            Mixed                    => Mixed,
+            Empty                    => Empty,
+            Existential              => Existential,
+            Primitive(primitive)     => Primitive(primitive),
+            StringLiteral(raw)       => StringLiteral(arena.alloc_str(raw)),
+            NumberLiteral(raw)       => NumberLiteral(arena.alloc_str(raw)),
+            BooleanLiteral(value)    => BooleanLiteral(value),
+            Identifier(name)         => Identifier(arena.alloc_str(name)),
+            Typeof(name)             => Typeof(arena.alloc_str(name)),
+            Nullable(ref item)       => Nullable(item.clone_in(arena)),
+            Array(ref item)          => Array(item.clone_in(arena)),
+            Union { ref variants }   => Union { variants: variants.clone_in(arena) },
+            Intersection { ref variants } => Intersection { variants: variants.clone_in(arena) },
+            Generic { ident, ref subtypes } => Generic {
+                ident: arena.alloc_str(ident),
+                subtypes: subtypes.clone_in(arena),
+            },
+            Tuple { ref elements }   => Tuple { elements: elements.clone_in(arena) },
+            Object { exact, ref members } => Object {
+                exact,
+                members: members.clone_in(arena),
+            },
+            Function(ref item)       => Function(item.clone_in(arena)),
+            Comment(ref item)        => Comment(item.clone_in(arena)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use parser::parse_in;
+    use parser::{ParseOptions, Goal};
+
+    #[test]
+    fn clone_is_equal() {
+        let arena = Arena::new();
+        let source = "
+            // @flow
+            import foo, { bar as baz } from 'foo';
+            type Maybe<T> = ?T | Array<T>;
+            var { a, b: [c, , ...d] } = e, f = `a${b}c`;
+            class Foo extends Bar { static baz = 1; qux(a: number = 2) { return this[a] || new Foo(a); } }
+            label: for (let i = 0; i < 10; i++) { if (i) continue label; else break; }
+            export default (x, y) => ({ x, y, [x]: /y/g });
+        ";
+
+        let options = ParseOptions { goal: Goal::Module, ..ParseOptions::default() };
+        let program = parse_in(source, &arena, options).unwrap();
+
+        let other = Arena::new();
+        let clone = program.clone_in(&other);
+
+        assert_eq!(clone, program);
+        assert_eq!(clone.source, program.source);
+        assert_ne!(clone.source.as_ptr(), program.source.as_ptr());
+    }
+
+    #[test]
+    fn clone_does_not_alias() {
+        let arena = Arena::new();
+        let program = parse_in("a.b + c;", &arena, ParseOptions::default()).unwrap();
+
+        let expression = match program.body.only_element().unwrap().item {
+            Statement::Expression(expression) => expression,
+            _ => panic!("Expected an expression"),
+        };
+
+        let clone = expression.clone_in(&arena);
+
+        if let Expression::Binary(ref binary) = clone.item {
+            binary.left.set(arena.alloc(Loc::new(0, 0, Expression::Identifier("d"))));
+        }
+
+        let original = parse_in("a.b + c;", &arena, ParseOptions::default()).unwrap();
+
+        assert_eq!(program, original);
+        assert_ne!(clone, expression);
+    }
+
+    #[test]
+    fn clone_keeps_locations() {
+        let arena = Arena::new();
+        let program = parse_in("foo(bar);", &arena, ParseOptions::default()).unwrap();
+
+        let statement = program.body.only_element().unwrap();
+        let clone = statement.clone_in(&arena);
+
+        assert_eq!((clone.start, clone.end), (statement.start, statement.end));
+    }
+}
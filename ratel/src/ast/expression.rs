@@ -1,14 +1,15 @@
 use ast::{Node, NodeList, Literal, OperatorKind, Function, Class, EmptyName, OptionalName};
 use ast::{Identifier, IdentifierNode, BlockNode, ExpressionNode, Statement, ExpressionList, Pattern};
+use ast::{TypeNode, TypeParameterList};
 
-#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 pub enum PropertyKey<'ast> {
     Computed(ExpressionNode<'ast>),
     Literal(&'ast str),
     Binary(&'ast str),
 }
 
-#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 pub enum Property<'ast> {
     Shorthand(&'ast str),
     Literal {
@@ -23,7 +24,7 @@ pub enum Property<'ast> {
 
 /// While not technically necessary, having a type
 /// helps with implementing the visitor pattern on AST.
-#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 pub struct ThisExpression;
 
 #[derive(Debug, PartialEq, Clone, Copy)]
@@ -36,13 +37,13 @@ pub struct ArrayExpression<'ast> {
     pub body: ExpressionList<'ast>
 }
 
-#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 pub struct MemberExpression<'ast> {
     pub object: ExpressionNode<'ast>,
     pub property: IdentifierNode<'ast>,
 }
 
-#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 pub struct ComputedMemberExpression<'ast> {
     pub object: ExpressionNode<'ast>,
     pub property: ExpressionNode<'ast>,
@@ -54,26 +55,26 @@ pub struct CallExpression<'ast> {
     pub arguments: ExpressionList<'ast>,
 }
 
-#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 pub struct BinaryExpression<'ast> {
     pub operator: OperatorKind,
     pub left: ExpressionNode<'ast>,
     pub right: ExpressionNode<'ast>,
 }
 
-#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 pub struct PrefixExpression<'ast> {
     pub operator: OperatorKind,
     pub operand: ExpressionNode<'ast>,
 }
 
-#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 pub struct PostfixExpression<'ast> {
     pub operator: OperatorKind,
     pub operand: ExpressionNode<'ast>,
 }
 
-#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 pub struct ConditionalExpression<'ast> {
     pub test: ExpressionNode<'ast>,
     pub consequent: ExpressionNode<'ast>,
@@ -86,18 +87,18 @@ pub struct TemplateLiteral<'ast> {
     pub quasis: NodeList<'ast, &'ast str>,
 }
 
-#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 pub struct TaggedTemplateExpression<'ast> {
     pub tag: ExpressionNode<'ast>,
     pub quasi: Node<'ast, TemplateLiteral<'ast>>
 }
 
-#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 pub struct SpreadExpression<'ast> {
     pub argument: ExpressionNode<'ast>
 }
 
-#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 pub enum ArrowBody<'ast> {
     Expression(ExpressionNode<'ast>),
     Block(BlockNode<'ast, Statement<'ast>>)
@@ -105,7 +106,9 @@ pub enum ArrowBody<'ast> {
 
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub struct ArrowExpression<'ast> {
+    pub type_params: TypeParameterList<'ast>,
     pub params: NodeList<'ast, Pattern<'ast>>,
+    pub return_type: Option<TypeNode<'ast>>,
     pub body: ArrowBody<'ast>,
 }
 
@@ -114,10 +117,17 @@ pub struct ObjectExpression<'ast> {
     pub body: NodeList<'ast, Property<'ast>>,
 }
 
+/// Flow type cast, `(foo: number)`
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct TypeCastExpression<'ast> {
+    pub expression: ExpressionNode<'ast>,
+    pub annotation: TypeNode<'ast>,
+}
+
 pub type FunctionExpression<'ast> = Function<'ast, OptionalName<'ast>>;
 pub type ClassExpression<'ast> = Class<'ast, OptionalName<'ast>>;
 
-#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 pub enum Expression<'ast> {
     Void,
     This(ThisExpression),
@@ -139,6 +149,7 @@ pub enum Expression<'ast> {
     Object(ObjectExpression<'ast>),
     Function(FunctionExpression<'ast>),
     Class(ClassExpression<'ast>),
+    TypeCast(TypeCastExpression<'ast>),
 }
 
 macro_rules! impl_from {
@@ -171,7 +182,8 @@ impl_from! {
     ArrowExpression<'ast> => Arrow,
     ObjectExpression<'ast> => Object,
     FunctionExpression<'ast> => Function,
-    ClassExpression<'ast> => Class
+    ClassExpression<'ast> => Class,
+    TypeCastExpression<'ast> => TypeCast
 }
 
 impl<'ast> Expression<'ast> {
@@ -1,17 +1,18 @@
 use ast::{Node, Loc, IdentifierNode, ExpressionNode};
 use ast::{BlockNode, Statement, PatternList, PropertyKey};
+use ast::{TypeNode, TypeList, TypeParameterList};
 
 pub trait Name<'ast>: Copy {
     fn empty() -> Self;
 }
 
-#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 pub struct EmptyName;
 
-#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 pub struct MandatoryName<'ast>(pub IdentifierNode<'ast>);
 
-#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 pub struct OptionalName<'ast>(pub Option<IdentifierNode<'ast>>);
 
 pub type Method<'ast> = Function<'ast, EmptyName>;
@@ -65,11 +66,13 @@ impl<'ast> From<Option<IdentifierNode<'ast>>> for OptionalName<'ast> {
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub struct Function<'ast, N: Name<'ast>> {
     pub name: N,
+    pub type_params: TypeParameterList<'ast>,
     pub params: PatternList<'ast>,
+    pub return_type: Option<TypeNode<'ast>>,
     pub body: BlockNode<'ast, Statement<'ast>>,
 }
 
-#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 pub enum MethodKind {
     Constructor,
     Method,
@@ -77,25 +80,35 @@ pub enum MethodKind {
     Set,
 }
 
-#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 pub enum ClassMember<'ast> {
     Error,
     Method {
         is_static: bool,
-        key: PropertyKey<'ast>,
+        key: Node<'ast, PropertyKey<'ast>>,
         kind: MethodKind,
         value: Node<'ast, Function<'ast, EmptyName>>,
     },
     Literal {
         is_static: bool,
-        key: PropertyKey<'ast>,
+        key: Node<'ast, PropertyKey<'ast>>,
         value: ExpressionNode<'ast>,
+    },
+    /// Class property with a Flow type annotation
+    Property {
+        is_static: bool,
+        key: Node<'ast, PropertyKey<'ast>>,
+        annotation: TypeNode<'ast>,
+        value: Option<ExpressionNode<'ast>>,
     }
 }
 
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub struct Class<'ast, N: Name<'ast>> {
     pub name: N,
+    pub type_params: TypeParameterList<'ast>,
     pub extends: Option<ExpressionNode<'ast>>,
+    pub super_type_args: TypeList<'ast>,
+    pub implements: TypeList<'ast>,
     pub body: BlockNode<'ast, ClassMember<'ast>>,
 }
@@ -0,0 +1,324 @@
+//! Structural hashing of AST subtrees.
+//!
+//! Just like `PartialEq`, `Hash` ignores the positions of the nodes, so two
+//! subtrees that are the same code hash the same, wherever they are found.
+//! Most types derive `Hash`, the ones holding a `List` implement it here.
+
+use std::hash::{Hash, Hasher};
+use std::mem;
+use toolshed::list::List;
+use ast::*;
+use ast::expression::*;
+use ast::statement::*;
+use ast::types::*;
+
+/// Hash the items of a list, prefixed by their count, like a slice.
+#[inline]
+fn hash_list<'ast, T: Hash, H: Hasher>(list: &List<'ast, T>, state: &mut H) {
+    state.write_usize(list.iter().count());
+
+    for item in list.iter() {
+        item.hash(state);
+    }
+}
+
+macro_rules! hash_field {
+    ($field:expr, $state:ident) => ($field.hash($state));
+    ($field:expr, $state:ident, list) => (hash_list(&$field, $state));
+}
+
+/// Structs are hashed field by field, `list` marks fields holding a `List`.
+macro_rules! impl_struct {
+    ($( $type:ident { $( $field:ident $(: $list:ident)* ),* } )*) => ($(
+        impl<'ast> Eq for $type<'ast> {}
+
+        impl<'ast> Hash for $type<'ast> {
+            #[inline]
+            fn hash<H: Hasher>(&self, state: &mut H) {
+                $( hash_field!(self.$field, state $(, $list)*); )*
+            }
+        }
+    )*)
+}
+
+impl_struct! {
+    Program { source, body: list, hashbang }
+    SequenceExpression { body: list }
+    ArrayExpression { body: list }
+    CallExpression { callee, arguments: list }
+    TemplateLiteral { expressions: list, quasis: list }
+    ArrowExpression { type_params: list, params: list, return_type, body }
+    ObjectExpression { body: list }
+    DeclarationStatement { kind, declarators: list }
+    SwitchCase { test, consequent: list }
+    TypeAliasStatement { opaque, name, type_params: list, supertype, value }
+    ImportStatement { kind, specifiers: list, source }
+    FunctionType { type_params: list, params: list, rest, return_type }
+}
+
+impl<'ast, T: Eq> Eq for Block<'ast, T> {}
+
+impl<'ast, T: Hash> Hash for Block<'ast, T> {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        hash_list(&self.body, state);
+    }
+}
+
+impl<'ast, N: Name<'ast> + Eq> Eq for Function<'ast, N> {}
+
+impl<'ast, N: Name<'ast> + Hash> Hash for Function<'ast, N> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.name.hash(state);
+        hash_list(&self.type_params, state);
+        hash_list(&self.params, state);
+        self.return_type.hash(state);
+        self.body.hash(state);
+    }
+}
+
+impl<'ast, N: Name<'ast> + Eq> Eq for Class<'ast, N> {}
+
+impl<'ast, N: Name<'ast> + Hash> Hash for Class<'ast, N> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.name.hash(state);
+        hash_list(&self.type_params, state);
+        self.extends.hash(state);
+        hash_list(&self.super_type_args, state);
+        hash_list(&self.implements, state);
+        self.body.hash(state);
+    }
+}
+
+impl<'ast> Eq for Pattern<'ast> {}
+
+impl<'ast> Hash for Pattern<'ast> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        use self::Pattern::*;
+
+        mem::discriminant(self).hash(state);
+
+        match *self {
+            Void => {},
+            Identifier(name) => name.hash(state),
+            ObjectPattern { ref properties } => hash_list(properties, state),
+            ArrayPattern { ref elements } => hash_list(elements, state),
+            RestElement { ref argument } => argument.hash(state),
+            AssignmentPattern { ref left, ref right } => {
+                left.hash(state);
+                right.hash(state);
+            },
+            Typed { ref pattern, optional, ref annotation } => {
+                pattern.hash(state);
+                optional.hash(state);
+                annotation.hash(state);
+            },
+        }
+    }
+}
+
+impl<'ast> Eq for DeclareStatement<'ast> {}
+
+impl<'ast> Hash for DeclareStatement<'ast> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        use self::DeclareStatement::*;
+
+        mem::discriminant(self).hash(state);
+
+        match *self {
+            Variable { kind, ref name, ref annotation } => {
+                kind.hash(state);
+                name.hash(state);
+                annotation.hash(state);
+            },
+            Function { ref name, ref annotation } => {
+                name.hash(state);
+                annotation.hash(state);
+            },
+            Class { ref name, ref type_params, ref extends, ref body } => {
+                name.hash(state);
+                hash_list(type_params, state);
+                extends.hash(state);
+                body.hash(state);
+            },
+            TypeAlias(ref alias) => alias.hash(state),
+        }
+    }
+}
+
+impl<'ast> Eq for ExportStatement<'ast> {}
+
+impl<'ast> Hash for ExportStatement<'ast> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        use self::ExportStatement::*;
+
+        mem::discriminant(self).hash(state);
+
+        match *self {
+            Declaration(ref statement) => statement.hash(state),
+            Default(ref expression) => expression.hash(state),
+            Named { kind, ref specifiers, ref source } => {
+                kind.hash(state);
+                hash_list(specifiers, state);
+                source.hash(state);
+            },
+            All { ref source } => source.hash(state),
+        }
+    }
+}
+
+impl<'ast> Eq for Type<'ast> {}
+
+impl<'ast> Hash for Type<'ast> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        use self::Type::*;
+
+        mem::discriminant(self).hash(state);
+
+        match *self {
+            Any | Mixed | Empty | Existential => {},
+            Primitive(primitive) => primitive.hash(state),
+            StringLiteral(raw) | NumberLiteral(raw) => raw.hash(state),
+            BooleanLiteral(value) => value.hash(state),
+            Identifier(name) | Typeof(name) => name.hash(state),
+            Nullable(ref item) | Array(ref item) | Comment(ref item) => item.hash(state),
+            Union { ref variants } | Intersection { ref variants } => hash_list(variants, state),
+            Generic { ident, ref subtypes } => {
+                ident.hash(state);
+                hash_list(subtypes, state);
+            },
+            Tuple { ref elements } => hash_list(elements, state),
+            Object { exact, ref members } => {
+                exact.hash(state);
+                hash_list(members, state);
+            },
+            Function(ref function) => function.hash(state),
+        }
+    }
+}
+
+/// 64-bit FNV-1a, which unlike `DefaultHasher` is specified, so that
+/// fingerprints don't depend on the process or the platform.
+struct FnvHasher(u64);
+
+impl Hasher for FnvHasher {
+    #[inline]
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    #[inline]
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x100_0000_01b3);
+        }
+    }
+
+    // Lengths and discriminants are hashed the same on every platform
+    #[inline]
+    fn write_u64(&mut self, value: u64) {
+        self.write(&value.to_le_bytes());
+    }
+
+    #[inline]
+    fn write_usize(&mut self, value: usize) {
+        self.write_u64(value as u64);
+    }
+
+    #[inline]
+    fn write_isize(&mut self, value: isize) {
+        self.write_u64(value as u64);
+    }
+}
+
+/// Get a stable fingerprint of a subtree. Subtrees that are the same code,
+/// regardless of where they are in the source, have the same fingerprint.
+///
+/// ```
+/// use ratel::ast::fingerprint;
+///
+/// let a = ratel::parse("foo(a + b);").unwrap();
+/// let b = ratel::parse("bar(); foo(a+b) ;").unwrap();
+///
+/// let first = a.body().first_element().unwrap();
+/// let second = b.body().iter().nth(1).unwrap();
+///
+/// assert_eq!(fingerprint(first), fingerprint(second));
+/// ```
+pub fn fingerprint<T: Hash + ?Sized>(value: &T) -> u64 {
+    let mut hasher = FnvHasher(0xcbf2_9ce4_8422_2325);
+
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::HashSet;
+    use parser::{parse_in, ParseOptions};
+    use toolshed::Arena;
+
+    fn statements<'ast>(source: &str, arena: &'ast Arena) -> Vec<StatementNode<'ast>> {
+        let program = parse_in(source, arena, ParseOptions::default()).unwrap();
+
+        program.body.iter().cloned().collect()
+    }
+
+    #[test]
+    fn same_code_same_fingerprint() {
+        let arena = Arena::new();
+        let body = statements("
+            function foo(a, b = 2) { return a ** b; }
+            x = [1, , { y }];
+            function foo(a, b=2) {return a**b}
+        ", &arena);
+
+        assert_eq!(body[0], body[2]);
+        assert_eq!(fingerprint(&body[0]), fingerprint(&body[2]));
+        assert_ne!(fingerprint(&body[0]), fingerprint(&body[1]));
+    }
+
+    #[test]
+    fn different_code_different_fingerprint() {
+        let arena = Arena::new();
+        let body = statements("
+            a + b;
+            a - b;
+            b + a;
+            [a, b];
+            [[a, b]];
+            [[a], b];
+            (a, b);
+            f(a, b);
+            f(a)(b);
+            `${a}b`;
+            `a${b}`;
+        ", &arena);
+
+        let fingerprints: HashSet<u64> = body.iter().map(fingerprint).collect();
+
+        assert_eq!(fingerprints.len(), body.len());
+    }
+
+    #[test]
+    fn fingerprint_across_arenas() {
+        let arena = Arena::new();
+        let other = Arena::new();
+        let body = statements("foo(bar);", &arena);
+
+        assert_eq!(fingerprint(&body[0]), fingerprint(&statements("foo( bar )", &other)[0]));
+        assert_eq!(fingerprint(&body[0]), fingerprint(&body[0].clone_in(&other)));
+    }
+
+    #[test]
+    fn hash_set_of_expressions() {
+        let arena = Arena::new();
+        let body = statements("a.b; c; a.b; a['b']; c;", &arena);
+
+        let unique: HashSet<&Statement> = body.iter().map(|statement| &statement.item).collect();
+
+        assert_eq!(unique.len(), 3);
+    }
+}
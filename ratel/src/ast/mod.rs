@@ -1,25 +1,30 @@
 #[macro_use]
 mod variable;
 mod operator;
-// mod types;
+pub mod types;
 mod function;
 mod literal;
 pub mod node;
 pub mod expression;
 pub mod statement;
+pub mod clone;
+pub mod hash;
 
 use toolshed::list::List;
 use std::ops::Deref;
+use std::hash::{Hash, Hasher};
 
 pub use ast::variable::*;
 pub use ast::operator::*;
 pub use ast::node::Node;
-// pub use ast::types::{Type, Primitive};
+pub use ast::types::{Type, Primitive, TypeParameter, TypeParameterList};
 pub use ast::expression::{Expression, Property, PropertyKey};
 pub use ast::statement::{Statement, Declarator, BlockStatement};
 pub use ast::function::{Function, Class, ClassMember, Method, MethodKind};
 pub use ast::function::{Name, EmptyName, OptionalName, MandatoryName};
 pub use ast::literal::Literal;
+pub use ast::clone::CloneIn;
+pub use ast::hash::fingerprint;
 
 
 #[derive(Debug, PartialEq, Clone, Copy)]
@@ -44,6 +49,12 @@ pub enum Pattern<'ast> {
     AssignmentPattern {
         left: Node<'ast, Pattern<'ast>>,
         right: ExpressionNode<'ast>,
+    },
+    /// Pattern with a Flow type annotation, `foo: number` or `foo?: number`
+    Typed {
+        pattern: Node<'ast, Pattern<'ast>>,
+        optional: bool,
+        annotation: TypeNode<'ast>,
     }
 }
 
@@ -59,8 +70,8 @@ pub type StatementNode<'ast> = Node<'ast, Statement<'ast>>;
 pub type StatementList<'ast> = NodeList<'ast, Statement<'ast>>;
 pub type IdentifierNode<'ast> = Node<'ast, &'ast str>;
 pub type IdentifierList<'ast> = NodeList<'ast, &'ast str>;
-// pub type TypeNode<'ast> = NodeList<'ast, Type<'ast>>;
-// pub type TypeList<'ast> = NodeList<'ast, Type<'ast>>;
+pub type TypeNode<'ast> = Node<'ast, Type<'ast>>;
+pub type TypeList<'ast> = NodeList<'ast, Type<'ast>>;
 
 #[derive(Debug, Clone)]
 pub struct Loc<T> {
@@ -80,9 +91,19 @@ impl<T> Deref for Loc<T> {
     }
 }
 
+/// A parsed source borrowed from an `Arena` owned by the caller,
+/// as produced by `parse_in`.
+#[derive(Debug, PartialEq, Clone, Copy)]
 pub struct Program<'ast> {
+    /// Source the program was parsed from, stored on the arena.
     pub source: &'ast str,
+
+    /// Top level statements.
     pub body: NodeList<'ast, Statement<'ast>>,
+
+    /// Interpreter directive from the first line of the source,
+    /// without the leading `#!`.
+    pub hashbang: Option<&'ast str>,
 }
 
 impl<T> Loc<T> {
@@ -103,6 +124,16 @@ impl<T: PartialEq> PartialEq for Loc<T> {
     }
 }
 
+impl<T: Eq> Eq for Loc<T> {}
+
+/// Like `PartialEq`, hashing ignores the position of the node.
+impl<T: Hash> Hash for Loc<T> {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.item.hash(state)
+    }
+}
+
 impl<'ast> Program<'ast> {
     #[inline]
     pub fn statements(&'ast self) -> &'ast NodeList<'ast, Statement<'ast>> {
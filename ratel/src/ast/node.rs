@@ -1,6 +1,7 @@
 use toolshed::CopyCell;
 use std::ops::Deref;
 use std::fmt::{self, Debug};
+use std::hash::{Hash, Hasher};
 use ast::Loc;
 
 /// `Node` is a specialized `Cell` that holds a reference to T instead of T.
@@ -45,6 +46,15 @@ impl<'ast, T: 'ast + PartialEq> PartialEq for Node<'ast, T> {
     }
 }
 
+impl<'ast, T: 'ast + Eq> Eq for Node<'ast, T> {}
+
+impl<'ast, T: 'ast + Hash> Hash for Node<'ast, T> {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.deref().hash(state)
+    }
+}
+
 impl<'ast, T: 'ast + Debug> Debug for Node<'ast, T> {
     #[inline]
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -1,7 +1,7 @@
 use lexer::Token;
 use lexer::Token::*;
 
-#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 pub enum OperatorKind {
     FatArrow,         //   …  => …
     New,              //     new …
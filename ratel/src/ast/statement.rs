@@ -1,7 +1,8 @@
 use ast::{Node, NodeList, DeclarationKind, Function, Class, MandatoryName, IdentifierNode};
 use ast::{ExpressionNode, StatementNode, StatementList, Block, BlockNode, Pattern};
+use ast::{Literal, TypeNode, TypeParameterList};
 
-#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 pub struct Declarator<'ast> {
     pub id: Node<'ast, Pattern<'ast>>,
     pub init: Option<ExpressionNode<'ast>>,
@@ -13,54 +14,60 @@ pub struct DeclarationStatement<'ast> {
     pub declarators: NodeList<'ast, Declarator<'ast>>,
 }
 
-#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 pub struct ReturnStatement<'ast> {
     pub value: Option<ExpressionNode<'ast>>,
 }
 
-#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 pub struct BreakStatement<'ast> {
     // TODO: This should be a `LabelNode`, with `Label` being a newtype for &str.
     pub label: Option<IdentifierNode<'ast>>,
 }
 
-#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 pub struct ContinueStatement<'ast> {
     // TODO: This should be a `LabelNode`, with `Label` being a newtype for &str.
     pub label: Option<IdentifierNode<'ast>>
 }
 
-#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 pub struct ThrowStatement<'ast> {
     pub value: ExpressionNode<'ast>
 }
 
-#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 pub struct IfStatement<'ast> {
     pub test: ExpressionNode<'ast>,
     pub consequent: StatementNode<'ast>,
     pub alternate: Option<StatementNode<'ast>>,
 }
 
-#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 pub struct WhileStatement<'ast> {
     pub test: ExpressionNode<'ast>,
     pub body: StatementNode<'ast>,
 }
 
-#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 pub struct DoStatement<'ast> {
     pub body: StatementNode<'ast>,
     pub test: ExpressionNode<'ast>,
 }
 
-#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct WithStatement<'ast> {
+    pub object: ExpressionNode<'ast>,
+    pub body: StatementNode<'ast>,
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 pub enum ForInit<'ast> {
     Declaration(DeclarationStatement<'ast>),
     Expression(ExpressionNode<'ast>)
 }
 
-#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 pub struct ForStatement<'ast> {
     pub init: Option<Node<'ast, ForInit<'ast>>>,
     pub test: Option<ExpressionNode<'ast>>,
@@ -68,40 +75,40 @@ pub struct ForStatement<'ast> {
     pub body: StatementNode<'ast>
 }
 
-#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 pub struct ForInStatement<'ast> {
     pub left: Node<'ast, ForInit<'ast>>,
     pub right: ExpressionNode<'ast>,
     pub body: StatementNode<'ast>
 }
 
-#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 pub struct ForOfStatement<'ast> {
     pub left: Node<'ast, ForInit<'ast>>,
     pub right: ExpressionNode<'ast>,
     pub body: StatementNode<'ast>
 }
 
-#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 pub struct CatchClause<'ast> {
     pub param: Node<'ast, Pattern<'ast>>,
     pub body: BlockNode<'ast, Statement<'ast>>,
 }
 
-#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 pub struct TryStatement<'ast> {
     pub block: BlockNode<'ast, Statement<'ast>>,
     pub handler: Option<Node<'ast, CatchClause<'ast>>>,
     pub finalizer: Option<BlockNode<'ast, Statement<'ast>>>,
 }
 
-#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 pub struct LabeledStatement<'ast> {
     pub label: &'ast str,
     pub body: StatementNode<'ast>,
 }
 
-#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 pub struct SwitchStatement<'ast> {
     pub discriminant: ExpressionNode<'ast>,
     pub cases: BlockNode<'ast, SwitchCase<'ast>>,
@@ -113,11 +120,100 @@ pub struct SwitchCase<'ast> {
     pub consequent: StatementList<'ast>,
 }
 
+/// Flow type alias, `type Foo = Bar;` or `opaque type Foo: Super = Bar;`
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct TypeAliasStatement<'ast> {
+    pub opaque: bool,
+    pub name: IdentifierNode<'ast>,
+    pub type_params: TypeParameterList<'ast>,
+    pub supertype: Option<TypeNode<'ast>>,
+    /// Only `None` for `declare opaque type`
+    pub value: Option<TypeNode<'ast>>,
+}
+
+/// Flow `declare` statements
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum DeclareStatement<'ast> {
+    Variable {
+        kind: DeclarationKind,
+        name: IdentifierNode<'ast>,
+        annotation: Option<TypeNode<'ast>>,
+    },
+    Function {
+        name: IdentifierNode<'ast>,
+        annotation: TypeNode<'ast>,
+    },
+    Class {
+        name: IdentifierNode<'ast>,
+        type_params: TypeParameterList<'ast>,
+        extends: Option<TypeNode<'ast>>,
+        body: TypeNode<'ast>,
+    },
+    TypeAlias(TypeAliasStatement<'ast>),
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum ImportKind {
+    Value,
+    /// Flow `import type`
+    Type,
+    /// Flow `import typeof`
+    Typeof,
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum ExportKind {
+    Value,
+    /// Flow `export type`
+    Type,
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum ImportSpecifier<'ast> {
+    Default(&'ast str),
+    Namespace(&'ast str),
+    Named {
+        imported: &'ast str,
+        local: &'ast str,
+    },
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct ImportStatement<'ast> {
+    pub kind: ImportKind,
+    pub specifiers: NodeList<'ast, ImportSpecifier<'ast>>,
+    pub source: Node<'ast, Literal<'ast>>,
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct ExportSpecifier<'ast> {
+    pub local: &'ast str,
+    pub exported: &'ast str,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ExportStatement<'ast> {
+    /// `export var a;`, `export function a() {}`, `export class A {}`
+    Declaration(StatementNode<'ast>),
+    /// `export default a;`
+    Default(ExpressionNode<'ast>),
+    /// `export { a, b as c };`, optionally `from` another module
+    Named {
+        kind: ExportKind,
+        specifiers: NodeList<'ast, ExportSpecifier<'ast>>,
+        source: Option<Node<'ast, Literal<'ast>>>,
+    },
+    /// `export * from 'a';`
+    All {
+        source: Node<'ast, Literal<'ast>>,
+    },
+}
+
 pub type BlockStatement<'ast> = Block<'ast, Statement<'ast>>;
 pub type FunctionStatement<'ast> = Function<'ast, MandatoryName<'ast>>;
 pub type ClassStatement<'ast> = Class<'ast, MandatoryName<'ast>>;
 
-#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 pub enum Statement<'ast> {
     Empty,
     Expression(ExpressionNode<'ast>),
@@ -129,6 +225,7 @@ pub enum Statement<'ast> {
     If(IfStatement<'ast>),
     While(WhileStatement<'ast>),
     Do(DoStatement<'ast>),
+    With(WithStatement<'ast>),
     For(ForStatement<'ast>),
     ForIn(ForInStatement<'ast>),
     ForOf(ForOfStatement<'ast>),
@@ -137,7 +234,14 @@ pub enum Statement<'ast> {
     Labeled(LabeledStatement<'ast>),
     Function(FunctionStatement<'ast>),
     Class(ClassStatement<'ast>),
-    Switch(SwitchStatement<'ast>)
+    Switch(SwitchStatement<'ast>),
+    TypeAlias(TypeAliasStatement<'ast>),
+    Declare(DeclareStatement<'ast>),
+    Import(ImportStatement<'ast>),
+    Export(ExportStatement<'ast>),
+    /// Statement that only exists for the type checker, kept in the
+    /// output inside of a `/*:: */` comment.
+    Commented(StatementNode<'ast>),
 }
 
 macro_rules! impl_from {
@@ -160,6 +264,7 @@ impl_from! {
     IfStatement => If,
     WhileStatement => While,
     DoStatement => Do,
+    WithStatement => With,
     ForStatement => For,
     ForInStatement => ForIn,
     ForOfStatement => ForOf,
@@ -169,7 +274,11 @@ impl_from! {
     ContinueStatement => Continue,
     FunctionStatement => Function,
     ClassStatement => Class,
-    SwitchStatement => Switch
+    SwitchStatement => Switch,
+    TypeAliasStatement => TypeAlias,
+    DeclareStatement => Declare,
+    ImportStatement => Import,
+    ExportStatement => Export
 }
 
 impl<'ast> From<DeclarationStatement<'ast>> for ForInit<'ast> {
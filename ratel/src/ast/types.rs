@@ -1,30 +1,132 @@
-use ast::{Loc, TypeNode, TypeList};
+use ast::{Node, NodeList, IdentifierNode, TypeNode, TypeList};
 
-static TYPE_ANY: &Loc<Type<'static>> = &Loc {
-    start: 0,
-    end: 0,
-    item: Type::Any
-};
-
-#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 pub enum Primitive {
     Number,
     String,
     Boolean,
     Null,
-    Undefined,
+    Void,
+    Symbol,
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct TypeParameter<'ast> {
+    pub name: &'ast str,
+    pub bound: Option<TypeNode<'ast>>,
+    pub default: Option<TypeNode<'ast>>,
+}
+
+pub type TypeParameterList<'ast> = NodeList<'ast, TypeParameter<'ast>>;
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct FunctionTypeParam<'ast> {
+    pub name: Option<IdentifierNode<'ast>>,
+    pub optional: bool,
+    pub annotation: TypeNode<'ast>,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct FunctionType<'ast> {
+    pub type_params: TypeParameterList<'ast>,
+    pub params: NodeList<'ast, FunctionTypeParam<'ast>>,
+    pub rest: Option<Node<'ast, FunctionTypeParam<'ast>>>,
+    pub return_type: TypeNode<'ast>,
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum ObjectTypeMember<'ast> {
+    Property {
+        key: &'ast str,
+        optional: bool,
+        method: bool,
+        value: TypeNode<'ast>,
+    },
+    Indexer {
+        name: Option<IdentifierNode<'ast>>,
+        key: TypeNode<'ast>,
+        value: TypeNode<'ast>,
+    },
+    Spread(TypeNode<'ast>),
 }
 
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub enum Type<'ast> {
     Any,
+    Mixed,
+    Empty,
+    /// The existential type `*`
+    Existential,
     Primitive(Primitive),
+    StringLiteral(&'ast str),
+    NumberLiteral(&'ast str),
+    BooleanLiteral(bool),
+    /// Can be a qualified name, such as `React.Node`
     Identifier(&'ast str),
+    Typeof(&'ast str),
+    Nullable(TypeNode<'ast>),
+    Array(TypeNode<'ast>),
     Union {
         variants: TypeList<'ast>,
     },
+    Intersection {
+        variants: TypeList<'ast>,
+    },
     Generic {
         ident: &'ast str,
         subtypes: TypeList<'ast>,
+    },
+    Tuple {
+        elements: TypeList<'ast>,
+    },
+    Object {
+        exact: bool,
+        members: NodeList<'ast, ObjectTypeMember<'ast>>,
+    },
+    Function(FunctionType<'ast>),
+    /// Annotation that has been moved to a comment by the transformer,
+    /// generates `/*: T */` instead of `: T`.
+    Comment(TypeNode<'ast>),
+}
+
+impl<'ast> From<Primitive> for Type<'ast> {
+    #[inline]
+    fn from(val: Primitive) -> Self {
+        Type::Primitive(val)
+    }
+}
+
+impl<'ast> From<FunctionType<'ast>> for Type<'ast> {
+    #[inline]
+    fn from(val: FunctionType<'ast>) -> Self {
+        Type::Function(val)
+    }
+}
+
+impl<'ast> Type<'ast> {
+    #[inline]
+    pub fn binding_power(&self) -> u8 {
+        use self::Type::*;
+
+        match *self {
+            Function(_) => 1,
+
+            Union { .. } => 2,
+
+            Intersection { .. } => 3,
+
+            Nullable(_) => 4,
+
+            Array(_) => 5,
+
+            Comment(ref annotation) => annotation.binding_power(),
+
+            _ => 100,
+        }
+    }
+
+    #[inline]
+    pub fn is_comment(&self) -> bool {
+        matches!(*self, Type::Comment(_))
     }
 }
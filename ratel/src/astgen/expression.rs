@@ -2,6 +2,7 @@ use serde::ser::{Serialize, Serializer, SerializeStruct};
 use ast::{Expression, Loc, OperatorKind};
 use ast::expression::*;
 use astgen::SerializeInLoc;
+use astgen::types::{TypeAnnotation, TypeParameters};
 
 #[inline]
 fn expression_type<'ast>(operator: OperatorKind, prefix: bool) -> &'static str {
@@ -118,7 +119,13 @@ impl<'ast> SerializeInLoc for ArrowExpression<'ast> {
         S: Serializer,
     {
         self.in_loc(serializer, "ArrowFunctionExpression", 2, |state| {
+            if !self.type_params.is_empty() {
+                state.serialize_field("typeParameters", &TypeParameters(self.type_params))?;
+            }
             state.serialize_field("params", &self.params)?;
+            if let Some(return_type) = self.return_type {
+                state.serialize_field("returnType", &TypeAnnotation::wrap(return_type))?;
+            }
             state.serialize_field("body", &Loc::new(0, 0, self.body))
         })
     }
@@ -152,7 +159,7 @@ impl<'ast> SerializeInLoc for PrefixExpression<'ast> {
                         state.serialize_field("arguments", arguments)
                     })
                 },
-                Literal(_) => {
+                _ => {
                     self.in_loc(serializer, expr_type, 2, |state| {
                         // 0 byte array, will be optimized away
                         let arguments: [(); 0] = [];
@@ -160,7 +167,6 @@ impl<'ast> SerializeInLoc for PrefixExpression<'ast> {
                         state.serialize_field("arguments", &arguments)
                     })
                 },
-                _ => unimplemented!()
             }
         } else {
             self.in_loc(serializer, expr_type, 3, |state| {
@@ -223,7 +229,9 @@ impl<'ast> SerializeInLoc for BinaryExpression<'ast> {
     where
         S: Serializer,
     {
-        self.in_loc(serializer, "BinaryExpression", 3, |state| {
+        let prefix = false;
+        let expr_type = expression_type(self.operator, prefix);
+        self.in_loc(serializer, expr_type, 3, |state| {
             state.serialize_field("operator", &self.operator)?;
             state.serialize_field("left", &self.left)?;
             state.serialize_field("right", &self.right)
@@ -263,6 +271,7 @@ impl<'ast> SerializeInLoc for Expression<'ast> {
             Arrow(ref expression)          => expression.serialize(serializer),
             Function(ref expression)       => expression.serialize(serializer),
             Class(ref expression)          => expression.serialize(serializer),
+            TypeCast(ref expression)       => expression.serialize(serializer),
         }
     }
 }
@@ -1304,8 +1313,8 @@ mod test {
                                 "type": "Identifier",
                                 "name": "bar",
                                 // FIXME
-                                "start": 0,
-                                "end": 0,
+                                "start": 12,
+                                "end": 15,
                             },
                             "value": {
                                 "type": "FunctionExpression",
@@ -1335,6 +1344,54 @@ mod test {
             "end": 22
         });
 
+        expect_parse!("class Foo { bar = 1; }", {
+            "type": "Program",
+            "body": [
+                {
+                    "type": "ClassDeclaration",
+                    "id": {
+                        "type": "Identifier",
+                        "name": "Foo",
+                        "start": 6,
+                        "end": 9,
+                    },
+                    "superClass": null,
+                    "body": {
+                        "type": "ClassBody",
+                        "body": [
+                        {
+                            "type": "ClassProperty",
+                            "static": false,
+                            "computed": false,
+                            "key": {
+                                "type": "Identifier",
+                                "name": "bar",
+                                // FIXME
+                                "start": 12,
+                                "end": 15,
+                            },
+                            "value": {
+                                "type": "Literal",
+                                "value": 1,
+                                "raw": "1",
+                                "start": 18,
+                                "end": 19,
+                            },
+                            "start": 12,
+                            "end": 19,
+                        }
+                        ],
+                        "start": 10,
+                        "end": 22
+                    },
+                    "start": 0,
+                    "end": 22,
+                }
+            ],
+            "start": 0,
+            "end": 22
+        });
+
         expect_parse!("class Foo { static bar() {} }", {
             "type": "Program",
             "body": [
@@ -1359,8 +1416,8 @@ mod test {
                                 "type": "Identifier",
                                 "name": "bar",
                                 // FIXME
-                                "start": 0,
-                                "end": 0,
+                                "start": 19,
+                                "end": 22,
                             },
                             "value": {
                                 "type": "FunctionExpression",
@@ -1,6 +1,7 @@
 use serde::ser::{Serialize, Serializer, SerializeStruct};
 use astgen::SerializeInLoc;
-use ast::{Loc, Function, Class, Name, MandatoryName, OptionalName, EmptyName, ClassMember, Block};
+use astgen::types::{TypeAnnotation, TypeParameters, TypeArguments};
+use ast::{Node, Function, Class, Name, MandatoryName, OptionalName, EmptyName, ClassMember, Block, PropertyKey};
 use ast::MethodKind;
 
 impl<'ast> Serialize for MethodKind {
@@ -18,6 +19,10 @@ impl<'ast> Serialize for MethodKind {
     }
 }
 
+fn is_computed(key: Node<PropertyKey>) -> bool {
+    matches!(key.item, PropertyKey::Computed(_))
+}
+
 impl<'ast> SerializeInLoc for ClassMember<'ast> {
     fn serialize<S>(&self, serializer: S) -> Result<S::SerializeStruct, S::Error>
     where
@@ -31,14 +36,27 @@ impl<'ast> SerializeInLoc for ClassMember<'ast> {
                 self.in_loc(serializer, "MethodDefinition", 5, |state| {
                     state.serialize_field("kind", &kind)?;
                     state.serialize_field("static", &is_static)?;
-                    state.serialize_field("computed", &false)?;
-                    // FIXME
-                    state.serialize_field("key", &Loc::new(0, 0, key))?;
+                    state.serialize_field("computed", &is_computed(key))?;
+                    state.serialize_field("key", &*key)?;
                     state.serialize_field("value", &value)
                 })
             },
-            Literal { is_static: _, key: _, value: _ } => {
-                unimplemented!()
+            Literal { is_static, key, value } => {
+                self.in_loc(serializer, "ClassProperty", 4, |state| {
+                    state.serialize_field("static", &is_static)?;
+                    state.serialize_field("computed", &is_computed(key))?;
+                    state.serialize_field("key", &*key)?;
+                    state.serialize_field("value", &value)
+                })
+            },
+            Property { is_static, key, annotation, value } => {
+                self.in_loc(serializer, "ClassProperty", 5, |state| {
+                    state.serialize_field("static", &is_static)?;
+                    state.serialize_field("computed", &is_computed(key))?;
+                    state.serialize_field("key", &*key)?;
+                    state.serialize_field("typeAnnotation", &TypeAnnotation::wrap(annotation))?;
+                    state.serialize_field("value", &value)
+                })
             }
         }
     }
@@ -107,7 +125,16 @@ where
     {
         self.in_loc(serializer, N::IN_CLASS, 3, |state| {
             state.serialize_field("id", &self.name)?;
+            if !self.type_params.is_empty() {
+                state.serialize_field("typeParameters", &TypeParameters(self.type_params))?;
+            }
             state.serialize_field("superClass", &self.extends)?;
+            if !self.super_type_args.is_empty() {
+                state.serialize_field("superTypeParameters", &TypeArguments(self.super_type_args))?;
+            }
+            if !self.implements.is_empty() {
+                state.serialize_field("implements", &self.implements)?;
+            }
             state.serialize_field("body", &self.body)
         })
     }
@@ -123,7 +150,13 @@ where
     {
         self.in_loc(serializer, N::IN_FUNCTION, 3, |state| {
             state.serialize_field("id", &self.name)?;
+            if !self.type_params.is_empty() {
+                state.serialize_field("typeParameters", &TypeParameters(self.type_params))?;
+            }
             state.serialize_field("params", &self.params)?;
+            if let Some(return_type) = self.return_type {
+                state.serialize_field("returnType", &TypeAnnotation::wrap(return_type))?;
+            }
             state.serialize_field("body", &self.body)
         })
     }
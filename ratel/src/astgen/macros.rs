@@ -5,10 +5,13 @@
 #[macro_export]
 macro_rules! expect_parse {
     ($expr:expr, $expected:tt) => {{
-        use $crate::parser::parse;
+        use $crate::parser::{parse_with_options, ParseOptions, Goal};
         use $crate::serde_json::to_value;
 
-        let module = parse($expr).unwrap();
+        let module = parse_with_options($expr, ParseOptions {
+            goal: Goal::Module,
+            ..ParseOptions::default()
+        }).unwrap();
         let result = to_value(&module).unwrap();
         let expected = json!($expected);
         assert_eq!(result, expected);
@@ -4,11 +4,15 @@ mod statement;
 mod expression;
 mod function;
 mod value;
+mod types;
+mod options;
 
 use serde::ser::{Serialize, Serializer, SerializeStruct};
 use ast::{Loc, Node};
 use module::Module;
 
+pub use self::options::{Estree, EstreeOptions, Flavor};
+
 pub trait SerializeInLoc {
     #[inline]
     fn in_loc<S, F>(&self, serializer: S, name: &'static str, length: usize, build: F) -> Result<S::SerializeStruct, S::Error>
@@ -16,7 +20,7 @@ pub trait SerializeInLoc {
         S: Serializer,
         F: FnOnce(&mut S::SerializeStruct) -> Result<(), S::Error>
     {
-        let mut state = serializer.serialize_struct(name, length + 3)?;
+        let mut state = serializer.serialize_struct(name, length + 1 + options::location_fields())?;
         state.serialize_field("type", name)?;
         build(&mut state).map(move |_| state)
     }
@@ -31,8 +35,7 @@ impl<'ast, T: SerializeInLoc> Serialize for Loc<T> {
         S: Serializer
     {
         let mut state = self.item.serialize(serializer)?;
-        state.serialize_field("start", &self.start)?;
-        state.serialize_field("end", &self.end)?;
+        options::serialize_location(&mut state, self.start, self.end)?;
         state.end()
     }
 }
@@ -46,6 +49,22 @@ impl<'ast, T: SerializeInLoc> Serialize for Node<'ast, T> {
     }
 }
 
+/// The hashbang of a `Module`, serialized as an `InterpreterDirective`.
+struct Interpreter<'ast>(&'ast str);
+
+impl<'ast> Serialize for Interpreter<'ast> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer
+    {
+        let name = "InterpreterDirective";
+        let mut state = serializer.serialize_struct(name, 2)?;
+        state.serialize_field("type", &name)?;
+        state.serialize_field("value", &self.0)?;
+        state.end()
+    }
+}
+
 impl<'ast> Serialize for Module<'ast> {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -67,11 +86,14 @@ impl<'ast> Serialize for Module<'ast> {
         }
 
         let name = "Program";
-        let mut state = serializer.serialize_struct(name, 4)?;
+        let length = 2 + self.hashbang().is_some() as usize + options::location_fields();
+        let mut state = serializer.serialize_struct(name, length)?;
         state.serialize_field("type", &name)?;
         state.serialize_field("body", &body)?;
-        state.serialize_field("start", &start)?;
-        state.serialize_field("end", &end)?;
+        if let Some(hashbang) = self.hashbang() {
+            state.serialize_field("interpreter", &Interpreter(hashbang))?;
+        }
+        options::serialize_location(&mut state, start, end)?;
         state.end()
     }
 }
@@ -107,4 +129,29 @@ mod test {
               "end": 4,
         });
     }
+
+    #[test]
+    fn test_generate_ast_interpreter() {
+        expect_parse!("#!/usr/bin/env node\nthis;", {
+            "type": "Program",
+            "body": [
+                {
+                    "type": "ExpressionStatement",
+                    "expression": {
+                        "type": "ThisExpression",
+                        "start": 20,
+                        "end": 24,
+                    },
+                    "start": 20,
+                    "end": 24,
+                }
+              ],
+              "interpreter": {
+                  "type": "InterpreterDirective",
+                  "value": "/usr/bin/env node",
+              },
+              "start": 20,
+              "end": 24,
+        });
+    }
 }
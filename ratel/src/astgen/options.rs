@@ -0,0 +1,465 @@
+use std::cell::RefCell;
+use serde::ser::{Serialize, Serializer, SerializeStruct};
+use module::Module;
+
+/// Node types and shapes used in the ESTree output.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Flavor {
+    /// Plain ESTree, as produced by Acorn or Esprima.
+    Estree,
+
+    /// The AST used by Babel, with `StringLiteral`, `NumericLiteral`,
+    /// `ObjectProperty`, `ObjectMethod` and friends, and the raw source of
+    /// literals moved into `extra`.
+    Babel,
+}
+
+/// Fields and flavor of the ESTree output, on top of `start` and `end`
+/// offsets, which are always present.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct EstreeOptions {
+    /// Add `loc` to every node, with the `line` and `column` of its start
+    /// and end. Lines start at 1, columns at 0 and count UTF-16 code units.
+    pub loc: bool,
+
+    /// Add `range` to every node, the `[start, end]` pair of offsets.
+    pub range: bool,
+
+    /// Add the `raw` source of literals.
+    pub raw: bool,
+
+    /// Node types and shapes to use.
+    pub flavor: Flavor,
+}
+
+impl Default for EstreeOptions {
+    #[inline]
+    fn default() -> Self {
+        EstreeOptions {
+            loc: false,
+            range: false,
+            raw: true,
+            flavor: Flavor::Estree,
+        }
+    }
+}
+
+/// Options and source in effect while a module is serialized by `Estree`.
+struct Context {
+    options: EstreeOptions,
+    /// Offsets at which the lines start.
+    lines: Vec<u32>,
+    /// Offsets following each character that takes more bytes in UTF-8
+    /// than code units in UTF-16, along with the total of the extra bytes
+    /// up to that offset.
+    wide: Vec<(u32, u32)>,
+}
+
+thread_local! {
+    static CONTEXT: RefCell<Option<Context>> = const { RefCell::new(None) };
+}
+
+impl Context {
+    fn new(source: &str, options: EstreeOptions) -> Self {
+        let mut lines = vec![0];
+        let mut wide = Vec::new();
+        let mut extra = 0;
+
+        // Line terminators as defined by the spec, with `\r\n` counted once
+        let mut chars = source.char_indices().peekable();
+
+        while let Some((index, character)) = chars.next() {
+            let next = index + character.len_utf8();
+
+            if character.len_utf8() > character.len_utf16() {
+                extra += (character.len_utf8() - character.len_utf16()) as u32;
+                wide.push((next as u32, extra));
+            }
+
+            match character {
+                '\r' => if let Some(&(_, '\n')) = chars.peek() {
+                    continue;
+                },
+                '\n' | '\u{2028}' | '\u{2029}' => {},
+                _ => continue,
+            }
+
+            lines.push(next as u32);
+        }
+
+        Context {
+            options,
+            lines,
+            wide,
+        }
+    }
+
+    /// Convert a byte offset into an offset in UTF-16 code units.
+    fn utf16_offset(&self, offset: u32) -> u32 {
+        let extra = match self.wide.binary_search_by_key(&offset, |&(end, _)| end) {
+            Ok(index)  => self.wide[index].1,
+            Err(0)     => 0,
+            Err(index) => self.wide[index - 1].1,
+        };
+
+        offset - extra
+    }
+
+    fn position(&self, offset: u32) -> Position {
+        let line = match self.lines.binary_search(&offset) {
+            Ok(line)  => line,
+            Err(line) => line - 1,
+        };
+
+        let column = self.utf16_offset(offset) - self.utf16_offset(self.lines[line]);
+
+        Position {
+            line: line + 1,
+            column: column as usize,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct Position {
+    line: usize,
+    column: usize,
+}
+
+#[derive(Serialize)]
+struct SourceLocation {
+    start: Position,
+    end: Position,
+}
+
+/// Get the options in effect, the defaults outside of `Estree`.
+#[inline]
+pub fn options() -> EstreeOptions {
+    CONTEXT.with(|context| {
+        context.borrow().as_ref().map(|context| context.options).unwrap_or_default()
+    })
+}
+
+/// Number of location fields added to every node with the options in
+/// effect, `start` and `end` along with `range` and `loc` if enabled.
+pub fn location_fields() -> usize {
+    let options = options();
+
+    2 + options.range as usize + options.loc as usize
+}
+
+/// Serialize the location fields of a node.
+pub fn serialize_location<S>(state: &mut S, start: u32, end: u32) -> Result<(), S::Error>
+where
+    S: SerializeStruct
+{
+    state.serialize_field("start", &start)?;
+    state.serialize_field("end", &end)?;
+
+    CONTEXT.with(|context| {
+        let context = context.borrow();
+        let context = match *context {
+            Some(ref context) => context,
+            None              => return Ok(()),
+        };
+
+        if context.options.range {
+            state.serialize_field("range", &[start, end])?;
+        }
+
+        if context.options.loc {
+            state.serialize_field("loc", &SourceLocation {
+                start: context.position(start),
+                end: context.position(end),
+            })?;
+        }
+
+        Ok(())
+    })
+}
+
+/// A `Module` serialized to ESTree with the given `EstreeOptions`.
+///
+/// ```
+/// use ratel::{Estree, EstreeOptions, Flavor};
+///
+/// let source = "foo('bar');";
+/// let module = ratel::parse(source).unwrap();
+/// let options = EstreeOptions {
+///     range: true,
+///     flavor: Flavor::Babel,
+///     ..EstreeOptions::default()
+/// };
+///
+/// let json = serde_json::to_string(&Estree::new(&module, source, options)).unwrap();
+///
+/// assert!(json.contains(r#""type":"StringLiteral""#));
+/// assert!(json.contains(r#""range":[4,9]"#));
+/// ```
+pub struct Estree<'a, 'ast: 'a> {
+    module: &'a Module<'ast>,
+    source: &'a str,
+    options: EstreeOptions,
+}
+
+impl<'a, 'ast> Estree<'a, 'ast> {
+    /// Create a serializable view of a module, along with the source it
+    /// has been parsed from, which is needed for `loc`.
+    #[inline]
+    pub fn new(module: &'a Module<'ast>, source: &'a str, options: EstreeOptions) -> Self {
+        Estree {
+            module,
+            source,
+            options,
+        }
+    }
+}
+
+/// Restores the previous `Context` when dropped, even if serialization panics.
+struct Guard(Option<Context>);
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        let previous = self.0.take();
+
+        CONTEXT.with(|context| *context.borrow_mut() = previous);
+    }
+}
+
+impl<'a, 'ast> Serialize for Estree<'a, 'ast> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer
+    {
+        let context = Context::new(self.source, self.options);
+        let _guard = Guard(CONTEXT.with(|current| current.borrow_mut().replace(context)));
+
+        self.module.serialize(serializer)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use parser::parse;
+    use serde_json::to_value;
+
+    fn estree(source: &str, options: EstreeOptions) -> ::serde_json::Value {
+        let module = parse(source).unwrap();
+
+        to_value(&Estree::new(&module, source, options)).unwrap()
+    }
+
+    #[test]
+    fn default_options() {
+        let module = parse("foo;").unwrap();
+
+        assert_eq!(
+            estree("foo;", EstreeOptions::default()),
+            to_value(&module).unwrap()
+        );
+    }
+
+    #[test]
+    fn range_and_loc() {
+        let options = EstreeOptions {
+            loc: true,
+            range: true,
+            ..EstreeOptions::default()
+        };
+
+        assert_eq!(estree("a;\r\n 'ł' + b;", options), json!({
+            "type": "Program",
+            "body": [
+                {
+                    "type": "ExpressionStatement",
+                    "expression": {
+                        "type": "Identifier",
+                        "name": "a",
+                        "start": 0,
+                        "end": 1,
+                        "range": [0, 1],
+                        "loc": { "start": { "line": 1, "column": 0 }, "end": { "line": 1, "column": 1 } },
+                    },
+                    "start": 0,
+                    "end": 1,
+                    "range": [0, 1],
+                    "loc": { "start": { "line": 1, "column": 0 }, "end": { "line": 1, "column": 1 } },
+                },
+                {
+                    "type": "ExpressionStatement",
+                    "expression": {
+                        "type": "BinaryExpression",
+                        "operator": "+",
+                        "left": {
+                            "type": "Literal",
+                            "value": "ł",
+                            "raw": "'ł'",
+                            "start": 5,
+                            "end": 9,
+                            "range": [5, 9],
+                            "loc": { "start": { "line": 2, "column": 1 }, "end": { "line": 2, "column": 4 } },
+                        },
+                        "right": {
+                            "type": "Identifier",
+                            "name": "b",
+                            "start": 12,
+                            "end": 13,
+                            "range": [12, 13],
+                            "loc": { "start": { "line": 2, "column": 7 }, "end": { "line": 2, "column": 8 } },
+                        },
+                        "start": 5,
+                        "end": 13,
+                        "range": [5, 13],
+                        "loc": { "start": { "line": 2, "column": 1 }, "end": { "line": 2, "column": 8 } },
+                    },
+                    "start": 5,
+                    "end": 13,
+                    "range": [5, 13],
+                    "loc": { "start": { "line": 2, "column": 1 }, "end": { "line": 2, "column": 8 } },
+                },
+            ],
+            "start": 0,
+            "end": 13,
+            "range": [0, 13],
+            "loc": { "start": { "line": 1, "column": 0 }, "end": { "line": 2, "column": 8 } },
+        }));
+    }
+
+    #[test]
+    fn loc_columns_in_utf16() {
+        let options = EstreeOptions {
+            loc: true,
+            ..EstreeOptions::default()
+        };
+
+        let tree = estree("'😀ł' + a;\n'ł😀' + b;", options);
+
+        assert_eq!(tree["body"][0]["expression"]["right"]["loc"], json!({
+            "start": { "line": 1, "column": 8 },
+            "end": { "line": 1, "column": 9 },
+        }));
+        assert_eq!(tree["body"][1]["expression"]["right"]["loc"], json!({
+            "start": { "line": 2, "column": 8 },
+            "end": { "line": 2, "column": 9 },
+        }));
+    }
+
+    #[test]
+    fn without_raw() {
+        let options = EstreeOptions {
+            raw: false,
+            ..EstreeOptions::default()
+        };
+
+        assert_eq!(estree("0x10;", options)["body"][0]["expression"], json!({
+            "type": "Literal",
+            "value": 16,
+            "start": 0,
+            "end": 4,
+        }));
+    }
+
+    #[test]
+    fn babel_literals() {
+        let options = EstreeOptions {
+            flavor: Flavor::Babel,
+            ..EstreeOptions::default()
+        };
+
+        let tree = estree("['a', 1.5, true, null, /b/g];", options);
+
+        assert_eq!(tree["body"][0]["expression"]["elements"], json!([
+            {
+                "type": "StringLiteral",
+                "value": "a",
+                "extra": { "rawValue": "a", "raw": "'a'" },
+                "start": 1,
+                "end": 4,
+            },
+            {
+                "type": "NumericLiteral",
+                "value": 1.5,
+                "extra": { "rawValue": 1.5, "raw": "1.5" },
+                "start": 6,
+                "end": 9,
+            },
+            {
+                "type": "BooleanLiteral",
+                "value": true,
+                "start": 11,
+                "end": 15,
+            },
+            {
+                "type": "NullLiteral",
+                "start": 17,
+                "end": 21,
+            },
+            {
+                "type": "RegExpLiteral",
+                "pattern": "b",
+                "flags": "g",
+                "extra": { "raw": "/b/g" },
+                "start": 23,
+                "end": 27,
+            },
+        ]));
+    }
+
+    #[test]
+    fn babel_object() {
+        let options = EstreeOptions {
+            flavor: Flavor::Babel,
+            ..EstreeOptions::default()
+        };
+
+        let tree = estree("({ a: 1, b() {} });", options);
+
+        assert_eq!(tree["body"][0]["expression"]["properties"], json!([
+            {
+                "type": "ObjectProperty",
+                "key": {
+                    "type": "Identifier",
+                    "name": "a",
+                    "start": 3,
+                    "end": 4,
+                },
+                "method": false,
+                "shorthand": false,
+                "computed": false,
+                "value": {
+                    "type": "NumericLiteral",
+                    "value": 1,
+                    "extra": { "rawValue": 1, "raw": "1" },
+                    "start": 6,
+                    "end": 7,
+                },
+                "start": 3,
+                "end": 7,
+            },
+            {
+                "type": "ObjectMethod",
+                "kind": "method",
+                "key": {
+                    "type": "Identifier",
+                    "name": "b",
+                    "start": 9,
+                    "end": 10,
+                },
+                "computed": false,
+                "id": null,
+                "generator": false,
+                "async": false,
+                "params": [],
+                "body": {
+                    "type": "BlockStatement",
+                    "body": [],
+                    "start": 13,
+                    "end": 15,
+                },
+                "start": 9,
+                "end": 15,
+            },
+        ]));
+    }
+}
@@ -1,5 +1,5 @@
 use ast::statement::*;
-use ast::{Block, DeclarationKind, Declarator, Loc, Statement};
+use ast::{Block, DeclarationKind, Declarator, Loc, NodeList, Statement};
 use astgen::SerializeInLoc;
 use serde::ser::{Serialize, SerializeStruct, Serializer};
 
@@ -203,6 +203,107 @@ impl<'ast> SerializeInLoc for IfStatement<'ast> {
     }
 }
 
+impl Serialize for ImportKind {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match *self {
+            ImportKind::Value  => serializer.serialize_str("value"),
+            ImportKind::Type   => serializer.serialize_str("type"),
+            ImportKind::Typeof => serializer.serialize_str("typeof"),
+        }
+    }
+}
+
+impl Serialize for ExportKind {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match *self {
+            ExportKind::Value => serializer.serialize_str("value"),
+            ExportKind::Type  => serializer.serialize_str("type"),
+        }
+    }
+}
+
+impl<'ast> SerializeInLoc for ImportSpecifier<'ast> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::SerializeStruct, S::Error>
+    where
+        S: Serializer,
+    {
+        use self::ImportSpecifier::*;
+
+        match *self {
+            Default(local) => self.in_loc(serializer, "ImportDefaultSpecifier", 1, |state| {
+                state.serialize_field("local", &Loc::new(0, 0, local))
+            }),
+            Namespace(local) => self.in_loc(serializer, "ImportNamespaceSpecifier", 1, |state| {
+                state.serialize_field("local", &Loc::new(0, 0, local))
+            }),
+            Named { imported, local } => self.in_loc(serializer, "ImportSpecifier", 2, |state| {
+                state.serialize_field("imported", &Loc::new(0, 0, imported))?;
+                state.serialize_field("local", &Loc::new(0, 0, local))
+            }),
+        }
+    }
+}
+
+impl<'ast> SerializeInLoc for ImportStatement<'ast> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::SerializeStruct, S::Error>
+    where
+        S: Serializer,
+    {
+        self.in_loc(serializer, "ImportDeclaration", 3, |state| {
+            state.serialize_field("importKind", &self.kind)?;
+            state.serialize_field("specifiers", &self.specifiers)?;
+            state.serialize_field("source", &self.source)
+        })
+    }
+}
+
+impl<'ast> SerializeInLoc for ExportSpecifier<'ast> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::SerializeStruct, S::Error>
+    where
+        S: Serializer,
+    {
+        self.in_loc(serializer, "ExportSpecifier", 2, |state| {
+            state.serialize_field("local", &Loc::new(0, 0, self.local))?;
+            state.serialize_field("exported", &Loc::new(0, 0, self.exported))
+        })
+    }
+}
+
+impl<'ast> SerializeInLoc for ExportStatement<'ast> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::SerializeStruct, S::Error>
+    where
+        S: Serializer,
+    {
+        use self::ExportStatement::*;
+
+        match *self {
+            Declaration(ref declaration) => self.in_loc(serializer, "ExportNamedDeclaration", 3, |state| {
+                state.serialize_field("declaration", declaration)?;
+                state.serialize_field("specifiers", &NodeList::<ExportSpecifier>::empty())?;
+                state.serialize_field("source", &())
+            }),
+            Default(ref declaration) => self.in_loc(serializer, "ExportDefaultDeclaration", 1, |state| {
+                state.serialize_field("declaration", declaration)
+            }),
+            Named { kind, ref specifiers, ref source } => self.in_loc(serializer, "ExportNamedDeclaration", 4, |state| {
+                state.serialize_field("exportKind", &kind)?;
+                state.serialize_field("declaration", &())?;
+                state.serialize_field("specifiers", specifiers)?;
+                state.serialize_field("source", source)
+            }),
+            All { ref source } => self.in_loc(serializer, "ExportAllDeclaration", 1, |state| {
+                state.serialize_field("source", source)
+            }),
+        }
+    }
+}
+
 impl<'ast> SerializeInLoc for Statement<'ast> {
     fn serialize<S>(&self, serializer: S) -> Result<S::SerializeStruct, S::Error>
     where
@@ -245,6 +346,12 @@ impl<'ast> SerializeInLoc for Statement<'ast> {
                     state.serialize_field("body", body)
                 })
             }
+            With(WithStatement { ref object, ref body }) => {
+                self.in_loc(serializer, "WithStatement", 2, |state| {
+                    state.serialize_field("object", object)?;
+                    state.serialize_field("body", body)
+                })
+            }
             Do(DoStatement { ref body, ref test }) => {
                 self.in_loc(serializer, "DoWhileStatement", 2, |state| {
                     state.serialize_field("body", body)?;
@@ -260,6 +367,11 @@ impl<'ast> SerializeInLoc for Statement<'ast> {
             Function(statement) => statement.serialize(serializer),
             Class(statement) => statement.serialize(serializer),
             Switch(statement) => statement.serialize(serializer),
+            TypeAlias(statement) => statement.serialize(serializer),
+            Declare(statement) => statement.serialize(serializer),
+            Import(statement) => statement.serialize(serializer),
+            Export(statement) => statement.serialize(serializer),
+            Commented(statement) => statement.item.serialize(serializer),
         }
     }
 }
@@ -1018,4 +1130,49 @@ mod test {
             "end": 33,
         });
     }
+
+    #[test]
+    fn test_export_all_declaration() {
+        expect_parse!("export * from 'foo';", {
+            "type": "Program",
+            "body": [
+                {
+                    "type": "ExportAllDeclaration",
+                    "source": {
+                        "type": "Literal",
+                        "value": "foo",
+                        "raw": "'foo'",
+                        "start": 14,
+                        "end": 19,
+                    },
+                    "start": 0,
+                    "end": 19,
+                }
+            ],
+            "start": 0,
+            "end": 19,
+        });
+    }
+
+    #[test]
+    fn test_export_default_declaration() {
+        expect_parse!("export default foo;", {
+            "type": "Program",
+            "body": [
+                {
+                    "type": "ExportDefaultDeclaration",
+                    "declaration": {
+                        "type": "Identifier",
+                        "name": "foo",
+                        "start": 15,
+                        "end": 18,
+                    },
+                    "start": 0,
+                    "end": 18,
+                }
+            ],
+            "start": 0,
+            "end": 18,
+        });
+    }
 }
@@ -0,0 +1,393 @@
+use serde::ser::{Serialize, Serializer, SerializeStruct};
+use ast::{Loc, Type, TypeNode, TypeList, Primitive, TypeParameter, TypeParameterList};
+use ast::types::{FunctionType, FunctionTypeParam, ObjectTypeMember};
+use ast::statement::{TypeAliasStatement, DeclareStatement};
+use ast::expression::TypeCastExpression;
+use astgen::SerializeInLoc;
+
+/// Babel wraps every type in a `TypeAnnotation` node when it's used as an annotation.
+pub struct TypeAnnotation<'ast>(TypeNode<'ast>);
+
+impl<'ast> TypeAnnotation<'ast> {
+    #[inline]
+    pub fn wrap(annotation: TypeNode<'ast>) -> Loc<Self> {
+        Loc::new(annotation.start, annotation.end, TypeAnnotation(annotation))
+    }
+}
+
+impl<'ast> SerializeInLoc for TypeAnnotation<'ast> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::SerializeStruct, S::Error>
+    where
+        S: Serializer,
+    {
+        self.in_loc(serializer, "TypeAnnotation", 1, |state| {
+            state.serialize_field("typeAnnotation", &self.0)
+        })
+    }
+}
+
+pub struct TypeParameters<'ast>(pub TypeParameterList<'ast>);
+
+impl<'ast> Serialize for TypeParameters<'ast> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let name = "TypeParameterDeclaration";
+        let mut state = serializer.serialize_struct(name, 2)?;
+        state.serialize_field("type", &name)?;
+        state.serialize_field("params", &self.0)?;
+        state.end()
+    }
+}
+
+pub struct TypeArguments<'ast>(pub TypeList<'ast>);
+
+impl<'ast> Serialize for TypeArguments<'ast> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let name = "TypeParameterInstantiation";
+        let mut state = serializer.serialize_struct(name, 2)?;
+        state.serialize_field("type", &name)?;
+        state.serialize_field("params", &self.0)?;
+        state.end()
+    }
+}
+
+impl<'ast> SerializeInLoc for TypeParameter<'ast> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::SerializeStruct, S::Error>
+    where
+        S: Serializer,
+    {
+        self.in_loc(serializer, "TypeParameter", 3, |state| {
+            state.serialize_field("name", &self.name)?;
+            state.serialize_field("bound", &self.bound.map(TypeAnnotation::wrap))?;
+            state.serialize_field("default", &self.default)
+        })
+    }
+}
+
+impl<'ast> SerializeInLoc for FunctionTypeParam<'ast> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::SerializeStruct, S::Error>
+    where
+        S: Serializer,
+    {
+        self.in_loc(serializer, "FunctionTypeParam", 3, |state| {
+            state.serialize_field("name", &self.name)?;
+            state.serialize_field("optional", &self.optional)?;
+            state.serialize_field("typeAnnotation", &self.annotation)
+        })
+    }
+}
+
+impl<'ast> SerializeInLoc for FunctionType<'ast> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::SerializeStruct, S::Error>
+    where
+        S: Serializer,
+    {
+        self.in_loc(serializer, "FunctionTypeAnnotation", 4, |state| {
+            if !self.type_params.is_empty() {
+                state.serialize_field("typeParameters", &TypeParameters(self.type_params))?;
+            }
+            state.serialize_field("params", &self.params)?;
+            state.serialize_field("rest", &self.rest)?;
+            state.serialize_field("returnType", &self.return_type)
+        })
+    }
+}
+
+impl<'ast> SerializeInLoc for ObjectTypeMember<'ast> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::SerializeStruct, S::Error>
+    where
+        S: Serializer,
+    {
+        use self::ObjectTypeMember::*;
+
+        match *self {
+            Property { key, optional, method, value } => {
+                self.in_loc(serializer, "ObjectTypeProperty", 4, |state| {
+                    // FIXME
+                    state.serialize_field("key", &Loc::new(0, 0, key))?;
+                    state.serialize_field("value", &value)?;
+                    state.serialize_field("optional", &optional)?;
+                    state.serialize_field("method", &method)
+                })
+            },
+            Indexer { name, key, value } => {
+                self.in_loc(serializer, "ObjectTypeIndexer", 3, |state| {
+                    state.serialize_field("id", &name)?;
+                    state.serialize_field("key", &key)?;
+                    state.serialize_field("value", &value)
+                })
+            },
+            Spread(argument) => {
+                self.in_loc(serializer, "ObjectTypeSpreadProperty", 1, |state| {
+                    state.serialize_field("argument", &argument)
+                })
+            },
+        }
+    }
+}
+
+impl<'ast> SerializeInLoc for Type<'ast> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::SerializeStruct, S::Error>
+    where
+        S: Serializer,
+    {
+        use self::Type::*;
+
+        match *self {
+            Any         => self.in_loc(serializer, "AnyTypeAnnotation", 0, |_| Ok(())),
+            Mixed       => self.in_loc(serializer, "MixedTypeAnnotation", 0, |_| Ok(())),
+            Empty       => self.in_loc(serializer, "EmptyTypeAnnotation", 0, |_| Ok(())),
+            Existential => self.in_loc(serializer, "ExistsTypeAnnotation", 0, |_| Ok(())),
+            Primitive(primitive) => {
+                let name = match primitive {
+                    self::Primitive::Number  => "NumberTypeAnnotation",
+                    self::Primitive::String  => "StringTypeAnnotation",
+                    self::Primitive::Boolean => "BooleanTypeAnnotation",
+                    self::Primitive::Null    => "NullLiteralTypeAnnotation",
+                    self::Primitive::Void    => "VoidTypeAnnotation",
+                    self::Primitive::Symbol  => "SymbolTypeAnnotation",
+                };
+
+                self.in_loc(serializer, name, 0, |_| Ok(()))
+            },
+            StringLiteral(raw) => {
+                self.in_loc(serializer, "StringLiteralTypeAnnotation", 2, |state| {
+                    state.serialize_field("value", &raw[1..raw.len() - 1])?;
+                    state.serialize_field("raw", &raw)
+                })
+            },
+            NumberLiteral(raw) => {
+                self.in_loc(serializer, "NumberLiteralTypeAnnotation", 2, |state| {
+                    state.serialize_field("value", &raw.parse::<f64>().ok())?;
+                    state.serialize_field("raw", &raw)
+                })
+            },
+            BooleanLiteral(value) => {
+                self.in_loc(serializer, "BooleanLiteralTypeAnnotation", 1, |state| {
+                    state.serialize_field("value", &value)
+                })
+            },
+            Identifier(ident) => {
+                self.in_loc(serializer, "GenericTypeAnnotation", 2, |state| {
+                    state.serialize_field("id", &Loc::new(0, 0, ident))?;
+                    state.serialize_field("typeParameters", &None::<()>)
+                })
+            },
+            Generic { ident, subtypes } => {
+                self.in_loc(serializer, "GenericTypeAnnotation", 2, |state| {
+                    state.serialize_field("id", &Loc::new(0, 0, ident))?;
+                    state.serialize_field("typeParameters", &TypeArguments(subtypes))
+                })
+            },
+            Typeof(ident) => {
+                self.in_loc(serializer, "TypeofTypeAnnotation", 1, |state| {
+                    state.serialize_field("argument", &Loc::new(0, 0, ident))
+                })
+            },
+            Nullable(inner) => {
+                self.in_loc(serializer, "NullableTypeAnnotation", 1, |state| {
+                    state.serialize_field("typeAnnotation", &inner)
+                })
+            },
+            Array(inner) => {
+                self.in_loc(serializer, "ArrayTypeAnnotation", 1, |state| {
+                    state.serialize_field("elementType", &inner)
+                })
+            },
+            Union { variants } => {
+                self.in_loc(serializer, "UnionTypeAnnotation", 1, |state| {
+                    state.serialize_field("types", &variants)
+                })
+            },
+            Intersection { variants } => {
+                self.in_loc(serializer, "IntersectionTypeAnnotation", 1, |state| {
+                    state.serialize_field("types", &variants)
+                })
+            },
+            Tuple { elements } => {
+                self.in_loc(serializer, "TupleTypeAnnotation", 1, |state| {
+                    state.serialize_field("types", &elements)
+                })
+            },
+            Object { exact, members } => {
+                self.in_loc(serializer, "ObjectTypeAnnotation", 3, |state| {
+                    let (indexers, properties): (Vec<&_>, Vec<&_>) = members.iter().partition(|member| {
+                        matches!(member.item, ObjectTypeMember::Indexer { .. })
+                    });
+
+                    state.serialize_field("exact", &exact)?;
+                    state.serialize_field("properties", &properties)?;
+                    state.serialize_field("indexers", &indexers)
+                })
+            },
+            Function(ref function) => function.serialize(serializer),
+            Comment(inner) => inner.item.serialize(serializer),
+        }
+    }
+}
+
+impl<'ast> SerializeInLoc for TypeCastExpression<'ast> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::SerializeStruct, S::Error>
+    where
+        S: Serializer,
+    {
+        self.in_loc(serializer, "TypeCastExpression", 2, |state| {
+            state.serialize_field("expression", &self.expression)?;
+            state.serialize_field("typeAnnotation", &TypeAnnotation::wrap(self.annotation))
+        })
+    }
+}
+
+impl<'ast> SerializeInLoc for TypeAliasStatement<'ast> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::SerializeStruct, S::Error>
+    where
+        S: Serializer,
+    {
+        let type_params = match self.type_params.is_empty() {
+            true  => None,
+            false => Some(TypeParameters(self.type_params)),
+        };
+
+        match self.opaque {
+            true => self.in_loc(serializer, "OpaqueType", 4, |state| {
+                state.serialize_field("id", &self.name)?;
+                state.serialize_field("typeParameters", &type_params)?;
+                state.serialize_field("supertype", &self.supertype)?;
+                state.serialize_field("impltype", &self.value)
+            }),
+            false => self.in_loc(serializer, "TypeAlias", 3, |state| {
+                state.serialize_field("id", &self.name)?;
+                state.serialize_field("typeParameters", &type_params)?;
+                state.serialize_field("right", &self.value)
+            }),
+        }
+    }
+}
+
+impl<'ast> SerializeInLoc for DeclareStatement<'ast> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::SerializeStruct, S::Error>
+    where
+        S: Serializer,
+    {
+        use self::DeclareStatement::*;
+
+        match *self {
+            Variable { kind, name, annotation } => {
+                self.in_loc(serializer, "DeclareVariable", 3, |state| {
+                    state.serialize_field("kind", &kind)?;
+                    state.serialize_field("id", &name)?;
+                    state.serialize_field("typeAnnotation", &annotation.map(TypeAnnotation::wrap))
+                })
+            },
+            Function { name, annotation } => {
+                self.in_loc(serializer, "DeclareFunction", 2, |state| {
+                    state.serialize_field("id", &name)?;
+                    state.serialize_field("typeAnnotation", &TypeAnnotation::wrap(annotation))
+                })
+            },
+            Class { name, type_params, extends, body } => {
+                self.in_loc(serializer, "DeclareClass", 4, |state| {
+                    let type_params = match type_params.is_empty() {
+                        true  => None,
+                        false => Some(TypeParameters(type_params)),
+                    };
+
+                    state.serialize_field("id", &name)?;
+                    state.serialize_field("typeParameters", &type_params)?;
+                    state.serialize_field("extends", &extends)?;
+                    state.serialize_field("body", &body)
+                })
+            },
+            TypeAlias(ref alias) => {
+                let name = match alias.opaque {
+                    true  => "DeclareOpaqueType",
+                    false => "DeclareTypeAlias",
+                };
+
+                self.in_loc(serializer, name, 3, |state| {
+                    state.serialize_field("id", &alias.name)?;
+                    state.serialize_field("supertype", &alias.supertype)?;
+                    state.serialize_field("right", &alias.value)
+                })
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn test_type_alias() {
+        expect_parse!("// @flow\ntype Foo = ?number;", {
+            "type": "Program",
+            "body": [
+                {
+                    "type": "TypeAlias",
+                    "id": {
+                        "type": "Identifier",
+                        "name": "Foo",
+                        "start": 14,
+                        "end": 17,
+                    },
+                    "typeParameters": null,
+                    "right": {
+                        "type": "NullableTypeAnnotation",
+                        "typeAnnotation": {
+                            "type": "NumberTypeAnnotation",
+                            "start": 21,
+                            "end": 27,
+                        },
+                        "start": 20,
+                        "end": 27,
+                    },
+                    "start": 9,
+                    "end": 27,
+                }
+            ],
+            "start": 9,
+            "end": 27,
+        });
+    }
+
+    #[test]
+    fn test_type_cast_expression() {
+        expect_parse!("// @flow\n(foo: any);", {
+            "type": "Program",
+            "body": [
+                {
+                    "type": "ExpressionStatement",
+                    "expression": {
+                        "type": "TypeCastExpression",
+                        "expression": {
+                            "type": "Identifier",
+                            "name": "foo",
+                            "start": 10,
+                            "end": 13,
+                        },
+                        "typeAnnotation": {
+                            "type": "TypeAnnotation",
+                            "typeAnnotation": {
+                                "type": "AnyTypeAnnotation",
+                                "start": 15,
+                                "end": 18,
+                            },
+                            "start": 15,
+                            "end": 18,
+                        },
+                        "start": 10,
+                        "end": 18,
+                    },
+                    "start": 10,
+                    "end": 18,
+                }
+            ],
+            "start": 10,
+            "end": 18,
+        });
+    }
+}
@@ -1,6 +1,8 @@
 use ast::expression::{PropertyKey, TaggedTemplateExpression, TemplateLiteral};
 use ast::{Expression, Literal, Loc, NodeList, Pattern, Property};
 use astgen::SerializeInLoc;
+use astgen::options::{self, EstreeOptions, Flavor};
+use astgen::types::TypeAnnotation;
 use serde::ser::{Serialize, SerializeSeq, SerializeStruct, Serializer};
 
 #[derive(Debug, Serialize, PartialEq)]
@@ -27,12 +29,8 @@ pub fn parse_regex<'ast>(value: &'ast str) -> RegExLiteral<'ast> {
 
 #[inline]
 pub fn is_float<'ast>(value: &'ast str) -> bool {
-    for index in 0..value.len() {
-        if "." == &value[index..(index + 1)] {
-            return true;
-        }
-    }
-    false
+    // Exponents always make a float, `1e3`
+    value.bytes().any(|byte| matches!(byte, b'.' | b'e' | b'E'))
 }
 
 #[derive(Debug)]
@@ -114,6 +112,9 @@ impl<'ast> SerializeInLoc for Property<'ast> {
         S: Serializer,
     {
         use self::Property::*;
+
+        let babel = options::options().flavor == Flavor::Babel;
+
         match *self {
             Shorthand(value) => {
                 let state = Expression::Identifier(value).serialize(serializer);
@@ -126,15 +127,34 @@ impl<'ast> SerializeInLoc for Property<'ast> {
                     false
                 };
 
-                self.in_loc(serializer, "Property", 6, |state| {
+                let name = if babel { "ObjectProperty" } else { "Property" };
+
+                self.in_loc(serializer, name, 6, |state| {
                     state.serialize_field("key", &*key)?;
                     state.serialize_field("method", &false)?;
                     state.serialize_field("shorthand", &false)?;
                     state.serialize_field("computed", &computed)?;
                     state.serialize_field("value", &value)?;
+
+                    if babel {
+                        return Ok(());
+                    }
+
                     state.serialize_field("kind", &"init")
                 })
             }
+
+            // Babel puts the function of the method in the property itself
+            Method { key, value } if babel => self.in_loc(serializer, "ObjectMethod", 8, |state| {
+                state.serialize_field("kind", &"method")?;
+                state.serialize_field("key", &*key)?;
+                state.serialize_field("computed", &false)?;
+                state.serialize_field("id", &())?;
+                state.serialize_field("generator", &false)?;
+                state.serialize_field("async", &false)?;
+                state.serialize_field("params", &value.params)?;
+                state.serialize_field("body", &value.body)
+            }),
             Method { key, value } => self.in_loc(serializer, "Property", 6, |state| {
                 state.serialize_field("key", &*key)?;
                 state.serialize_field("method", &true)?;
@@ -170,6 +190,82 @@ impl<'ast> Serialize for Loc<PropertyKey<'ast>> {
     }
 }
 
+/// Value of a number literal.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NumericValue {
+    Integer(i64),
+    Float(f64),
+}
+
+impl Serialize for NumericValue {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match *self {
+            NumericValue::Integer(value) => serializer.serialize_i64(value),
+            NumericValue::Float(value)   => serializer.serialize_f64(value),
+        }
+    }
+}
+
+#[inline]
+pub fn parse_number(number: &str) -> NumericValue {
+    if number.len() > 2 {
+        let prefix = &number[0..2];
+        let radix = match prefix {
+            "0x" | "0X" => Some(16),
+            "0o" | "0O" => Some(8),
+            "0b" | "0B" => Some(2),
+            _           => None,
+        };
+
+        if let Some(radix) = radix {
+            let digits = &number[2..];
+
+            return match i64::from_str_radix(digits, radix) {
+                Ok(value) => NumericValue::Integer(value),
+                // Too large for an integer, fold the digits into a float instead
+                Err(_) => NumericValue::Float(digits.chars().fold(0.0, |value, digit| {
+                    value * radix as f64 + digit.to_digit(radix).unwrap_or(0) as f64
+                })),
+            };
+        }
+    }
+
+    if !is_float(number) {
+        if let Ok(value) = number.parse() {
+            return NumericValue::Integer(value);
+        }
+    }
+
+    NumericValue::Float(number.parse().unwrap_or(f64::NAN))
+}
+
+/// Raw source of a Babel literal, along with its value.
+#[derive(Serialize)]
+struct Extra<'ast, V> {
+    #[serde(rename = "rawValue", skip_serializing_if = "Option::is_none")]
+    raw_value: Option<V>,
+    raw: &'ast str,
+}
+
+/// Serialize the raw source of a literal, if enabled.
+fn serialize_raw<S, V>(state: &mut S, options: EstreeOptions, value: Option<V>, raw: &str) -> Result<(), S::Error>
+where
+    S: SerializeStruct,
+    V: Serialize,
+{
+    if !options.raw {
+        return Ok(());
+    }
+
+    match options.flavor {
+        Flavor::Estree => state.serialize_field("raw", raw),
+        Flavor::Babel  => state.serialize_field("extra", &Extra { raw_value: value, raw }),
+    }
+}
+
 impl<'ast> SerializeInLoc for Literal<'ast> {
     fn serialize<S>(&self, serializer: S) -> Result<S::SerializeStruct, S::Error>
     where
@@ -177,71 +273,74 @@ impl<'ast> SerializeInLoc for Literal<'ast> {
     {
         use self::Literal::*;
 
-        // FIXME: StringLiteral vs. Literal
-        let literal_type = match *self {
-            String(_) => "Literal",
-            _ => "Literal",
+        let options = options::options();
+
+        let literal_type = match (options.flavor, *self) {
+            (Flavor::Estree, _)      => "Literal",
+            (Flavor::Babel, Undefined) => return Expression::Identifier("undefined").serialize(serializer),
+            (Flavor::Babel, Null)      => "NullLiteral",
+            (Flavor::Babel, True)      |
+            (Flavor::Babel, False)     => "BooleanLiteral",
+            (Flavor::Babel, Number(_)) |
+            (Flavor::Babel, Binary(_)) => "NumericLiteral",
+            (Flavor::Babel, String(_)) => "StringLiteral",
+            (Flavor::Babel, RegEx(_))  => "RegExpLiteral",
+        };
+
+        let babel = options.flavor == Flavor::Babel;
+
+        // Fields besides the raw source, and whether the raw source is added
+        let (fields, raw) = match *self {
+            Undefined             => (0, true),
+            Null if babel         => (0, false),
+            True | False if babel => (1, false),
+            RegEx(_) if babel     => (2, true),
+            _                     => (1, true),
         };
+        let length = fields + (raw && options.raw) as usize;
 
-        self.in_loc(serializer, literal_type, 1, |state| {
+        self.in_loc(serializer, literal_type, length, |state| {
             match *self {
-                Undefined => {
-                    state.serialize_field("raw", &"undefined")
-                },
+                Undefined => serialize_raw(state, options, None::<()>, "undefined"),
+                Null if babel => Ok(()),
                 Null => {
                     state.serialize_field("value", &())?;
-                    state.serialize_field("raw", &"null")
-                },
-                True => {
-                    state.serialize_field("value", &true)?;
-                    state.serialize_field("raw", &"true")
+                    serialize_raw(state, options, None::<()>, "null")
                 },
-                False => {
-                    state.serialize_field("value", &false)?;
-                    state.serialize_field("raw", &"false")
-                },
-                Number(number) => {
-                    if number.len() > 2 {
-                        let prefix = &number[0..2];
-                        let is_hexdecimal = prefix == "0x" || prefix == "0X";
-                        let is_octal = prefix == "0o" || prefix == "0O";
-                        if is_hexdecimal || is_octal {
-                            let value = unsafe { number.slice_unchecked(2, number.len()) };
-                            let radix = if is_hexdecimal { 16 } else { 8 };
-                            state.serialize_field(
-                                "value",
-                                &i32::from_str_radix(value, radix).expect("Invalid number"),
-                            )?;
-                            return state.serialize_field("raw", &number)
-                        }
-                    }
+                True | False => {
+                    let value = *self == True;
 
-                    if is_float(number) {
-                        let value: f64 = number.parse().expect("Invalid number");
-                        state.serialize_field("value", &value)?;
-                        return state.serialize_field("raw", &number)
+                    state.serialize_field("value", &value)?;
+
+                    if babel {
+                        return Ok(());
                     }
 
-                    let value: i64 = number.parse().expect("Invalid number");
+                    serialize_raw(state, options, None::<()>, if value { "true" } else { "false" })
+                },
+                Number(number) | Binary(number) => {
+                    let value = parse_number(number);
+
                     state.serialize_field("value", &value)?;
-                    state.serialize_field("raw", &number)
-                }
-                Binary(number) => {
-                    let value = unsafe { number.slice_unchecked(2, number.len()) };
-                    state.serialize_field(
-                        "value",
-                        &i32::from_str_radix(value, 2).expect("Invalid number"),
-                    )?;
-                    state.serialize_field("raw", &number)
-                }
+                    serialize_raw(state, options, Some(value), number)
+                },
                 String(value) => {
-                    let parsed_value = unsafe { value.slice_unchecked(1, value.len() - 1) };
+                    let parsed_value = &value[1..value.len() - 1];
+
                     state.serialize_field("value", &parsed_value)?;
-                    state.serialize_field("raw", &value)
+                    serialize_raw(state, options, Some(parsed_value), value)
                 },
                 RegEx(value) => {
-                    state.serialize_field("regex", &parse_regex(value))?;
-                    state.serialize_field("raw", &value)
+                    let regex = parse_regex(value);
+
+                    if babel {
+                        state.serialize_field("pattern", &regex.pattern)?;
+                        state.serialize_field("flags", &regex.flags)?;
+                    } else {
+                        state.serialize_field("regex", &regex)?;
+                    }
+
+                    serialize_raw(state, options, None::<()>, value)
                 },
             }
         })
@@ -273,6 +372,16 @@ impl<'ast> SerializeInLoc for Pattern<'ast> {
             RestElement { argument } => self.in_loc(serializer, "RestElement", 1, |state| {
                 state.serialize_field("argument", &argument)
             }),
+            Typed { pattern, optional, annotation } => {
+                let mut state = pattern.item.serialize(serializer)?;
+
+                if optional {
+                    state.serialize_field("optional", &optional)?;
+                }
+                state.serialize_field("typeAnnotation", &TypeAnnotation::wrap(annotation))?;
+
+                Ok(state)
+            },
         }
     }
 }
@@ -322,6 +431,8 @@ mod test {
         assert_eq!(is_float("3.142"), true);
         assert_eq!(is_float("1."), true);
         assert_eq!(is_float(".1"), true);
+        assert_eq!(is_float("1e3"), true);
+        assert_eq!(is_float("1E-3"), true);
     }
 
     #[test]
@@ -536,6 +647,58 @@ mod test {
             "start": 0,
             "end": 6,
         });
+
+        expect_parse!("a = 1e3;", {
+            "type": "Program",
+            "body": [
+                {
+                    "type": "ExpressionStatement",
+                    "expression": {
+                        "type": "AssignmentExpression",
+                        "operator": "=",
+                        "left": {
+                            "type": "Identifier",
+                            "name": "a",
+                            "start": 0,
+                            "end": 1
+                        },
+                        "right": {
+                            "type": "Literal",
+                            "value": 1000.0,
+                            "raw": "1e3",
+                            "start": 4,
+                            "end": 7
+                        },
+                        "start": 0,
+                        "end": 7
+                    },
+                    "start": 0,
+                    "end": 7,
+                }
+            ],
+            "start": 0,
+            "end": 7,
+        });
+
+        expect_parse!("0xFFFFFFFFFFFFFFFFFF", {
+            "type": "Program",
+            "body": [
+                {
+                    "type": "ExpressionStatement",
+                    "expression": {
+                        "type": "Literal",
+                        "value": 4.722366482869645e21,
+                        "raw": "0xFFFFFFFFFFFFFFFFFF",
+                        "start": 0,
+                        "end": 20
+                    },
+                    "start": 0,
+                    "end": 20,
+                }
+            ],
+            "start": 0,
+            "end": 20,
+        });
     }
 
     #[test]
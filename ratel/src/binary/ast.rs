@@ -0,0 +1,498 @@
+use toolshed::list::{List, ListBuilder};
+use ast::*;
+use ast::expression::*;
+use ast::statement::*;
+use ast::types::*;
+use binary::{Binary, Encoder, Decoder, Result};
+
+impl<'ast, T> Binary<'ast> for Node<'ast, T> where
+    T: 'ast + Binary<'ast> + Copy,
+{
+    #[inline]
+    fn encode(&self, encoder: &mut Encoder<'ast>) {
+        // Nodes mostly end close to where they start, so the length is
+        // smaller than the end offset. Transformed nodes can break that rule.
+        encoder.number(self.start);
+        encoder.number(self.end.wrapping_sub(self.start));
+
+        self.item.encode(encoder);
+    }
+
+    #[inline]
+    fn decode<'bytes>(decoder: &mut Decoder<'ast, 'bytes>) -> Result<Self> {
+        let start = decoder.number()?;
+        let end = start.wrapping_add(decoder.number()?);
+        let item = T::decode(decoder)?;
+
+        Ok(Node::new(decoder.arena.alloc(Loc::new(start, end, item))))
+    }
+}
+
+impl<'ast, T> Binary<'ast> for List<'ast, T> where
+    T: 'ast + Binary<'ast> + Copy,
+{
+    #[inline]
+    fn encode(&self, encoder: &mut Encoder<'ast>) {
+        encoder.number(self.iter().count() as u32);
+
+        for item in self.iter() {
+            item.encode(encoder);
+        }
+    }
+
+    #[inline]
+    fn decode<'bytes>(decoder: &mut Decoder<'ast, 'bytes>) -> Result<Self> {
+        let count = decoder.number()?;
+
+        if count == 0 {
+            return Ok(List::empty());
+        }
+
+        let builder = ListBuilder::new(decoder.arena, T::decode(decoder)?);
+
+        for _ in 1..count {
+            let item = T::decode(decoder)?;
+
+            builder.push(decoder.arena, item);
+        }
+
+        Ok(builder.as_list())
+    }
+}
+
+impl<'ast, T: Binary<'ast>> Binary<'ast> for Option<T> {
+    #[inline]
+    fn encode(&self, encoder: &mut Encoder<'ast>) {
+        match *self {
+            Some(ref item) => {
+                encoder.byte(1);
+                item.encode(encoder);
+            },
+            None => encoder.byte(0),
+        }
+    }
+
+    #[inline]
+    fn decode<'bytes>(decoder: &mut Decoder<'ast, 'bytes>) -> Result<Self> {
+        match decoder.byte()? {
+            0   => Ok(None),
+            1   => Ok(Some(T::decode(decoder)?)),
+            tag => decoder.invalid_tag("Option", tag),
+        }
+    }
+}
+
+impl<'ast> Binary<'ast> for &'ast str {
+    #[inline]
+    fn encode(&self, encoder: &mut Encoder<'ast>) {
+        encoder.string(self);
+    }
+
+    #[inline]
+    fn decode<'bytes>(decoder: &mut Decoder<'ast, 'bytes>) -> Result<Self> {
+        decoder.string()
+    }
+}
+
+impl<'ast> Binary<'ast> for bool {
+    #[inline]
+    fn encode(&self, encoder: &mut Encoder<'ast>) {
+        encoder.byte(*self as u8);
+    }
+
+    #[inline]
+    fn decode<'bytes>(decoder: &mut Decoder<'ast, 'bytes>) -> Result<Self> {
+        match decoder.byte()? {
+            0   => Ok(false),
+            1   => Ok(true),
+            tag => decoder.invalid_tag("bool", tag),
+        }
+    }
+}
+
+/// Types that don't hold any data aren't encoded at all.
+macro_rules! impl_unit {
+    ($( $type:ident ),*) => ($(
+        impl<'ast> Binary<'ast> for $type {
+            #[inline]
+            fn encode(&self, _: &mut Encoder<'ast>) {}
+
+            #[inline]
+            fn decode<'bytes>(_: &mut Decoder<'ast, 'bytes>) -> Result<Self> {
+                Ok($type)
+            }
+        }
+    )*)
+}
+
+impl_unit! {
+    EmptyName,
+    ThisExpression
+}
+
+/// Fieldless enums are encoded as a single byte, their discriminant.
+/// Variants must be listed in the order they are declared in.
+macro_rules! impl_fieldless {
+    ($( $type:ident { $( $variant:ident ),* } )*) => ($(
+        impl<'ast> Binary<'ast> for $type {
+            #[inline]
+            fn encode(&self, encoder: &mut Encoder<'ast>) {
+                encoder.byte(*self as u8);
+            }
+
+            #[inline]
+            fn decode<'bytes>(decoder: &mut Decoder<'ast, 'bytes>) -> Result<Self> {
+                const VARIANTS: &[$type] = &[ $( $type::$variant ),* ];
+
+                let tag = decoder.byte()?;
+
+                match VARIANTS.get(tag as usize) {
+                    Some(&variant) => Ok(variant),
+                    None           => decoder.invalid_tag(stringify!($type), tag),
+                }
+            }
+        }
+    )*)
+}
+
+impl_fieldless! {
+    OperatorKind {
+        FatArrow, New, Increment, Decrement, LogicalNot, BitwiseNot, Typeof, Void, Delete,
+        Multiplication, Division, Remainder, Exponent, Addition, Subtraction,
+        BitShiftLeft, BitShiftRight, UBitShiftRight, Lesser, LesserEquals, Greater,
+        GreaterEquals, Instanceof, In, StrictEquality, StrictInequality, Equality,
+        Inequality, BitwiseAnd, BitwiseXor, BitwiseOr, LogicalAnd, LogicalOr, Conditional,
+        Assign, AddAssign, SubtractAssign, ExponentAssign, MultiplyAssign, DivideAssign,
+        RemainderAssign, BSLAssign, BSRAssign, UBSRAssign, BitAndAssign, BitXorAssign,
+        BitOrAssign, Spread
+    }
+    DeclarationKind { Var, Let, Const }
+    MethodKind { Constructor, Method, Get, Set }
+    ImportKind { Value, Type, Typeof }
+    ExportKind { Value, Type }
+    Primitive { Number, String, Boolean, Null, Void, Symbol }
+}
+
+/// Structs are encoded field by field.
+macro_rules! impl_struct {
+    ($( $type:ident { $( $field:ident ),* } )*) => ($(
+        impl<'ast> Binary<'ast> for $type<'ast> {
+            #[inline]
+            fn encode(&self, encoder: &mut Encoder<'ast>) {
+                $( self.$field.encode(encoder); )*
+            }
+
+            #[inline]
+            fn decode<'bytes>(decoder: &mut Decoder<'ast, 'bytes>) -> Result<Self> {
+                Ok($type {
+                    $( $field: Binary::decode(decoder)? ),*
+                })
+            }
+        }
+    )*)
+}
+
+impl_struct! {
+    SequenceExpression { body }
+    ArrayExpression { body }
+    MemberExpression { object, property }
+    ComputedMemberExpression { object, property }
+    CallExpression { callee, arguments }
+    BinaryExpression { operator, left, right }
+    PrefixExpression { operator, operand }
+    PostfixExpression { operator, operand }
+    ConditionalExpression { test, consequent, alternate }
+    TemplateLiteral { expressions, quasis }
+    TaggedTemplateExpression { tag, quasi }
+    SpreadExpression { argument }
+    ArrowExpression { type_params, params, return_type, body }
+    ObjectExpression { body }
+    TypeCastExpression { expression, annotation }
+    Declarator { id, init }
+    DeclarationStatement { kind, declarators }
+    ReturnStatement { value }
+    BreakStatement { label }
+    ContinueStatement { label }
+    ThrowStatement { value }
+    IfStatement { test, consequent, alternate }
+    WhileStatement { test, body }
+    DoStatement { body, test }
+    WithStatement { object, body }
+    ForStatement { init, test, update, body }
+    ForInStatement { left, right, body }
+    ForOfStatement { left, right, body }
+    CatchClause { param, body }
+    TryStatement { block, handler, finalizer }
+    LabeledStatement { label, body }
+    SwitchStatement { discriminant, cases }
+    SwitchCase { test, consequent }
+    TypeAliasStatement { opaque, name, type_params, supertype, value }
+    ImportStatement { kind, specifiers, source }
+    ExportSpecifier { local, exported }
+    TypeParameter { name, bound, default }
+    FunctionTypeParam { name, optional, annotation }
+    FunctionType { type_params, params, rest, return_type }
+}
+
+macro_rules! decode_field {
+    ($field:ident, $decoder:ident) => (Binary::decode($decoder)?)
+}
+
+/// Enums are encoded as a tag byte followed by the fields of the variant.
+/// Tags are part of the format, changing them requires a new `VERSION`.
+macro_rules! impl_enum {
+    ($( $type:ident {
+        $( $tag:expr => $variant:ident $( ( $item:ident ) )* $( { $( $field:ident ),* } )* ),*
+    } )*) => ($(
+        impl<'ast> Binary<'ast> for $type<'ast> {
+            fn encode(&self, encoder: &mut Encoder<'ast>) {
+                match *self {
+                    $(
+                        $type::$variant $( (ref $item) )* $( { $( ref $field ),* } )* => {
+                            encoder.byte($tag);
+                            $( $item.encode(encoder); )*
+                            $( $( $field.encode(encoder); )* )*
+                        },
+                    )*
+                }
+            }
+
+            fn decode<'bytes>(decoder: &mut Decoder<'ast, 'bytes>) -> Result<Self> {
+                match decoder.byte()? {
+                    $(
+                        $tag => Ok($type::$variant
+                            $( (decode_field!($item, decoder)) )*
+                            $( { $( $field: decode_field!($field, decoder) ),* } )*
+                        ),
+                    )*
+                    tag => decoder.invalid_tag(stringify!($type), tag),
+                }
+            }
+        }
+    )*)
+}
+
+impl_enum! {
+    Literal {
+        0 => Undefined,
+        1 => Null,
+        2 => True,
+        3 => False,
+        4 => Number(raw),
+        5 => Binary(raw),
+        6 => String(raw),
+        7 => RegEx(raw)
+    }
+    Pattern {
+        0 => Void,
+        1 => Identifier(name),
+        2 => ObjectPattern { properties },
+        3 => ArrayPattern { elements },
+        4 => RestElement { argument },
+        5 => AssignmentPattern { left, right },
+        6 => Typed { pattern, optional, annotation }
+    }
+    PropertyKey {
+        0 => Computed(expression),
+        1 => Literal(raw),
+        2 => Binary(raw)
+    }
+    Property {
+        0 => Shorthand(name),
+        1 => Literal { key, value },
+        2 => Method { key, value }
+    }
+    ClassMember {
+        0 => Error,
+        1 => Method { is_static, key, kind, value },
+        2 => Literal { is_static, key, value },
+        3 => Property { is_static, key, annotation, value }
+    }
+    ArrowBody {
+        0 => Expression(expression),
+        1 => Block(block)
+    }
+    Expression {
+        0 => Void,
+        1 => This(item),
+        2 => Identifier(item),
+        3 => Literal(item),
+        4 => Sequence(item),
+        5 => Array(item),
+        6 => Member(item),
+        7 => ComputedMember(item),
+        8 => Call(item),
+        9 => Binary(item),
+        10 => Prefix(item),
+        11 => Postfix(item),
+        12 => Conditional(item),
+        13 => Template(item),
+        14 => TaggedTemplate(item),
+        15 => Spread(item),
+        16 => Arrow(item),
+        17 => Object(item),
+        18 => Function(item),
+        19 => Class(item),
+        20 => TypeCast(item)
+    }
+    ForInit {
+        0 => Declaration(declaration),
+        1 => Expression(expression)
+    }
+    DeclareStatement {
+        0 => Variable { kind, name, annotation },
+        1 => Function { name, annotation },
+        2 => Class { name, type_params, extends, body },
+        3 => TypeAlias(alias)
+    }
+    ImportSpecifier {
+        0 => Default(name),
+        1 => Namespace(name),
+        2 => Named { imported, local }
+    }
+    ExportStatement {
+        0 => Declaration(statement),
+        1 => Default(expression),
+        2 => Named { kind, specifiers, source },
+        3 => All { source }
+    }
+    Statement {
+        0 => Empty,
+        1 => Expression(item),
+        2 => Declaration(item),
+        3 => Return(item),
+        4 => Break(item),
+        5 => Continue(item),
+        6 => Throw(item),
+        7 => If(item),
+        8 => While(item),
+        9 => Do(item),
+        10 => For(item),
+        11 => ForIn(item),
+        12 => ForOf(item),
+        13 => Try(item),
+        14 => Block(item),
+        15 => Labeled(item),
+        16 => Function(item),
+        17 => Class(item),
+        18 => Switch(item),
+        19 => TypeAlias(item),
+        20 => Declare(item),
+        21 => Import(item),
+        22 => Export(item),
+        23 => Commented(item),
+        24 => With(item)
+    }
+    ObjectTypeMember {
+        0 => Property { key, optional, method, value },
+        1 => Indexer { name, key, value },
+        2 => Spread(argument)
+    }
+    Type {
+        0 => Any,
+        1 => Mixed,
+        2 => Empty,
+        3 => Existential,
+        4 => Primitive(primitive),
+        5 => StringLiteral(raw),
+        6 => NumberLiteral(raw),
+        7 => BooleanLiteral(value),
+        8 => Identifier(name),
+        9 => Typeof(name),
+        10 => Nullable(item),
+        11 => Array(item),
+        12 => Union { variants },
+        13 => Intersection { variants },
+        14 => Generic { ident, subtypes },
+        15 => Tuple { elements },
+        16 => Object { exact, members },
+        17 => Function(item),
+        18 => Comment(item)
+    }
+}
+
+impl<'ast, T> Binary<'ast> for Block<'ast, T> where
+    T: 'ast + Binary<'ast> + Copy,
+{
+    #[inline]
+    fn encode(&self, encoder: &mut Encoder<'ast>) {
+        self.body.encode(encoder);
+    }
+
+    #[inline]
+    fn decode<'bytes>(decoder: &mut Decoder<'ast, 'bytes>) -> Result<Self> {
+        Ok(Block {
+            body: Binary::decode(decoder)?,
+        })
+    }
+}
+
+impl<'ast> Binary<'ast> for MandatoryName<'ast> {
+    #[inline]
+    fn encode(&self, encoder: &mut Encoder<'ast>) {
+        self.0.encode(encoder);
+    }
+
+    #[inline]
+    fn decode<'bytes>(decoder: &mut Decoder<'ast, 'bytes>) -> Result<Self> {
+        Ok(MandatoryName(Binary::decode(decoder)?))
+    }
+}
+
+impl<'ast> Binary<'ast> for OptionalName<'ast> {
+    #[inline]
+    fn encode(&self, encoder: &mut Encoder<'ast>) {
+        self.0.encode(encoder);
+    }
+
+    #[inline]
+    fn decode<'bytes>(decoder: &mut Decoder<'ast, 'bytes>) -> Result<Self> {
+        Ok(OptionalName(Binary::decode(decoder)?))
+    }
+}
+
+impl<'ast, N> Binary<'ast> for Function<'ast, N> where
+    N: Name<'ast> + Binary<'ast>,
+{
+    fn encode(&self, encoder: &mut Encoder<'ast>) {
+        self.name.encode(encoder);
+        self.type_params.encode(encoder);
+        self.params.encode(encoder);
+        self.return_type.encode(encoder);
+        self.body.encode(encoder);
+    }
+
+    fn decode<'bytes>(decoder: &mut Decoder<'ast, 'bytes>) -> Result<Self> {
+        Ok(Function {
+            name: Binary::decode(decoder)?,
+            type_params: Binary::decode(decoder)?,
+            params: Binary::decode(decoder)?,
+            return_type: Binary::decode(decoder)?,
+            body: Binary::decode(decoder)?,
+        })
+    }
+}
+
+impl<'ast, N> Binary<'ast> for Class<'ast, N> where
+    N: Name<'ast> + Binary<'ast>,
+{
+    fn encode(&self, encoder: &mut Encoder<'ast>) {
+        self.name.encode(encoder);
+        self.type_params.encode(encoder);
+        self.extends.encode(encoder);
+        self.super_type_args.encode(encoder);
+        self.implements.encode(encoder);
+        self.body.encode(encoder);
+    }
+
+    fn decode<'bytes>(decoder: &mut Decoder<'ast, 'bytes>) -> Result<Self> {
+        Ok(Class {
+            name: Binary::decode(decoder)?,
+            type_params: Binary::decode(decoder)?,
+            extends: Binary::decode(decoder)?,
+            super_type_args: Binary::decode(decoder)?,
+            implements: Binary::decode(decoder)?,
+            body: Binary::decode(decoder)?,
+        })
+    }
+}
@@ -0,0 +1,501 @@
+//! Compact binary encoding of a `Module`, which can be cached on disk and
+//! decoded straight back into an arena, a lot faster than parsing the
+//! source again.
+//!
+//! The encoding starts with a header made of the `MAGIC` bytes and the
+//! format `VERSION`, followed by the table of all strings in the AST and
+//! the encoded body. Strings are referenced by their index in the table,
+//! and all numbers, offsets included, are stored as LEB128 varints.
+
+mod ast;
+
+use std::collections::HashMap;
+use std::fmt::{self, Debug, Display};
+use std::str;
+use toolshed::Arena;
+use ast::{Program, StatementList};
+use module::Module;
+
+/// Bytes every encoded module starts with.
+pub const MAGIC: &[u8; 4] = b"RATL";
+
+/// Version of the encoding. Changes whenever the AST or the encoding does,
+/// so that stale caches are rejected instead of being misread.
+pub const VERSION: u32 = 2;
+
+/// Kind of a `binary::Error`.
+#[derive(Debug, PartialEq, Clone)]
+pub enum ErrorKind {
+    /// Input doesn't start with the `MAGIC` bytes.
+    InvalidHeader,
+    /// Input has been encoded with a different version of the encoding.
+    VersionMismatch {
+        found: u32,
+        expected: u32,
+    },
+    /// Input ends in the middle of a value.
+    UnexpectedEnd,
+    /// Varint doesn't fit in 32 bits.
+    InvalidNumber,
+    /// String in the table isn't valid UTF-8.
+    InvalidString,
+    /// Reference to a string that isn't in the table.
+    InvalidStringIndex(u32),
+    /// Tag that doesn't match any variant of the type.
+    InvalidTag {
+        node: &'static str,
+        tag: u8,
+    },
+    /// Input continues after the end of the module.
+    TrailingBytes,
+}
+
+/// Error type returned when decoding a module, along with the offset
+/// of the input at which it occurred.
+#[derive(PartialEq, Clone)]
+pub struct Error {
+    pub kind: ErrorKind,
+    pub offset: usize,
+}
+
+impl Debug for Error {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.kind {
+            ErrorKind::InvalidHeader => {
+                write!(f, "Input is not an encoded module")
+            },
+            ErrorKind::VersionMismatch { found, expected } => {
+                write!(f, "Module is encoded with version {}, expected version {}", found, expected)
+            },
+            ErrorKind::UnexpectedEnd => {
+                write!(f, "Unexpected end of input at {}", self.offset)
+            },
+            ErrorKind::InvalidNumber => {
+                write!(f, "Invalid number at {}", self.offset)
+            },
+            ErrorKind::InvalidString => {
+                write!(f, "Invalid UTF-8 in string at {}", self.offset)
+            },
+            ErrorKind::InvalidStringIndex(index) => {
+                write!(f, "Unknown string {} at {}", index, self.offset)
+            },
+            ErrorKind::InvalidTag { node, tag } => {
+                write!(f, "Invalid tag {} of {} at {}", tag, node, self.offset)
+            },
+            ErrorKind::TrailingBytes => {
+                write!(f, "Unexpected bytes after the module at {}", self.offset)
+            },
+        }
+    }
+}
+
+pub type Result<T> = ::std::result::Result<T, Error>;
+
+/// AST types that can be encoded and decoded.
+pub trait Binary<'ast>: Sized {
+    fn encode(&self, encoder: &mut Encoder<'ast>);
+
+    fn decode<'bytes>(decoder: &mut Decoder<'ast, 'bytes>) -> Result<Self>;
+}
+
+/// Writes the encoded AST, interning the strings.
+pub struct Encoder<'ast> {
+    bytes: Vec<u8>,
+    strings: Vec<&'ast str>,
+    indexes: HashMap<&'ast str, u32>,
+}
+
+impl<'ast> Encoder<'ast> {
+    fn new() -> Self {
+        Encoder {
+            bytes: Vec::new(),
+            strings: Vec::new(),
+            indexes: HashMap::new(),
+        }
+    }
+
+    #[inline]
+    pub fn byte(&mut self, byte: u8) {
+        self.bytes.push(byte);
+    }
+
+    #[inline]
+    pub fn number(&mut self, number: u32) {
+        write_number(&mut self.bytes, number);
+    }
+
+    /// Write the index of the string in the table, adding it if necessary.
+    pub fn string(&mut self, string: &'ast str) {
+        let strings = &mut self.strings;
+        let index = *self.indexes.entry(string).or_insert_with(|| {
+            strings.push(string);
+
+            strings.len() as u32 - 1
+        });
+
+        self.number(index);
+    }
+
+    /// Put together the header, the string table and the body.
+    fn finish(self) -> Vec<u8> {
+        let table_len: usize = self.strings.iter().map(|string| string.len() + 5).sum();
+        let mut bytes = Vec::with_capacity(MAGIC.len() + 5 + table_len + self.bytes.len());
+
+        bytes.extend_from_slice(MAGIC);
+        write_number(&mut bytes, VERSION);
+        write_number(&mut bytes, self.strings.len() as u32);
+
+        for string in self.strings {
+            write_number(&mut bytes, string.len() as u32);
+            bytes.extend_from_slice(string.as_bytes());
+        }
+
+        bytes.extend_from_slice(&self.bytes);
+        bytes
+    }
+}
+
+#[inline]
+fn write_number(bytes: &mut Vec<u8>, mut number: u32) {
+    while number >= 0x80 {
+        bytes.push(number as u8 | 0x80);
+        number >>= 7;
+    }
+
+    bytes.push(number as u8);
+}
+
+/// Reads the encoded AST, allocating it on the arena.
+pub struct Decoder<'ast, 'bytes> {
+    pub arena: &'ast Arena,
+    bytes: &'bytes [u8],
+    offset: usize,
+    strings: Vec<&'ast str>,
+}
+
+impl<'ast, 'bytes> Decoder<'ast, 'bytes> {
+    #[inline]
+    pub fn error<T>(&self, kind: ErrorKind) -> Result<T> {
+        Err(Error {
+            kind,
+            offset: self.offset,
+        })
+    }
+
+    #[inline]
+    pub fn byte(&mut self) -> Result<u8> {
+        match self.bytes.get(self.offset) {
+            Some(&byte) => {
+                self.offset += 1;
+
+                Ok(byte)
+            },
+            None => self.error(ErrorKind::UnexpectedEnd),
+        }
+    }
+
+    pub fn number(&mut self) -> Result<u32> {
+        let mut number = 0u32;
+        let mut shift = 0;
+
+        loop {
+            let byte = self.byte()?;
+
+            if shift == 28 && byte > 0x0F {
+                return self.error(ErrorKind::InvalidNumber);
+            }
+
+            number |= u32::from(byte & 0x7F) << shift;
+
+            if byte & 0x80 == 0 {
+                return Ok(number);
+            }
+
+            shift += 7;
+        }
+    }
+
+    #[inline]
+    pub fn string(&mut self) -> Result<&'ast str> {
+        let index = self.number()?;
+
+        match self.strings.get(index as usize) {
+            Some(&string) => Ok(string),
+            None          => self.error(ErrorKind::InvalidStringIndex(index)),
+        }
+    }
+
+    #[inline]
+    pub fn invalid_tag<T>(&self, node: &'static str, tag: u8) -> Result<T> {
+        Err(Error {
+            kind: ErrorKind::InvalidTag { node, tag },
+            offset: self.offset - 1,
+        })
+    }
+
+    /// Read the header and the string table.
+    fn header(&mut self) -> Result<()> {
+        if !self.bytes.starts_with(MAGIC) {
+            return self.error(ErrorKind::InvalidHeader);
+        }
+
+        self.offset = MAGIC.len();
+
+        let version = self.number()?;
+
+        if version != VERSION {
+            return self.error(ErrorKind::VersionMismatch {
+                found: version,
+                expected: VERSION,
+            });
+        }
+
+        let count = self.number()? as usize;
+
+        // Every string takes at least a byte, don't let the count reserve more
+        self.strings.reserve(count.min(self.bytes.len()));
+
+        for _ in 0..count {
+            let len = self.number()? as usize;
+            let end = self.offset.saturating_add(len);
+
+            let string = match self.bytes.get(self.offset..end) {
+                Some(bytes) => match str::from_utf8(bytes) {
+                    Ok(string) => string,
+                    Err(_)     => return self.error(ErrorKind::InvalidString),
+                },
+                None => return self.error(ErrorKind::UnexpectedEnd),
+            };
+
+            self.strings.push(self.arena.alloc_str(string));
+            self.offset = end;
+        }
+
+        Ok(())
+    }
+}
+
+/// Encode the body and the hashbang of a module.
+fn encode_body<'ast>(body: StatementList<'ast>, hashbang: Option<&'ast str>) -> Vec<u8> {
+    let mut encoder = Encoder::new();
+
+    hashbang.encode(&mut encoder);
+    body.encode(&mut encoder);
+
+    encoder.finish()
+}
+
+/// Encode a `Module` into bytes.
+pub fn encode(module: &Module) -> Vec<u8> {
+    encode_body(module.body(), module.hashbang())
+}
+
+/// Encode a `Program` into bytes. The source of the program isn't
+/// included, only its AST.
+pub fn encode_program(program: &Program) -> Vec<u8> {
+    encode_body(program.body, program.hashbang)
+}
+
+/// Decode a module from bytes produced by `encode` or `encode_program`.
+pub fn decode<'ast>(bytes: &[u8]) -> Result<Module<'ast>> {
+    Module::from_arena(Arena::new(), |arena| decode_in(bytes, arena))
+}
+
+/// Same as `decode`, but allocates the AST in an `Arena` owned by the
+/// caller, producing a `Program` borrowed from it. Since the source isn't
+/// encoded, the `source` of the `Program` is empty.
+pub fn decode_in<'ast>(bytes: &[u8], arena: &'ast Arena) -> Result<Program<'ast>> {
+    let mut decoder = Decoder {
+        arena,
+        bytes,
+        offset: 0,
+        strings: Vec::new(),
+    };
+
+    decoder.header()?;
+
+    let hashbang = Binary::decode(&mut decoder)?;
+    let body = Binary::decode(&mut decoder)?;
+
+    if decoder.offset != bytes.len() {
+        return decoder.error(ErrorKind::TrailingBytes);
+    }
+
+    Ok(Program {
+        source: "",
+        body,
+        hashbang,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use parser::{parse, parse_in, ParseOptions, Goal};
+
+    fn assert_roundtrip(source: &str) {
+        let arena = Arena::new();
+        let options = ParseOptions { goal: Goal::Module, ..ParseOptions::default() };
+        let program = parse_in(source, &arena, options).unwrap();
+        let bytes = encode_program(&program);
+
+        let other = Arena::new();
+        let decoded = decode_in(&bytes, &other).unwrap();
+
+        assert_eq!(decoded.body, program.body);
+        assert_eq!(decoded.hashbang, program.hashbang);
+
+        // Locations are ignored by `PartialEq`
+        let locations = |program: &Program| {
+            program.body.iter().map(|statement| (statement.start, statement.end)).collect::<Vec<_>>()
+        };
+
+        assert_eq!(locations(&decoded), locations(&program));
+    }
+
+    #[test]
+    fn roundtrip_statements() {
+        assert_roundtrip("#!/usr/bin/env node\nvar a = 1, { b, c: [d, , ...e] } = f;");
+        assert_roundtrip("let a; const b = 2; if (a) b; else { c(); }");
+        assert_roundtrip("for (var i = 0; i < 10; i++) continue; for (a in b) break; for (const a of b) {}");
+        assert_roundtrip("while (a) {} do a(); while (b) label: for (;;) break label;");
+        assert_roundtrip("try { a() } catch (err) { throw err } finally { b() }");
+        assert_roundtrip("switch (a) { case 1: b; default: c; }");
+        assert_roundtrip("function foo(a, b = 2) { return a ** b; }");
+        assert_roundtrip("class Foo extends Bar { constructor() {} static baz() {} get qux() { return 1; } }");
+        assert_roundtrip("import foo, { bar as baz } from 'foo'; import * as qux from 'qux';");
+        assert_roundtrip("export default a; export { b as c }; export * from 'd'; export var e;");
+    }
+
+    #[test]
+    fn roundtrip_expressions() {
+        assert_roundtrip("a = b += c **= d, e ? f : g;");
+        assert_roundtrip("new Foo(a).bar[baz](...qux);");
+        assert_roundtrip("!a, ~b, typeof c, void d, delete e.f, ++g, h--;");
+        assert_roundtrip("[1, 0x10, 0b11, 1.5, 'a', \"b\", /c/g, true, false, null, undefined, this];");
+        assert_roundtrip("({ a, b: 1, [c]: 2, d() {}, 'e': 3, 4: 5 });");
+        assert_roundtrip("tag`a${b}c${d}e`; (a, b) => a + b; x => { return x; };");
+        assert_roundtrip("(function () {}); (function named() {}); (class {});");
+    }
+
+    #[test]
+    fn roundtrip_flow() {
+        assert_roundtrip("
+            // @flow
+            type Maybe<T> = ?T | Array<T> & { +a: number, [key: string]: mixed };
+            opaque type Id: string = string;
+            declare var a: number;
+            declare function b(x: number, ...rest: Array<string>): void;
+            import type { C } from 'c';
+            function d<T: Object = {}>(e: T, f?: string): Promise<T> { return (e: any); }
+            class G<T> extends H<T> implements I { j: number = 1; }
+        ");
+    }
+
+    #[test]
+    fn decode_module() {
+        let module = parse("#!/usr/bin/env node\nfoo(bar);").unwrap();
+        let decoded = decode(&encode(&module)).unwrap();
+
+        assert_eq!(decoded.body(), module.body());
+        assert_eq!(decoded.hashbang(), Some("/usr/bin/env node"));
+    }
+
+    #[test]
+    fn strings_are_interned() {
+        let repeated = encode(&parse("foo; foo; foo;").unwrap());
+        let distinct = encode(&parse("foo; bar; baz;").unwrap());
+
+        // `bar` and `baz` take a length byte and three bytes in the table
+        assert_eq!(distinct.len(), repeated.len() + 2 * 4);
+    }
+
+    #[test]
+    fn numbers() {
+        for &number in &[0, 1, 0x7F, 0x80, 0x3FFF, 0x4000, 0xFFFF_FFFF] {
+            let mut bytes = Vec::new();
+
+            write_number(&mut bytes, number);
+
+            let arena = Arena::new();
+            let mut decoder = Decoder {
+                arena: &arena,
+                bytes: &bytes,
+                offset: 0,
+                strings: Vec::new(),
+            };
+
+            assert_eq!(decoder.number(), Ok(number));
+            assert_eq!(decoder.offset, bytes.len());
+        }
+    }
+
+    #[test]
+    fn invalid_input() {
+        let arena = Arena::new();
+        let bytes = encode(&parse("foo(bar);").unwrap());
+
+        let kind = |bytes: &[u8]| decode_in(bytes, &arena).err().unwrap().kind;
+
+        assert_eq!(kind(b"{\"type\": \"Program\"}"), ErrorKind::InvalidHeader);
+        assert_eq!(kind(b"RATL\x00"), ErrorKind::VersionMismatch { found: 0, expected: VERSION });
+        assert_eq!(kind(&bytes[..bytes.len() - 1]), ErrorKind::UnexpectedEnd);
+
+        let mut trailing = bytes.clone();
+
+        trailing.push(0);
+
+        assert_eq!(kind(&trailing), ErrorKind::TrailingBytes);
+
+        // Header, string table, hashbang and the number of statements take
+        // 16 bytes, followed by the location and the tag of the statement
+        let mut invalid = bytes.clone();
+
+        invalid[18] = 0xFF;
+
+        assert_eq!(kind(&invalid), ErrorKind::InvalidTag { node: "Statement", tag: 0xFF });
+    }
+
+    const SAMPLE: &str = "#!/usr/bin/env node\nvar a = [1, 'b'], { c } = d; if (a) { e(...a); } class F { g() {} }";
+
+    #[test]
+    fn truncated_input() {
+        let bytes = encode(&parse(SAMPLE).unwrap());
+
+        for len in 0..bytes.len() {
+            assert!(decode(&bytes[..len]).is_err(), "decoded {} of {} bytes", len, bytes.len());
+        }
+    }
+
+    #[test]
+    fn flipped_bytes() {
+        let bytes = encode(&parse("foo(bar);").unwrap());
+
+        // Tags of the hashbang, the statement, the call and both identifiers
+        for &(offset, node) in &[(14, "Option"), (18, "Statement"), (21, "Expression"), (24, "Expression"), (29, "Expression")] {
+            let mut flipped = bytes.clone();
+
+            flipped[offset] ^= 0xFF;
+
+            assert_eq!(decode(&flipped).err().unwrap().kind, ErrorKind::InvalidTag { node, tag: !bytes[offset] });
+        }
+
+        // A flipped location can still decode, into a different AST, but no byte may panic
+        let bytes = encode(&parse(SAMPLE).unwrap());
+
+        for offset in 0..bytes.len() {
+            let mut flipped = bytes.clone();
+
+            flipped[offset] ^= 0xFF;
+
+            let _ = decode(&flipped);
+        }
+    }
+}
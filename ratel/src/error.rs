@@ -56,7 +56,9 @@ impl Display for ParseError {
                                        .unwrap_or((0, ""));
 
                 let colno = line.chars().count();
-                let token_len = source[start..end].chars().count();
+                // Sources with invalid UTF-8 are printed lossily, in which
+                // case `end` can land in the middle of a replacement character
+                let token_len = source.get(start..end).map_or(1, |token| token.chars().count());
 
                 try!(writeln!(f, "Unexpected token at {}:{}\n", lineno + 1, colno + 1));
 
@@ -131,4 +133,16 @@ mod test {
         assert_eq!(format!("{}", err), expected);
     }
 
+    #[test]
+    fn test_format_lossy_source_error () {
+        let err = ParseError::UnexpectedToken {
+            source: String::from_utf8_lossy(b"foo\xFF").into_owned(),
+            start: 3,
+            end: 4
+        };
+
+        let expected = "Unexpected token at 1:4\n\n> 1 | foo\u{FFFD}\n    |    ^\n";
+
+        assert_eq!(format!("{}", err), expected);
+    }
 }
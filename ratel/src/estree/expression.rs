@@ -0,0 +1,213 @@
+use serde_json::Value;
+use ast::{Expression, ExpressionNode, NodeList, OperatorKind};
+use ast::expression::*;
+use estree::{Reader, Result};
+
+use ast::OperatorKind::*;
+
+/// Operators that can be looked up by their ESTree `operator` string.
+const OPERATORS: &[OperatorKind] = &[
+    Increment, Decrement, LogicalNot, BitwiseNot, Typeof, Void, Delete,
+    Multiplication, Division, Remainder, Exponent, Addition, Subtraction,
+    BitShiftLeft, BitShiftRight, UBitShiftRight, Lesser, LesserEquals,
+    Greater, GreaterEquals, Instanceof, In, StrictEquality, StrictInequality,
+    Equality, Inequality, BitwiseAnd, BitwiseXor, BitwiseOr, LogicalAnd,
+    LogicalOr, Assign, AddAssign, SubtractAssign, ExponentAssign,
+    MultiplyAssign, DivideAssign, RemainderAssign, BSLAssign, BSRAssign,
+    UBSRAssign, BitAndAssign, BitXorAssign, BitOrAssign,
+];
+
+impl<'ast> Reader<'ast> {
+    /// Read the `operator` of a node, which has to satisfy `allowed`.
+    fn operator<F>(&self, value: &Value, allowed: F) -> Result<OperatorKind>
+    where
+        F: Fn(OperatorKind) -> bool,
+    {
+        let operator = self.str_field(value, "operator")?;
+
+        match OPERATORS.iter().find(|kind| kind.as_str() == operator) {
+            Some(&kind) if allowed(kind) => Ok(kind),
+            _ => self.unsupported(&format!("{} with operator `{}`", self.node_type(value)?, operator)),
+        }
+    }
+
+    /// Read an element of an `ArrayExpression`, where `null` marks a hole.
+    fn element(&mut self, value: &Value) -> Result<ExpressionNode<'ast>> {
+        match *value {
+            // Hole in an array
+            Value::Null => Ok(self.alloc(value, Expression::Void)),
+            _           => self.expression(value),
+        }
+    }
+
+    pub fn expression(&mut self, value: &Value) -> Result<ExpressionNode<'ast>> {
+        let kind = self.node_type(value)?;
+
+        let expression = match kind {
+            "Identifier" => Expression::Identifier(self.identifier_str(value)?),
+
+            // ratel has no `super` node, keeping it as an identifier prints it as is
+            "Super" => Expression::Identifier("super"),
+            "ThisExpression" => Expression::This(ThisExpression),
+            "Literal" => Expression::Literal(self.literal(value)?),
+            "ArrayExpression" => Expression::Array(ArrayExpression {
+                body: self.read_list(value, "elements", Reader::element)?,
+            }),
+            "ObjectExpression" => Expression::Object(ObjectExpression {
+                body: self.read_list(value, "properties", Reader::property)?,
+            }),
+            "SequenceExpression" => Expression::Sequence(SequenceExpression {
+                body: self.read_list(value, "expressions", Reader::expression)?,
+            }),
+            "SpreadElement" => Expression::Spread(SpreadExpression {
+                argument: self.read(value, "argument", Reader::expression)?,
+            }),
+            "MemberExpression" => {
+                if self.flag(value, "optional")? {
+                    return self.unsupported("optional MemberExpression");
+                }
+
+                let object = self.read(value, "object", Reader::expression)?;
+
+                if self.flag(value, "computed")? {
+                    Expression::ComputedMember(ComputedMemberExpression {
+                        object,
+                        property: self.read(value, "property", Reader::expression)?,
+                    })
+                } else {
+                    Expression::Member(MemberExpression {
+                        object,
+                        property: self.read(value, "property", Reader::identifier)?,
+                    })
+                }
+            },
+            "CallExpression" => {
+                if self.flag(value, "optional")? {
+                    return self.unsupported("optional CallExpression");
+                }
+
+                Expression::Call(CallExpression {
+                    callee: self.read(value, "callee", Reader::expression)?,
+                    arguments: self.read_list(value, "arguments", Reader::expression)?,
+                })
+            },
+            "NewExpression" => {
+                // The parser reads `new Foo(a)` as `new` applied to a call
+                let call = CallExpression {
+                    callee: self.read(value, "callee", Reader::expression)?,
+                    arguments: self.read_list(value, "arguments", Reader::expression)?,
+                };
+
+                Expression::Prefix(PrefixExpression {
+                    operator: New,
+                    operand: self.alloc(value, Expression::Call(call)),
+                })
+            },
+            "UnaryExpression" => Expression::Prefix(PrefixExpression {
+                operator: self.operator(value, |kind| match kind {
+                    Increment | Decrement => false,
+                    kind                  => kind.prefix(),
+                })?,
+                operand: self.read(value, "argument", Reader::expression)?,
+            }),
+            "UpdateExpression" => {
+                let operator = self.operator(value, |kind| kind == Increment || kind == Decrement)?;
+                let operand = self.read(value, "argument", Reader::target)?;
+
+                if self.flag(value, "prefix")? {
+                    Expression::Prefix(PrefixExpression { operator, operand })
+                } else {
+                    Expression::Postfix(PostfixExpression { operator, operand })
+                }
+            },
+            "BinaryExpression" => Expression::Binary(BinaryExpression {
+                operator: self.operator(value, |kind| match kind {
+                    LogicalAnd | LogicalOr => false,
+                    kind                   => kind.infix() && !kind.assignment(),
+                })?,
+                left: self.read(value, "left", Reader::expression)?,
+                right: self.read(value, "right", Reader::expression)?,
+            }),
+            "LogicalExpression" => Expression::Binary(BinaryExpression {
+                operator: self.operator(value, |kind| kind == LogicalAnd || kind == LogicalOr)?,
+                left: self.read(value, "left", Reader::expression)?,
+                right: self.read(value, "right", Reader::expression)?,
+            }),
+            "AssignmentExpression" => Expression::Binary(BinaryExpression {
+                operator: self.operator(value, |kind| kind.assignment())?,
+                left: self.read(value, "left", Reader::target)?,
+                right: self.read(value, "right", Reader::expression)?,
+            }),
+            "ConditionalExpression" => Expression::Conditional(ConditionalExpression {
+                test: self.read(value, "test", Reader::expression)?,
+                consequent: self.read(value, "consequent", Reader::expression)?,
+                alternate: self.read(value, "alternate", Reader::expression)?,
+            }),
+            "TemplateLiteral" => Expression::Template(self.template(value)?),
+            "TaggedTemplateExpression" => {
+                self.no_annotations(value)?;
+
+                Expression::TaggedTemplate(TaggedTemplateExpression {
+                    tag: self.read(value, "tag", Reader::expression)?,
+                    quasi: self.read(value, "quasi", |reader, quasi| {
+                        let template = reader.template(quasi)?;
+
+                        Ok(reader.alloc(quasi, template))
+                    })?,
+                })
+            },
+            "ArrowFunctionExpression" => {
+                if self.flag(value, "async")? {
+                    return self.unsupported("async ArrowFunctionExpression");
+                }
+
+                self.no_annotations(value)?;
+
+                let params = self.read_list(value, "params", Reader::pattern)?;
+                let body = self.read(value, "body", |reader, body| {
+                    match reader.node_type(body)? {
+                        "BlockStatement" => reader.block(body).map(ArrowBody::Block),
+                        _                => reader.expression(body).map(ArrowBody::Expression),
+                    }
+                })?;
+
+                Expression::Arrow(ArrowExpression {
+                    type_params: NodeList::empty(),
+                    params,
+                    return_type: None,
+                    body,
+                })
+            },
+            "FunctionExpression" => Expression::Function(self.function_item(value)?),
+            "ClassExpression" => Expression::Class(self.class_item(value)?),
+            "ParenthesizedExpression" => return self.read(value, "expression", Reader::expression),
+            "ExpressionStatement" | "BlockStatement" | "EmptyStatement" |
+            "Program" | "VariableDeclaration" | "FunctionDeclaration" |
+            "ClassDeclaration" => return self.unexpected("an expression", kind),
+            _ => return self.unsupported(kind),
+        };
+
+        Ok(self.alloc(value, expression))
+    }
+
+    fn template(&mut self, value: &Value) -> Result<TemplateLiteral<'ast>> {
+        match self.node_type(value)? {
+            "TemplateLiteral" => {},
+            kind              => return self.unexpected("a TemplateLiteral", kind),
+        }
+
+        let quasis = self.read_list(value, "quasis", |reader, quasi| {
+            let raw = reader.read(quasi, "value", |reader, value| {
+                let raw = reader.str_field(value, "raw")?;
+
+                Ok(reader.alloc_str(raw))
+            })?;
+
+            Ok(reader.alloc(quasi, raw))
+        })?;
+
+        let expressions = self.read_list(value, "expressions", Reader::expression)?;
+
+        Ok(TemplateLiteral { expressions, quasis })
+    }
+}
@@ -0,0 +1,132 @@
+use serde_json::Value;
+use ast::{Node, Function, Class, ClassMember, MethodKind, Block, NodeList};
+use ast::{Name, EmptyName, OptionalName, MandatoryName};
+use estree::{Reader, Result, ErrorKind};
+
+/// Name of a function or a class, read from the `id` of the node.
+pub trait ReadName<'ast>: Name<'ast> {
+    fn read(reader: &mut Reader<'ast>, value: &Value) -> Result<Self>;
+}
+
+impl<'ast> ReadName<'ast> for EmptyName {
+    #[inline]
+    fn read(_: &mut Reader<'ast>, _: &Value) -> Result<Self> {
+        Ok(EmptyName)
+    }
+}
+
+impl<'ast> ReadName<'ast> for OptionalName<'ast> {
+    #[inline]
+    fn read(reader: &mut Reader<'ast>, value: &Value) -> Result<Self> {
+        reader.read_optional(value, "id", Reader::identifier).map(OptionalName)
+    }
+}
+
+impl<'ast> ReadName<'ast> for MandatoryName<'ast> {
+    #[inline]
+    fn read(reader: &mut Reader<'ast>, value: &Value) -> Result<Self> {
+        reader.read(value, "id", Reader::identifier).map(MandatoryName)
+    }
+}
+
+impl<'ast> Reader<'ast> {
+    pub fn function_item<N: ReadName<'ast>>(&mut self, value: &Value) -> Result<Function<'ast, N>> {
+        let kind = self.node_type(value)?;
+
+        if self.flag(value, "async")? {
+            return self.unsupported(&format!("async {}", kind));
+        }
+
+        if self.flag(value, "generator")? {
+            return self.unsupported(&format!("generator {}", kind));
+        }
+
+        self.no_annotations(value)?;
+
+        Ok(Function {
+            name: N::read(self, value)?,
+            type_params: NodeList::empty(),
+            params: self.read_list(value, "params", Reader::pattern)?,
+            return_type: None,
+            body: self.read(value, "body", Reader::block)?,
+        })
+    }
+
+    /// Read the `FunctionExpression` of a method.
+    pub fn function(&mut self, value: &Value) -> Result<Node<'ast, Function<'ast, EmptyName>>> {
+        match self.node_type(value)? {
+            "FunctionExpression" => {},
+            kind                 => return self.unexpected("a FunctionExpression", kind),
+        }
+
+        let function = self.function_item(value)?;
+
+        Ok(self.alloc(value, function))
+    }
+
+    pub fn class_item<N: ReadName<'ast>>(&mut self, value: &Value) -> Result<Class<'ast, N>> {
+        self.no_annotations(value)?;
+
+        if let Some(implements) = value.get("implements").and_then(Value::as_array) {
+            if !implements.is_empty() {
+                return self.unsupported("ClassImplements");
+            }
+        }
+
+        Ok(Class {
+            name: N::read(self, value)?,
+            type_params: NodeList::empty(),
+            extends: self.read_optional(value, "superClass", Reader::expression)?,
+            super_type_args: NodeList::empty(),
+            implements: NodeList::empty(),
+            body: self.read(value, "body", |reader, body| {
+                match reader.node_type(body)? {
+                    "ClassBody" => {},
+                    kind        => return reader.unexpected("a ClassBody", kind),
+                }
+
+                let members = reader.read_list(body, "body", Reader::class_member)?;
+
+                Ok(reader.alloc(body, Block { body: members }))
+            })?,
+        })
+    }
+
+    fn class_member(&mut self, value: &Value) -> Result<Node<'ast, ClassMember<'ast>>> {
+        let kind = self.node_type(value)?;
+        let is_static = self.flag(value, "static")?;
+
+        let member = match kind {
+            "MethodDefinition" => {
+                let kind = match self.str_field(value, "kind")? {
+                    "constructor" => MethodKind::Constructor,
+                    "method"      => MethodKind::Method,
+                    "get"         => MethodKind::Get,
+                    "set"         => MethodKind::Set,
+                    _             => return self.error(ErrorKind::InvalidField("kind")),
+                };
+
+                ClassMember::Method {
+                    is_static,
+                    key: self.read(value, "key", |reader, key| reader.property_key(value, key))?,
+                    kind,
+                    value: self.read(value, "value", Reader::function)?,
+                }
+            },
+
+            // Acorn calls these `PropertyDefinition`, Babel `ClassProperty`
+            "PropertyDefinition" | "ClassProperty" => {
+                self.no_annotations(value)?;
+
+                ClassMember::Literal {
+                    is_static,
+                    key: self.read(value, "key", |reader, key| reader.property_key(value, key))?,
+                    value: self.read(value, "value", Reader::expression)?,
+                }
+            },
+            _ => return self.unsupported(kind),
+        };
+
+        Ok(self.alloc(value, member))
+    }
+}
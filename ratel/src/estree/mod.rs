@@ -0,0 +1,539 @@
+//! Reading of ESTree ASTs, as produced by Acorn, Esprima or ratel itself,
+//! back into ratel's arena-allocated AST.
+
+mod statement;
+mod expression;
+mod function;
+mod value;
+
+use std::fmt::{self, Debug, Display};
+use serde_json::{self, Value};
+use toolshed::Arena;
+use toolshed::list::List;
+use ast::{Loc, Node, NodeList, Program};
+use module::Module;
+
+/// Kind of an `estree::Error`.
+#[derive(Debug, PartialEq, Clone)]
+pub enum ErrorKind {
+    /// The input is not valid JSON.
+    InvalidJson(String),
+    /// Value is not an object with a string `type` field.
+    NotANode,
+    /// Node is missing a required field.
+    MissingField(&'static str),
+    /// Field of the node has a value ratel doesn't understand.
+    InvalidField(&'static str),
+    /// Node type, or a flavor of it, that ratel can't represent.
+    UnsupportedNode(String),
+    /// Node type that is known, but not allowed in this position.
+    UnexpectedNode {
+        expected: &'static str,
+        found: String,
+    },
+}
+
+/// Error type returned when reading an ESTree AST. The `path` points to
+/// the offending node, such as `program.body[2].expression.callee`.
+#[derive(PartialEq, Clone)]
+pub struct Error {
+    pub kind: ErrorKind,
+    pub path: String,
+}
+
+impl Debug for Error {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.kind {
+            ErrorKind::InvalidJson(ref message) => {
+                write!(f, "Invalid JSON: {}", message)
+            },
+            ErrorKind::NotANode => {
+                write!(f, "Expected an ESTree node at {}", self.path)
+            },
+            ErrorKind::MissingField(field) => {
+                write!(f, "Missing field `{}` at {}", field, self.path)
+            },
+            ErrorKind::InvalidField(field) => {
+                write!(f, "Invalid value of field `{}` at {}", field, self.path)
+            },
+            ErrorKind::UnsupportedNode(ref kind) => {
+                write!(f, "Unsupported node type `{}` at {}", kind, self.path)
+            },
+            ErrorKind::UnexpectedNode { expected, ref found } => {
+                write!(f, "Expected {}, found `{}` at {}", expected, found, self.path)
+            },
+        }
+    }
+}
+
+pub type Result<T> = ::std::result::Result<T, Error>;
+
+/// Read an ESTree `Program` from a JSON string into a new `Module`.
+pub fn from_str<'ast>(json: &str) -> Result<Module<'ast>> {
+    let value = serde_json::from_str(json).map_err(|err| Error {
+        kind: ErrorKind::InvalidJson(err.to_string()),
+        path: String::new(),
+    })?;
+
+    from_value(&value)
+}
+
+/// Read an ESTree `Program` from a parsed JSON value into a new `Module`.
+pub fn from_value<'ast>(value: &Value) -> Result<Module<'ast>> {
+    Module::from_arena(Arena::new(), |arena| from_value_in(value, arena))
+}
+
+/// Same as `from_value`, but allocates the AST in an `Arena` owned by the
+/// caller, producing a `Program` borrowed from it. Since there is no source,
+/// the `source` of the `Program` is empty.
+pub fn from_value_in<'ast>(value: &Value, arena: &'ast Arena) -> Result<Program<'ast>> {
+    let mut reader = Reader {
+        arena,
+        path: Vec::new(),
+    };
+
+    reader.program(value)
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Segment {
+    Field(&'static str),
+    Index(usize),
+}
+
+/// Walks the JSON tree, keeping track of the path to the current node for
+/// error reporting.
+struct Reader<'ast> {
+    arena: &'ast Arena,
+    path: Vec<Segment>,
+}
+
+impl<'ast> Reader<'ast> {
+    fn error<T>(&self, kind: ErrorKind) -> Result<T> {
+        let mut path = String::from("program");
+
+        for segment in &self.path {
+            match *segment {
+                Segment::Field(name) => {
+                    path.push('.');
+                    path.push_str(name);
+                },
+                Segment::Index(index) => {
+                    path.push_str(&format!("[{}]", index));
+                },
+            }
+        }
+
+        Err(Error { kind, path })
+    }
+
+    #[inline]
+    fn unsupported<T>(&self, kind: &str) -> Result<T> {
+        self.error(ErrorKind::UnsupportedNode(kind.to_owned()))
+    }
+
+    #[inline]
+    fn unexpected<T>(&self, expected: &'static str, found: &str) -> Result<T> {
+        self.error(ErrorKind::UnexpectedNode {
+            expected,
+            found: found.to_owned(),
+        })
+    }
+
+    /// Get the `type` of a node.
+    fn node_type<'v>(&self, value: &'v Value) -> Result<&'v str> {
+        match value.get("type").and_then(Value::as_str) {
+            Some(kind) => Ok(kind),
+            None       => self.error(ErrorKind::NotANode),
+        }
+    }
+
+    /// Get a required field, treating `null` as missing.
+    fn field<'v>(&self, value: &'v Value, name: &'static str) -> Result<&'v Value> {
+        match value.get(name) {
+            None | Some(&Value::Null) => self.error(ErrorKind::MissingField(name)),
+            Some(field)               => Ok(field),
+        }
+    }
+
+    fn str_field<'v>(&self, value: &'v Value, name: &'static str) -> Result<&'v str> {
+        match self.field(value, name)?.as_str() {
+            Some(string) => Ok(string),
+            None         => self.error(ErrorKind::InvalidField(name)),
+        }
+    }
+
+    /// Get a boolean flag, which is `false` when missing.
+    fn flag(&self, value: &Value, name: &'static str) -> Result<bool> {
+        match value.get(name) {
+            None | Some(&Value::Null) => Ok(false),
+            Some(&Value::Bool(flag))  => Ok(flag),
+            Some(_)                   => self.error(ErrorKind::InvalidField(name)),
+        }
+    }
+
+    /// Get the location of a node from either Acorn style `start` and `end`,
+    /// or Esprima style `range`. Nodes without either are placed at 0.
+    fn loc(&self, value: &Value) -> (u32, u32) {
+        let offset = |value: Option<&Value>| {
+            value.and_then(Value::as_u64).unwrap_or(0) as u32
+        };
+
+        if value.get("start").is_some() {
+            return (offset(value.get("start")), offset(value.get("end")));
+        }
+
+        match value.get("range").and_then(Value::as_array) {
+            Some(range) => (offset(range.first()), offset(range.get(1))),
+            None        => (0, 0),
+        }
+    }
+
+    #[inline]
+    fn alloc<T: Copy>(&self, value: &Value, item: T) -> Node<'ast, T> {
+        let (start, end) = self.loc(value);
+
+        Node::new(self.arena.alloc(Loc::new(start, end, item)))
+    }
+
+    #[inline]
+    fn alloc_str(&self, value: &str) -> &'ast str {
+        self.arena.alloc_str(value)
+    }
+
+    /// Read a required field with `read`.
+    fn read<T, F>(&mut self, value: &Value, name: &'static str, read: F) -> Result<T>
+    where
+        F: FnOnce(&mut Self, &Value) -> Result<T>,
+    {
+        let field = self.field(value, name)?;
+
+        self.path.push(Segment::Field(name));
+        let result = read(self, field)?;
+        self.path.pop();
+
+        Ok(result)
+    }
+
+    /// Read a field with `read`, unless it's missing or `null`.
+    fn read_optional<T, F>(&mut self, value: &Value, name: &'static str, read: F) -> Result<Option<T>>
+    where
+        F: FnOnce(&mut Self, &Value) -> Result<T>,
+    {
+        match value.get(name) {
+            None | Some(&Value::Null) => Ok(None),
+            Some(_)                   => self.read(value, name, read).map(Some),
+        }
+    }
+
+    /// Read an array field, passing each element to `read`.
+    fn read_list<T, F>(&mut self, value: &Value, name: &'static str, mut read: F) -> Result<NodeList<'ast, T>>
+    where
+        T: 'ast + Copy,
+        F: FnMut(&mut Self, &Value) -> Result<Node<'ast, T>>,
+    {
+        self.read(value, name, |reader, field| {
+            let elements = match field.as_array() {
+                Some(elements) => elements,
+                None           => return reader.error(ErrorKind::InvalidField(name)),
+            };
+
+            let mut nodes = Vec::with_capacity(elements.len());
+
+            for (index, element) in elements.iter().enumerate() {
+                reader.path.push(Segment::Index(index));
+                nodes.push(read(reader, element)?);
+                reader.path.pop();
+            }
+
+            Ok(List::from_iter(reader.arena, nodes))
+        })
+    }
+
+    /// Error out on Flow or TypeScript annotations attached to a node,
+    /// since those can't be read.
+    fn no_annotations(&mut self, value: &Value) -> Result<()> {
+        for &name in &["typeAnnotation", "returnType", "typeParameters", "superTypeParameters"] {
+            self.read_optional(value, name, |reader, annotation| {
+                let kind = reader.node_type(annotation)?;
+
+                reader.unsupported::<()>(kind)
+            })?;
+        }
+
+        Ok(())
+    }
+
+    fn program(&mut self, value: &Value) -> Result<Program<'ast>> {
+        match self.node_type(value)? {
+            "Program" => {},
+
+            // Babel wraps the `Program` in a `File`
+            "File"    => return self.read(value, "program", Reader::program),
+
+            kind      => return self.unexpected("Program", kind),
+        }
+
+        let body = self.read_list(value, "body", Reader::statement)?;
+
+        let hashbang = self.read_optional(value, "interpreter", |reader, interpreter| {
+            let hashbang = reader.str_field(interpreter, "value")?;
+
+            Ok(reader.alloc_str(hashbang))
+        })?;
+
+        Ok(Program {
+            source: "",
+            body,
+            hashbang,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use parser::{parse, parse_with_options, ParseOptions, Goal};
+
+    /// Serialize the parsed source to ESTree JSON, read it back and
+    /// check that the resulting AST is identical, minus locations.
+    fn assert_roundtrip(source: &str) {
+        let module = parse_with_options(source, ParseOptions { goal: Goal::Module, ..ParseOptions::default() }).unwrap();
+        let json = serde_json::to_string(&module).unwrap();
+        let read = from_str(&json).unwrap();
+
+        assert_eq!(read.body(), module.body(), "{}", source);
+    }
+
+    #[test]
+    fn roundtrip_statements() {
+        assert_roundtrip("var a = 1, b;");
+        assert_roundtrip("let [a, b = 2] = c;");
+        assert_roundtrip("const { a, b: c } = d;");
+        assert_roundtrip("if (a) { b; } else c;");
+        assert_roundtrip("while (a) b;");
+        assert_roundtrip("do a; while (b);");
+        assert_roundtrip("for (var i = 0; i < 10; i++) {}");
+        assert_roundtrip("for (;;) {}");
+        assert_roundtrip("for (var a in b) {}");
+        assert_roundtrip("for (a of b) {}");
+        assert_roundtrip("foo: for (;;) { break foo; continue foo; }");
+        assert_roundtrip("try { a; } catch (err) { b; } finally { c; }");
+        assert_roundtrip("throw a;");
+        assert_roundtrip("function foo(a, b = 1, ...c) { return a; }");
+        assert_roundtrip("class Foo extends Bar { constructor() {} static baz() {} }");
+        assert_roundtrip("import foo, { bar as baz } from 'foo';");
+        assert_roundtrip("import * as foo from 'foo';");
+        assert_roundtrip("export default foo;");
+        assert_roundtrip("export { foo as bar } from 'foo';");
+        assert_roundtrip("export * from 'foo';");
+        assert_roundtrip("export const foo = 1;");
+    }
+
+    #[test]
+    fn roundtrip_expressions() {
+        assert_roundtrip("this;");
+        assert_roundtrip("a = b += c;");
+        assert_roundtrip("a || b && c;");
+        assert_roundtrip("-a + !b * typeof c;");
+        assert_roundtrip("++a, b--;");
+        assert_roundtrip("a.b[c](d, ...e);");
+        assert_roundtrip("new Foo(a);");
+        assert_roundtrip("a ? b : c;");
+        assert_roundtrip("[a, ...b];");
+        assert_roundtrip("({ a, b: 1, [c]: 2, d() {} });");
+        assert_roundtrip("(a, b) => a + b;");
+        assert_roundtrip("() => { return 1; };");
+        assert_roundtrip("(function foo() {});");
+        assert_roundtrip("`foo${ bar }baz`;");
+        assert_roundtrip("tag`foo`;");
+    }
+
+    #[test]
+    fn roundtrip_literals() {
+        assert_roundtrip("null; true; false; undefined;");
+        assert_roundtrip("'foo'; \"bar\";");
+        assert_roundtrip("42; 3.14; 0xff; 0b101;");
+        assert_roundtrip("/foo/gi;");
+    }
+
+    #[test]
+    fn acorn_output() {
+        // `foo("bar", 1)` as printed by Acorn
+        let json = r#"{
+            "type": "Program",
+            "start": 0,
+            "end": 13,
+            "body": [{
+                "type": "ExpressionStatement",
+                "start": 0,
+                "end": 13,
+                "expression": {
+                    "type": "CallExpression",
+                    "start": 0,
+                    "end": 13,
+                    "callee": { "type": "Identifier", "start": 0, "end": 3, "name": "foo" },
+                    "arguments": [
+                        { "type": "Literal", "start": 4, "end": 9, "value": "bar", "raw": "\"bar\"" },
+                        { "type": "Literal", "start": 11, "end": 12, "value": 1, "raw": "1" }
+                    ],
+                    "optional": false
+                }
+            }],
+            "sourceType": "module"
+        }"#;
+
+        let module = from_str(json).unwrap();
+
+        assert_eq!(module.body(), parse(r#"foo("bar", 1);"#).unwrap().body());
+    }
+
+    #[test]
+    fn literals_without_raw() {
+        let json = json!({
+            "type": "Program",
+            "body": [{
+                "type": "ExpressionStatement",
+                "expression": {
+                    "type": "ArrayExpression",
+                    "elements": [
+                        { "type": "Literal", "value": "a\"b" },
+                        null,
+                        { "type": "Literal", "value": 2 },
+                        { "type": "Literal", "regex": { "pattern": "x", "flags": "g" } }
+                    ]
+                }
+            }]
+        });
+
+        let module = from_value(&json).unwrap();
+
+        assert_eq!(module.body(), parse(r#"["a\"b", , 2, /x/g];"#).unwrap().body());
+    }
+
+    #[test]
+    fn esprima_range() {
+        let json = json!({
+            "type": "Program",
+            "body": [{
+                "type": "EmptyStatement",
+                "range": [3, 4]
+            }]
+        });
+
+        let module = from_value(&json).unwrap();
+        let statement = module.body().only_element().unwrap();
+
+        assert_eq!((statement.start, statement.end), (3, 4));
+    }
+
+    #[test]
+    fn hashbang() {
+        let module = parse("#!/usr/bin/env node\nfoo;").unwrap();
+        let json = serde_json::to_string(&module).unwrap();
+
+        assert_eq!(from_str(&json).unwrap().hashbang(), Some("/usr/bin/env node"));
+    }
+
+    #[test]
+    fn from_value_in_arena() {
+        let arena = Arena::new();
+        let json = json!({
+            "type": "File",
+            "program": {
+                "type": "Program",
+                "body": [{ "type": "EmptyStatement" }]
+            }
+        });
+
+        let program = from_value_in(&json, &arena).unwrap();
+
+        assert_eq!(program.source, "");
+        assert_eq!(program.body.iter().count(), 1);
+    }
+
+    #[test]
+    fn unsupported_node() {
+        let json = json!({
+            "type": "Program",
+            "body": [{
+                "type": "ExpressionStatement",
+                "expression": {
+                    "type": "JSXElement"
+                }
+            }]
+        });
+
+        let error = from_value(&json).err().unwrap();
+
+        assert_eq!(error, Error {
+            kind: ErrorKind::UnsupportedNode("JSXElement".into()),
+            path: "program.body[0].expression".into(),
+        });
+        assert_eq!(
+            error.to_string(),
+            "Unsupported node type `JSXElement` at program.body[0].expression"
+        );
+    }
+
+    #[test]
+    fn unsupported_flavors() {
+        let read = |source: &str| {
+            let json = json!({
+                "type": "Program",
+                "body": [serde_json::from_str::<Value>(source).unwrap()]
+            });
+
+            from_value(&json).err().unwrap().kind
+        };
+
+        assert_eq!(
+            read(r#"{ "type": "FunctionDeclaration", "id": { "type": "Identifier", "name": "f" }, "params": [], "body": { "type": "BlockStatement", "body": [] }, "async": true }"#),
+            ErrorKind::UnsupportedNode("async FunctionDeclaration".into())
+        );
+        assert_eq!(
+            read(r#"{ "type": "DebuggerStatement" }"#),
+            ErrorKind::UnsupportedNode("DebuggerStatement".into())
+        );
+        assert_eq!(
+            read(r#"{ "type": "VariableDeclaration", "kind": "var", "declarations": [{ "type": "VariableDeclarator", "id": { "type": "Identifier", "name": "a", "typeAnnotation": { "type": "TypeAnnotation" } } }] }"#),
+            ErrorKind::UnsupportedNode("TypeAnnotation".into())
+        );
+    }
+
+    #[test]
+    fn malformed_input() {
+        assert_eq!(from_str("{").err().unwrap().path, "");
+
+        let json = json!({ "type": "Program", "body": [{ "type": "ReturnStatement" }, { "foo": 1 }] });
+
+        assert_eq!(from_value(&json).err().unwrap(), Error {
+            kind: ErrorKind::NotANode,
+            path: "program.body[1]".into(),
+        });
+
+        let json = json!({ "type": "Program", "body": [{ "type": "ThrowStatement" }] });
+
+        assert_eq!(from_value(&json).err().unwrap(), Error {
+            kind: ErrorKind::MissingField("argument"),
+            path: "program.body[0]".into(),
+        });
+
+        let json = json!({ "type": "Program", "body": [{ "type": "Identifier", "name": "a" }] });
+
+        assert_eq!(from_value(&json).err().unwrap(), Error {
+            kind: ErrorKind::UnexpectedNode {
+                expected: "a statement",
+                found: "Identifier".into(),
+            },
+            path: "program.body[0]".into(),
+        });
+    }
+}
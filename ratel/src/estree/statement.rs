@@ -0,0 +1,273 @@
+use serde_json::Value;
+use ast::{Node, Statement, StatementNode, Block, BlockNode, Expression, DeclarationKind, Declarator};
+use ast::statement::*;
+use estree::{Reader, Result, ErrorKind};
+
+/// Whether the node type is one of the expressions, which can't be used
+/// where a statement is expected without an `ExpressionStatement`.
+#[inline]
+fn is_expression(kind: &str) -> bool {
+    kind.ends_with("Expression") || matches!(kind, "Identifier" | "Literal" | "Super" | "TemplateLiteral")
+}
+
+impl<'ast> Reader<'ast> {
+    pub fn statement(&mut self, value: &Value) -> Result<StatementNode<'ast>> {
+        let kind = self.node_type(value)?;
+
+        let statement = match kind {
+            "EmptyStatement" => Statement::Empty,
+            "ExpressionStatement" => Statement::Expression(self.read(value, "expression", Reader::expression)?),
+            "BlockStatement" => Statement::Block(self.block(value)?.item),
+            "VariableDeclaration" => Statement::Declaration(self.declaration(value)?),
+            "ReturnStatement" => Statement::Return(ReturnStatement {
+                value: self.read_optional(value, "argument", Reader::expression)?,
+            }),
+            "BreakStatement" => Statement::Break(BreakStatement {
+                label: self.read_optional(value, "label", Reader::identifier)?,
+            }),
+            "ContinueStatement" => Statement::Continue(ContinueStatement {
+                label: self.read_optional(value, "label", Reader::identifier)?,
+            }),
+            "ThrowStatement" => Statement::Throw(ThrowStatement {
+                value: self.read(value, "argument", Reader::expression)?,
+            }),
+            "IfStatement" => Statement::If(IfStatement {
+                test: self.read(value, "test", Reader::expression)?,
+                consequent: self.read(value, "consequent", Reader::statement)?,
+                alternate: self.read_optional(value, "alternate", Reader::statement)?,
+            }),
+            "WhileStatement" => Statement::While(WhileStatement {
+                test: self.read(value, "test", Reader::expression)?,
+                body: self.read(value, "body", Reader::statement)?,
+            }),
+            "WithStatement" => Statement::With(WithStatement {
+                object: self.read(value, "object", Reader::expression)?,
+                body: self.read(value, "body", Reader::statement)?,
+            }),
+            "DoWhileStatement" => Statement::Do(DoStatement {
+                body: self.read(value, "body", Reader::statement)?,
+                test: self.read(value, "test", Reader::expression)?,
+            }),
+            "ForStatement" => Statement::For(ForStatement {
+                init: self.read_optional(value, "init", Reader::for_init)?,
+                test: self.read_optional(value, "test", Reader::expression)?,
+                update: self.read_optional(value, "update", Reader::expression)?,
+                body: self.read(value, "body", Reader::statement)?,
+            }),
+            "ForInStatement" => Statement::ForIn(ForInStatement {
+                left: self.read(value, "left", Reader::for_init)?,
+                right: self.read(value, "right", Reader::expression)?,
+                body: self.read(value, "body", Reader::statement)?,
+            }),
+            "ForOfStatement" => {
+                if self.flag(value, "await")? {
+                    return self.unsupported("for await ForOfStatement");
+                }
+
+                Statement::ForOf(ForOfStatement {
+                    left: self.read(value, "left", Reader::for_init)?,
+                    right: self.read(value, "right", Reader::expression)?,
+                    body: self.read(value, "body", Reader::statement)?,
+                })
+            },
+            "TryStatement" => Statement::Try(TryStatement {
+                block: self.read(value, "block", Reader::block)?,
+                handler: self.read_optional(value, "handler", Reader::catch_clause)?,
+                finalizer: self.read_optional(value, "finalizer", Reader::block)?,
+            }),
+            "LabeledStatement" => Statement::Labeled(LabeledStatement {
+                label: self.read(value, "label", Reader::label)?,
+                body: self.read(value, "body", Reader::statement)?,
+            }),
+            "SwitchStatement" => {
+                let discriminant = self.read(value, "discriminant", Reader::expression)?;
+                let cases = self.read_list(value, "cases", Reader::switch_case)?;
+
+                Statement::Switch(SwitchStatement {
+                    discriminant,
+                    cases: self.alloc(value, Block { body: cases }),
+                })
+            },
+            "FunctionDeclaration" => Statement::Function(self.function_item(value)?),
+            "ClassDeclaration" => Statement::Class(self.class_item(value)?),
+            "ImportDeclaration" => Statement::Import(self.import(value)?),
+            "ExportNamedDeclaration" |
+            "ExportDefaultDeclaration" |
+            "ExportAllDeclaration" => Statement::Export(self.export(value)?),
+            _ if is_expression(kind) => return self.unexpected("a statement", kind),
+            _ => return self.unsupported(kind),
+        };
+
+        Ok(self.alloc(value, statement))
+    }
+
+    pub fn block(&mut self, value: &Value) -> Result<BlockNode<'ast, Statement<'ast>>> {
+        match self.node_type(value)? {
+            "BlockStatement" => {},
+            kind             => return self.unexpected("a BlockStatement", kind),
+        }
+
+        let body = self.read_list(value, "body", Reader::statement)?;
+
+        Ok(self.alloc(value, Block { body }))
+    }
+
+    fn declaration(&mut self, value: &Value) -> Result<DeclarationStatement<'ast>> {
+        let kind = match self.str_field(value, "kind")? {
+            "var"   => DeclarationKind::Var,
+            "let"   => DeclarationKind::Let,
+            "const" => DeclarationKind::Const,
+            _       => return self.error(ErrorKind::InvalidField("kind")),
+        };
+
+        let declarators = self.read_list(value, "declarations", |reader, declarator| {
+            match reader.node_type(declarator)? {
+                "VariableDeclarator" => {},
+                kind                 => return reader.unexpected("a VariableDeclarator", kind),
+            }
+
+            let declarator_item = Declarator {
+                id: reader.read(declarator, "id", Reader::pattern)?,
+                init: reader.read_optional(declarator, "init", Reader::expression)?,
+            };
+
+            Ok(reader.alloc(declarator, declarator_item))
+        })?;
+
+        Ok(DeclarationStatement { kind, declarators })
+    }
+
+    fn for_init(&mut self, value: &Value) -> Result<Node<'ast, ForInit<'ast>>> {
+        let init = match self.node_type(value)? {
+            "VariableDeclaration" => ForInit::Declaration(self.declaration(value)?),
+            _                     => ForInit::Expression(self.target(value)?),
+        };
+
+        Ok(self.alloc(value, init))
+    }
+
+    fn catch_clause(&mut self, value: &Value) -> Result<Node<'ast, CatchClause<'ast>>> {
+        match self.node_type(value)? {
+            "CatchClause" => {},
+            kind          => return self.unexpected("a CatchClause", kind),
+        }
+
+        if value.get("param").unwrap_or(&Value::Null).is_null() {
+            return self.unsupported("CatchClause without a param");
+        }
+
+        let clause = CatchClause {
+            param: self.read(value, "param", Reader::pattern)?,
+            body: self.read(value, "body", Reader::block)?,
+        };
+
+        Ok(self.alloc(value, clause))
+    }
+
+    fn switch_case(&mut self, value: &Value) -> Result<Node<'ast, SwitchCase<'ast>>> {
+        match self.node_type(value)? {
+            "SwitchCase" => {},
+            kind         => return self.unexpected("a SwitchCase", kind),
+        }
+
+        let case = SwitchCase {
+            test: self.read_optional(value, "test", Reader::expression)?,
+            consequent: self.read_list(value, "consequent", Reader::statement)?,
+        };
+
+        Ok(self.alloc(value, case))
+    }
+
+    fn import(&mut self, value: &Value) -> Result<ImportStatement<'ast>> {
+        let kind = match value.get("importKind").and_then(Value::as_str) {
+            None | Some("value") => ImportKind::Value,
+            Some("type")         => ImportKind::Type,
+            Some("typeof")       => ImportKind::Typeof,
+            Some(_)              => return self.error(ErrorKind::InvalidField("importKind")),
+        };
+
+        let specifiers = self.read_list(value, "specifiers", |reader, specifier| {
+            let specifier_item = match reader.node_type(specifier)? {
+                "ImportDefaultSpecifier" => {
+                    ImportSpecifier::Default(reader.read(specifier, "local", Reader::identifier_str)?)
+                },
+                "ImportNamespaceSpecifier" => {
+                    ImportSpecifier::Namespace(reader.read(specifier, "local", Reader::identifier_str)?)
+                },
+                "ImportSpecifier" => ImportSpecifier::Named {
+                    imported: reader.read(specifier, "imported", Reader::identifier_str)?,
+                    local: reader.read(specifier, "local", Reader::identifier_str)?,
+                },
+                kind => return reader.unexpected("an import specifier", kind),
+            };
+
+            Ok(reader.alloc(specifier, specifier_item))
+        })?;
+
+        Ok(ImportStatement {
+            kind,
+            specifiers,
+            source: self.read(value, "source", Reader::literal_node)?,
+        })
+    }
+
+    fn export(&mut self, value: &Value) -> Result<ExportStatement<'ast>> {
+        let kind = match value.get("exportKind").and_then(Value::as_str) {
+            None | Some("value") => ExportKind::Value,
+            Some("type")         => ExportKind::Type,
+            Some(kind)           => return self.unexpected("an exportKind", kind),
+        };
+
+        if kind == ExportKind::Type && !value.get("declaration").unwrap_or(&Value::Null).is_null() {
+            return self.unsupported("ExportNamedDeclaration of a type");
+        }
+
+        let export = match self.node_type(value)? {
+            "ExportDefaultDeclaration" => {
+                let declaration = self.read(value, "declaration", |reader, declaration| {
+                    // Declarations are exported as their expression counterparts
+                    let expression = match reader.node_type(declaration)? {
+                        "FunctionDeclaration" => Expression::Function(reader.function_item(declaration)?),
+                        "ClassDeclaration"    => Expression::Class(reader.class_item(declaration)?),
+                        _                     => return reader.expression(declaration),
+                    };
+
+                    Ok(reader.alloc(declaration, expression))
+                })?;
+
+                ExportStatement::Default(declaration)
+            },
+            "ExportAllDeclaration" => {
+                if !value.get("exported").unwrap_or(&Value::Null).is_null() {
+                    return self.unsupported("ExportAllDeclaration with an exported name");
+                }
+
+                ExportStatement::All {
+                    source: self.read(value, "source", Reader::literal_node)?,
+                }
+            },
+            _ => match self.read_optional(value, "declaration", Reader::statement)? {
+                Some(declaration) => ExportStatement::Declaration(declaration),
+                None => ExportStatement::Named {
+                    kind,
+                    specifiers: self.read_list(value, "specifiers", |reader, specifier| {
+                        match reader.node_type(specifier)? {
+                            "ExportSpecifier" => {},
+                            kind              => return reader.unexpected("an ExportSpecifier", kind),
+                        }
+
+                        let specifier_item = ExportSpecifier {
+                            local: reader.read(specifier, "local", Reader::identifier_str)?,
+                            exported: reader.read(specifier, "exported", Reader::identifier_str)?,
+                        };
+
+                        Ok(reader.alloc(specifier, specifier_item))
+                    })?,
+                    source: self.read_optional(value, "source", Reader::literal_node)?,
+                },
+            },
+        };
+
+        Ok(export)
+    }
+}
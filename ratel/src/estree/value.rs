@@ -0,0 +1,269 @@
+use serde_json::{self, Value};
+use ast::{Node, Literal, Pattern, Property, PropertyKey, Expression, ExpressionNode};
+use ast::{IdentifierNode, OperatorKind};
+use ast::expression::{BinaryExpression, ArrayExpression, ObjectExpression, SpreadExpression};
+use estree::{Reader, Result, ErrorKind};
+
+impl<'ast> Reader<'ast> {
+    /// Read the name of an `Identifier` node.
+    pub fn identifier_str(&mut self, value: &Value) -> Result<&'ast str> {
+        match self.node_type(value)? {
+            "Identifier" => {},
+            kind         => return self.unexpected("an Identifier", kind),
+        }
+
+        self.no_annotations(value)?;
+
+        let name = self.str_field(value, "name")?;
+
+        Ok(self.alloc_str(name))
+    }
+
+    pub fn identifier(&mut self, value: &Value) -> Result<IdentifierNode<'ast>> {
+        let name = self.identifier_str(value)?;
+
+        Ok(self.alloc(value, name))
+    }
+
+    /// Read a statement label, which ratel itself emits as a bare string.
+    pub fn label(&mut self, value: &Value) -> Result<&'ast str> {
+        match value.as_str() {
+            Some(label) => Ok(self.alloc_str(label)),
+            None        => self.identifier_str(value),
+        }
+    }
+
+    pub fn literal(&mut self, value: &Value) -> Result<Literal<'ast>> {
+        let raw = value.get("raw").and_then(Value::as_str);
+
+        if value.get("regex").is_some() {
+            let raw = match raw {
+                Some(raw) => self.alloc_str(raw),
+                None      => self.read(value, "regex", |reader, regex| {
+                    let pattern = reader.str_field(regex, "pattern")?;
+                    let flags = regex.get("flags").and_then(Value::as_str).unwrap_or("");
+
+                    Ok(reader.arena.alloc_string(format!("/{}/{}", pattern, flags)))
+                })?,
+            };
+
+            return Ok(Literal::RegEx(raw));
+        }
+
+        if value.get("bigint").is_some() {
+            return self.unsupported("BigInt Literal");
+        }
+
+        let literal = match value.get("value") {
+            // ratel serializes `undefined` as a `Literal` without a value
+            None if raw == Some("undefined") => Literal::Undefined,
+            None | Some(Value::Null)         => Literal::Null,
+            Some(Value::Bool(true))          => Literal::True,
+            Some(Value::Bool(false))         => Literal::False,
+            Some(Value::Number(number))      => {
+                let raw = match raw {
+                    Some(raw) => self.alloc_str(raw),
+                    None      => self.arena.alloc_string(number.to_string()),
+                };
+
+                match raw.get(0..2) {
+                    Some("0b") | Some("0B") => Literal::Binary(raw),
+                    _                       => Literal::Number(raw),
+                }
+            },
+            Some(Value::String(string))      => {
+                let raw = match raw {
+                    Some(raw) => self.alloc_str(raw),
+
+                    // JSON strings are valid JavaScript strings
+                    None      => match serde_json::to_string(string) {
+                        Ok(quoted) => self.arena.alloc_string(quoted),
+                        Err(_)     => return self.error(ErrorKind::InvalidField("value")),
+                    },
+                };
+
+                Literal::String(raw)
+            },
+            Some(_) => return self.error(ErrorKind::InvalidField("value")),
+        };
+
+        Ok(literal)
+    }
+
+    pub fn literal_node(&mut self, value: &Value) -> Result<Node<'ast, Literal<'ast>>> {
+        match self.node_type(value)? {
+            "Literal" => {},
+            kind      => return self.unexpected("a Literal", kind),
+        }
+
+        let literal = self.literal(value)?;
+
+        Ok(self.alloc(value, literal))
+    }
+
+    pub fn pattern(&mut self, value: &Value) -> Result<Node<'ast, Pattern<'ast>>> {
+        let kind = self.node_type(value)?;
+
+        self.no_annotations(value)?;
+
+        let pattern = match kind {
+            "Identifier" => Pattern::Identifier(self.identifier_str(value)?),
+            "ObjectPattern" => {
+                // ratel itself serializes the properties as `keys`
+                let name = if value.get("properties").is_some() { "properties" } else { "keys" };
+
+                Pattern::ObjectPattern {
+                    properties: self.read_list(value, name, Reader::pattern_property)?,
+                }
+            },
+            "ArrayPattern" => Pattern::ArrayPattern {
+                elements: self.read_list(value, "elements", |reader, element| {
+                    match *element {
+                        Value::Null => Ok(reader.alloc(element, Pattern::Void)),
+                        _           => reader.pattern(element),
+                    }
+                })?,
+            },
+            "AssignmentPattern" => Pattern::AssignmentPattern {
+                left: self.read(value, "left", Reader::pattern)?,
+                right: self.read(value, "right", Reader::expression)?,
+            },
+            "RestElement" => Pattern::RestElement {
+                argument: self.read(value, "argument", Reader::identifier)?,
+            },
+            "MemberExpression" => return self.unsupported("MemberExpression in a pattern"),
+            _ => return self.unexpected("a pattern", kind),
+        };
+
+        Ok(self.alloc(value, pattern))
+    }
+
+    /// Read an assignment target, such as the left side of an
+    /// `AssignmentExpression`. ratel keeps these as expressions, so
+    /// patterns are converted to their expression counterparts.
+    pub fn target(&mut self, value: &Value) -> Result<ExpressionNode<'ast>> {
+        let expression = match self.node_type(value)? {
+            "ObjectPattern" => {
+                let name = if value.get("properties").is_some() { "properties" } else { "keys" };
+
+                Expression::Object(ObjectExpression {
+                    body: self.read_list(value, name, Reader::pattern_property)?,
+                })
+            },
+            "ArrayPattern" => Expression::Array(ArrayExpression {
+                body: self.read_list(value, "elements", |reader, element| {
+                    match *element {
+                        Value::Null => Ok(reader.alloc(element, Expression::Void)),
+                        _           => reader.target(element),
+                    }
+                })?,
+            }),
+            "AssignmentPattern" => Expression::Binary(BinaryExpression {
+                operator: OperatorKind::Assign,
+                left: self.read(value, "left", Reader::target)?,
+                right: self.read(value, "right", Reader::expression)?,
+            }),
+            "RestElement" => Expression::Spread(SpreadExpression {
+                argument: self.read(value, "argument", Reader::target)?,
+            }),
+            _ => return self.expression(value),
+        };
+
+        Ok(self.alloc(value, expression))
+    }
+
+    fn pattern_property(&mut self, value: &Value) -> Result<Node<'ast, Property<'ast>>> {
+        match self.node_type(value)? {
+            "Property" => {},
+
+            // Shorthand properties are serialized by ratel as bare identifiers
+            "Identifier" => {
+                let name = self.identifier_str(value)?;
+
+                return Ok(self.alloc(value, Property::Shorthand(name)));
+            },
+            kind => return self.unsupported(kind),
+        }
+
+        let key = self.read(value, "key", |reader, key| reader.property_key(value, key))?;
+
+        if self.flag(value, "shorthand")? {
+            if let PropertyKey::Literal(name) = key.item {
+                if self.node_type(self.field(value, "value")?)? == "Identifier" {
+                    return Ok(self.alloc(value, Property::Shorthand(name)));
+                }
+            }
+        }
+
+        // ratel has no shorthand with a default, `{ a = 1 }` becomes `{ a: a = 1 }`
+        let property = Property::Literal {
+            key,
+            value: self.read(value, "value", Reader::target)?,
+        };
+
+        Ok(self.alloc(value, property))
+    }
+
+    /// Read a property of an `ObjectExpression`.
+    pub fn property(&mut self, value: &Value) -> Result<Node<'ast, Property<'ast>>> {
+        match self.node_type(value)? {
+            "Property" => {},
+            "Identifier" => {
+                let name = self.identifier_str(value)?;
+
+                return Ok(self.alloc(value, Property::Shorthand(name)));
+            },
+            kind => return self.unsupported(kind),
+        }
+
+        match self.str_field(value, "kind")? {
+            "init" => {},
+            kind   => return self.unsupported(&format!("Property with kind `{}`", kind)),
+        }
+
+        let key = self.read(value, "key", |reader, key| reader.property_key(value, key))?;
+
+        if self.flag(value, "shorthand")? {
+            if let PropertyKey::Literal(name) = key.item {
+                return Ok(self.alloc(value, Property::Shorthand(name)));
+            }
+        }
+
+        let property = if self.flag(value, "method")? {
+            Property::Method {
+                key,
+                value: self.read(value, "value", Reader::function)?,
+            }
+        } else {
+            Property::Literal {
+                key,
+                value: self.read(value, "value", Reader::expression)?,
+            }
+        };
+
+        Ok(self.alloc(value, property))
+    }
+
+    /// Read the `key` of a property or a class member, where `parent`
+    /// holds the `computed` flag.
+    pub fn property_key(&mut self, parent: &Value, key: &Value) -> Result<Node<'ast, PropertyKey<'ast>>> {
+        if self.flag(parent, "computed")? {
+            let expression = self.expression(key)?;
+
+            return Ok(self.alloc(key, PropertyKey::Computed(expression)));
+        }
+
+        let key_item = match self.node_type(key)? {
+            "Identifier" => PropertyKey::Literal(self.identifier_str(key)?),
+            "Literal"    => match self.literal(key)? {
+                Literal::Number(raw) |
+                Literal::String(raw) => PropertyKey::Literal(raw),
+                Literal::Binary(raw) => PropertyKey::Binary(raw),
+                _                    => return self.error(ErrorKind::InvalidField("key")),
+            },
+            kind => return self.unexpected("a property key", kind),
+        };
+
+        Ok(self.alloc(key, key_item))
+    }
+}
@@ -33,16 +33,19 @@ macro_rules! match_label {
 // Non-keyword Identifier: starting with a letter, _ or $
 pub const IDT: ByteHandler = Some(|lex| {
     lex.bump();
-    lex.read_label();
-    lex.token = Identifier;
+    lex.read_identifier();
+});
+
+// Identifier starting with a unicode escape
+pub const BSL: ByteHandler = Some(|lex| {
+    lex.read_identifier();
 });
 
 // Identifier or keyword starting with a letter `b`
 pub const L_B: ByteHandler = Some(|lex| {
     match_label!(lex [b'r' b'e' b'a' b'k' => Break]);
 
-    lex.read_label();
-    lex.token = Identifier;
+    lex.read_identifier();
 });
 
 // Identifier or keyword starting with a letter `c`
@@ -61,8 +64,7 @@ pub const L_C: ByteHandler = Some(|lex| {
         b'l'[b'a' b's' b's' => Class]
     });
 
-    lex.read_label();
-    lex.token = Identifier;
+    lex.read_identifier();
 });
 
 // Identifier or keyword starting with a letter `d`
@@ -76,8 +78,7 @@ pub const L_D: ByteHandler = Some(|lex| {
         }
     });
 
-    lex.read_label();
-    lex.token = Identifier;
+    lex.read_identifier();
 });
 
 // Identifier or keyword starting with a letter `e`
@@ -91,8 +92,7 @@ pub const L_E: ByteHandler = Some(|lex| {
         b'n'[b'u' b'm'               => ReservedEnum]
     });
 
-    lex.read_label();
-    lex.token = Identifier;
+    lex.read_identifier();
 });
 
 // Identifier or keyword starting with a letter `f`
@@ -104,8 +104,7 @@ pub const L_F: ByteHandler = Some(|lex| {
         b'a'[b'l' b's' b'e'                => LiteralFalse]
     });
 
-    lex.read_label();
-    lex.token = Identifier;
+    lex.read_identifier();
 });
 
 // Identifier or keyword starting with a letter `i`
@@ -125,16 +124,14 @@ pub const L_I: ByteHandler = Some(|lex| {
         }
     });
 
-    lex.read_label();
-    lex.token = Identifier;
+    lex.read_identifier();
 });
 
 // Identifier or keyword starting with a letter `l`
 pub const L_L: ByteHandler = Some(|lex| {
     match_label!(lex [b'e' b't' => DeclarationLet]);
 
-    lex.read_label();
-    lex.token = Identifier;
+    lex.read_identifier();
 });
 
 // Identifier or keyword starting with a letter `n`
@@ -144,8 +141,7 @@ pub const L_N: ByteHandler = Some(|lex| {
         b'u'[b'l' b'l' => LiteralNull]
     });
 
-    lex.read_label();
-    lex.token = Identifier;
+    lex.read_identifier();
 });
 
 // Identifier or keyword starting with a letter `p`
@@ -159,16 +155,14 @@ pub const L_P: ByteHandler = Some(|lex| {
         }
     });
 
-    lex.read_label();
-    lex.token = Identifier;
+    lex.read_identifier();
 });
 
 // Identifier or keyword starting with a letter `r`
 pub const L_R: ByteHandler = Some(|lex| {
     match_label!(lex [b'e' b't' b'u' b'r' b'n' => Return]);
 
-    lex.read_label();
-    lex.token = Identifier;
+    lex.read_identifier();
 });
 
 // Identifier or keyword starting with a letter `s`
@@ -179,8 +173,7 @@ pub const L_S: ByteHandler = Some(|lex| {
         b't'[b'a' b't' b'i' b'c' => Static]
     });
 
-    lex.read_label();
-    lex.token = Identifier;
+    lex.read_identifier();
 });
 
 // Identifier or keyword starting with a letter `t`
@@ -197,16 +190,14 @@ pub const L_T: ByteHandler = Some(|lex| {
         }
     });
 
-    lex.read_label();
-    lex.token = Identifier;
+    lex.read_identifier();
 });
 
 // Identifier or keyword starting with a letter `u`
 pub const L_U: ByteHandler = Some(|lex| {
     match_label!(lex [b'n' b'd' b'e' b'f' b'i' b'n' b'e' b'd' => LiteralUndefined]);
 
-    lex.read_label();
-    lex.token = Identifier;
+    lex.read_identifier();
 });
 
 // Identifier or keyword starting with a letter `v`
@@ -216,8 +207,7 @@ pub const L_V: ByteHandler = Some(|lex| {
         b'o'[b'i' b'd' => OperatorVoid]
     });
 
-    lex.read_label();
-    lex.token = Identifier;
+    lex.read_identifier();
 });
 
 // Identifier or keyword starting with a letter `w`
@@ -227,14 +217,27 @@ pub const L_W: ByteHandler = Some(|lex| {
         b'i'[b't' b'h'      => With]
     });
 
-    lex.read_label();
-    lex.token = Identifier;
+    lex.read_identifier();
 });
 
 // Identifier or keyword starting with a letter `y`
 pub const L_Y: ByteHandler = Some(|lex| {
     match_label!(lex [b'i' b'e' b'l' b'd' => Yield]);
 
-    lex.read_label();
-    lex.token = Identifier;
-});
+    lex.read_identifier();
+});
+
+/// Check whether a decoded identifier matches any of the keywords. Keywords
+/// can't be written using unicode escapes, so those are rejected. Words that
+/// are only reserved in strict mode, such as `yield` or `let`, are left for
+/// the parser to check, since the lexer doesn't know which mode it's in.
+pub fn is_keyword(label: &str) -> bool {
+    matches!(label,
+        "break" | "case" | "catch" | "class" | "const" | "continue" |
+        "debugger" | "default" | "delete" | "do" | "else" | "enum" |
+        "export" | "extends" | "false" | "finally" | "for" | "function" |
+        "if" | "import" | "in" | "instanceof" | "new" | "null" | "return" |
+        "super" | "switch" | "this" | "throw" | "true" | "try" | "typeof" |
+        "var" | "void" | "while" | "with"
+    )
+}
@@ -64,7 +64,7 @@ static BYTE_HANDLERS: [ByteHandler; 256] = [
     ___, EXL, QOT, ERR, IDT, PRC, AMP, QOT, PNO, PNC, ATR, PLS, COM, MIN, PRD, SLH, // 2
     ZER, DIG, DIG, DIG, DIG, DIG, DIG, DIG, DIG, DIG, COL, SEM, LSS, EQL, MOR, QST, // 3
     ERR, IDT, IDT, IDT, IDT, IDT, IDT, IDT, IDT, IDT, IDT, IDT, IDT, IDT, IDT, IDT, // 4
-    IDT, IDT, IDT, IDT, IDT, IDT, IDT, IDT, IDT, IDT, IDT, BTO, IDT, BTC, CRT, IDT, // 5
+    IDT, IDT, IDT, IDT, IDT, IDT, IDT, IDT, IDT, IDT, IDT, BTO, BSL, BTC, CRT, IDT, // 5
     TPL, IDT, L_B, L_C, L_D, L_E, L_F, IDT, IDT, L_I, IDT, IDT, L_L, IDT, L_N, IDT, // 6
     L_P, IDT, L_R, L_S, L_T, L_U, L_V, L_W, IDT, L_Y, IDT, BEO, PIP, BEC, TLD, ERR, // 7
     UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, // 8
@@ -456,22 +456,17 @@ const PRC: ByteHandler = Some(|lex| {
 
 // Unicode character
 const UNI: ByteHandler = Some(|lex| {
-    let start = lex.index;
+    let first = lex.read_char();
 
-    // TODO: unicodes with different lengths
-    let first = lex.slice_source(start, start + 4).chars().next().expect("Has to have one");
+    if !util::is_id_start(first) {
+        lex.index += first.len_utf8();
 
-    if !first.is_alphanumeric() {
         return lex.token = UnexpectedToken;
     }
 
-    // `read_label` bumps one at the beginning,
-    // so we subtract it here.
-    lex.index += first.len_utf8() - 1;
+    lex.index += first.len_utf8();
 
-    lex.read_label();
-
-    lex.token = Identifier;
+    lex.read_identifier();
 });
 
 // 0
@@ -597,6 +592,7 @@ const TPL: ByteHandler = Some(|lex| {
     lex.read_template_kind();
 });
 
+#[derive(Clone)]
 pub struct Lexer<'arena> {
     /// Current `Token` from the source.
     pub token: Token,
@@ -615,6 +611,13 @@ pub struct Lexer<'arena> {
 
     accessor_start: usize,
 
+    /// Decoded name of the last identifier or accessor that contained
+    /// unicode escapes, along with the position it starts at.
+    escaped: Option<(usize, &'arena str)>,
+
+    /// Arena to allocate decoded identifiers on.
+    arena: &'arena Arena,
+
     pub quasi: &'arena str,
 }
 
@@ -623,7 +626,7 @@ impl<'arena> Lexer<'arena> {
     /// Create a new `Lexer` from source using an existing arena.
     #[inline]
     pub fn new(arena: &'arena Arena, source: &str) -> Self {
-        unsafe { Lexer::from_ptr(arena.alloc_str_with_nul(source)) }
+        unsafe { Lexer::from_ptr(arena, arena.alloc_str_with_nul(source)) }
     }
 
     /// Create a new `Lexer` from a raw pointer to byte string.
@@ -635,7 +638,7 @@ impl<'arena> Lexer<'arena> {
     /// Passing a pointer to data that is not valid UTF8 will lead
     /// to bugs or undefined behavior.
     #[inline]
-    pub unsafe fn from_ptr(ptr: *const u8) -> Self {
+    pub unsafe fn from_ptr(arena: &'arena Arena, ptr: *const u8) -> Self {
         let mut lexer = Lexer {
             token: UnexpectedToken,
             asi: Asi::NoSemicolon,
@@ -643,6 +646,8 @@ impl<'arena> Lexer<'arena> {
             index: 0,
             token_start: 0,
             accessor_start: 0,
+            escaped: None,
+            arena,
             quasi: "",
         };
 
@@ -675,10 +680,13 @@ impl<'arena> Lexer<'arena> {
     }
 
     /// Create an `&str` slice from source spanning current token.
+    ///
+    /// Identifiers containing unicode escapes are returned decoded,
+    /// so that `\u0061` and `a` produce the same name.
     #[inline]
     pub fn token_as_str(&self) -> &'arena str {
         let start = self.token_start;
-        self.slice_from(start)
+        self.label_from(start)
     }
 
     /// Specialized version of `token_as_str` that crates an `&str`
@@ -686,7 +694,15 @@ impl<'arena> Lexer<'arena> {
     #[inline]
     pub fn accessor_as_str(&self) -> &'arena str {
         let start = self.accessor_start;
-        self.slice_from(start)
+        self.label_from(start)
+    }
+
+    #[inline]
+    fn label_from(&self, start: usize) -> &'arena str {
+        match self.escaped {
+            Some((escaped_start, label)) if escaped_start == start => label,
+            _ => self.slice_from(start),
+        }
     }
 
     #[inline]
@@ -779,6 +795,61 @@ impl<'arena> Lexer<'arena> {
         }
     }
 
+    /// Read the interpreter directive, such as `#!/usr/bin/env node`, if
+    /// the source begins with one, and advance to the first token following
+    /// it. Must be called before the first token is consumed.
+    #[inline]
+    pub fn hashbang(&mut self) -> Option<&'arena str> {
+        unsafe {
+            if self.token_start != 0 || *self.ptr != b'#' || *self.ptr.add(1) != b'!' {
+                return None;
+            }
+        }
+
+        self.index = 2;
+
+        loop {
+            match self.read_byte() {
+                b'\n' | b'\r' | 0 => break,
+                _                 => self.bump()
+            }
+        }
+
+        let hashbang = self.slice_from(2);
+
+        self.consume();
+
+        Some(hashbang)
+    }
+
+    /// Split off the leading `>` of a `>>` or `>>>` token, leaving the rest
+    /// of it as the current token. Used when closing nested Flow type
+    /// arguments, such as `Array<Array<T>>`. Returns the end position of
+    /// the split off `>`.
+    #[inline]
+    pub fn split_greater(&mut self) -> u32 {
+        self.token_start += 1;
+        self.token = match self.token {
+            OperatorUBitShiftRight => OperatorBitShiftRight,
+            _                      => OperatorGreater,
+        };
+
+        self.token_start as u32
+    }
+
+    /// Peek at the first non-whitespace byte following the current token.
+    #[inline]
+    pub fn peek_byte(&self) -> u8 {
+        let mut index = self.index;
+
+        loop {
+            match unsafe { *self.ptr.add(index) } {
+                b' ' | b'\t' | b'\n' | b'\r' => index += 1,
+                byte                       => return byte,
+            }
+        }
+    }
+
     /// Get a definition of which ASI rules can be applied.
     #[inline]
     pub fn asi(&self) -> Asi {
@@ -878,38 +949,144 @@ impl<'arena> Lexer<'arena> {
             ch = self.read_byte();
 
             if ch > 0x20 {
-                self.accessor_start = self.index;
+                let start = self.index;
+
+                self.accessor_start = start;
 
                 if ch > 127 {
-                    unimplemented!();
-                    // return unicode(self)
-                } else if TABLE[ch as usize] {
-                    self.read_label();
-                    return self.token = Accessor;
-                } else {
+                    let first = self.read_char();
+
+                    if !util::is_id_start(first) {
+                        return self.token = UnexpectedToken;
+                    }
+
+                    self.index += first.len_utf8();
+                } else if !TABLE[ch as usize] {
                     return self.token = UnexpectedToken;
                 }
+
+                if self.read_label() && !self.decode_label(start) {
+                    return self.token = UnexpectedToken;
+                }
+
+                return self.token = Accessor;
             }
 
             self.bump();
         })
     }
 
+    /// Read the remainder of an identifier starting at `token_start`, and set
+    /// the token to `Identifier`. Identifiers that use unicode escapes must
+    /// decode to a valid name that isn't a keyword.
     #[inline]
-    fn read_label(&mut self) {
-        while util::legal_in_label(self.read_byte()) {
-            self.bump();
+    fn read_identifier(&mut self) {
+        let start = self.token_start;
+
+        self.token = match self.read_label() {
+            false => Identifier,
+            true  => match self.decode_label(start) && !is_keyword(self.label_from(start)) {
+                true  => Identifier,
+                false => UnexpectedToken,
+            },
+        };
+    }
+
+    /// Advance to the end of the current label. Returns `true` if the label
+    /// contained any unicode escapes, in which case it needs to be decoded.
+    #[inline]
+    fn read_label(&mut self) -> bool {
+        let mut escaped = false;
+
+        loop {
+            let byte = self.read_byte();
+
+            if !util::legal_in_label(byte) {
+                return escaped;
+            }
+
+            if byte == b'\\' {
+                escaped = true;
+                self.read_label_escape();
+            } else if byte > 127 {
+                let ch = self.read_char();
+
+                if !util::is_id_continue(ch) {
+                    return escaped;
+                }
+
+                self.index += ch.len_utf8();
+            } else {
+                self.bump();
+            }
         }
     }
 
+    /// Skip over a `\uXXXX` or `\u{...}` escape. Malformed escapes are
+    /// caught when the label is decoded.
+    #[inline]
+    fn read_label_escape(&mut self) {
+        if self.next_byte() != b'u' {
+            return;
+        }
+
+        match self.next_byte() {
+            b'{' => {
+                loop {
+                    match self.next_byte() {
+                        byte if byte.is_ascii_hexdigit() => {},
+                        b'}' => return self.bump(),
+                        _    => return,
+                    }
+                }
+            },
+            _ => {
+                for _ in 0..4 {
+                    match self.read_byte() {
+                        byte if byte.is_ascii_hexdigit() => self.bump(),
+                        _ => return,
+                    }
+                }
+            }
+        }
+    }
+
+    /// Decode the escapes in the label spanning from `start` to the current
+    /// index, storing the result so it's returned by `token_as_str` and
+    /// `accessor_as_str`. Returns `false` if the label is not a valid name.
+    #[inline]
+    fn decode_label(&mut self, start: usize) -> bool {
+        match util::decode_label(self.slice_from(start)) {
+            Some(label) => {
+                self.escaped = Some((start, self.arena.alloc_string(label)));
+
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Read the unicode character at the current index.
+    #[inline]
+    fn read_char(&self) -> char {
+        let len = match self.read_byte() {
+            0xF0..=0xFF => 4,
+            0xE0..=0xEF => 3,
+            _           => 2,
+        };
+
+        self.slice_source(self.index, self.index + len).chars().next().unwrap_or('\0')
+    }
+
     #[inline]
     fn slice_from(&self, start: usize) -> &'arena str {
         let end = self.index;
         self.slice_source(start, end)
     }
 
+    /// Create an `&str` slice from source spanning given positions.
     #[inline]
-    fn slice_source(&self, start: usize, end: usize) -> &'arena str {
+    pub fn slice_source(&self, start: usize, end: usize) -> &'arena str {
         use std::str::from_utf8_unchecked;
         use std::slice::from_raw_parts;
 
@@ -1272,4 +1449,59 @@ mod test {
             ][..]
         );
     }
+
+    #[test]
+    fn unicode_identifiers() {
+        assert_lex(
+            "π ñame café foo.λ x\u{200C}y",
+            [
+                (Identifier, "π"),
+                (Identifier, "ñame"),
+                (Identifier, "café"),
+                (Identifier, "foo"),
+                (Accessor, ".λ"),
+                (Identifier, "x\u{200C}y"),
+            ]
+        );
+    }
+
+    #[test]
+    fn identifier_escapes() {
+        assert_lex(
+            r"\u0061bc a\u{62}c \u{1D49C} foo.b\u0061r",
+            [
+                (Identifier, "abc"),
+                (Identifier, "abc"),
+                (Identifier, "\u{1D49C}"),
+                (Identifier, "foo"),
+                (Accessor, ".b\\u0061r"),
+            ]
+        );
+
+        let arena = Arena::new();
+        let mut lex = Lexer::new(&arena, r"foo.b\u0061r");
+
+        lex.consume();
+
+        assert_eq!(lex.accessor_as_str(), "bar");
+    }
+
+    #[test]
+    fn invalid_identifiers() {
+        assert_lex("\\u00", [(UnexpectedToken, "\\u00")]);
+        assert_lex("\\x61", [(UnexpectedToken, "\\x61")]);
+        assert_lex("\\u0031", [(UnexpectedToken, "\\u0031")]);
+        assert_lex("\\u{110000}", [(UnexpectedToken, "\\u{110000}")]);
+        assert_lex("\u{2026}", [(UnexpectedToken, "\u{2026}")]);
+    }
+
+    #[test]
+    fn escaped_keywords() {
+        assert_lex("\\u0062reak", [(UnexpectedToken, "break")]);
+        assert_lex("v\\u{61}r", [(UnexpectedToken, "var")]);
+        assert_lex("\\u0062reaks", [(Identifier, "breaks")]);
+        assert_lex("\\u0079ield", [(Identifier, "yield")]);
+        assert_lex("l\\u0065t", [(Identifier, "let")]);
+        assert_lex("\\u0061wait", [(Identifier, "await")]);
+    }
 }
@@ -1,3 +1,5 @@
+use unicode_ident::{is_xid_start, is_xid_continue};
+
 #[inline]
 pub fn legal_in_label(byte: u8) -> bool {
     // Look up table that marks which ASCII characters are allowed in identifiers
@@ -31,3 +33,83 @@ pub fn legal_in_label(byte: u8) -> bool {
 
     unsafe { *(&TABLE as *const bool).offset(byte as isize) }
 }
+
+/// Zero width non-joiner, allowed inside identifiers.
+const ZWNJ: char = '\u{200C}';
+
+/// Zero width joiner, allowed inside identifiers.
+const ZWJ: char = '\u{200D}';
+
+/// Check if the character can start an identifier (ID_Start, `$` or `_`).
+#[inline]
+pub fn is_id_start(ch: char) -> bool {
+    match ch {
+        'a'..='z' | 'A'..='Z' | '$' | '_' => true,
+        _ if ch.is_ascii()                => false,
+        _                                 => is_xid_start(ch),
+    }
+}
+
+/// Check if the character can be a part of an identifier (ID_Continue, `$`,
+/// zero width joiner and non-joiner).
+#[inline]
+pub fn is_id_continue(ch: char) -> bool {
+    match ch {
+        'a'..='z' | 'A'..='Z' | '0'..='9' | '$' | '_' => true,
+        _ if ch.is_ascii()                            => false,
+        ZWNJ | ZWJ                                    => true,
+        _                                             => is_xid_continue(ch),
+    }
+}
+
+/// Decode the `\uXXXX` and `\u{...}` escapes in an identifier, returning
+/// `None` if an escape is malformed or the result isn't a valid name.
+pub fn decode_label(raw: &str) -> Option<String> {
+    let mut label = String::with_capacity(raw.len());
+    let mut rest = raw;
+
+    while let Some(ch) = rest.chars().next() {
+        rest = &rest[ch.len_utf8()..];
+
+        let ch = match ch {
+            '\\' => {
+                if !rest.starts_with('u') {
+                    return None;
+                }
+
+                rest = &rest[1..];
+
+                let (digits, len) = match rest.starts_with('{') {
+                    true  => {
+                        let end = rest.find('}')?;
+
+                        (&rest[1..end], end + 1)
+                    },
+                    false => (rest.get(..4)?, 4),
+                };
+
+                if digits.is_empty() || !digits.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+                    return None;
+                }
+
+                rest = &rest[len..];
+
+                u32::from_str_radix(digits, 16).ok().and_then(::std::char::from_u32)?
+            },
+            ch => ch,
+        };
+
+        let legal = match label.is_empty() {
+            true  => is_id_start(ch),
+            false => is_id_continue(ch),
+        };
+
+        if !legal {
+            return None;
+        }
+
+        label.push(ch);
+    }
+
+    Some(label)
+}
@@ -2,12 +2,12 @@
 
 extern crate serde;
 extern crate toolshed;
+extern crate unicode_ident;
 
 #[macro_use]
 extern crate serde_derive;
 
-#[cfg(test)]
-#[macro_use]
+#[cfg_attr(test, macro_use)]
 extern crate serde_json;
 
 #[cfg(test)]
@@ -17,10 +17,14 @@ extern crate pretty_assertions;
 pub mod ast;
 pub mod error;
 pub mod lexer;
+pub mod estree;
+pub mod binary;
 
 mod module;
 mod parser;
 mod astgen;
 
-pub use parser::parse;
-pub use module::Module;
+pub use parser::{parse, parse_with_options, parse_bytes, parse_bytes_with_options, parse_in};
+pub use parser::{ParseOptions, Goal, Version};
+pub use module::{Module, ModuleMap, FileId, Span};
+pub use astgen::{Estree, EstreeOptions, Flavor};
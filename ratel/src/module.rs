@@ -1,29 +1,68 @@
+use std::cell::Cell;
+use std::convert::Infallible;
 use toolshed::list::UnsafeList;
 use toolshed::Arena;
-use ast::StatementList;
+use ast::{Loc, Program, StatementList, CloneIn};
+use error::Error;
+use parser::{parse_in, ParseOptions};
 use std::marker::PhantomData;
 
 /// A JavaScript module parsed to an AST.
 pub struct Module<'ast> {
-    body: UnsafeList,
+    body: Cell<UnsafeList>,
+    hashbang: Option<&'ast str>,
     arena: Arena,
     _phantom: PhantomData<&'ast StatementList<'ast>>
 }
 
 impl<'ast> Module<'ast> {
     #[inline]
-    pub(crate) fn new(body: UnsafeList, arena: Arena) -> Self {
+    pub(crate) fn new(body: UnsafeList, hashbang: Option<&'ast str>, arena: Arena) -> Self {
         Module {
-            body,
+            body: Cell::new(body),
+            hashbang,
             arena,
             _phantom: PhantomData,
         }
     }
 
+    /// Create a module owning the given `Arena`, along with the program
+    /// that `program` allocates on it.
+    pub(crate) fn from_arena<F, E>(arena: Arena, program: F) -> Result<Self, E>
+    where
+        F: for<'arena> FnOnce(&'arena Arena) -> Result<Program<'arena>, E>,
+    {
+        let (body, hashbang) = {
+            let program = program(&arena)?;
+
+            // Like the body, the hashbang lives on the arena, which is moved into the `Module`
+            let hashbang = program.hashbang.map(|hashbang| unsafe { &*(hashbang as *const str) });
+
+            (program.body.into_unsafe(), hashbang)
+        };
+
+        Ok(Module::new(body, hashbang, arena))
+    }
+
     /// Get the body of the module as a list of statements.
     #[inline]
     pub fn body(&self) -> StatementList<'ast> {
-        unsafe { self.body.into_list() }
+        unsafe { self.body.get().into_list() }
+    }
+
+    /// Replace the body of the module, such as with a list that has been
+    /// rebuilt by a transformation. The statements should be allocated on
+    /// the arena of the module.
+    #[inline]
+    pub fn set_body(&self, body: StatementList<'ast>) {
+        self.body.set(body.into_unsafe());
+    }
+
+    /// Get the interpreter directive from the first line of the source,
+    /// such as `/usr/bin/env node`, without the leading `#!`.
+    #[inline]
+    pub fn hashbang(&self) -> Option<&'ast str> {
+        self.hashbang
     }
 
     /// Get a reference to the `Arena` on which the AST is allocated.
@@ -31,4 +70,194 @@ impl<'ast> Module<'ast> {
     pub fn arena(&'ast self) -> &'ast Arena {
         &self.arena
     }
+
+    /// Copy a `Program` into a module owning its own `Arena`, so that it
+    /// can outlive the arena the program was parsed into.
+    pub fn from_program(program: &Program) -> Self {
+        let module = Module::from_arena(Arena::new(), |arena| Ok::<_, Infallible>(Program {
+            source: "",
+            body: program.body.clone_in(arena),
+            hashbang: program.hashbang.map(|hashbang| arena.alloc_str(hashbang)),
+        }));
+
+        match module {
+            Ok(module) => module,
+            Err(never) => match never {},
+        }
+    }
+}
+
+impl<'ast> Clone for Module<'ast> {
+    /// Deep copy of the module, on a new `Arena`.
+    fn clone(&self) -> Self {
+        Module::from_program(&Program {
+            source: "",
+            body: self.body(),
+            hashbang: self.hashbang,
+        })
+    }
+}
+
+/// Identifier of a file within a `ModuleMap`.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+pub struct FileId(u32);
+
+impl FileId {
+    /// Get the index of the file, in the order files were added.
+    #[inline]
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+
+    /// Get the `Span` of a node within this file.
+    #[inline]
+    pub fn span<T>(self, loc: &Loc<T>) -> Span {
+        Span {
+            file: self,
+            start: loc.start,
+            end: loc.end,
+        }
+    }
+}
+
+/// Position of a piece of source, along with the file it comes from.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct Span {
+    pub file: FileId,
+    pub start: u32,
+    pub end: u32,
+}
+
+/// Any number of modules parsed into a single `Arena` owned by the caller,
+/// keyed by `FileId`. Dropping the map and the arena, then creating new ones,
+/// frees all the modules at once.
+pub struct ModuleMap<'ast> {
+    arena: &'ast Arena,
+    files: Vec<(&'ast str, Program<'ast>)>,
+}
+
+impl<'ast> ModuleMap<'ast> {
+    /// Create an empty map that allocates on the given `Arena`.
+    #[inline]
+    pub fn new(arena: &'ast Arena) -> Self {
+        ModuleMap {
+            arena,
+            files: Vec::new(),
+        }
+    }
+
+    /// Parse the source of a file and add it to the map. The file isn't
+    /// added if the source fails to parse.
+    pub fn parse(&mut self, name: &str, source: &str, options: ParseOptions) -> Result<FileId, Vec<Error>> {
+        let program = parse_in(source, self.arena, options)?;
+        let file = FileId(self.files.len() as u32);
+
+        self.files.push((self.arena.alloc_str(name), program));
+
+        Ok(file)
+    }
+
+    /// Get the parsed program of a file.
+    #[inline]
+    pub fn get(&self, file: FileId) -> Option<Program<'ast>> {
+        self.files.get(file.index()).map(|&(_, program)| program)
+    }
+
+    /// Get the name a file was added with.
+    #[inline]
+    pub fn name(&self, file: FileId) -> Option<&'ast str> {
+        self.files.get(file.index()).map(|&(name, _)| name)
+    }
+
+    /// Get the number of files in the map.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    /// Check if there are no files in the map.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// Iterate over all files and their parsed programs.
+    #[inline]
+    pub fn iter<'map>(&'map self) -> impl Iterator<Item = (FileId, Program<'ast>)> + 'map {
+        self.files.iter().enumerate().map(|(index, &(_, program))| (FileId(index as u32), program))
+    }
+
+    /// Get a reference to the `Arena` on which the modules are allocated.
+    #[inline]
+    pub fn arena(&self) -> &'ast Arena {
+        self.arena
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use parser::{parse_in, Goal};
+
+    #[test]
+    fn parse_into_shared_arena() {
+        let arena = Arena::new();
+
+        let foo = parse_in("foo;", &arena, ParseOptions::default()).unwrap();
+        let bar = parse_in("#!/usr/bin/env node\nbar; baz;", &arena, ParseOptions::default()).unwrap();
+
+        assert_eq!(foo.source, "foo;");
+        assert_eq!(foo.body.iter().count(), 1);
+        assert_eq!(foo.hashbang, None);
+        assert_eq!(bar.body.iter().count(), 2);
+        assert_eq!(bar.hashbang, Some("/usr/bin/env node"));
+        assert!(parse_in("foo(;", &arena, ParseOptions::default()).is_err());
+    }
+
+    #[test]
+    fn module_map() {
+        let arena = Arena::new();
+        let mut modules = ModuleMap::new(&arena);
+
+        assert!(modules.is_empty());
+
+        let options = ParseOptions { goal: Goal::Module, ..ParseOptions::default() };
+        let foo = modules.parse("foo.js", "import bar from './bar';", options).unwrap();
+        let bar = modules.parse("bar.js", "export default 42;", options).unwrap();
+
+        assert!(modules.parse("baz.js", "import", options).is_err());
+        assert_eq!(modules.len(), 2);
+        assert_ne!(foo, bar);
+        assert_eq!(modules.name(foo), Some("foo.js"));
+        assert_eq!(modules.name(bar), Some("bar.js"));
+        assert_eq!(modules.get(bar).unwrap().source, "export default 42;");
+        assert_eq!(modules.iter().map(|(file, _)| file).collect::<Vec<_>>(), vec![foo, bar]);
+
+        let statement = modules.get(bar).unwrap().body.only_element().unwrap();
+
+        assert_eq!(bar.span(statement), Span { file: bar, start: 0, end: 17 });
+    }
+
+    #[test]
+    fn module_from_program() {
+        let module = {
+            let arena = Arena::new();
+            let program = parse_in("#!/usr/bin/env node\nfoo(bar);", &arena, ParseOptions::default()).unwrap();
+
+            Module::from_program(&program)
+        };
+
+        let arena = Arena::new();
+        let expected = parse_in("foo(bar);", &arena, ParseOptions::default()).unwrap().body;
+
+        assert_eq!(module.body(), expected);
+        assert_eq!(module.hashbang(), Some("/usr/bin/env node"));
+
+        let clone = module.clone();
+
+        drop(module);
+
+        assert_eq!(clone.body(), expected);
+        assert_eq!(clone.hashbang(), Some("/usr/bin/env node"));
+    }
 }
@@ -1,7 +1,7 @@
 use error::Error;
 
 use ast::{Node, Loc, NodeList, Pattern};
-use ast::{Name, ClassMember, Property, PropertyKey, MandatoryName, Block};
+use ast::{Name, ClassMember, Property, PropertyKey, MandatoryName, Block, Type, Literal};
 use parser::Parser;
 
 pub trait Handle<'ast> {
@@ -60,6 +60,13 @@ impl<'ast, T: 'ast + Copy> ToError for NodeList<'ast, T> {
     }
 }
 
+impl ToError for &str {
+    #[inline]
+    fn to_error() -> Self {
+        ""
+    }
+}
+
 impl<'ast> ToError for Node<'ast, &'ast str> {
     fn to_error() -> Self {
         Node::new(&Loc {
@@ -100,6 +107,28 @@ impl<'ast> ToError for Node<'ast, PropertyKey<'ast>> {
     }
 }
 
+impl<'ast> ToError for Node<'ast, Type<'ast>> {
+    #[inline]
+    fn to_error() -> Self {
+        Node::new(&Loc {
+            start: 0,
+            end: 0,
+            item: Type::Any
+        })
+    }
+}
+
+impl<'ast> ToError for Node<'ast, Literal<'ast>> {
+    #[inline]
+    fn to_error() -> Self {
+        Node::new(&Loc {
+            start: 0,
+            end: 0,
+            item: Literal::String("''")
+        })
+    }
+}
+
 impl ToError for () {
     #[inline]
     fn to_error() -> Self {
@@ -1,7 +1,7 @@
 use toolshed::list::ListBuilder;
-use parser::{Parser, Parse, BindingPower, ANY, B0, B15};
+use parser::{Parser, Parse, BindingPower, Version, ANY, B0, B15};
 use lexer::Token::*;
-use ast::{Node, NodeList, Expression, ExpressionNode, ExpressionList};
+use ast::{Node, NodeList, Expression, ExpressionNode, ExpressionList, TypeNode, TypeParameterList};
 use ast::{Property, PropertyKey, OperatorKind, Literal, Function, Class, StatementNode};
 use ast::expression::*;
 
@@ -17,7 +17,7 @@ static DEF_CONTEXT: Context = &[
     OP,   OP,   OP,   OP,   OP,   OP,   OP,   ____, REG,  ____, ____, OP,
 //  ++    --    !     ~     TYPOF VOID  DELET *     /     %     **    +
 
-    OP,   ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
+    OP,   ____, ____, ____, GNRC, ____, ____, ____, ____, ____, ____, ____,
 //  -     <<    >>    >>>   <     <=    >     >=    INSOF IN    ===   !==
 
     ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
@@ -29,7 +29,7 @@ static DEF_CONTEXT: Context = &[
     ____, ____, ____, ____, ____, ____, ____, CLAS, ____, ____, ____, ____,
 //  CONST BREAK DO    CASE  ELSE  CATCH EXPRT CLASS EXTND RET   WHILE FINLY
 
-    ____, ____, ____, ____, ____, ____, ____, FUNC, THIS, ____, ____, ____,
+    SUPR, ____, ____, ____, ____, YILD, ____, FUNC, THIS, ____, ____, ____,
 //  SUPER WITH  CONT  FOR   SWTCH YIELD DBGGR FUNCT THIS  DEFLT IF    THROW
 
     ____, ____, ____, TRUE, FALS, NULL, UNDE, STR,  NUM,  BIN,  ____, ____,
@@ -43,11 +43,11 @@ static DEF_CONTEXT: Context = &[
 pub static ARRAY_CONTEXT: Context = &[
     ____, ____, ____, VOID, PRN,  ____, ARR,  VOID, OBJ,  ____, ____, OP,
     OP,   OP,   OP,   OP,   OP,   OP,   OP,   ____, REG,  ____, ____, OP,
-    OP,   ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
+    OP,   ____, ____, ____, GNRC, ____, ____, ____, ____, ____, ____, ____,
     ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
     ____, ____, ____, ____, ____, ____, ____, ____, ____, SPRD, ____, ____,
     ____, ____, ____, ____, ____, ____, ____, CLAS, ____, ____, ____, ____,
-    ____, ____, ____, ____, ____, ____, ____, FUNC, THIS, ____, ____, ____,
+    SUPR, ____, ____, ____, ____, YILD, ____, FUNC, THIS, ____, ____, ____,
     ____, ____, ____, TRUE, FALS, NULL, UNDE, STR,  NUM,  BIN,  ____, ____,
     ____, ____, ____, ____, ____, ____, IDEN, ____, TPLE, TPLS, ____, ____,
 ];
@@ -56,11 +56,11 @@ pub static ARRAY_CONTEXT: Context = &[
 pub static CALL_CONTEXT: Context = &[
     ____, ____, ____, ____, PRN,  ____, ARR,  ____, OBJ,  ____, ____, OP,
     OP,   OP,   OP,   OP,   OP,   OP,   OP,   ____, REG,  ____, ____, OP,
-    OP,   ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
+    OP,   ____, ____, ____, GNRC, ____, ____, ____, ____, ____, ____, ____,
     ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
     ____, ____, ____, ____, ____, ____, ____, ____, ____, SPRD, ____, ____,
     ____, ____, ____, ____, ____, ____, ____, CLAS, ____, ____, ____, ____,
-    ____, ____, ____, ____, ____, ____, ____, FUNC, THIS, ____, ____, ____,
+    SUPR, ____, ____, ____, ____, YILD, ____, FUNC, THIS, ____, ____, ____,
     ____, ____, ____, TRUE, FALS, NULL, UNDE, STR,  NUM,  BIN,  ____, ____,
     ____, ____, ____, ____, ____, ____, IDEN, ____, TPLE, TPLS, ____, ____,
 ];
@@ -110,8 +110,17 @@ create_handlers! {
 
     const FUNC = |par| par.function_expression();
 
+    // Generators aren't supported, so `yield` can only be an identifier
+    const YILD = |par| {
+        if par.strict {
+            par.error::<()>();
+        }
+
+        IDEN(par)
+    };
+
     const IDEN = |par| {
-        let ident = par.lexer.token_as_str();
+        let ident = par.identifier_str();
         let expr = par.alloc_in_loc(ident);
 
         par.lexer.consume();
@@ -119,6 +128,8 @@ create_handlers! {
     };
 
     const SPRD = |par| {
+        par.require(Version::ES2015);
+
         let start = par.lexer.start_then_consume();
         let argument = par.expression::<B0>();
 
@@ -132,6 +143,17 @@ create_handlers! {
         expr
     };
 
+    // There is no `super` node, like in the ESTree reader it is kept as an
+    // identifier, which is printed as is
+    pub const SUPR = |par| {
+        par.require(Version::ES2015);
+
+        let expr = par.alloc_in_loc("super");
+        par.lexer.consume();
+
+        expr
+    };
+
     pub const OP = |par| {
         let start = par.lexer.start();
         let op = OperatorKind::from_token(par.lexer.token).expect("Must be a prefix operator");
@@ -148,6 +170,14 @@ create_handlers! {
 
     pub const ARR = |par| par.array_expression();
 
+    pub const GNRC = |par| {
+        if par.flow {
+            par.generic_arrow_function_expression()
+        } else {
+            ____(par)
+        }
+    };
+
     pub const REG = |par| par.regular_expression();
 
     pub const TRUE = |par| {
@@ -188,6 +218,12 @@ create_handlers! {
 
     pub const NUM = |par| {
         let value = par.lexer.token_as_str();
+
+        // Legacy octal literals, such as `010`, aren't allowed in strict mode
+        if par.strict && value.len() > 1 && value.as_bytes()[0] == b'0' && value.as_bytes()[1].is_ascii_digit() {
+            par.error::<()>();
+        }
+
         let expr = par.alloc_in_loc(Literal::Number(value));
 
         par.lexer.consume();
@@ -203,6 +239,8 @@ create_handlers! {
     };
 
     pub const TPLS = |par| {
+        par.require(Version::ES2015);
+
         let quasi = par.lexer.quasi;
         let quasi = par.alloc_in_loc(quasi);
 
@@ -249,20 +287,55 @@ impl<'ast> Parser<'ast> {
     }
 
     #[inline]
-    pub fn arrow_function_expression(&mut self, params: ExpressionList<'ast>) -> ExpressionNode<'ast> {
+    pub fn arrow_function_expression(
+        &mut self,
+        type_params: TypeParameterList<'ast>,
+        params: ExpressionList<'ast>,
+        return_type: Option<TypeNode<'ast>>,
+    ) -> ExpressionNode<'ast> {
         let params = self.params_from_expressions(params);
 
         let body = match self.lexer.token {
-            BraceOpen => ArrowBody::Block(self.unchecked_block()),
+            BraceOpen => ArrowBody::Block(self.function_body()),
             _         => ArrowBody::Expression(self.expression::<B0>()),
         };
 
         self.alloc_at_loc(0, 0, ArrowExpression {
+            type_params,
             params,
+            return_type,
             body,
         })
     }
 
+    /// Parse a Flow arrow function with type params, `<T>(a: T): T => a`,
+    /// starting at the `<`.
+    pub fn generic_arrow_function_expression(&mut self) -> ExpressionNode<'ast> {
+        let type_params = self.type_params();
+
+        expect!(self, ParenOpen);
+
+        let expression = self.paren_expression();
+
+        // `()` params and return types are already parsed into an arrow
+        if let Expression::Arrow(arrow) = expression.item {
+            return self.alloc_at_loc(0, 0, ArrowExpression {
+                type_params,
+                ..arrow
+            });
+        }
+
+        self.require(Version::ES2015);
+        expect!(self, OperatorFatArrow);
+
+        let params = match expression.item {
+            Expression::Sequence(SequenceExpression { body }) => body,
+            _ => NodeList::from(self.arena, expression)
+        };
+
+        self.arrow_function_expression(type_params, params, None)
+    }
+
     #[inline]
     pub fn call_arguments(&mut self) -> ExpressionList<'ast> {
         if self.lexer.token == ParenClose {
@@ -296,8 +369,47 @@ impl<'ast> Parser<'ast> {
         match self.lexer.token {
             ParenClose => {
                 self.lexer.consume();
+                self.require(Version::ES2015);
+
+                let return_type = self.arrow_return_type();
+
                 expect!(self, OperatorFatArrow);
-                self.arrow_function_expression(NodeList::empty())
+                self.arrow_function_expression(NodeList::empty(), NodeList::empty(), return_type)
+            },
+            // Type casts can appear in each expression of a sequence,
+            // which also covers typed arrow function params
+            _ if self.flow => {
+                let expression = self.expression::<B0>();
+                let expression = self.type_cast(expression);
+                let builder = ListBuilder::new(self.arena, expression);
+                let mut end = expression.end;
+
+                while self.lexer.token == Comma {
+                    self.lexer.consume();
+
+                    let expression = self.expression::<B0>();
+                    let expression = self.type_cast(expression);
+
+                    end = expression.end;
+                    builder.push(self.arena, expression);
+                }
+
+                expect!(self, ParenClose);
+
+                let body = builder.as_list();
+
+                // A return type makes this the params of an arrow function
+                if let Some(return_type) = self.arrow_return_type() {
+                    self.require(Version::ES2015);
+                    self.lexer.consume();
+
+                    return self.arrow_function_expression(NodeList::empty(), body, Some(return_type));
+                }
+
+                match body.only_element() {
+                    Some(&expression) => expression,
+                    None => self.alloc_at_loc(expression.start, end, SequenceExpression { body }),
+                }
             },
             _ => {
                 let expression = self.expression::<ANY>();
@@ -371,7 +483,13 @@ impl<'ast> Parser<'ast> {
                 match self.lexer.token {
                     Colon | ParenOpen => self.alloc_at_loc(start, end, PropertyKey::Literal(label)),
 
-                    _ => return self.alloc_at_loc(start, end, Property::Shorthand(label)),
+                    _ => {
+                        if self.is_reserved(label) {
+                            self.error::<()>();
+                        }
+
+                        return self.alloc_at_loc(start, end, Property::Shorthand(label));
+                    },
                 }
             },
             LiteralString |
@@ -486,6 +604,8 @@ impl<'ast> Parser<'ast> {
     where
         T: Copy + From<TemplateLiteral<'ast>>,
     {
+        self.require(Version::ES2015);
+
         let quasi = self.lexer.quasi;
         let quasi = self.alloc_in_loc(quasi);
 
@@ -502,6 +622,8 @@ impl<'ast> Parser<'ast> {
     where
         T: Copy + From<TemplateLiteral<'ast>>,
     {
+        self.require(Version::ES2015);
+
         let quasi = self.lexer.quasi;
         let quasi = self.alloc_in_loc(quasi);
 
@@ -580,6 +702,8 @@ impl<'ast> Parser<'ast> {
 
     #[inline]
     pub fn class_expression(&mut self) -> ExpressionNode<'ast> {
+        self.require(Version::ES2015);
+
         let start = self.lexer.start_then_consume();
         let class = Class::parse(self);
 
@@ -859,6 +983,25 @@ mod test {
         assert_expr!(src, expected);
     }
 
+    #[test]
+    fn super_expression() {
+        let mock = Mock::new();
+
+        let expected = CallExpression {
+            callee: mock.ptr("super"),
+            arguments: mock.list(["a"]),
+        };
+
+        assert_expr!("super(a);", expected);
+
+        let expected = MemberExpression {
+            object: mock.ptr("super"),
+            property: mock.ptr("foo"),
+        };
+
+        assert_expr!("super.foo;", expected);
+    }
+
     #[test]
     fn spread_expression_in_call() {
         let src = "foo(a, b, ...c)";
@@ -891,7 +1034,9 @@ mod test {
 
         let expected = Function {
             name: None.into(),
+            type_params: NodeList::empty(),
             params: NodeList::empty(),
+            return_type: None,
             body: mock.empty_block()
         };
 
@@ -905,7 +1050,9 @@ mod test {
 
         let expected = Function {
             name: mock.name("foo"),
+            type_params: NodeList::empty(),
             params: NodeList::empty(),
+            return_type: None,
             body: mock.empty_block()
         };
 
@@ -918,7 +1065,9 @@ mod test {
         let mock = Mock::new();
 
         let expected = ArrowExpression {
+            type_params: NodeList::empty(),
             params: NodeList::empty(),
+            return_type: None,
             body: ArrowBody::Expression(mock.ptr("bar")),
         };
         assert_expr!(src, expected);
@@ -930,10 +1079,12 @@ mod test {
         let mock = Mock::new();
 
         let expected = ArrowExpression {
+            type_params: NodeList::empty(),
             params: mock.list([
                 Pattern::Identifier("n")
             ]),
 
+            return_type: None,
             body: ArrowBody::Expression(mock.ptr(BinaryExpression {
                 operator: OperatorKind::Multiplication,
                 left: mock.ptr("n"),
@@ -950,11 +1101,13 @@ mod test {
         let mock = Mock::new();
 
         let expected = ArrowExpression {
+            type_params: NodeList::empty(),
             params: mock.list([
                 Pattern::Identifier("a"),
                 Pattern::Identifier("b"),
                 Pattern::Identifier("c")
             ]),
+            return_type: None,
             body: ArrowBody::Expression(mock.ptr("bar"))
         };
         assert_expr!(src, expected);
@@ -971,6 +1124,7 @@ mod test {
         let mock = Mock::new();
 
         let expected = ArrowExpression {
+            type_params: NodeList::empty(),
             params: mock.list([
                 Pattern::Identifier("a"),
                 Pattern::Identifier("b"),
@@ -979,6 +1133,7 @@ mod test {
                     right: mock.number("2")
                 }
             ]),
+            return_type: None,
             body: ArrowBody::Expression(mock.ptr("bar"))
         };
         assert_expr!(src, expected);
@@ -991,7 +1146,10 @@ mod test {
 
         let expected = Class {
             name: None.into(),
+            type_params: NodeList::empty(),
             extends: None,
+            super_type_args: NodeList::empty(),
+            implements: NodeList::empty(),
             body: mock.empty_block()
         };
 
@@ -1005,7 +1163,10 @@ mod test {
 
         let expected = Class {
             name: mock.name("Foo"),
+            type_params: NodeList::empty(),
             extends: None,
+            super_type_args: NodeList::empty(),
+            implements: NodeList::empty(),
             body: mock.empty_block()
         };
 
@@ -1019,7 +1180,10 @@ mod test {
 
         let expected = Class {
             name: mock.name("Foo"),
+            type_params: NodeList::empty(),
             extends: Some(mock.ptr("Bar")),
+            super_type_args: NodeList::empty(),
+            implements: NodeList::empty(),
             body: mock.empty_block()
         };
 
@@ -1056,7 +1220,9 @@ mod test {
         let expected = SequenceExpression {
             body: mock.list([
                 Expression::Arrow(ArrowExpression {
+                    type_params: NodeList::empty(),
                     params: NodeList::empty(),
+                    return_type: None,
                     body: ArrowBody::Block(mock.ptr(BlockStatement {
                         body: NodeList::empty()
                     }))
@@ -1,5 +1,5 @@
 use toolshed::list::ListBuilder;
-use parser::{Parser, Parse, ANY, B0};
+use parser::{Parser, Parse, Version, ANY, B0, B15};
 use lexer::Token::*;
 use ast::{Node, NodeList, EmptyName, OptionalName, MandatoryName, Name};
 use ast::{MethodKind, Pattern, Function, Class, ClassMember, PropertyKey};
@@ -22,7 +22,7 @@ impl<'ast> Parse<'ast> for OptionalName<'ast> {
             return OptionalName(None);
         }
 
-        let name = par.lexer.token_as_str();
+        let name = par.identifier_str();
         let name = OptionalName(Some(par.alloc_in_loc(name)));
         par.lexer.consume();
         name
@@ -38,7 +38,7 @@ impl<'ast> Parse<'ast> for MandatoryName<'ast> {
             return par.error();
         }
 
-        let name = par.lexer.token_as_str();
+        let name = par.identifier_str();
         let name = MandatoryName(par.alloc_in_loc(name));
         par.lexer.consume();
         name
@@ -52,6 +52,7 @@ impl<'ast> Parse<'ast> for Pattern<'ast> {
     fn parse(par: &mut Parser<'ast>) -> Self::Output {
         match par.lexer.token {
             Identifier  => par.pattern_identifier(),
+            Yield if !par.strict => par.pattern_identifier(),
             BracketOpen => par.pattern_array(),
             BraceOpen   => par.pattern_object(),
             _           => par.error()
@@ -70,8 +71,13 @@ impl<'ast, N> Parse<'ast> for Function<'ast, N> where
 
         Function {
             name,
+            type_params: par.type_params(),
             params: par.params(),
-            body: par.block(),
+            return_type: match par.lexer.token {
+                Colon if par.flow => Some(par.type_annotation()),
+                _                 => None,
+            },
+            body: par.function_body(),
         }
     }
 }
@@ -109,6 +115,7 @@ impl<'ast> Parse<'ast> for ClassMember<'ast> {
 
         let key = match par.lexer.token {
             _ if par.lexer.token.is_word() => {
+                let (mut key_start, mut key_end) = par.lexer.loc();
                 let mut label = par.lexer.token_as_str();
                 par.lexer.consume();
 
@@ -118,38 +125,55 @@ impl<'ast> Parse<'ast> for ClassMember<'ast> {
                         "set" => MethodKind::Set,
                         _     => return par.error()
                     };
+                    let (start, end) = par.lexer.loc();
+                    key_start = start;
+                    key_end = end;
                     label = par.lexer.token_as_str();
                     par.lexer.consume();
                 } else if !is_static && label == "constructor" {
                     kind = MethodKind::Constructor;
                 }
 
-                PropertyKey::Literal(label)
+                par.alloc_at_loc(key_start, key_end, PropertyKey::Literal(label))
             },
             LiteralNumber => {
                 let num = par.lexer.token_as_str();
+                let key = par.alloc_in_loc(PropertyKey::Literal(num));
                 par.lexer.consume();
-                PropertyKey::Literal(num)
+                key
             },
             LiteralBinary => {
                 let num = par.lexer.token_as_str();
+                let key = par.alloc_in_loc(PropertyKey::Binary(num));
                 par.lexer.consume();
-                PropertyKey::Binary(num)
+                key
             },
             BracketOpen => {
-                par.lexer.consume();
-
+                let start = par.lexer.start_then_consume();
                 let expression = par.expression::<ANY>();
+                let end = par.lexer.end();
 
                 expect!(par, BracketClose);
 
-                PropertyKey::Computed(expression)
+                par.alloc_at_loc(start, end, PropertyKey::Computed(expression))
             },
             _ => return par.error()
         };
 
         let end;
         let member = match par.lexer.token {
+            OperatorLesser if par.flow => {
+                let value = Node::parse(par);
+
+                end = value.end;
+
+                ClassMember::Method {
+                    is_static,
+                    key,
+                    kind,
+                    value,
+                }
+            },
             ParenOpen => {
                 let value = Node::parse(par);
 
@@ -175,6 +199,27 @@ impl<'ast> Parse<'ast> for ClassMember<'ast> {
                     value: expression,
                 }
             },
+            Colon if par.flow => {
+                let annotation = par.type_annotation();
+
+                let value = match par.lexer.token {
+                    OperatorAssign => {
+                        par.lexer.consume();
+
+                        Some(par.expression::<B0>())
+                    },
+                    _ => None
+                };
+
+                end = value.map(|value| value.end).unwrap_or(annotation.end);
+
+                ClassMember::Property {
+                    is_static,
+                    key,
+                    annotation,
+                    value,
+                }
+            },
             _ => return par.error(),
         };
 
@@ -194,8 +239,23 @@ impl<'ast, N> Parse<'ast> for Class<'ast, N> where
     #[inline]
     fn parse(par: &mut Parser<'ast>) -> Self::Output {
         let name = N::parse(par);
+        let type_params = par.type_params();
 
+        let mut super_type_args = NodeList::empty();
         let super_class = match par.lexer.token {
+            // Type arguments of the super class would otherwise be
+            // parsed as comparison operators
+            Extends if par.flow => {
+                par.lexer.consume();
+
+                let super_class = par.expression::<B15>();
+
+                if par.lexer.token == OperatorLesser {
+                    super_type_args = par.type_args().0;
+                }
+
+                Some(super_class)
+            },
             Extends => {
                 par.lexer.consume();
 
@@ -204,9 +264,28 @@ impl<'ast, N> Parse<'ast> for Class<'ast, N> where
             _ => None
         };
 
+        let implements = match par.lexer.token {
+            ReservedImplements if par.flow => {
+                par.lexer.consume();
+
+                let builder = ListBuilder::new(par.arena, par.generic_type());
+
+                while par.lexer.token == Comma {
+                    par.lexer.consume();
+                    builder.push(par.arena, par.generic_type());
+                }
+
+                builder.as_list()
+            },
+            _ => NodeList::empty()
+        };
+
         Class {
             name: name.into(),
+            type_params,
             extends: super_class,
+            super_type_args,
+            implements,
             body: par.block(),
         }
     }
@@ -221,7 +300,7 @@ impl<'ast> Parser<'ast> {
 
     #[inline]
     fn pattern_identifier(&mut self) -> Node<'ast, Pattern<'ast>> {
-        let ident = Pattern::Identifier(self.lexer.token_as_str());
+        let ident = Pattern::Identifier(self.identifier_str());
         let ident = self.alloc_in_loc(ident);
 
         self.lexer.consume();
@@ -272,6 +351,7 @@ impl<'ast> Parser<'ast> {
     fn pattern_array_element(&mut self) -> Node<'ast, Pattern<'ast>> {
         let left = match self.lexer.token {
             Identifier           => self.pattern_identifier(),
+            Yield if !self.strict => self.pattern_identifier(),
             BracketOpen          => self.pattern_array(),
             BraceOpen            => self.pattern_object(),
             Comma | BracketClose => return self.pattern_void(),
@@ -285,20 +365,57 @@ impl<'ast> Parser<'ast> {
     fn pattern_param(&mut self) -> Node<'ast, Pattern<'ast>> {
         let left = match self.lexer.token {
             Identifier           => self.pattern_identifier(),
+            Yield if !self.strict => self.pattern_identifier(),
             BracketOpen          => self.pattern_array(),
             BraceOpen            => self.pattern_object(),
             _                    => self.error()
         };
+        let left = self.typed_pattern(left, true);
 
         self.pattern_assign(left)
     }
 
+    /// Attach an optional Flow type annotation to the pattern. The `?`
+    /// marking an optional parameter is only accepted if `allow_optional`
+    /// is set.
+    #[inline]
+    pub fn typed_pattern(&mut self, pattern: Node<'ast, Pattern<'ast>>, allow_optional: bool) -> Node<'ast, Pattern<'ast>> {
+        if !self.flow {
+            return pattern;
+        }
+
+        let optional = match self.lexer.token {
+            OperatorConditional if allow_optional => {
+                self.lexer.consume();
+
+                true
+            },
+            _ => false
+        };
+
+        match self.lexer.token {
+            Colon => {
+                let annotation = self.type_annotation();
+
+                self.alloc_at_loc(pattern.start, annotation.end, Pattern::Typed {
+                    pattern,
+                    optional,
+                    annotation,
+                })
+            },
+            _ if optional => self.error(),
+            _ => pattern
+        }
+    }
+
     #[inline]
     fn rest_element(&mut self) -> Node<'ast, Pattern<'ast>> {
+        self.require(Version::ES2015);
+
         let start = self.lexer.start_then_consume();
         let argument = match self.lexer.token {
             Identifier => {
-                let ident = self.lexer.token_as_str();
+                let ident = self.identifier_str();
                 let ident = self.alloc_in_loc(ident);
 
                 self.lexer.consume();
@@ -308,11 +425,14 @@ impl<'ast> Parser<'ast> {
             _ => self.error()
         };
 
+        let rest = self.alloc_at_loc(start, argument.end, Pattern::RestElement {
+            argument
+        });
+        let rest = self.typed_pattern(rest, false);
+
         expect!(self, ParenClose);
 
-        self.alloc_at_loc(start, argument.end, Pattern::RestElement {
-            argument
-        })
+        rest
     }
 
     #[inline]
@@ -386,7 +506,9 @@ mod test {
         let expected = mock.list([
             Function {
                 name: mock.name("foo"),
+                type_params: NodeList::empty(),
                 params: NodeList::empty(),
+                return_type: None,
                 body: mock.empty_block(),
             }
         ]);
@@ -402,10 +524,12 @@ mod test {
         let expected = mock.list([
             Function {
                 name: mock.name("foo"),
+                type_params: NodeList::empty(),
                 params: mock.list([
                     Pattern::Identifier("bar"),
                     Pattern::Identifier("baz"),
                 ]),
+                return_type: None,
                 body: mock.empty_block(),
             }
         ]);
@@ -421,7 +545,9 @@ mod test {
         let expected = mock.list([
             Function {
                 name: mock.name("foo"),
+                type_params: NodeList::empty(),
                 params: NodeList::empty(),
+                return_type: None,
                 body: mock.block([
                     mock.ptr("bar"),
                     mock.ptr("baz"),
@@ -440,6 +566,7 @@ mod test {
         let expected = mock.list([
             Function {
                 name: mock.name("foo"),
+                type_params: NodeList::empty(),
                 params: mock.list([
                     Pattern::AssignmentPattern {
                         left: mock.ptr(Pattern::Identifier("a")),
@@ -454,6 +581,7 @@ mod test {
                         right: mock.number("2")
                     }
                 ]),
+                return_type: None,
                 body: mock.block([
                     ReturnStatement {
                         value: Some(mock.number("2"))
@@ -472,6 +600,7 @@ mod test {
         let expected = mock.list([
             Function {
                 name: mock.name("foo"),
+                type_params: NodeList::empty(),
                 params: mock.list([
                     Pattern::Identifier("a"),
                     Pattern::Identifier("b"),
@@ -481,6 +610,7 @@ mod test {
                     },
                     Pattern::Identifier("d")
                 ]),
+                return_type: None,
                 body: mock.block([
                     ReturnStatement {
                         value: Some(mock.number("2"))
@@ -499,11 +629,13 @@ mod test {
         let expected = mock.list([
             Function {
                 name: mock.name("foo"),
+                type_params: NodeList::empty(),
                 params: mock.list([
                     Pattern::RestElement {
                         argument: mock.ptr("rest"),
                     }
                 ]),
+                return_type: None,
                 body: mock.empty_block()
             }
         ]);
@@ -518,6 +650,7 @@ mod test {
         let expected = mock.list([
             Function {
                 name: mock.name("foo"),
+                type_params: NodeList::empty(),
                 params: mock.list([
                     Pattern::Identifier("a"),
                     Pattern::AssignmentPattern {
@@ -528,6 +661,7 @@ mod test {
                         argument: mock.ptr("rest"),
                     }
                 ]),
+                return_type: None,
                 body: mock.empty_block()
             }
         ]);
@@ -547,7 +681,10 @@ mod test {
         let expected = mock.list([
             Class {
                 name: mock.name("Foo"),
+                type_params: NodeList::empty(),
                 extends: None,
+                super_type_args: NodeList::empty(),
+                implements: NodeList::empty(),
                 body: mock.empty_block(),
             }
         ]);
@@ -563,7 +700,10 @@ mod test {
         let expected = mock.list([
             Class {
                 name: mock.name("Foo"),
+                type_params: NodeList::empty(),
                 extends: Some(mock.ptr("Bar")),
+                super_type_args: NodeList::empty(),
+                implements: NodeList::empty(),
                 body: mock.empty_block(),
             }
         ]);
@@ -587,18 +727,23 @@ mod test {
         let expected = mock.list([
             Class {
                 name: mock.name("Foo"),
+                type_params: NodeList::empty(),
                 extends: None,
+                super_type_args: NodeList::empty(),
+                implements: NodeList::empty(),
                 body: mock.block([
                     ClassMember::Method {
                         is_static: false,
-                        key: PropertyKey::Literal("constructor"),
+                        key: mock.ptr(PropertyKey::Literal("constructor")),
                         kind: MethodKind::Constructor,
                         value: mock.ptr(Function {
                             name: EmptyName,
+                            type_params: NodeList::empty(),
                             params: mock.list([
                                 Pattern::Identifier("bar"),
                                 Pattern::Identifier("baz")
                             ]),
+                            return_type: None,
                             body: mock.block([
                                 mock.ptr("debug")
                             ])
@@ -635,18 +780,23 @@ mod test {
         let expected = mock.list([
             Class {
                 name: mock.name("Foo"),
+                type_params: NodeList::empty(),
                 extends: None,
+                super_type_args: NodeList::empty(),
+                implements: NodeList::empty(),
                 body: mock.block([
                     ClassMember::Method {
                         is_static: false,
-                        key: PropertyKey::Literal("doge"),
+                        key: mock.ptr(PropertyKey::Literal("doge")),
                         kind: MethodKind::Method,
                         value: mock.ptr(Function {
                             name: EmptyName,
+                            type_params: NodeList::empty(),
                             params: mock.list([
                                 Pattern::Identifier("bar"),
                                 Pattern::Identifier("baz")
                             ]),
+                            return_type: None,
                             body: mock.block([
                                 mock.ptr("debug")
                             ])
@@ -654,13 +804,15 @@ mod test {
                     },
                     ClassMember::Method {
                         is_static: true,
-                        key: PropertyKey::Literal("toThe"),
+                        key: mock.ptr(PropertyKey::Literal("toThe")),
                         kind: MethodKind::Method,
                         value: mock.ptr(Function {
                             name: EmptyName,
+                            type_params: NodeList::empty(),
                             params: mock.list([
                                 Pattern::Identifier("moon")
                             ]),
+                            return_type: None,
                             body: mock.block([
                                 mock.ptr("debug")
                             ])
@@ -668,31 +820,37 @@ mod test {
                     },
                     ClassMember::Method {
                         is_static: false,
-                        key: PropertyKey::Literal("function"),
+                        key: mock.ptr(PropertyKey::Literal("function")),
                         kind: MethodKind::Method,
                         value: mock.ptr(Function {
                             name: EmptyName,
+                            type_params: NodeList::empty(),
                             params: NodeList::empty(),
+                            return_type: None,
                             body: mock.empty_block()
                         })
                     },
                     ClassMember::Method {
                         is_static: true,
-                        key: PropertyKey::Literal("function"),
+                        key: mock.ptr(PropertyKey::Literal("function")),
                         kind: MethodKind::Method,
                         value: mock.ptr(Function {
                             name: EmptyName,
+                            type_params: NodeList::empty(),
                             params: NodeList::empty(),
+                            return_type: None,
                             body: mock.empty_block()
                         })
                     },
                     ClassMember::Method {
                         is_static: true,
-                        key: PropertyKey::Literal("constructor"),
+                        key: mock.ptr(PropertyKey::Literal("constructor")),
                         kind: MethodKind::Method,
                         value: mock.ptr(Function {
                             name: EmptyName,
+                            type_params: NodeList::empty(),
                             params: NodeList::empty(),
+                            return_type: None,
                             body: mock.empty_block()
                         })
                     },
@@ -720,26 +878,29 @@ mod test {
         let expected = mock.list([
             Class {
                 name: mock.name("Foo"),
+                type_params: NodeList::empty(),
                 extends: None,
+                super_type_args: NodeList::empty(),
+                implements: NodeList::empty(),
                 body: mock.block([
                     ClassMember::Literal {
                         is_static: false,
-                        key: PropertyKey::Literal("doge"),
+                        key: mock.ptr(PropertyKey::Literal("doge")),
                         value: mock.number("10")
                     },
                     ClassMember::Literal {
                         is_static: false,
-                        key: PropertyKey::Literal("to"),
+                        key: mock.ptr(PropertyKey::Literal("to")),
                         value: mock.number("20")
                     },
                     ClassMember::Literal {
                         is_static: false,
-                        key: PropertyKey::Literal("the"),
+                        key: mock.ptr(PropertyKey::Literal("the")),
                         value: mock.number("30")
                     },
                     ClassMember::Literal {
                         is_static: true,
-                        key: PropertyKey::Literal("moon"),
+                        key: mock.ptr(PropertyKey::Literal("moon")),
                         value: mock.number("42")
                     },
                 ])
@@ -758,7 +919,10 @@ mod test {
         let expected = mock.list([
             Class {
                 name: mock.name("Foo"),
+                type_params: NodeList::empty(),
                 extends: Some(mock.ptr(Expression::Literal(Literal::Null))),
+                super_type_args: NodeList::empty(),
+                implements: NodeList::empty(),
                 body: mock.empty_block()
             }
         ]);
@@ -781,29 +945,36 @@ mod test {
         let expected = mock.list([
             Class {
                 name: mock.name("Foo"),
+                type_params: NodeList::empty(),
                 extends: None,
+                super_type_args: NodeList::empty(),
+                implements: NodeList::empty(),
                 body: mock.block([
                     ClassMember::Method {
                         is_static: false,
-                        key: PropertyKey::Literal("length"),
+                        key: mock.ptr(PropertyKey::Literal("length")),
                         kind: MethodKind::Get,
                         value: mock.ptr(Function {
                             name: EmptyName,
+                            type_params: NodeList::empty(),
                             params: mock.list([
                                 Pattern::Identifier("foo")
                             ]),
+                            return_type: None,
                             body: mock.empty_block()
                         })
                     },
                     ClassMember::Method {
                         is_static: false,
-                        key: PropertyKey::Literal("length"),
+                        key: mock.ptr(PropertyKey::Literal("length")),
                         kind: MethodKind::Set,
                         value: mock.ptr(Function {
                             name: EmptyName,
+                            type_params: NodeList::empty(),
                             params: mock.list([
                                 Pattern::Identifier("bar")
                             ]),
+                            return_type: None,
                             body: mock.empty_block()
                         })
                     },
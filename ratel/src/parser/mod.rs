@@ -4,7 +4,11 @@ mod error;
 mod expression;
 mod statement;
 mod function;
+mod types;
 mod nested;
+mod options;
+
+use std::str;
 
 use toolshed::list::ListBuilder;
 use toolshed::Arena;
@@ -14,10 +18,12 @@ use module::Module;
 use self::error::ToError;
 use self::nested::*;
 
-use ast::{Loc, Node, Statement, NodeList, Block, BlockNode};
+pub use self::options::{ParseOptions, Goal, Version};
+
+use ast::{Loc, Node, Statement, NodeList, Block, BlockNode, Program};
 use ast::{Expression, ExpressionNode, ExpressionList, IdentifierNode};
-use ast::{OperatorKind, Pattern};
-use ast::expression::BinaryExpression;
+use ast::{OperatorKind, Pattern, Literal};
+use ast::expression::{BinaryExpression, TypeCastExpression};
 use lexer::{Lexer, Asi};
 use lexer::Token::*;
 
@@ -38,15 +44,34 @@ pub struct Parser<'ast> {
 
     /// AST under construction
     body: NodeList<'ast, Statement<'ast>>,
+
+    /// Options the parser was created with
+    options: ParseOptions,
+
+    /// Whether or not Flow type annotations are allowed
+    flow: bool,
+
+    /// Whether or not the code is in strict mode
+    strict: bool,
+
+    /// Interpreter directive on the first line, without the leading `#!`
+    hashbang: Option<&'ast str>,
 }
 
 impl<'ast> Parser<'ast> {
-    pub fn new(source: &str, arena: &'ast Arena) -> Self {
+    pub fn new(source: &str, arena: &'ast Arena, options: ParseOptions) -> Self {
+        let mut lexer = Lexer::new(arena, source);
+        let hashbang = lexer.hashbang();
+
         Parser {
             arena,
-            lexer: Lexer::new(arena, source),
+            lexer,
             errors: Vec::new(),
             body: NodeList::empty(),
+            options,
+            flow: options.flow || has_flow_pragma(source),
+            strict: options.goal == Goal::Module,
+            hashbang,
         }
     }
 
@@ -58,6 +83,21 @@ impl<'ast> Parser<'ast> {
         T::to_error()
     }
 
+    /// Produce an error on the current token if it's syntax introduced
+    /// after the targeted ECMAScript edition.
+    #[inline]
+    fn require(&mut self, version: Version) {
+        if self.options.version < version {
+            self.error::<()>();
+        }
+    }
+
+    /// Whether `import` and `export` statements are allowed.
+    #[inline]
+    fn is_module(&self) -> bool {
+        self.options.goal == Goal::Module
+    }
+
     #[inline]
     fn asi(&mut self) -> Asi {
         self.lexer.asi()
@@ -105,20 +145,50 @@ impl<'ast> Parser<'ast> {
             return;
         }
 
+        let start = self.lexer.start();
         let statement = self.statement();
         let builder = ListBuilder::new(self.arena, statement);
+        let mut prologue = self.directive(start, statement);
 
         while self.lexer.token != EndOfProgram {
-            builder.push(self.arena, self.statement());
+            let start = self.lexer.start();
+            let statement = self.statement();
+
+            if prologue {
+                prologue = self.directive(start, statement);
+            }
+
+            builder.push(self.arena, statement);
         }
 
         self.body = builder.as_list()
     }
 
+    /// Check if the statement starting at `start` is a directive, such as
+    /// `"use strict"`, switching to strict mode if needed. A directive has
+    /// to be a bare string, so `("use strict");` doesn't count.
     #[inline]
-    fn block<I>(&mut self) -> BlockNode<'ast, I> where
-        I: Parse<'ast, Output = Node<'ast, I>> + Copy
-    {
+    fn directive(&mut self, start: u32, statement: Node<'ast, Statement<'ast>>) -> bool {
+        let value = match statement.item {
+            Statement::Expression(expression) if expression.start == start => match expression.item {
+                Expression::Literal(Literal::String(value)) => value,
+                _ => return false,
+            },
+            _ => return false,
+        };
+
+        if &value[1..value.len() - 1] == "use strict" {
+            self.strict = true;
+        }
+
+        true
+    }
+
+    /// Parse the body of a function. A `"use strict"` directive in it
+    /// only applies until the end of the function.
+    #[inline]
+    fn function_body(&mut self) -> BlockNode<'ast, Statement<'ast>> {
+        let strict = self.strict;
         let start = self.lexer.start();
 
         match self.lexer.token {
@@ -126,18 +196,45 @@ impl<'ast> Parser<'ast> {
             _         => self.error::<()>(),
         }
 
-        let block = self.raw_block();
-        let end   = self.lexer.end_then_consume();
+        let body = if self.lexer.token == BraceClose {
+            NodeList::empty()
+        } else {
+            let start = self.lexer.start();
+            let statement = self.statement();
+            let builder = ListBuilder::new(self.arena, statement);
+            let mut prologue = self.directive(start, statement);
 
-        self.alloc_at_loc(start, end, block)
+            while self.lexer.token != BraceClose && self.lexer.token != EndOfProgram {
+                let start = self.lexer.start();
+                let statement = self.statement();
+
+                if prologue {
+                    prologue = self.directive(start, statement);
+                }
+
+                builder.push(self.arena, statement);
+            }
+
+            builder.as_list()
+        };
+
+        let end = self.lexer.end_then_consume();
+
+        self.strict = strict;
+        self.alloc_at_loc(start, end, Block { body })
     }
 
-    /// Same as above, but assumes that the opening brace has already been checked
     #[inline]
-    fn unchecked_block<I>(&mut self) -> BlockNode<'ast, I> where
+    fn block<I>(&mut self) -> BlockNode<'ast, I> where
         I: Parse<'ast, Output = Node<'ast, I>> + Copy
     {
-        let start = self.lexer.start_then_consume();
+        let start = self.lexer.start();
+
+        match self.lexer.token {
+            BraceOpen => self.lexer.consume(),
+            _         => self.error::<()>(),
+        }
+
         let block = self.raw_block();
         let end   = self.lexer.end_then_consume();
 
@@ -162,11 +259,32 @@ impl<'ast> Parser<'ast> {
         Block { body: builder.as_list() }
     }
 
+    /// Check if the identifier can't be used as a name in the current
+    /// goal and mode: `await` in modules, and words such as `yield` or
+    /// `let` in strict mode.
+    #[inline]
+    fn is_reserved(&self, ident: &str) -> bool {
+        (ident == "await" && self.is_module()) || (self.strict && is_strict_reserved(ident))
+    }
+
+    /// Get the current token as an identifier, producing an error if
+    /// it's reserved.
+    #[inline]
+    fn identifier_str(&mut self) -> &'ast str {
+        let ident = self.lexer.token_as_str();
+
+        if self.is_reserved(ident) {
+            self.error::<()>();
+        }
+
+        ident
+    }
+
     #[inline]
     fn identifier(&mut self) -> IdentifierNode<'ast> {
         match self.lexer.token {
             Identifier => {
-                let ident = self.lexer.token_as_str();
+                let ident = self.identifier_str();
                 let ident = self.alloc_in_loc(ident);
                 self.lexer.consume();
                 ident
@@ -191,6 +309,16 @@ impl<'ast> Parser<'ast> {
             Expression::Identifier(ident) => {
                 Pattern::Identifier(ident)
             },
+            Expression::TypeCast(TypeCastExpression {
+                expression,
+                annotation,
+            }) => {
+                Pattern::Typed {
+                    pattern: self.pattern_from_expression(expression),
+                    optional: false,
+                    annotation,
+                }
+            },
             _ => self.error()
         };
 
@@ -218,24 +346,112 @@ impl<'ast> Parser<'ast> {
     }
 }
 
-/// Parse the JavaScript source `&str` and produce an Abstract Syntax Tree `Module`.
-pub fn parse<'src, 'ast>(source: &'src str) -> Result<Module<'ast>, Vec<Error>> {
-    let arena = Arena::new();
+/// Check whether the identifier is a word only reserved in strict mode.
+fn is_strict_reserved(ident: &str) -> bool {
+    matches!(ident,
+        "implements" | "interface" | "let" | "package" | "private" |
+        "protected" | "public" | "static" | "yield"
+    )
+}
+
+/// Check if the leading comments of the source contain the `@flow` pragma.
+fn has_flow_pragma(source: &str) -> bool {
+    // The pragma can follow a hashbang, which is always the first line
+    let source = match source.strip_prefix("#!") {
+        Some(rest) => &rest[rest.find('\n').unwrap_or(rest.len())..],
+        None       => source,
+    };
 
-    let (body, errors) = {
-        let mut parser = Parser::new(source, &arena);
+    let mut source = source.trim_start();
 
-        parser.parse();
+    loop {
+        let (comment, rest) = if source.starts_with("//") {
+            let end = source.find('\n').unwrap_or(source.len());
 
-        (parser.body.into_unsafe(), parser.errors)
-    };
+            (&source[2..end], &source[end..])
+        } else if let Some(block) = source.strip_prefix("/*") {
+            match block.find("*/") {
+                Some(end) => (&block[..end], &block[end + 2..]),
+                None      => return false,
+            }
+        } else {
+            return false;
+        };
+
+        let mut words = comment.split(|ch: char| ch.is_whitespace() || ch == '*');
+
+        if words.any(|word| word == "@flow") {
+            return true;
+        }
 
-    match errors.len() {
-        0 => Ok(Module::new(body, arena)),
-        _ => Err(errors)
+        source = rest.trim_start();
     }
 }
 
+/// Parse the JavaScript source `&str` and produce an Abstract Syntax Tree `Module`,
+/// using the default `ParseOptions`. The source is read as a `Script`, to parse
+/// `import` and `export` statements use `Goal::Module` with `parse_with_options`.
+///
+/// Flow type annotations are allowed if the source begins with a `// @flow` comment.
+pub fn parse<'ast>(source: &str) -> Result<Module<'ast>, Vec<Error>> {
+    parse_with_options(source, ParseOptions::default())
+}
+
+/// Same as `parse`, but with explicit `ParseOptions`.
+pub fn parse_with_options<'ast>(source: &str, options: ParseOptions) -> Result<Module<'ast>, Vec<Error>> {
+    Module::from_arena(Arena::new(), |arena| parse_in(source, arena, options))
+}
+
+/// Parse the source into an `Arena` owned by the caller, producing a `Program`
+/// borrowed from it. Any number of sources can share a single arena, which
+/// avoids allocating a new one for every file.
+pub fn parse_in<'ast>(source: &str, arena: &'ast Arena, options: ParseOptions) -> Result<Program<'ast>, Vec<Error>> {
+    let mut parser = Parser::new(source, arena, options);
+
+    parser.parse();
+
+    if !parser.errors.is_empty() {
+        return Err(parser.errors);
+    }
+
+    Ok(Program {
+        source: parser.lexer.slice_source(0, source.len()),
+        body: parser.body,
+        hashbang: parser.hashbang,
+    })
+}
+
+/// Same as `parse`, but reads the source from a byte slice, such as the
+/// contents of a file or a Node `Buffer`, so that it doesn't have to be
+/// converted to a string first. Invalid UTF-8 is reported as an error at
+/// the position of the offending bytes.
+pub fn parse_bytes<'ast>(source: &[u8]) -> Result<Module<'ast>, Vec<Error>> {
+    parse_bytes_with_options(source, ParseOptions::default())
+}
+
+/// Same as `parse_bytes`, but with explicit `ParseOptions`.
+pub fn parse_bytes_with_options<'ast>(source: &[u8], options: ParseOptions) -> Result<Module<'ast>, Vec<Error>> {
+    let err = match str::from_utf8(source) {
+        Ok(source) => return parse_with_options(source, options),
+        Err(err)   => err,
+    };
+
+    let start = err.valid_up_to();
+
+    // Without the length, the source ends in the middle of a character
+    let (token, end) = match err.error_len() {
+        Some(len) => (UnexpectedToken, start + len),
+        None      => (UnexpectedEndOfProgram, source.len()),
+    };
+
+    Err(vec![Error {
+        token,
+        start,
+        end,
+        raw: String::from_utf8_lossy(&source[start..end]).into_owned().into_boxed_str(),
+    }])
+}
+
 #[cfg(test)]
 mod mock {
     use super::*;
@@ -315,4 +531,167 @@ mod test {
 
         assert_eq!(parse(";;;").unwrap().body(), expected);
     }
+
+    #[test]
+    fn flow_pragma() {
+        assert!(has_flow_pragma("// @flow\nfoo;"));
+        assert!(has_flow_pragma("/* @flow */"));
+        assert!(has_flow_pragma("/**\n * Copyright\n *\n * @flow\n */"));
+        assert!(has_flow_pragma("// License\n\n// @flow strict"));
+        assert!(has_flow_pragma("#!/usr/bin/env node\n// @flow\nfoo;"));
+        assert!(!has_flow_pragma("#!/usr/bin/env node @flow\nfoo;"));
+        assert!(!has_flow_pragma("// @noflow"));
+        assert!(!has_flow_pragma("foo; // @flow"));
+        assert!(!has_flow_pragma("/* @flow"));
+    }
+
+    fn module(source: &str) -> Result<Module, Vec<Error>> {
+        parse_with_options(source, ParseOptions { goal: Goal::Module, ..ParseOptions::default() })
+    }
+
+    fn target(source: &str, version: Version) -> Result<Module, Vec<Error>> {
+        parse_with_options(source, ParseOptions { version, ..ParseOptions::default() })
+    }
+
+    #[test]
+    fn script_goal_rejects_modules() {
+        assert!(module("import foo from 'foo';").is_ok());
+        assert!(module("export default foo;").is_ok());
+        assert!(parse("import foo from 'foo';").is_err());
+        assert!(parse("export default foo;").is_err());
+    }
+
+    #[test]
+    fn await_identifier() {
+        assert!(parse("var await = 1; await;").is_ok());
+        assert!(module("var await = 1;").is_err());
+        assert!(module("await;").is_err());
+    }
+
+    #[test]
+    fn yield_identifier() {
+        assert!(parse("var yield = 1; yield;").is_ok());
+        assert!(parse("function foo(yield) {}").is_ok());
+        assert!(parse("'use strict'; var yield = 1;").is_err());
+        assert!(module("var yield = 1;").is_err());
+        assert!(module("yield;").is_err());
+    }
+
+    #[test]
+    fn strict_legacy_octal() {
+        assert!(parse("017;").is_ok());
+        assert!(parse("'use strict'; 017;").is_err());
+        assert!(parse("foo; 'use strict'; 017;").is_ok());
+        assert!(module("017;").is_err());
+    }
+
+    #[test]
+    fn function_directives() {
+        assert!(parse("function foo() { 'use strict'; 017; }").is_err());
+        assert!(parse("function foo() { 'bar'; \"use strict\"; var yield; }").is_err());
+        assert!(parse("(() => { 'use strict'; 017; });").is_err());
+        assert!(parse("function foo() { 'use strict'; } 017;").is_ok());
+        assert!(parse("function foo() { bar; 'use strict'; 017; }").is_ok());
+    }
+
+    #[test]
+    fn parenthesized_directive() {
+        assert!(parse("('use strict'); 017;").is_ok());
+        assert!(parse("function foo() { ('use strict'); 017; }").is_ok());
+        assert!(parse("('foo'); 'use strict'; 017;").is_ok());
+    }
+
+    #[test]
+    fn with_statement() {
+        assert!(parse("with (foo) bar;").is_ok());
+        assert!(parse("'use strict'; with (foo) bar;").is_err());
+        assert!(parse("function foo() { 'use strict'; with (foo) bar; }").is_err());
+        assert!(module("with (foo) bar;").is_err());
+    }
+
+    #[test]
+    fn target_version() {
+        assert!(target("var a = 1;", Version::ES5).is_ok());
+        assert!(target("let a = 1;", Version::ES5).is_err());
+        assert!(target("let a = 1;", Version::ES2015).is_ok());
+        assert!(target("(a) => a;", Version::ES5).is_err());
+        assert!(target("class Foo {}", Version::ES5).is_err());
+        assert!(target("`foo`;", Version::ES5).is_err());
+        assert!(target("2 ** 8;", Version::ES2015).is_err());
+        assert!(target("2 ** 8;", Version::ES2016).is_ok());
+        assert!(parse_with_options("import foo from 'foo';", ParseOptions {
+            goal: Goal::Module,
+            version: Version::ES5,
+            ..ParseOptions::default()
+        }).is_err());
+    }
+
+    #[test]
+    fn hashbang() {
+        let module = parse("#!/usr/bin/env node\nfoo;").unwrap();
+        let mock = Mock::new();
+
+        assert_eq!(module.hashbang(), Some("/usr/bin/env node"));
+        assert_eq!(module.body(), mock.list([mock.ptr("foo")]));
+        assert_eq!(module.body().only_element().unwrap().start, 20);
+
+        assert_eq!(parse("foo;").unwrap().hashbang(), None);
+        assert!(parse("foo;\n#!/usr/bin/env node").is_err());
+    }
+
+    #[test]
+    fn unicode_identifiers() {
+        let mock = Mock::new();
+
+        assert_eq!(parse("ñame;").unwrap().body(), mock.list([mock.ptr("ñame")]));
+        assert_eq!(parse(r"var \u0061 = a\u{62}; \u0061.b\u0061r;").unwrap().body(), parse("var a = ab; a.bar;").unwrap().body());
+        assert!(parse(r"var \u0076ar = 1;").is_err());
+        assert!(parse(r"\u{74}his;").is_err());
+    }
+
+    #[test]
+    fn escaped_strict_reserved_words() {
+        assert!(parse(r"var \u0079ield = 1; \u0079ield;").is_ok());
+        assert!(parse(r"var l\u0065t = 1, \u0073tatic, \u{69}mplements;").is_ok());
+        assert!(parse(r"'use strict'; var \u0079ield;").is_err());
+        assert!(parse(r"'use strict'; l\u0065t;").is_err());
+        assert!(parse(r"function foo() { 'use strict'; \u0073tatic; }").is_err());
+        assert!(module(r"var \u{69}nterface;").is_err());
+        assert!(parse(r"({ \u0079ield });").is_ok());
+        assert!(parse(r"'use strict'; ({ \u0079ield });").is_err());
+    }
+
+    #[test]
+    fn escaped_await() {
+        assert!(parse(r"var \u0061wait = 1; \u0061wait;").is_ok());
+        assert!(module(r"var \u0061wait;").is_err());
+        assert!(module(r"\u0061wait;").is_err());
+        assert!(module(r"aw\u{61}it: foo;").is_err());
+        assert!(module(r"({ \u0061wait });").is_err());
+        assert!(module(r"let { \u0061wait } = foo;").is_err());
+        assert!(module(r"import \u0061wait from 'foo';").is_err());
+        assert!(module(r"import * as \u0061wait from 'foo';").is_err());
+        assert!(module(r"import { \u0061wait } from 'foo';").is_err());
+        assert!(module(r"import { foo as \u0061wait } from 'foo';").is_err());
+        assert!(module(r"import { \u0061wait as foo } from 'foo';").is_ok());
+        assert!(module(r"({ \u0061wait: 1 }).\u0061wait;").is_ok());
+    }
+
+    #[test]
+    fn parse_from_bytes() {
+        let mock = Mock::new();
+
+        assert_eq!(parse_bytes("café;".as_bytes()).unwrap().body(), mock.list([mock.ptr("café")]));
+
+        let errors = parse_bytes(b"foo;\xFF bar;").err().unwrap();
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].token, UnexpectedToken);
+        assert_eq!((errors[0].start, errors[0].end), (4, 5));
+
+        let errors = parse_bytes(b"foo;\xE2\x82").err().unwrap();
+
+        assert_eq!(errors[0].token, UnexpectedEndOfProgram);
+        assert_eq!((errors[0].start, errors[0].end), (4, 6));
+    }
 }
@@ -1,5 +1,5 @@
 use toolshed::list::ListBuilder;
-use parser::Parser;
+use parser::{Parser, Version};
 use lexer::Token;
 use lexer::Token::*;
 use ast::{NodeList, OperatorKind, Expression, ExpressionNode};
@@ -281,6 +281,7 @@ const COND: NestedHandler = Some(|par, left| {
 });
 
 const ARRW: NestedHandler = Some(|par, left| {
+    par.require(Version::ES2015);
     par.lexer.consume();
 
     let params = match left.item {
@@ -288,7 +289,7 @@ const ARRW: NestedHandler = Some(|par, left| {
         _ => NodeList::from(par.arena, left)
     };
 
-    return par.arrow_function_expression(params);
+    return par.arrow_function_expression(NodeList::empty(), params, None);
 });
 
 const ACCS: NestedHandler = Some(|par, left| {
@@ -341,9 +342,10 @@ const TPLE: NestedHandler = Some(|par, left| {
 });
 
 macro_rules! binary {
-    ($name:ident, $bp:ident => $op:ident) => {
+    ($name:ident, $bp:ident => $op:ident $(, $version:ident)*) => {
         const $name: NestedHandler = {
             fn handler<'ast>(par: &mut Parser<'ast>, left: ExpressionNode<'ast>) -> ExpressionNode<'ast> {
+                $( par.require(Version::$version); )*
                 par.lexer.consume();
 
                 let right = par.expression::<$bp>();
@@ -363,7 +365,7 @@ macro_rules! binary {
 binary!(ASGN , B1  => Assign);
 binary!(ADDA , B1  => AddAssign);
 binary!(SUBA , B1  => SubtractAssign);
-binary!(EXPA , B1  => ExponentAssign);
+binary!(EXPA , B1  => ExponentAssign, ES2016);
 binary!(MULA , B1  => MultiplyAssign);
 binary!(DIVA , B1  => DivideAssign);
 binary!(REMA , B1  => RemainderAssign);
@@ -396,7 +398,7 @@ binary!(SUB  , B13 => Subtraction);
 binary!(MUL  , B14 => Multiplication);
 binary!(DIV  , B14 => Division);
 binary!(REM  , B14 => Remainder);
-binary!(EXPN , B15 => Exponent);
+binary!(EXPN , B15 => Exponent, ES2016);
 
 
 impl<'ast> Parser<'ast> {
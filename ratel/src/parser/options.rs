@@ -0,0 +1,47 @@
+/// The goal symbol the source is parsed with.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Goal {
+    /// Classic script. `import` and `export` statements are not allowed,
+    /// `await` is an identifier, and the code is only strict if it opens
+    /// with a `"use strict"` directive.
+    Script,
+
+    /// ES module. Always strict, `await` is reserved.
+    Module,
+}
+
+/// ECMAScript edition. Syntax introduced after the targeted edition is
+/// rejected by the parser.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub enum Version {
+    ES5,
+    ES2015,
+    ES2016,
+    ES2017,
+    ES2018,
+}
+
+/// Options controlling how `Parser` reads the source.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct ParseOptions {
+    /// Parse the source as a `Script` or a `Module`, defaults to `Script`.
+    pub goal: Goal,
+
+    /// Latest edition whose syntax is accepted, defaults to `ES2018`.
+    pub version: Version,
+
+    /// Allow Flow type annotations. If `false`, they are still allowed
+    /// when the source begins with a `// @flow` comment.
+    pub flow: bool,
+}
+
+impl Default for ParseOptions {
+    #[inline]
+    fn default() -> Self {
+        ParseOptions {
+            goal: Goal::Script,
+            version: Version::ES2018,
+            flow: false,
+        }
+    }
+}
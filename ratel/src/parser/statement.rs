@@ -1,14 +1,15 @@
 use toolshed::list::{ListBuilder, GrowableList};
-use parser::{Parser, Parse, ANY, B0};
+use parser::{Parser, Parse, Version, ANY, B0};
 use lexer::Token::*;
 use lexer::Asi;
-use ast::{Node, NodeList, Declarator, DeclarationKind};
+use ast::{Node, NodeList, Declarator, DeclarationKind, Literal};
 use ast::{Statement, StatementNode, Expression, ExpressionNode, Class, Function, Pattern};
 use ast::expression::BinaryExpression;
 use ast::statement::{ThrowStatement, ContinueStatement, BreakStatement, ReturnStatement};
 use ast::statement::{TryStatement, CatchClause, IfStatement, WhileStatement, DoStatement};
 use ast::statement::{DeclarationStatement, ForStatement, ForInStatement, ForOfStatement};
-use ast::statement::{SwitchStatement, SwitchCase, LabeledStatement, ForInit};
+use ast::statement::{SwitchStatement, SwitchCase, LabeledStatement, ForInit, WithStatement};
+use ast::statement::{ImportStatement, ImportKind, ImportSpecifier, ExportStatement, ExportKind, ExportSpecifier};
 use ast::OperatorKind::*;
 
 
@@ -21,7 +22,7 @@ static STMT_HANDLERS: [StatementHandler; 108] = [
     OP,   OP,   OP,   OP,   OP,   OP,   OP,   ____, REG,  ____, ____, OP,
 //  ++    --    !     ~     TYPOF VOID  DELET *     /     %     **    +
 
-    OP,   ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
+    OP,   ____, ____, ____, GNRC, ____, ____, ____, ____, ____, ____, ____,
 //  -     <<    >>    >>>   <     <=    >     >=    INSOF IN    ===   !==
 
     ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
@@ -30,13 +31,13 @@ static STMT_HANDLERS: [StatementHandler; 108] = [
     ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, VAR,  LET,
 //  *=    /=    %=    <<=   >>=   >>>=  &=    ^=    |=    ...   VAR   LET
 
-    CONS, BRK,  DO,   ____, ____, ____, ____, CLAS, ____, RET,  WHL,  ____,
+    CONS, BRK,  DO,   ____, ____, ____, EXPR, CLAS, ____, RET,  WHL,  ____,
 //  CONST BREAK DO    CASE  ELSE  CATCH EXPRT CLASS EXTND RET   WHILE FINLY
 
-    ____, ____, CONT, FOR,  SWCH, ____, ____, FUNC, THIS, ____, IF,   THRW,
+    SUPR, WITH, CONT, FOR,  SWCH, YILD, ____, FUNC, THIS, ____, IF,   THRW,
 //  SUPER WITH  CONT  FOR   SWTCH YIELD DBGGR FUNCT THIS  DEFLT IF    THROW
 
-    ____, TRY,  ____, TRUE, FALS, NULL, UNDE, STR,  NUM,  BIN,  ____, ____,
+    IMPR, TRY,  ____, TRUE, FALS, NULL, UNDE, STR,  NUM,  BIN,  ____, ____,
 //  IMPRT TRY   STATI TRUE  FALSE NULL  UNDEF STR   NUM   BIN   REGEX ENUM
 
     ____, ____, ____, ____, ____, ____, LABL, ____, TPLE, TPLS, ____, ____,
@@ -57,7 +58,7 @@ macro_rules! create_handlers {
 
 /// Shared expression handlers that produce StatementNode<'ast>
 use parser::expression::handlers::{
-    PRN, ARR, OP, REG, THIS, TRUE, FALS, NULL, UNDE, STR, NUM, BIN, TPLS, TPLE
+    PRN, ARR, GNRC, OP, REG, THIS, SUPR, TRUE, FALS, NULL, UNDE, STR, NUM, BIN, TPLS, TPLE
 };
 
 create_handlers! {
@@ -84,11 +85,21 @@ create_handlers! {
     const CLAS = |par| par.class_statement();
     const IF   = |par| par.if_statement();
     const WHL  = |par| par.while_statement();
+    const WITH = |par| par.with_statement();
     const DO   = |par| par.do_statement();
     const FOR  = |par| par.for_statement();
     const TRY  = |par| par.try_statement();
     const SWCH = |par| par.switch_statement();
+    const IMPR = |par| par.import_statement();
+    const EXPR = |par| par.export_statement();
     const LABL = |par| par.labeled_or_expression_statement();
+    const YILD = |par| {
+        if par.strict {
+            par.error::<()>();
+        }
+
+        par.labeled_or_expression_statement()
+    };
 }
 
 impl<'ast> Parse<'ast> for Statement<'ast> {
@@ -156,7 +167,7 @@ impl<'ast> Parser<'ast> {
     /// Expect a semicolon to terminate a statement. Will assume a semicolon
     /// following the ASI rules.
     #[inline]
-    fn expect_semicolon(&mut self) {
+    pub fn expect_semicolon(&mut self) {
         match self.asi() {
             Asi::ExplicitSemicolon => self.lexer.consume(),
             Asi::ImplicitSemicolon => {},
@@ -188,7 +199,7 @@ impl<'ast> Parser<'ast> {
 
     #[inline]
     pub fn labeled_or_expression_statement(&mut self) -> StatementNode<'ast> {
-        let label = self.lexer.token_as_str();
+        let label = self.identifier_str();
         let (start, end) = self.lexer.loc();
 
         self.lexer.consume();
@@ -204,6 +215,12 @@ impl<'ast> Parser<'ast> {
             });
         }
 
+        if self.flow {
+            if let Some(statement) = self.flow_statement(label, start) {
+                return statement;
+            }
+        }
+
         let expression = self.alloc_at_loc(start, end, label);
         let expression = self.nested_expression::<ANY>(expression);
 
@@ -212,6 +229,34 @@ impl<'ast> Parser<'ast> {
         self.alloc_at_loc(start, expression.end, expression)
     }
 
+    /// Flow statements start with contextual keywords, which remain valid
+    /// identifiers everywhere else.
+    fn flow_statement(&mut self, label: &str, start: u32) -> Option<StatementNode<'ast>> {
+        match self.asi() {
+            Asi::NoSemicolon => {},
+            _                => return None,
+        }
+
+        match (label, self.lexer.token) {
+            ("type", Identifier) => Some(self.type_alias_statement(start, false)),
+            ("opaque", Identifier) if self.lexer.token_as_str() == "type" => {
+                self.lexer.consume();
+
+                Some(self.type_alias_statement(start, true))
+            },
+            ("declare", DeclarationVar)   |
+            ("declare", DeclarationLet)   |
+            ("declare", DeclarationConst) |
+            ("declare", Function)         |
+            ("declare", Class)            => Some(self.declare_statement(start)),
+            ("declare", Identifier) => match self.lexer.token_as_str() {
+                "type" | "opaque" => Some(self.declare_statement(start)),
+                _                 => None,
+            },
+            _ => None
+        }
+    }
+
     #[inline]
     pub fn function_statement(&mut self) -> StatementNode<'ast> {
         let start = self.lexer.start_then_consume();
@@ -222,6 +267,8 @@ impl<'ast> Parser<'ast> {
 
     #[inline]
     fn class_statement(&mut self) -> StatementNode<'ast> {
+        self.require(Version::ES2015);
+
         let start = self.lexer.start_then_consume();
         let class = Class::parse(self);
 
@@ -230,6 +277,10 @@ impl<'ast> Parser<'ast> {
 
     #[inline]
     pub fn variable_declaration_statement(&mut self, kind: DeclarationKind) -> StatementNode<'ast> {
+        if kind != DeclarationKind::Var {
+            self.require(Version::ES2015);
+        }
+
         let start = self.lexer.start_then_consume();
         let declarators = self.variable_declarators();
         let end = self.lexer.end();
@@ -246,6 +297,7 @@ impl<'ast> Parser<'ast> {
     #[inline]
     pub fn variable_declarator(&mut self) -> Node<'ast, Declarator<'ast>> {
         let id = Pattern::parse(self);
+        let id = self.typed_pattern(id, false);
 
         let (init, end) = match self.lexer.token {
             OperatorAssign => {
@@ -454,6 +506,26 @@ impl<'ast> Parser<'ast> {
         })
     }
 
+    #[inline]
+    pub fn with_statement(&mut self) -> StatementNode<'ast> {
+        // `with` is not allowed in strict mode
+        if self.strict {
+            self.error::<()>();
+        }
+
+        let start = self.lexer.start_then_consume();
+        expect!(self, ParenOpen);
+        let object = self.expression::<ANY>();
+        expect!(self, ParenClose);
+
+        let body = self.statement();
+
+        self.alloc_at_loc(start, body.end, WithStatement {
+            object,
+            body,
+        })
+    }
+
     #[inline]
     pub fn do_statement(&mut self) -> StatementNode<'ast> {
         let start = self.lexer.start_then_consume();
@@ -472,6 +544,10 @@ impl<'ast> Parser<'ast> {
 
     #[inline]
     fn for_init(&mut self, kind: DeclarationKind) -> Node<'ast, ForInit<'ast>> {
+        if kind != DeclarationKind::Var {
+            self.require(Version::ES2015);
+        }
+
         let start = self.lexer.start_then_consume();
         let declarators = self.variable_declarators();
         let end = self.lexer.end();
@@ -521,6 +597,7 @@ impl<'ast> Parser<'ast> {
                     return self.for_in_statement(start, *init);
                 },
                 Identifier if self.lexer.token_as_str() == "of" => {
+                    self.require(Version::ES2015);
                     self.lexer.consume();
                     return self.for_of_statement(start, *init);
                 },
@@ -604,6 +681,338 @@ impl<'ast> Parser<'ast> {
         })
     }
 
+    pub fn import_statement(&mut self) -> StatementNode<'ast> {
+        if !self.is_module() {
+            self.error::<()>();
+        }
+
+        self.require(Version::ES2015);
+
+        let start = self.lexer.start_then_consume();
+
+        let kind = match self.lexer.token {
+            Identifier if self.flow && self.lexer.token_as_str() == "type" => {
+                let (type_start, type_end) = self.lexer.loc();
+
+                self.lexer.consume();
+
+                // `import type from 'a'` is a default import named `type`
+                if self.lexer.token == Identifier && self.lexer.token_as_str() == "from" {
+                    match self.lexer.peek_byte() {
+                        b'\'' | b'"' => {
+                            let specifier = self.alloc_at_loc(type_start, type_end, ImportSpecifier::Default("type"));
+                            let source = self.module_from();
+
+                            self.expect_semicolon();
+
+                            return self.alloc_at_loc(start, source.end, ImportStatement {
+                                kind: ImportKind::Value,
+                                specifiers: NodeList::from(self.arena, specifier),
+                                source,
+                            });
+                        },
+                        _ => {}
+                    }
+                }
+
+                ImportKind::Type
+            },
+            OperatorTypeof if self.flow => {
+                self.lexer.consume();
+
+                ImportKind::Typeof
+            },
+            _ => ImportKind::Value,
+        };
+
+        // Imported only for side effects, `import 'a';`
+        if kind == ImportKind::Value && self.lexer.token == LiteralString {
+            let source = self.module_source();
+
+            self.expect_semicolon();
+
+            return self.alloc_at_loc(start, source.end, ImportStatement {
+                kind,
+                specifiers: NodeList::empty(),
+                source,
+            });
+        }
+
+        let specifiers = self.import_specifiers();
+        let source = self.module_from();
+
+        self.expect_semicolon();
+
+        self.alloc_at_loc(start, source.end, ImportStatement {
+            kind,
+            specifiers,
+            source,
+        })
+    }
+
+    /// Parse the `from 'module'` clause of imports and exports.
+    #[inline]
+    fn module_from(&mut self) -> Node<'ast, Literal<'ast>> {
+        match self.lexer.token {
+            Identifier if self.lexer.token_as_str() == "from" => self.lexer.consume(),
+            _ => self.error()
+        }
+
+        self.module_source()
+    }
+
+    #[inline]
+    fn module_source(&mut self) -> Node<'ast, Literal<'ast>> {
+        match self.lexer.token {
+            LiteralString => {
+                let source = self.alloc_in_loc(Literal::String(self.lexer.token_as_str()));
+                self.lexer.consume();
+
+                source
+            },
+            _ => self.error()
+        }
+    }
+
+    fn import_specifiers(&mut self) -> NodeList<'ast, ImportSpecifier<'ast>> {
+        let mut specifiers = Vec::new();
+
+        if self.lexer.token == Identifier {
+            let local = self.identifier_str();
+            let specifier = self.alloc_in_loc(ImportSpecifier::Default(local));
+            self.lexer.consume();
+            specifiers.push(specifier);
+
+            match self.lexer.token {
+                Comma => self.lexer.consume(),
+                _     => return NodeList::from_iter(self.arena, specifiers),
+            }
+        }
+
+        match self.lexer.token {
+            OperatorMultiplication => {
+                let start = self.lexer.start_then_consume();
+
+                match self.lexer.token {
+                    Identifier if self.lexer.token_as_str() == "as" => self.lexer.consume(),
+                    _ => self.error()
+                }
+
+                let (local, end) = match self.lexer.token {
+                    Identifier => (self.identifier_str(), self.lexer.end_then_consume()),
+                    _          => (self.error(), start),
+                };
+
+                specifiers.push(self.alloc_at_loc(start, end, ImportSpecifier::Namespace(local)));
+            },
+            BraceOpen => {
+                self.lexer.consume();
+
+                while self.lexer.token != BraceClose {
+                    specifiers.push(self.named_import_specifier());
+
+                    match self.lexer.token {
+                        Comma => self.lexer.consume(),
+                        _     => break,
+                    }
+                }
+
+                expect!(self, BraceClose);
+            },
+            _ => self.error()
+        }
+
+        NodeList::from_iter(self.arena, specifiers)
+    }
+
+    #[inline]
+    fn named_import_specifier(&mut self) -> Node<'ast, ImportSpecifier<'ast>> {
+        let (start, mut end) = self.lexer.loc();
+
+        let imported = match self.lexer.token {
+            _ if self.lexer.token.is_word() => self.lexer.token_as_str(),
+            _ => self.error()
+        };
+
+        self.lexer.consume();
+
+        let local = match self.lexer.token {
+            Identifier if self.lexer.token_as_str() == "as" => {
+                self.lexer.consume();
+
+                match self.lexer.token {
+                    Identifier => {
+                        let local = self.identifier_str();
+                        end = self.lexer.end_then_consume();
+
+                        local
+                    },
+                    _ => self.error()
+                }
+            },
+            _ => {
+                if self.is_reserved(imported) {
+                    self.error::<()>();
+                }
+
+                imported
+            }
+        };
+
+        self.alloc_at_loc(start, end, ImportSpecifier::Named {
+            imported,
+            local,
+        })
+    }
+
+    pub fn export_statement(&mut self) -> StatementNode<'ast> {
+        if !self.is_module() {
+            self.error::<()>();
+        }
+
+        self.require(Version::ES2015);
+
+        let start = self.lexer.start_then_consume();
+
+        let (export, end) = match self.lexer.token {
+            Default => {
+                self.lexer.consume();
+
+                let expression = match self.lexer.token {
+                    Function => self.function_expression(),
+                    Class    => self.class_expression(),
+                    _        => {
+                        let expression = self.expression::<B0>();
+
+                        self.expect_semicolon();
+
+                        expression
+                    }
+                };
+
+                (ExportStatement::Default(expression), expression.end)
+            },
+            OperatorMultiplication => {
+                self.lexer.consume();
+
+                let source = self.module_from();
+
+                self.expect_semicolon();
+
+                (ExportStatement::All { source }, source.end)
+            },
+            BraceOpen => self.export_named(ExportKind::Value),
+            DeclarationVar   |
+            DeclarationLet   |
+            DeclarationConst |
+            Function         |
+            Class            => {
+                let declaration = self.statement();
+
+                (ExportStatement::Declaration(declaration), declaration.end)
+            },
+            // `export type { A, B };`
+            Identifier if self.flow && self.lexer.token_as_str() == "type" && self.lexer.peek_byte() == b'{' => {
+                self.lexer.consume();
+                self.export_named(ExportKind::Type)
+            },
+            // `export type A = B;` and `export opaque type A = B;`
+            Identifier if self.flow => {
+                let declaration = self.statement();
+
+                match declaration.item {
+                    Statement::TypeAlias(_) => {},
+                    _ => self.error()
+                }
+
+                (ExportStatement::Declaration(declaration), declaration.end)
+            },
+            _ => {
+                self.error::<()>();
+
+                (ExportStatement::Named {
+                    kind: ExportKind::Value,
+                    specifiers: NodeList::empty(),
+                    source: None,
+                }, start)
+            }
+        };
+
+        self.alloc_at_loc(start, end, export)
+    }
+
+    /// Parse the specifiers of a named export, starting at the `{`.
+    fn export_named(&mut self, kind: ExportKind) -> (ExportStatement<'ast>, u32) {
+        self.lexer.consume();
+
+        let mut specifiers = Vec::new();
+
+        while self.lexer.token != BraceClose {
+            specifiers.push(self.export_specifier());
+
+            match self.lexer.token {
+                Comma => self.lexer.consume(),
+                _     => break,
+            }
+        }
+
+        let mut end = self.lexer.end();
+        expect!(self, BraceClose);
+
+        let source = match self.lexer.token {
+            Identifier if self.lexer.token_as_str() == "from" => {
+                let source = self.module_from();
+
+                end = source.end;
+
+                Some(source)
+            },
+            _ => None
+        };
+
+        self.expect_semicolon();
+
+        (ExportStatement::Named {
+            kind,
+            specifiers: NodeList::from_iter(self.arena, specifiers),
+            source,
+        }, end)
+    }
+
+    #[inline]
+    fn export_specifier(&mut self) -> Node<'ast, ExportSpecifier<'ast>> {
+        let (start, mut end) = self.lexer.loc();
+
+        let local = match self.lexer.token {
+            _ if self.lexer.token.is_word() => self.lexer.token_as_str(),
+            _ => self.error()
+        };
+
+        self.lexer.consume();
+
+        let exported = match self.lexer.token {
+            Identifier if self.lexer.token_as_str() == "as" => {
+                self.lexer.consume();
+
+                match self.lexer.token {
+                    _ if self.lexer.token.is_word() => {
+                        let exported = self.lexer.token_as_str();
+                        end = self.lexer.end_then_consume();
+
+                        exported
+                    },
+                    _ => self.error()
+                }
+            },
+            _ => local
+        };
+
+        self.alloc_at_loc(start, end, ExportSpecifier {
+            local,
+            exported,
+        })
+    }
+
     fn switch_statement(&mut self) -> StatementNode<'ast> {
         let start = self.lexer.start_then_consume();
         expect!(self, ParenOpen);
@@ -624,10 +1033,16 @@ impl<'ast> Parser<'ast> {
 #[cfg(test)]
 mod test {
     use super::*;
-    use parser::parse;
+    use parser::{parse, parse_with_options, ParseOptions, Goal};
     use parser::mock::Mock;
     use ast::{NodeList, Literal, Function, Class, OperatorKind, BlockStatement};
     use ast::expression::*;
+    use module::Module;
+    use error::Error;
+
+    fn parse_module<'ast>(source: &str) -> Result<Module<'ast>, Vec<Error>> {
+        parse_with_options(source, ParseOptions { goal: Goal::Module, ..ParseOptions::default() })
+    }
 
     #[test]
     fn block_statement() {
@@ -734,6 +1149,21 @@ mod test {
         assert_eq!(parse(src).unwrap().body(), expected);
     }
 
+    #[test]
+    fn with_statement() {
+        let src = "with (foo) bar;";
+        let mock = Mock::new();
+
+        let expected = mock.list([
+            WithStatement {
+                object: mock.ptr("foo"),
+                body: mock.ptr(mock.ptr("bar"))
+            }
+        ]);
+
+        assert_eq!(parse(src).unwrap().body(), expected);
+    }
+
     #[test]
     fn do_statement() {
         let src = "do foo; while (true)";
@@ -1132,7 +1562,9 @@ mod test {
         let expected = mock.list([
             Function {
                 name: mock.name("foo"),
+                type_params: NodeList::empty(),
                 params: NodeList::empty(),
+                return_type: None,
                 body: mock.empty_block(),
             }
         ]);
@@ -1153,7 +1585,10 @@ mod test {
         let expected = mock.list([
             Class {
                 name: mock.name("Foo"),
+                type_params: NodeList::empty(),
                 extends: None,
+                super_type_args: NodeList::empty(),
+                implements: NodeList::empty(),
                 body: mock.empty_block(),
             }
         ]);
@@ -1213,4 +1648,97 @@ mod test {
         ]);
         assert_eq!(parse(src).unwrap().body(), expected);
     }
+
+    #[test]
+    fn import_statement() {
+        let src = "import foo, * as bar from 'foo'; import { baz as qux } from 'baz'; import 'side-effect';";
+        let mock = Mock::new();
+
+        let expected = mock.list([
+            ImportStatement {
+                kind: ImportKind::Value,
+                specifiers: mock.list([
+                    ImportSpecifier::Default("foo"),
+                    ImportSpecifier::Namespace("bar"),
+                ]),
+                source: mock.ptr(Literal::String("'foo'")),
+            },
+            ImportStatement {
+                kind: ImportKind::Value,
+                specifiers: mock.list([
+                    ImportSpecifier::Named {
+                        imported: "baz",
+                        local: "qux",
+                    },
+                ]),
+                source: mock.ptr(Literal::String("'baz'")),
+            },
+            ImportStatement {
+                kind: ImportKind::Value,
+                specifiers: NodeList::empty(),
+                source: mock.ptr(Literal::String("'side-effect'")),
+            },
+        ]);
+
+        assert_eq!(parse_module(src).unwrap().body(), expected);
+    }
+
+    #[test]
+    fn export_statement() {
+        let src = "export { foo, bar as baz }; export * from 'qux'; export default foo; export const a = 1;";
+        let mock = Mock::new();
+
+        let expected = mock.list([
+            Statement::from(ExportStatement::Named {
+                kind: ExportKind::Value,
+                specifiers: mock.list([
+                    ExportSpecifier {
+                        local: "foo",
+                        exported: "foo",
+                    },
+                    ExportSpecifier {
+                        local: "bar",
+                        exported: "baz",
+                    },
+                ]),
+                source: None,
+            }),
+            Statement::from(ExportStatement::All {
+                source: mock.ptr(Literal::String("'qux'")),
+            }),
+            Statement::from(ExportStatement::Default(mock.ptr("foo"))),
+            Statement::from(ExportStatement::Declaration(mock.ptr(DeclarationStatement {
+                kind: DeclarationKind::Const,
+                declarators: mock.list([
+                    Declarator {
+                        id: mock.ptr(Pattern::Identifier("a")),
+                        init: Some(mock.number("1")),
+                    }
+                ]),
+            }))),
+        ]);
+
+        assert_eq!(parse_module(src).unwrap().body(), expected);
+    }
+
+    #[test]
+    fn export_named_from() {
+        let src = "export { default as foo } from 'foo';";
+        let mock = Mock::new();
+
+        let expected = mock.list([
+            ExportStatement::Named {
+                kind: ExportKind::Value,
+                specifiers: mock.list([
+                    ExportSpecifier {
+                        local: "default",
+                        exported: "foo",
+                    },
+                ]),
+                source: Some(mock.ptr(Literal::String("'foo'"))),
+            },
+        ]);
+
+        assert_eq!(parse_module(src).unwrap().body(), expected);
+    }
 }
@@ -0,0 +1,1359 @@
+use toolshed::list::ListBuilder;
+use parser::Parser;
+use lexer::Token::*;
+use lexer::Asi;
+use ast::{Node, NodeList, Statement, StatementNode, ExpressionNode, DeclarationKind};
+use ast::{Type, TypeNode, TypeList, Primitive, TypeParameter, TypeParameterList};
+use ast::types::{FunctionType, FunctionTypeParam, ObjectTypeMember};
+use ast::statement::{TypeAliasStatement, DeclareStatement};
+use ast::expression::TypeCastExpression;
+
+
+type FunctionTypeParams<'ast> = (
+    NodeList<'ast, FunctionTypeParam<'ast>>,
+    Option<Node<'ast, FunctionTypeParam<'ast>>>
+);
+
+impl<'ast> Parser<'ast> {
+    /// Parse a colon followed by a Flow type.
+    #[inline]
+    pub fn type_annotation(&mut self) -> TypeNode<'ast> {
+        expect!(self, Colon);
+
+        self.flow_type()
+    }
+
+    /// Parse a Flow type, including unions and intersections.
+    pub fn flow_type(&mut self) -> TypeNode<'ast> {
+        let start = self.lexer.start();
+
+        // Leading `|` is allowed for multiline unions
+        allow!(self, OperatorBitwiseOr => {});
+
+        let first = self.intersection_type();
+
+        // `|}` closes an exact object type
+        if self.lexer.token != OperatorBitwiseOr || self.lexer.peek_byte() == b'}' {
+            return first;
+        }
+
+        let builder = ListBuilder::new(self.arena, first);
+        let mut end = first.end;
+
+        while self.lexer.token == OperatorBitwiseOr && self.lexer.peek_byte() != b'}' {
+            self.lexer.consume();
+
+            let variant = self.intersection_type();
+
+            end = variant.end;
+            builder.push(self.arena, variant);
+        }
+
+        self.alloc_at_loc(start, end, Type::Union {
+            variants: builder.as_list()
+        })
+    }
+
+    #[inline]
+    fn intersection_type(&mut self) -> TypeNode<'ast> {
+        let first = self.prefix_type();
+
+        if self.lexer.token != OperatorBitwiseAnd {
+            return first;
+        }
+
+        let builder = ListBuilder::new(self.arena, first);
+        let mut end = first.end;
+
+        while self.lexer.token == OperatorBitwiseAnd {
+            self.lexer.consume();
+
+            let variant = self.prefix_type();
+
+            end = variant.end;
+            builder.push(self.arena, variant);
+        }
+
+        self.alloc_at_loc(first.start, end, Type::Intersection {
+            variants: builder.as_list()
+        })
+    }
+
+    #[inline]
+    fn prefix_type(&mut self) -> TypeNode<'ast> {
+        match self.lexer.token {
+            OperatorConditional => {
+                let start = self.lexer.start_then_consume();
+                let inner = self.prefix_type();
+
+                self.alloc_at_loc(start, inner.end, Type::Nullable(inner))
+            },
+            _ => self.postfix_type()
+        }
+    }
+
+    #[inline]
+    fn postfix_type(&mut self) -> TypeNode<'ast> {
+        let mut ty = self.primary_type();
+
+        loop {
+            match (self.lexer.token, self.asi()) {
+                (BracketOpen, Asi::NoSemicolon) => {
+                    self.lexer.consume();
+                    let end = self.lexer.end();
+                    expect!(self, BracketClose);
+
+                    ty = self.alloc_at_loc(ty.start, end, Type::Array(ty));
+                },
+                _ => return ty
+            }
+        }
+    }
+
+    fn primary_type(&mut self) -> TypeNode<'ast> {
+        let (start, end) = self.lexer.loc();
+
+        let ty = match self.lexer.token {
+            Identifier => match self.lexer.token_as_str() {
+                "any"               => Type::Any,
+                "mixed"             => Type::Mixed,
+                "empty"             => Type::Empty,
+                "number"            => Primitive::Number.into(),
+                "string"            => Primitive::String.into(),
+                "boolean" | "bool"  => Primitive::Boolean.into(),
+                "symbol"            => Primitive::Symbol.into(),
+                _                   => return self.generic_type(),
+            },
+            OperatorVoid           => Primitive::Void.into(),
+            LiteralNull            => Primitive::Null.into(),
+            LiteralTrue            => Type::BooleanLiteral(true),
+            LiteralFalse           => Type::BooleanLiteral(false),
+            LiteralString          => Type::StringLiteral(self.lexer.token_as_str()),
+            LiteralNumber          => Type::NumberLiteral(self.lexer.token_as_str()),
+            OperatorMultiplication => Type::Existential,
+            OperatorSubtraction    => {
+                self.lexer.consume();
+
+                let end = self.lexer.end();
+                let number = self.lexer.slice_source(start as usize, end as usize);
+
+                expect!(self, LiteralNumber);
+
+                return self.alloc_at_loc(start, end, Type::NumberLiteral(number));
+            },
+            OperatorTypeof => {
+                self.lexer.consume();
+
+                let (name, end) = self.qualified_name();
+
+                return self.alloc_at_loc(start, end, Type::Typeof(name));
+            },
+            BraceOpen      => return self.object_type(),
+            BracketOpen    => return self.tuple_type(),
+            ParenOpen      => return self.paren_or_function_type(),
+            OperatorLesser => return self.generic_function_type(),
+            _ if self.lexer.token.is_word() => return self.generic_type(),
+            _ => return self.error()
+        };
+
+        self.lexer.consume();
+
+        self.alloc_at_loc(start, end, ty)
+    }
+
+    /// Parse a possibly qualified name, such as `React.Node`.
+    fn qualified_name(&mut self) -> (&'ast str, u32) {
+        let start = self.lexer.start();
+        let mut end = self.lexer.end();
+
+        if !self.lexer.token.is_word() {
+            self.error::<()>();
+
+            return ("", end);
+        }
+
+        self.lexer.consume();
+
+        while self.lexer.token == Accessor {
+            end = self.lexer.end();
+            self.lexer.consume();
+        }
+
+        (self.lexer.slice_source(start as usize, end as usize), end)
+    }
+
+    /// Parse a type reference with optional type arguments, such as `Array<T>`.
+    pub fn generic_type(&mut self) -> TypeNode<'ast> {
+        let start = self.lexer.start();
+        let (ident, end) = self.qualified_name();
+
+        if self.lexer.token != OperatorLesser {
+            return self.alloc_at_loc(start, end, Type::Identifier(ident));
+        }
+
+        let (subtypes, end) = self.type_args();
+
+        self.alloc_at_loc(start, end, Type::Generic {
+            ident,
+            subtypes,
+        })
+    }
+
+    /// Consume the closing `>` of type parameters or arguments,
+    /// returning its end position.
+    #[inline]
+    fn close_angle(&mut self) -> u32 {
+        match self.lexer.token {
+            OperatorGreater => self.lexer.end_then_consume(),
+            OperatorBitShiftRight |
+            OperatorUBitShiftRight => self.lexer.split_greater(),
+            _ => {
+                let end = self.lexer.end();
+                self.error::<()>();
+
+                end
+            }
+        }
+    }
+
+    /// Parse type arguments, such as `<string, number>`.
+    pub fn type_args(&mut self) -> (TypeList<'ast>, u32) {
+        expect!(self, OperatorLesser);
+
+        let builder = ListBuilder::new(self.arena, self.flow_type());
+
+        while self.lexer.token == Comma {
+            self.lexer.consume();
+
+            match self.lexer.token {
+                OperatorGreater        |
+                OperatorBitShiftRight  |
+                OperatorUBitShiftRight |
+                EndOfProgram           => break,
+                _ => builder.push(self.arena, self.flow_type())
+            }
+        }
+
+        let end = self.close_angle();
+
+        (builder.as_list(), end)
+    }
+
+    /// Parse type parameters, such as `<T, U: Bound = Default>`. Returns
+    /// an empty list if the current token isn't `<` or Flow isn't enabled.
+    pub fn type_params(&mut self) -> TypeParameterList<'ast> {
+        if !self.flow || self.lexer.token != OperatorLesser {
+            return NodeList::empty();
+        }
+
+        self.lexer.consume();
+
+        let builder = ListBuilder::new(self.arena, self.type_param());
+
+        while self.lexer.token == Comma {
+            self.lexer.consume();
+
+            match self.lexer.token {
+                OperatorGreater        |
+                OperatorBitShiftRight  |
+                OperatorUBitShiftRight |
+                EndOfProgram           => break,
+                _ => builder.push(self.arena, self.type_param())
+            }
+        }
+
+        self.close_angle();
+
+        builder.as_list()
+    }
+
+    #[inline]
+    fn type_param(&mut self) -> Node<'ast, TypeParameter<'ast>> {
+        let start = self.lexer.start();
+
+        // Variance sigils are accepted, but not kept in the AST
+        match self.lexer.token {
+            OperatorAddition | OperatorSubtraction => self.lexer.consume(),
+            _ => {}
+        }
+
+        let mut end = self.lexer.end();
+        let name = match self.lexer.token {
+            Identifier => {
+                let name = self.lexer.token_as_str();
+                self.lexer.consume();
+
+                name
+            },
+            _ => {
+                self.error::<()>();
+
+                ""
+            }
+        };
+
+        let bound = match self.lexer.token {
+            Colon => {
+                let bound = self.type_annotation();
+                end = bound.end;
+
+                Some(bound)
+            },
+            _ => None
+        };
+
+        let default = match self.lexer.token {
+            OperatorAssign => {
+                self.lexer.consume();
+
+                let default = self.flow_type();
+                end = default.end;
+
+                Some(default)
+            },
+            _ => None
+        };
+
+        self.alloc_at_loc(start, end, TypeParameter {
+            name,
+            bound,
+            default,
+        })
+    }
+
+    fn object_type(&mut self) -> TypeNode<'ast> {
+        let start = self.lexer.start();
+
+        expect!(self, BraceOpen);
+
+        let exact = match self.lexer.token {
+            OperatorBitwiseOr => {
+                self.lexer.consume();
+
+                true
+            },
+            // `{||}`
+            OperatorLogicalOr => {
+                self.lexer.consume();
+
+                let end = self.lexer.end();
+                expect!(self, BraceClose);
+
+                return self.alloc_at_loc(start, end, Type::Object {
+                    exact: true,
+                    members: NodeList::empty(),
+                });
+            },
+            _ => false
+        };
+
+        let empty = match self.lexer.token {
+            BraceClose        => !exact,
+            OperatorBitwiseOr => exact,
+            _                 => false,
+        };
+
+        if empty {
+            if exact {
+                self.lexer.consume();
+            }
+
+            let end = self.lexer.end();
+            expect!(self, BraceClose);
+
+            return self.alloc_at_loc(start, end, Type::Object {
+                exact,
+                members: NodeList::empty(),
+            });
+        }
+
+        let builder = ListBuilder::new(self.arena, self.object_type_member());
+
+        loop {
+            match self.lexer.token {
+                Comma | Semicolon => self.lexer.consume(),
+                _                 => {}
+            }
+
+            match self.lexer.token {
+                BraceClose if !exact => break,
+                OperatorBitwiseOr if exact => {
+                    self.lexer.consume();
+
+                    break;
+                },
+                EndOfProgram => break,
+                _ => builder.push(self.arena, self.object_type_member())
+            }
+        }
+
+        let end = self.lexer.end();
+        expect!(self, BraceClose);
+
+        self.alloc_at_loc(start, end, Type::Object {
+            exact,
+            members: builder.as_list(),
+        })
+    }
+
+    fn object_type_member(&mut self) -> Node<'ast, ObjectTypeMember<'ast>> {
+        let start = self.lexer.start();
+
+        match self.lexer.token {
+            OperatorSpread => {
+                self.lexer.consume();
+
+                let value = self.flow_type();
+
+                return self.alloc_at_loc(start, value.end, ObjectTypeMember::Spread(value));
+            },
+            // Variance sigils are accepted, but not kept in the AST
+            OperatorAddition | OperatorSubtraction => self.lexer.consume(),
+            _ => {}
+        }
+
+        if self.lexer.token == BracketOpen {
+            self.lexer.consume();
+
+            let first = self.flow_type();
+
+            let (name, key) = match (self.lexer.token, first.item) {
+                (Colon, Type::Identifier(name)) => {
+                    self.lexer.consume();
+
+                    (Some(self.alloc_at_loc(first.start, first.end, name)), self.flow_type())
+                },
+                _ => (None, first)
+            };
+
+            expect!(self, BracketClose);
+
+            let value = self.type_annotation();
+
+            return self.alloc_at_loc(start, value.end, ObjectTypeMember::Indexer {
+                name,
+                key,
+                value,
+            });
+        }
+
+        let key = match self.lexer.token {
+            LiteralString | LiteralNumber => self.lexer.token_as_str(),
+            _ if self.lexer.token.is_word() => self.lexer.token_as_str(),
+            _ => {
+                self.error::<()>();
+
+                ""
+            }
+        };
+
+        self.lexer.consume();
+
+        match self.lexer.token {
+            ParenOpen | OperatorLesser => {
+                let value = self.method_type();
+
+                return self.alloc_at_loc(start, value.end, ObjectTypeMember::Property {
+                    key,
+                    optional: false,
+                    method: true,
+                    value,
+                });
+            },
+            _ => {}
+        }
+
+        let optional = match self.lexer.token {
+            OperatorConditional => {
+                self.lexer.consume();
+
+                true
+            },
+            _ => false
+        };
+
+        let value = self.type_annotation();
+
+        self.alloc_at_loc(start, value.end, ObjectTypeMember::Property {
+            key,
+            optional,
+            method: false,
+            value,
+        })
+    }
+
+    fn tuple_type(&mut self) -> TypeNode<'ast> {
+        let start = self.lexer.start_then_consume();
+
+        if self.lexer.token == BracketClose {
+            let end = self.lexer.end_then_consume();
+
+            return self.alloc_at_loc(start, end, Type::Tuple {
+                elements: NodeList::empty()
+            });
+        }
+
+        let builder = ListBuilder::new(self.arena, self.flow_type());
+
+        while self.lexer.token == Comma {
+            self.lexer.consume();
+
+            match self.lexer.token {
+                BracketClose | EndOfProgram => break,
+                _ => builder.push(self.arena, self.flow_type())
+            }
+        }
+
+        let end = self.lexer.end();
+        expect!(self, BracketClose);
+
+        self.alloc_at_loc(start, end, Type::Tuple {
+            elements: builder.as_list()
+        })
+    }
+
+    fn function_type_params(&mut self) -> FunctionTypeParams<'ast> {
+        expect!(self, ParenOpen);
+
+        let builder = match self.lexer.token {
+            ParenClose => {
+                self.lexer.consume();
+
+                return (NodeList::empty(), None);
+            },
+            OperatorSpread => {
+                self.lexer.consume();
+
+                let rest = Some(self.function_type_param());
+                expect!(self, ParenClose);
+
+                return (NodeList::empty(), rest);
+            },
+            _ => ListBuilder::new(self.arena, self.function_type_param())
+        };
+
+        let mut rest = None;
+
+        while self.lexer.token == Comma {
+            self.lexer.consume();
+
+            match self.lexer.token {
+                ParenClose | EndOfProgram => break,
+                OperatorSpread => {
+                    self.lexer.consume();
+                    rest = Some(self.function_type_param());
+
+                    break;
+                },
+                _ => builder.push(self.arena, self.function_type_param())
+            }
+        }
+
+        expect!(self, ParenClose);
+
+        (builder.as_list(), rest)
+    }
+
+    #[inline]
+    fn function_type_param(&mut self) -> Node<'ast, FunctionTypeParam<'ast>> {
+        let ty = self.flow_type();
+
+        match self.lexer.token {
+            Colon | OperatorConditional => {
+                let name = match ty.item {
+                    Type::Identifier(name) => self.alloc_at_loc(ty.start, ty.end, name),
+                    _                      => self.error()
+                };
+
+                let optional = match self.lexer.token {
+                    OperatorConditional => {
+                        self.lexer.consume();
+
+                        true
+                    },
+                    _ => false
+                };
+
+                let annotation = self.type_annotation();
+
+                self.alloc_at_loc(ty.start, annotation.end, FunctionTypeParam {
+                    name: Some(name),
+                    optional,
+                    annotation,
+                })
+            },
+            _ => self.alloc_at_loc(ty.start, ty.end, FunctionTypeParam {
+                name: None,
+                optional: false,
+                annotation: ty,
+            })
+        }
+    }
+
+    /// Parse a function type, `(T) => U`, or a type wrapped in parentheses.
+    fn paren_or_function_type(&mut self) -> TypeNode<'ast> {
+        let start = self.lexer.start();
+        let (params, rest) = self.function_type_params();
+
+        if self.lexer.token == OperatorFatArrow {
+            self.lexer.consume();
+
+            let return_type = self.flow_type();
+
+            return self.alloc_at_loc(start, return_type.end, FunctionType {
+                type_params: NodeList::empty(),
+                params,
+                rest,
+                return_type,
+            });
+        }
+
+        match (params.only_element(), rest) {
+            (Some(param), None) if param.name.is_none() => param.annotation,
+            _ => self.error()
+        }
+    }
+
+    /// Parse a function type with type parameters, `<T>(T) => T`.
+    fn generic_function_type(&mut self) -> TypeNode<'ast> {
+        let start = self.lexer.start();
+        let type_params = self.type_params();
+        let (params, rest) = self.function_type_params();
+
+        expect!(self, OperatorFatArrow);
+
+        let return_type = self.flow_type();
+
+        self.alloc_at_loc(start, return_type.end, FunctionType {
+            type_params,
+            params,
+            rest,
+            return_type,
+        })
+    }
+
+    /// Parse a function type with the return type following a colon, as used
+    /// by methods in object types and `declare function`.
+    fn method_type(&mut self) -> TypeNode<'ast> {
+        let start = self.lexer.start();
+        let type_params = self.type_params();
+        let (params, rest) = self.function_type_params();
+        let return_type = self.type_annotation();
+
+        self.alloc_at_loc(start, return_type.end, FunctionType {
+            type_params,
+            params,
+            rest,
+            return_type,
+        })
+    }
+
+    /// Wrap the expression in a type cast if followed by a colon.
+    #[inline]
+    pub fn type_cast(&mut self, expression: ExpressionNode<'ast>) -> ExpressionNode<'ast> {
+        match self.lexer.token {
+            Colon => {
+                let annotation = self.type_annotation();
+
+                self.alloc_at_loc(expression.start, annotation.end, TypeCastExpression {
+                    expression,
+                    annotation,
+                })
+            },
+            _ => expression
+        }
+    }
+
+    /// Parse the return type of an arrow function, `(a): number => a`. The colon
+    /// can also belong to a conditional, `a ? (b) : c`, so unless the type is
+    /// followed by `=>` the lexer is rewound to the colon.
+    pub fn arrow_return_type(&mut self) -> Option<TypeNode<'ast>> {
+        if !self.flow || self.lexer.token != Colon {
+            return None;
+        }
+
+        let lexer = self.lexer.clone();
+        let errors = self.errors.len();
+        let return_type = self.type_annotation();
+
+        if self.lexer.token == OperatorFatArrow && self.errors.len() == errors {
+            return Some(return_type);
+        }
+
+        self.lexer = lexer;
+        self.errors.truncate(errors);
+
+        None
+    }
+
+    /// Parse a type alias, starting at the name.
+    fn type_alias(&mut self, opaque: bool, declare: bool) -> (TypeAliasStatement<'ast>, u32) {
+        let name = self.identifier();
+        let type_params = self.type_params();
+        let mut end = name.end;
+
+        let supertype = match self.lexer.token {
+            Colon if opaque => {
+                let supertype = self.type_annotation();
+                end = supertype.end;
+
+                Some(supertype)
+            },
+            _ => None
+        };
+
+        let value = match self.lexer.token {
+            OperatorAssign => {
+                self.lexer.consume();
+
+                let value = self.flow_type();
+                end = value.end;
+
+                Some(value)
+            },
+            _ if opaque && declare => None,
+            _ => {
+                self.error::<()>();
+
+                None
+            }
+        };
+
+        let alias = TypeAliasStatement {
+            opaque,
+            name,
+            type_params,
+            supertype,
+            value,
+        };
+
+        (alias, end)
+    }
+
+    /// Parse `type Foo = Bar;` or `opaque type Foo = Bar;`, starting at the name.
+    pub fn type_alias_statement(&mut self, start: u32, opaque: bool) -> StatementNode<'ast> {
+        let (alias, end) = self.type_alias(opaque, false);
+
+        self.expect_semicolon();
+
+        self.alloc_at_loc(start, end, alias)
+    }
+
+    /// Parse a `declare` statement, starting after the `declare` keyword.
+    pub fn declare_statement(&mut self, start: u32) -> StatementNode<'ast> {
+        let (declaration, end) = match self.lexer.token {
+            DeclarationVar | DeclarationLet | DeclarationConst => {
+                let kind = match self.lexer.token {
+                    DeclarationVar => DeclarationKind::Var,
+                    DeclarationLet => DeclarationKind::Let,
+                    _              => DeclarationKind::Const,
+                };
+
+                self.lexer.consume();
+
+                let name = self.identifier();
+                let annotation = match self.lexer.token {
+                    Colon => Some(self.type_annotation()),
+                    _     => None
+                };
+                let end = annotation.map(|annotation| annotation.end).unwrap_or(name.end);
+
+                (DeclareStatement::Variable { kind, name, annotation }, end)
+            },
+            Function => {
+                self.lexer.consume();
+
+                let name = self.identifier();
+                let annotation = self.method_type();
+
+                (DeclareStatement::Function { name, annotation }, annotation.end)
+            },
+            Class => {
+                self.lexer.consume();
+
+                let name = self.identifier();
+                let type_params = self.type_params();
+                let extends = match self.lexer.token {
+                    Extends => {
+                        self.lexer.consume();
+
+                        Some(self.generic_type())
+                    },
+                    _ => None
+                };
+                let body = self.object_type();
+
+                let declaration = DeclareStatement::Class {
+                    name,
+                    type_params,
+                    extends,
+                    body,
+                };
+
+                // No semicolon after class body
+                return self.alloc_at_loc(start, body.end, declaration);
+            },
+            Identifier if self.lexer.token_as_str() == "type" => {
+                self.lexer.consume();
+
+                let (alias, end) = self.type_alias(false, true);
+
+                (DeclareStatement::TypeAlias(alias), end)
+            },
+            Identifier if self.lexer.token_as_str() == "opaque" => {
+                self.lexer.consume();
+
+                match self.lexer.token {
+                    Identifier if self.lexer.token_as_str() == "type" => self.lexer.consume(),
+                    _ => self.error()
+                }
+
+                let (alias, end) = self.type_alias(true, true);
+
+                (DeclareStatement::TypeAlias(alias), end)
+            },
+            _ => {
+                self.error::<()>();
+
+                return self.alloc_at_loc(start, start, Statement::Empty);
+            }
+        };
+
+        self.expect_semicolon();
+
+        self.alloc_at_loc(start, end, declaration)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use parser::{parse, parse_with_options, ParseOptions, Goal};
+    use parser::mock::Mock;
+    use ast::{NodeList, Pattern, Function, Class, ClassMember, PropertyKey};
+    use ast::{DeclarationKind, Declarator, Type, Primitive, TypeParameter, Literal, OperatorKind};
+    use ast::types::{FunctionType, FunctionTypeParam, ObjectTypeMember};
+    use ast::statement::*;
+    use ast::expression::*;
+    use module::Module;
+    use error::Error;
+
+    fn parse_flow<'ast>(source: &str) -> Result<Module<'ast>, Vec<Error>> {
+        parse_with_options(source, ParseOptions { goal: Goal::Module, flow: true, ..ParseOptions::default() })
+    }
+
+    #[test]
+    fn flow_requires_pragma() {
+        assert!(parse("let foo: number = 1;").is_err());
+        assert!(parse("// @flow\nlet foo: number = 1;").is_ok());
+        assert!(parse("#!/usr/bin/env node\n// @flow\nvar a: number = 1;").is_ok());
+        assert!(parse_flow("let foo: number = 1;").is_ok());
+    }
+
+    #[test]
+    fn flow_declarator_annotation() {
+        let src = "let foo: ?Array<string> = bar;";
+        let mock = Mock::new();
+
+        let expected = mock.list([
+            DeclarationStatement {
+                kind: DeclarationKind::Let,
+                declarators: mock.list([
+                    Declarator {
+                        id: mock.ptr(Pattern::Typed {
+                            pattern: mock.ptr(Pattern::Identifier("foo")),
+                            optional: false,
+                            annotation: mock.ptr(Type::Nullable(mock.ptr(Type::Generic {
+                                ident: "Array",
+                                subtypes: mock.list([
+                                    Type::Primitive(Primitive::String)
+                                ]),
+                            }))),
+                        }),
+                        init: Some(mock.ptr("bar")),
+                    }
+                ])
+            }
+        ]);
+
+        assert_eq!(parse_flow(src).unwrap().body(), expected);
+    }
+
+    #[test]
+    fn flow_function_annotations() {
+        let src = "function foo<T>(bar: T, baz?: number = 1, ...rest: Array<T>): T {}";
+        let mock = Mock::new();
+
+        let expected = mock.list([
+            Function {
+                name: mock.name("foo"),
+                type_params: mock.list([
+                    TypeParameter {
+                        name: "T",
+                        bound: None,
+                        default: None,
+                    }
+                ]),
+                params: mock.list([
+                    Pattern::Typed {
+                        pattern: mock.ptr(Pattern::Identifier("bar")),
+                        optional: false,
+                        annotation: mock.ptr(Type::Identifier("T")),
+                    },
+                    Pattern::AssignmentPattern {
+                        left: mock.ptr(Pattern::Typed {
+                            pattern: mock.ptr(Pattern::Identifier("baz")),
+                            optional: true,
+                            annotation: mock.ptr(Type::Primitive(Primitive::Number)),
+                        }),
+                        right: mock.number("1"),
+                    },
+                    Pattern::Typed {
+                        pattern: mock.ptr(Pattern::RestElement {
+                            argument: mock.ptr("rest"),
+                        }),
+                        optional: false,
+                        annotation: mock.ptr(Type::Generic {
+                            ident: "Array",
+                            subtypes: mock.list([
+                                Type::Identifier("T")
+                            ]),
+                        }),
+                    },
+                ]),
+                return_type: Some(mock.ptr(Type::Identifier("T"))),
+                body: mock.empty_block(),
+            }
+        ]);
+
+        assert_eq!(parse_flow(src).unwrap().body(), expected);
+    }
+
+    #[test]
+    fn flow_arrow_params_and_type_cast() {
+        let src = "(a: number, b) => (a: any);";
+        let mock = Mock::new();
+
+        let expected = mock.list([
+            Statement::Expression(mock.ptr(ArrowExpression {
+                type_params: NodeList::empty(),
+                params: mock.list([
+                    Pattern::Typed {
+                        pattern: mock.ptr(Pattern::Identifier("a")),
+                        optional: false,
+                        annotation: mock.ptr(Type::Primitive(Primitive::Number)),
+                    },
+                    Pattern::Identifier("b"),
+                ]),
+                return_type: None,
+                body: ArrowBody::Expression(mock.ptr(TypeCastExpression {
+                    expression: mock.ptr("a"),
+                    annotation: mock.ptr(Type::Any),
+                })),
+            }))
+        ]);
+
+        assert_eq!(parse_flow(src).unwrap().body(), expected);
+    }
+
+    #[test]
+    fn flow_arrow_return_type() {
+        let src = "(a: number, b: string): boolean => a > b; (): void => {};";
+        let mock = Mock::new();
+
+        let expected = mock.list([
+            Statement::Expression(mock.ptr(ArrowExpression {
+                type_params: NodeList::empty(),
+                params: mock.list([
+                    Pattern::Typed {
+                        pattern: mock.ptr(Pattern::Identifier("a")),
+                        optional: false,
+                        annotation: mock.ptr(Type::Primitive(Primitive::Number)),
+                    },
+                    Pattern::Typed {
+                        pattern: mock.ptr(Pattern::Identifier("b")),
+                        optional: false,
+                        annotation: mock.ptr(Type::Primitive(Primitive::String)),
+                    },
+                ]),
+                return_type: Some(mock.ptr(Type::Primitive(Primitive::Boolean))),
+                body: ArrowBody::Expression(mock.ptr(BinaryExpression {
+                    operator: OperatorKind::Greater,
+                    left: mock.ptr("a"),
+                    right: mock.ptr("b"),
+                })),
+            })),
+            Statement::Expression(mock.ptr(ArrowExpression {
+                type_params: NodeList::empty(),
+                params: NodeList::empty(),
+                return_type: Some(mock.ptr(Type::Primitive(Primitive::Void))),
+                body: ArrowBody::Block(mock.empty_block()),
+            })),
+        ]);
+
+        assert_eq!(parse_flow(src).unwrap().body(), expected);
+    }
+
+    #[test]
+    fn flow_generic_arrow() {
+        let src = "<T>(a: T): T => a;";
+        let mock = Mock::new();
+
+        let expected = mock.list([
+            Statement::Expression(mock.ptr(ArrowExpression {
+                type_params: mock.list([
+                    TypeParameter {
+                        name: "T",
+                        bound: None,
+                        default: None,
+                    }
+                ]),
+                params: mock.list([
+                    Pattern::Typed {
+                        pattern: mock.ptr(Pattern::Identifier("a")),
+                        optional: false,
+                        annotation: mock.ptr(Type::Identifier("T")),
+                    },
+                ]),
+                return_type: Some(mock.ptr(Type::Identifier("T"))),
+                body: ArrowBody::Expression(mock.ptr("a")),
+            }))
+        ]);
+
+        assert_eq!(parse_flow(src).unwrap().body(), expected);
+        assert!(parse("<T>(a) => a;").is_err());
+    }
+
+    #[test]
+    fn flow_conditional_is_not_arrow() {
+        let src = "a ? (b) : c;";
+        let mock = Mock::new();
+
+        let expected = mock.list([
+            Statement::Expression(mock.ptr(ConditionalExpression {
+                test: mock.ptr("a"),
+                consequent: mock.ptr("b"),
+                alternate: mock.ptr("c"),
+            }))
+        ]);
+
+        assert_eq!(parse_flow(src).unwrap().body(), expected);
+    }
+
+    #[test]
+    fn flow_type_alias() {
+        let src = "type Maybe<T> = ?T | void; opaque type ID: string = string;";
+        let mock = Mock::new();
+
+        let expected = mock.list([
+            TypeAliasStatement {
+                opaque: false,
+                name: mock.ptr("Maybe"),
+                type_params: mock.list([
+                    TypeParameter {
+                        name: "T",
+                        bound: None,
+                        default: None,
+                    }
+                ]),
+                supertype: None,
+                value: Some(mock.ptr(Type::Union {
+                    variants: mock.list([
+                        Type::Nullable(mock.ptr(Type::Identifier("T"))),
+                        Type::Primitive(Primitive::Void),
+                    ])
+                })),
+            },
+            TypeAliasStatement {
+                opaque: true,
+                name: mock.ptr("ID"),
+                type_params: NodeList::empty(),
+                supertype: Some(mock.ptr(Type::Primitive(Primitive::String))),
+                value: Some(mock.ptr(Type::Primitive(Primitive::String))),
+            },
+        ]);
+
+        assert_eq!(parse_flow(src).unwrap().body(), expected);
+    }
+
+    #[test]
+    fn flow_type_is_still_an_identifier() {
+        let src = "type = 1; type;";
+        let mock = Mock::new();
+
+        let expected = mock.list([
+            Statement::Expression(mock.ptr(BinaryExpression {
+                operator: ::ast::OperatorKind::Assign,
+                left: mock.ptr("type"),
+                right: mock.number("1"),
+            })),
+            Statement::Expression(mock.ptr("type")),
+        ]);
+
+        assert_eq!(parse_flow(src).unwrap().body(), expected);
+    }
+
+    #[test]
+    fn flow_object_and_function_types() {
+        let src = "type Props = {| +name: string, age?: number, [key: string]: mixed, ...Other, cb(x: T): void |};";
+        let mock = Mock::new();
+
+        let expected = mock.list([
+            TypeAliasStatement {
+                opaque: false,
+                name: mock.ptr("Props"),
+                type_params: NodeList::empty(),
+                supertype: None,
+                value: Some(mock.ptr(Type::Object {
+                    exact: true,
+                    members: mock.list([
+                        ObjectTypeMember::Property {
+                            key: "name",
+                            optional: false,
+                            method: false,
+                            value: mock.ptr(Type::Primitive(Primitive::String)),
+                        },
+                        ObjectTypeMember::Property {
+                            key: "age",
+                            optional: true,
+                            method: false,
+                            value: mock.ptr(Type::Primitive(Primitive::Number)),
+                        },
+                        ObjectTypeMember::Indexer {
+                            name: Some(mock.ptr("key")),
+                            key: mock.ptr(Type::Primitive(Primitive::String)),
+                            value: mock.ptr(Type::Mixed),
+                        },
+                        ObjectTypeMember::Spread(mock.ptr(Type::Identifier("Other"))),
+                        ObjectTypeMember::Property {
+                            key: "cb",
+                            optional: false,
+                            method: true,
+                            value: mock.ptr(FunctionType {
+                                type_params: NodeList::empty(),
+                                params: mock.list([
+                                    FunctionTypeParam {
+                                        name: Some(mock.ptr("x")),
+                                        optional: false,
+                                        annotation: mock.ptr(Type::Identifier("T")),
+                                    }
+                                ]),
+                                rest: None,
+                                return_type: mock.ptr(Type::Primitive(Primitive::Void)),
+                            }),
+                        },
+                    ]),
+                })),
+            },
+        ]);
+
+        assert_eq!(parse_flow(src).unwrap().body(), expected);
+    }
+
+    #[test]
+    fn flow_nested_type_args() {
+        let src = "type A = Map<string, Array<Array<number>>>; type B = (string, number) => React.Node[];";
+        let mock = Mock::new();
+
+        let expected = mock.list([
+            TypeAliasStatement {
+                opaque: false,
+                name: mock.ptr("A"),
+                type_params: NodeList::empty(),
+                supertype: None,
+                value: Some(mock.ptr(Type::Generic {
+                    ident: "Map",
+                    subtypes: mock.list([
+                        Type::Primitive(Primitive::String),
+                        Type::Generic {
+                            ident: "Array",
+                            subtypes: mock.list([
+                                Type::Generic {
+                                    ident: "Array",
+                                    subtypes: mock.list([
+                                        Type::Primitive(Primitive::Number)
+                                    ])
+                                }
+                            ])
+                        }
+                    ])
+                })),
+            },
+            TypeAliasStatement {
+                opaque: false,
+                name: mock.ptr("B"),
+                type_params: NodeList::empty(),
+                supertype: None,
+                value: Some(mock.ptr(FunctionType {
+                    type_params: NodeList::empty(),
+                    params: mock.list([
+                        FunctionTypeParam {
+                            name: None,
+                            optional: false,
+                            annotation: mock.ptr(Type::Primitive(Primitive::String)),
+                        },
+                        FunctionTypeParam {
+                            name: None,
+                            optional: false,
+                            annotation: mock.ptr(Type::Primitive(Primitive::Number)),
+                        },
+                    ]),
+                    rest: None,
+                    return_type: mock.ptr(Type::Array(mock.ptr(Type::Identifier("React.Node")))),
+                })),
+            },
+        ]);
+
+        assert_eq!(parse_flow(src).unwrap().body(), expected);
+    }
+
+    #[test]
+    fn flow_declare() {
+        let src = "declare var DEBUG: boolean; declare function foo(number): string; declare opaque type Token;";
+        let mock = Mock::new();
+
+        let expected = mock.list([
+            DeclareStatement::Variable {
+                kind: DeclarationKind::Var,
+                name: mock.ptr("DEBUG"),
+                annotation: Some(mock.ptr(Type::Primitive(Primitive::Boolean))),
+            },
+            DeclareStatement::Function {
+                name: mock.ptr("foo"),
+                annotation: mock.ptr(FunctionType {
+                    type_params: NodeList::empty(),
+                    params: mock.list([
+                        FunctionTypeParam {
+                            name: None,
+                            optional: false,
+                            annotation: mock.ptr(Type::Primitive(Primitive::Number)),
+                        },
+                    ]),
+                    rest: None,
+                    return_type: mock.ptr(Type::Primitive(Primitive::String)),
+                }),
+            },
+            DeclareStatement::TypeAlias(TypeAliasStatement {
+                opaque: true,
+                name: mock.ptr("Token"),
+                type_params: NodeList::empty(),
+                supertype: None,
+                value: None,
+            }),
+        ]);
+
+        assert_eq!(parse_flow(src).unwrap().body(), expected);
+    }
+
+    #[test]
+    fn flow_import_type() {
+        let src = "import type Foo, { Bar, Baz as Qux } from './types'; import typeof * as Lib from 'lib';";
+        let mock = Mock::new();
+
+        let expected = mock.list([
+            ImportStatement {
+                kind: ImportKind::Type,
+                specifiers: mock.list([
+                    ImportSpecifier::Default("Foo"),
+                    ImportSpecifier::Named {
+                        imported: "Bar",
+                        local: "Bar",
+                    },
+                    ImportSpecifier::Named {
+                        imported: "Baz",
+                        local: "Qux",
+                    },
+                ]),
+                source: mock.ptr(Literal::String("'./types'")),
+            },
+            ImportStatement {
+                kind: ImportKind::Typeof,
+                specifiers: mock.list([
+                    ImportSpecifier::Namespace("Lib"),
+                ]),
+                source: mock.ptr(Literal::String("'lib'")),
+            },
+        ]);
+
+        assert_eq!(parse_flow(src).unwrap().body(), expected);
+    }
+
+    #[test]
+    fn flow_export_type() {
+        let src = "export type { A, B as C }; export type { D } from './types';";
+        let mock = Mock::new();
+
+        let expected = mock.list([
+            ExportStatement::Named {
+                kind: ExportKind::Type,
+                specifiers: mock.list([
+                    ExportSpecifier {
+                        local: "A",
+                        exported: "A",
+                    },
+                    ExportSpecifier {
+                        local: "B",
+                        exported: "C",
+                    },
+                ]),
+                source: None,
+            },
+            ExportStatement::Named {
+                kind: ExportKind::Type,
+                specifiers: mock.list([
+                    ExportSpecifier {
+                        local: "D",
+                        exported: "D",
+                    },
+                ]),
+                source: Some(mock.ptr(Literal::String("'./types'"))),
+            },
+        ]);
+
+        assert_eq!(parse_flow(src).unwrap().body(), expected);
+    }
+
+    #[test]
+    fn flow_class() {
+        let src = "class Foo<T> extends React.Component<Props> implements Bar { static x: number = 1; y: T; }";
+        let mock = Mock::new();
+
+        let expected = mock.list([
+            Class {
+                name: mock.name("Foo"),
+                type_params: mock.list([
+                    TypeParameter {
+                        name: "T",
+                        bound: None,
+                        default: None,
+                    }
+                ]),
+                extends: Some(mock.ptr(MemberExpression {
+                    object: mock.ptr("React"),
+                    property: mock.ptr("Component"),
+                })),
+                super_type_args: mock.list([
+                    Type::Identifier("Props")
+                ]),
+                implements: mock.list([
+                    Type::Identifier("Bar")
+                ]),
+                body: mock.block([
+                    ClassMember::Property {
+                        is_static: true,
+                        key: mock.ptr(PropertyKey::Literal("x")),
+                        annotation: mock.ptr(Type::Primitive(Primitive::Number)),
+                        value: Some(mock.number("1")),
+                    },
+                    ClassMember::Property {
+                        is_static: false,
+                        key: mock.ptr(PropertyKey::Literal("y")),
+                        annotation: mock.ptr(Type::Identifier("T")),
+                        value: None,
+                    },
+                ]),
+            }
+        ]);
+
+        assert_eq!(parse_flow(src).unwrap().body(), expected);
+    }
+}